@@ -0,0 +1,34 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use minmon::bench::LevelAlarm;
+
+fn alarm_throughput(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("put_data");
+    group.throughput(Throughput::Elements(1));
+    for (data_name, data) in [("good", &[10][..]), ("bad", &[20]), ("mixed", &[10, 20])] {
+        for cycles in [1, 100, 1000] {
+            group.bench_with_input(
+                BenchmarkId::new(data_name, cycles),
+                &cycles,
+                |b, &cycles| {
+                    let mut alarm = LevelAlarm::new(cycles);
+                    b.iter_custom(|iters| {
+                        runtime.block_on(async {
+                            let start = std::time::Instant::now();
+                            for i in 0..iters as usize {
+                                alarm.put_data(data[i % data.len()]).await;
+                            }
+                            start.elapsed()
+                        })
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, alarm_throughput);
+criterion_main!(benches);
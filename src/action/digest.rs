@@ -0,0 +1,185 @@
+use super::Action;
+use crate::config;
+use crate::{ActionMap, Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
+// The first trigger is forwarded to the action right away and opens a window. Triggers during the
+// window are buffered and forwarded as a single digest when it ends.
+pub struct Digest {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    name: String,
+    action: Arc<dyn Action>,
+    window: std::time::Duration,
+    item_template: String,
+    // `None` if no window is open.
+    buffer: Mutex<Option<Vec<PlaceholderMap>>>,
+}
+
+impl Digest {
+    pub fn new(action: &config::Action, actions: &ActionMap) -> Result<Self> {
+        if let config::ActionType::Digest(digest) = &action.type_ {
+            if digest.window == 0 {
+                Err(Error::invalid_config("window", "cannot be 0."))
+            } else if digest.item_template.is_empty() {
+                Err(Error::invalid_config("item_template", "cannot be empty."))
+            } else {
+                Ok(Self {
+                    inner: Arc::new(Inner {
+                        name: action.name.clone(),
+                        action: super::get_action(&digest.action, actions)?,
+                        window: std::time::Duration::from_secs(digest.window as u64),
+                        item_template: digest.item_template.clone(),
+                        buffer: Mutex::new(None),
+                    }),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+impl Inner {
+    fn digest_placeholders(&self, items: &[PlaceholderMap]) -> PlaceholderMap {
+        let count = |event| {
+            items
+                .iter()
+                .filter(|x| x.get("alarm_event").map(|x| x.as_str()) == Some(event))
+                .count()
+                .to_string()
+        };
+        let lines: Vec<String> = items
+            .iter()
+            .map(|x| crate::fill_placeholders(&self.item_template, x))
+            .collect();
+        PlaceholderMap::from([
+            (String::from("digest_count"), items.len().to_string()),
+            (String::from("digest_bad_count"), count("trigger")),
+            (String::from("digest_recover_count"), count("recover")),
+            (String::from("digest_items"), lines.join("\n")),
+        ])
+    }
+
+    async fn flush(&self) {
+        tokio::time::sleep(self.window).await;
+        let items = self.buffer.lock().unwrap().take().unwrap_or_default();
+        if items.is_empty() {
+            return;
+        }
+        if let Err(err) = self.action.trigger(self.digest_placeholders(&items)).await {
+            log::error!(
+                "Digest '{}' could not forward {} trigger(s): {}",
+                self.name,
+                items.len(),
+                err
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl Action for Digest {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        {
+            let mut buffer = self.inner.buffer.lock().unwrap();
+            if let Some(items) = buffer.as_mut() {
+                items.push(placeholders);
+                return Ok(());
+            }
+            *buffer = Some(Vec::new());
+        }
+        let inner = self.inner.clone();
+        tokio::spawn(async move { inner.flush().await });
+        self.inner.action.trigger(placeholders).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::TestAction;
+
+    fn digest(target: &TestAction) -> Digest {
+        let config: config::Action = toml::from_str(
+            r#"
+            name = "Digest"
+            type = "Digest"
+            action = "Target"
+            window = 60
+            item_template = "{{alarm_name}}: {{alarm_event}}"
+            "#,
+        )
+        .unwrap();
+        let actions = ActionMap::from([(
+            String::from("Target"),
+            Arc::new(target.clone()) as Arc<dyn Action>,
+        )]);
+        Digest::new(&config, &actions).unwrap()
+    }
+
+    fn event(alarm_name: &str, alarm_event: &str) -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from(alarm_name)),
+            (String::from("alarm_event"), String::from(alarm_event)),
+        ])
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_digest() {
+        let target = TestAction::new();
+        let digest = digest(&target);
+        for (i, alarm_event) in ["trigger", "trigger", "recover", "trigger", "error"]
+            .iter()
+            .enumerate()
+        {
+            digest
+                .trigger(event(&format!("Alarm{i}"), alarm_event))
+                .await
+                .unwrap();
+        }
+        // The first trigger is forwarded without delay.
+        target.assert_triggered_once();
+        assert_eq!(target.last_placeholders().unwrap()["alarm_name"], "Alarm0");
+
+        tokio::time::sleep(std::time::Duration::from_secs(61)).await;
+        assert_eq!(target.trigger_count(), 2);
+        let placeholders = target.last_placeholders().unwrap();
+        assert_eq!(placeholders["digest_count"], "4");
+        assert_eq!(placeholders["digest_bad_count"], "2");
+        assert_eq!(placeholders["digest_recover_count"], "1");
+        assert_eq!(
+            placeholders["digest_items"],
+            "Alarm1: trigger\nAlarm2: recover\nAlarm3: trigger\nAlarm4: error"
+        );
+
+        // The window has ended, so the next trigger opens a new one.
+        digest.trigger(event("Alarm5", "trigger")).await.unwrap();
+        assert_eq!(target.trigger_count(), 3);
+        assert_eq!(target.last_placeholders().unwrap()["alarm_name"], "Alarm5");
+        tokio::time::sleep(std::time::Duration::from_secs(61)).await;
+        assert_eq!(target.trigger_count(), 3);
+    }
+
+    #[test]
+    fn test_validation() {
+        let config: config::Action = toml::from_str(
+            r#"
+            name = "Digest"
+            type = "Digest"
+            action = "Missing"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            Digest::new(&config, &ActionMap::new())
+                .err()
+                .unwrap()
+                .to_string(),
+            "Action 'Missing' not found."
+        );
+    }
+}
@@ -22,28 +22,26 @@ impl TryFrom<&config::Action> for Email {
     fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
         if let config::ActionType::Email(email) = &action.type_ {
             if email.subject.is_empty() {
-                Err(Error(String::from("'subject' cannot be empty.")))
+                Err(Error::invalid_config("subject", "cannot be empty."))
             } else if email.body.is_empty() {
-                Err(Error(String::from("'body' cannot be empty.")))
+                Err(Error::invalid_config("body", "cannot be empty."))
             } else if email.smtp_server.is_empty() {
-                Err(Error(String::from("'smtp_server' cannot be empty.")))
+                Err(Error::invalid_config("smtp_server", "cannot be empty."))
             } else if email.username.is_empty() {
-                Err(Error(String::from("'username' cannot be empty.")))
+                Err(Error::invalid_config("username", "cannot be empty."))
             } else if email.password.is_empty() {
-                Err(Error(String::from("'password' cannot be empty.")))
+                Err(Error::invalid_config("password", "cannot be empty."))
             } else {
                 Ok(Self {
-                    from: email
-                        .from
-                        .parse()
-                        .map_err(|x| Error(format!("Invalid sender email address: {x}")))?,
-                    to: email
-                        .to
-                        .parse()
-                        .map_err(|x| Error(format!("Invalid recipient email address: {x}")))?,
-                    reply_to: email.reply_to.as_ref().map_or(Ok(None), |x| {
+                    from: email.from.parse().map_err(|x| {
+                        Error::Message(format!("Invalid sender email address: {x}"))
+                    })?,
+                    to: email.to.parse().map_err(|x| {
+                        Error::Message(format!("Invalid recipient email address: {x}"))
+                    })?,
+                    reply_to: email.reply_to.as_ref().map_or(Ok::<_, Error>(None), |x| {
                         Ok(Some(x.parse().map_err(|x| {
-                            Error(format!("Invalid reply-to email address: {x}"))
+                            Error::Message(format!("Invalid reply-to email address: {x}"))
                         })?))
                     })?,
                     subject: email.subject.clone(),
@@ -79,16 +77,16 @@ impl Action for Email {
         }
         let email = message_builder
             .body(body)
-            .map_err(|x| Error(x.to_string()))?;
+            .map_err(|x| Error::Message(x.to_string()))?;
         let credentials = Credentials::new(self.username.clone(), self.password.clone());
         let mut mailer_builder = match self.smtp_security {
             config::SmtpSecurity::TLS => {
                 AsyncSmtpTransport::<Tokio1Executor>::relay(&self.smtp_server)
-                    .map_err(|x| Error(x.to_string()))
+                    .map_err(|x| Error::Message(x.to_string()))
             }
             config::SmtpSecurity::STARTTLS => {
                 AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.smtp_server)
-                    .map_err(|x| Error(x.to_string()))
+                    .map_err(|x| Error::Message(x.to_string()))
             }
             config::SmtpSecurity::Plain => Ok(
                 AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.smtp_server),
@@ -103,7 +101,7 @@ impl Action for Email {
         mailer
             .send(email)
             .await
-            .map_err(|x| Error(format!("Failed to send email: {x}")))?;
+            .map_err(|x| Error::Message(format!("Failed to send email: {x}")))?;
         Ok(())
     }
 }
@@ -0,0 +1,233 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_server::{Health, HealthServer};
+use tonic_health::pb::{HealthCheckRequest, HealthCheckResponse};
+
+// Maps a service name (i.e. an alarm name) to the check IDs that are currently bad. The same alarm
+// can be bad for one ID and good for another one so storing a single status is not enough.
+type BadAlarms = std::sync::Arc<
+    std::sync::RwLock<std::collections::HashMap<String, std::collections::HashSet<String>>>,
+>;
+
+pub struct GrpcHealth {
+    bad_alarms: BadAlarms,
+    server: tokio::task::JoinHandle<()>,
+}
+
+// The server stops with the action, e.g. when it was removed from the config on reload.
+impl Drop for GrpcHealth {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+struct HealthService {
+    bad_alarms: BadAlarms,
+}
+
+impl HealthService {
+    fn status(&self, service: &str) -> ServingStatus {
+        let bad_alarms = self.bad_alarms.read().unwrap();
+        // The empty service name stands for the health of the whole server.
+        let is_bad = if service.is_empty() {
+            !bad_alarms.is_empty()
+        } else {
+            bad_alarms.contains_key(service)
+        };
+        if is_bad {
+            ServingStatus::NotServing
+        } else {
+            ServingStatus::Serving
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Health for HealthService {
+    async fn check(
+        &self,
+        request: tonic::Request<HealthCheckRequest>,
+    ) -> std::result::Result<tonic::Response<HealthCheckResponse>, tonic::Status> {
+        let status = self.status(&request.get_ref().service);
+        Ok(tonic::Response::new(HealthCheckResponse {
+            status: status.into(),
+        }))
+    }
+
+    type WatchStream = tokio_stream::Empty<std::result::Result<HealthCheckResponse, tonic::Status>>;
+
+    // Clients are expected to fall back to polling if this is not implemented.
+    async fn watch(
+        &self,
+        _request: tonic::Request<HealthCheckRequest>,
+    ) -> std::result::Result<tonic::Response<Self::WatchStream>, tonic::Status> {
+        Err(tonic::Status::unimplemented("Watch is not supported."))
+    }
+}
+
+impl GrpcHealth {
+    fn new(listener: std::net::TcpListener) -> Result<Self> {
+        let address = listener
+            .local_addr()
+            .map_err(|x| Error::Message(format!("Could not get gRPC server address: {x}")))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|x| Error::Message(format!("Could not set up gRPC server: {x}")))?;
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|x| Error::Message(format!("Could not start gRPC server: {x}")))?;
+        let listener = {
+            let _guard = runtime.enter();
+            tokio::net::TcpListener::from_std(listener)
+                .map_err(|x| Error::Message(format!("Could not start gRPC server: {x}")))?
+        };
+        let bad_alarms = BadAlarms::default();
+        let service = HealthService {
+            bad_alarms: bad_alarms.clone(),
+        };
+        let server = runtime.spawn(async move {
+            if let Err(err) = tonic::transport::Server::builder()
+                .add_service(HealthServer::new(service))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+                .await
+            {
+                log::error!("gRPC server stopped: {err}");
+            }
+        });
+        log::info!("gRPC health server listening on {address}.");
+        Ok(Self { bad_alarms, server })
+    }
+}
+
+impl TryFrom<&config::Action> for GrpcHealth {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::GrpcHealth(grpc_health) = &action.type_ {
+            let listener = std::net::TcpListener::bind(grpc_health.bind_address).map_err(|x| {
+                Error::Message(format!(
+                    "Could not bind gRPC server to {}: {x}",
+                    grpc_health.bind_address
+                ))
+            })?;
+            Self::new(listener)
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for GrpcHealth {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let get = |name: &str| {
+            placeholders
+                .get(name)
+                .ok_or_else(|| Error::Message(format!("Placeholder '{name}' is required.")))
+        };
+        let service = get("alarm_name")?;
+        let check_id = get("check_id")?;
+        let mut bad_alarms = self.bad_alarms.write().unwrap();
+        match get("alarm_event")?.as_str() {
+            "trigger" => {
+                bad_alarms
+                    .entry(service.clone())
+                    .or_default()
+                    .insert(check_id.clone());
+            }
+            "recover" => {
+                if let Some(check_ids) = bad_alarms.get_mut(service) {
+                    check_ids.remove(check_id);
+                    if check_ids.is_empty() {
+                        bad_alarms.remove(service);
+                    }
+                }
+            }
+            // Errors do not tell anything about the state of the service.
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tonic_health::pb::health_client::HealthClient;
+
+    fn placeholders(alarm_name: &str, check_id: &str, alarm_event: &str) -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from(alarm_name)),
+            (String::from("check_id"), String::from(check_id)),
+            (String::from("alarm_event"), String::from(alarm_event)),
+        ])
+    }
+
+    async fn status(client: &mut HealthClient<tonic::transport::Channel>, service: &str) -> i32 {
+        client
+            .check(HealthCheckRequest {
+                service: String::from(service),
+            })
+            .await
+            .unwrap()
+            .get_ref()
+            .status
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let action = GrpcHealth::new(listener).unwrap();
+        let channel = tonic::transport::Endpoint::from_shared(format!("http://{address}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = HealthClient::new(channel);
+        let serving = i32::from(ServingStatus::Serving);
+        let not_serving = i32::from(ServingStatus::NotServing);
+        assert_eq!(status(&mut client, "").await, serving);
+        assert_eq!(status(&mut client, "Foo").await, serving);
+
+        action
+            .trigger(placeholders("Foo", "/srv", "trigger"))
+            .await
+            .unwrap();
+        action
+            .trigger(placeholders("Foo", "/home", "trigger"))
+            .await
+            .unwrap();
+        assert_eq!(status(&mut client, "").await, not_serving);
+        assert_eq!(status(&mut client, "Foo").await, not_serving);
+        assert_eq!(status(&mut client, "Bar").await, serving);
+
+        action
+            .trigger(placeholders("Foo", "/srv", "recover"))
+            .await
+            .unwrap();
+        assert_eq!(status(&mut client, "Foo").await, not_serving);
+        action
+            .trigger(placeholders("Foo", "/home", "error"))
+            .await
+            .unwrap();
+        assert_eq!(status(&mut client, "Foo").await, not_serving);
+        action
+            .trigger(placeholders("Foo", "/home", "recover"))
+            .await
+            .unwrap();
+        assert_eq!(status(&mut client, "").await, serving);
+        assert_eq!(status(&mut client, "Foo").await, serving);
+    }
+
+    #[tokio::test]
+    async fn test_missing_placeholder() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let action = GrpcHealth::new(listener).unwrap();
+        let mut placeholders = placeholders("Foo", "/srv", "trigger");
+        placeholders.remove("alarm_name");
+        assert!(action.trigger(placeholders).await.is_err());
+    }
+}
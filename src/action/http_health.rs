@@ -0,0 +1,334 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+// Maps an alarm name to the check IDs that are currently bad (or in the error state). The same
+// alarm can be bad for one ID and good for another one so storing a single status is not enough.
+type AlarmIds = std::collections::HashMap<String, std::collections::HashSet<String>>;
+
+#[derive(Default)]
+struct State {
+    bad: AlarmIds,
+    error: AlarmIds,
+}
+
+struct HealthService {
+    state: std::sync::RwLock<State>,
+    readiness_alarms: Vec<String>,
+}
+
+impl HealthService {
+    // Sorted names of the alarms that are bad or in the error state. If `alarms` is given, only
+    // those are considered.
+    fn failing_alarms(&self, alarms: Option<&[String]>) -> Vec<String> {
+        let state = self.state.read().unwrap();
+        let mut failing: Vec<String> = state
+            .bad
+            .keys()
+            .chain(state.error.keys())
+            .filter(|name| alarms.is_none_or(|alarms| alarms.contains(name)))
+            .cloned()
+            .collect();
+        failing.sort();
+        failing.dedup();
+        failing
+    }
+
+    fn response(&self, alarms: Option<&[String]>) -> (axum::http::StatusCode, String) {
+        let failing_alarms = self.failing_alarms(alarms);
+        let (status_code, status) = if failing_alarms.is_empty() {
+            (axum::http::StatusCode::OK, "ok")
+        } else {
+            (axum::http::StatusCode::SERVICE_UNAVAILABLE, "degraded")
+        };
+        let body = serde_json::to_string(&HealthResponse {
+            status,
+            failing_alarms,
+        })
+        .unwrap_or_default();
+        (status_code, body)
+    }
+}
+
+#[derive(serde::Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    failing_alarms: Vec<String>,
+}
+
+async fn healthz(
+    axum::extract::State(service): axum::extract::State<std::sync::Arc<HealthService>>,
+) -> impl axum::response::IntoResponse {
+    let (status, body) = service.response(None);
+    (
+        status,
+        [(axum::http::header::CONTENT_TYPE, "application/json")],
+        body,
+    )
+}
+
+async fn readyz(
+    axum::extract::State(service): axum::extract::State<std::sync::Arc<HealthService>>,
+) -> impl axum::response::IntoResponse {
+    // Without readiness alarms, the readiness is the same as the health.
+    let alarms = if service.readiness_alarms.is_empty() {
+        None
+    } else {
+        Some(service.readiness_alarms.as_slice())
+    };
+    let (status, body) = service.response(alarms);
+    (
+        status,
+        [(axum::http::header::CONTENT_TYPE, "application/json")],
+        body,
+    )
+}
+
+#[derive(serde::Deserialize)]
+struct AlarmsConfigQuery {
+    tag: Option<String>,
+}
+
+// The alarms of the config as exported by `minmon --export`, optionally only the ones with a tag.
+// The server can be asked before the checks are loaded.
+async fn alarms_config(
+    axum::extract::Query(query): axum::extract::Query<AlarmsConfigQuery>,
+) -> impl axum::response::IntoResponse {
+    let (status, export) = match crate::alarm_export(query.tag.as_deref()) {
+        Some(export) => (axum::http::StatusCode::OK, export),
+        None => (axum::http::StatusCode::SERVICE_UNAVAILABLE, String::new()),
+    };
+    (
+        status,
+        [(axum::http::header::CONTENT_TYPE, "application/json")],
+        export,
+    )
+}
+
+pub struct HttpHealth {
+    service: std::sync::Arc<HealthService>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+// Otherwise the socket would stay bound after a reload that changed the action.
+impl Drop for HttpHealth {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl HttpHealth {
+    fn new(listener: std::net::TcpListener, readiness_alarms: Vec<String>) -> Result<Self> {
+        let address = listener
+            .local_addr()
+            .map_err(|x| Error::Message(format!("Could not get HTTP server address: {x}")))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|x| Error::Message(format!("Could not set up HTTP server: {x}")))?;
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|x| Error::Message(format!("Could not start HTTP server: {x}")))?;
+        let listener = {
+            let _guard = runtime.enter();
+            tokio::net::TcpListener::from_std(listener)
+                .map_err(|x| Error::Message(format!("Could not start HTTP server: {x}")))?
+        };
+        let service = std::sync::Arc::new(HealthService {
+            state: std::sync::RwLock::new(State::default()),
+            readiness_alarms,
+        });
+        let router = axum::Router::new()
+            .route("/healthz", axum::routing::get(healthz))
+            .route("/readyz", axum::routing::get(readyz))
+            .route("/alarms/config", axum::routing::get(alarms_config))
+            .with_state(service.clone());
+        let server = runtime.spawn(async move {
+            if let Err(err) = axum::serve(listener, router).await {
+                log::error!("HTTP health server stopped: {err}");
+            }
+        });
+        log::info!("HTTP health server listening on {address}.");
+        Ok(Self { service, server })
+    }
+}
+
+impl TryFrom<&config::Action> for HttpHealth {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::HttpHealth(http_health) = &action.type_ {
+            let listener = std::net::TcpListener::bind(http_health.bind_address).map_err(|x| {
+                Error::Message(format!(
+                    "Could not bind HTTP server to {}: {x}",
+                    http_health.bind_address
+                ))
+            })?;
+            Self::new(listener, http_health.readiness_alarms.clone())
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for HttpHealth {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let get = |name: &str| {
+            placeholders
+                .get(name)
+                .ok_or_else(|| Error::Message(format!("Placeholder '{name}' is required.")))
+        };
+        let alarm_name = get("alarm_name")?;
+        let check_id = get("check_id")?;
+        let insert = |alarm_ids: &mut AlarmIds| {
+            alarm_ids
+                .entry(alarm_name.clone())
+                .or_default()
+                .insert(check_id.clone());
+        };
+        let remove = |alarm_ids: &mut AlarmIds| {
+            if let Some(check_ids) = alarm_ids.get_mut(alarm_name) {
+                check_ids.remove(check_id);
+                if check_ids.is_empty() {
+                    alarm_ids.remove(alarm_name);
+                }
+            }
+        };
+        let mut state = self.service.state.write().unwrap();
+        match get("alarm_event")?.as_str() {
+            "trigger" => insert(&mut state.bad),
+            "recover" => remove(&mut state.bad),
+            "error" => insert(&mut state.error),
+            "error_recover" => remove(&mut state.error),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn placeholders(alarm_name: &str, check_id: &str, alarm_event: &str) -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from(alarm_name)),
+            (String::from("check_id"), String::from(check_id)),
+            (String::from("alarm_event"), String::from(alarm_event)),
+        ])
+    }
+
+    // Returns the status code and the body of the response.
+    async fn get(address: std::net::SocketAddr, path: &str) -> (u16, String) {
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                format!("GET {path} HTTP/1.1\r\nHost: test\r\nConnection: close\r\n\r\n")
+                    .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, String::from(body))
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let action = HttpHealth::new(listener, vec![String::from("Foo")]).unwrap();
+        let ok = (200, String::from(r#"{"status":"ok"}"#));
+        assert_eq!(get(address, "/healthz").await, ok);
+        assert_eq!(get(address, "/readyz").await, ok);
+
+        for (alarm_name, check_id, alarm_event) in [
+            ("Foo", "/srv", "trigger"),
+            ("Foo", "/home", "trigger"),
+            ("Bar", "", "error"),
+        ] {
+            action
+                .trigger(placeholders(alarm_name, check_id, alarm_event))
+                .await
+                .unwrap();
+        }
+        let degraded = |failing: &str| {
+            (
+                503,
+                format!(r#"{{"status":"degraded","failing_alarms":[{failing}]}}"#),
+            )
+        };
+        assert_eq!(get(address, "/healthz").await, degraded(r#""Bar","Foo""#));
+        assert_eq!(get(address, "/readyz").await, degraded(r#""Foo""#));
+
+        action
+            .trigger(placeholders("Foo", "/srv", "recover"))
+            .await
+            .unwrap();
+        assert_eq!(get(address, "/readyz").await, degraded(r#""Foo""#));
+        action
+            .trigger(placeholders("Foo", "/home", "recover"))
+            .await
+            .unwrap();
+        assert_eq!(get(address, "/readyz").await, ok);
+        assert_eq!(get(address, "/healthz").await, degraded(r#""Bar""#));
+        action
+            .trigger(placeholders("Bar", "", "error_recover"))
+            .await
+            .unwrap();
+        assert_eq!(get(address, "/healthz").await, ok);
+        assert_eq!(get(address, "/unknown").await.0, 404);
+    }
+
+    #[tokio::test]
+    async fn test_alarms_config() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let _action = HttpHealth::new(listener, Vec::new()).unwrap();
+        let config = config::Config::try_from(
+            r#"
+            [[actions]]
+            name = "Log"
+            type = "Log"
+            template = "{{alarm_name}}"
+
+            [[checks]]
+            name = "Filesystem"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "Full"
+            action = "Log"
+            tags = ["storage"]
+            level = 90
+            "#,
+        )
+        .unwrap();
+        crate::from_config(&config).unwrap();
+        let (status, body) = get(address, "/alarms/config").await;
+        assert_eq!(status, 200);
+        // Other tests may load another config at the same time.
+        assert!(serde_json::from_str::<Vec<serde_json::Value>>(&body).is_ok());
+        let (status, body) = get(address, "/alarms/config?tag=storage").await;
+        assert_eq!(status, 200);
+        let alarms: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert!(alarms
+            .iter()
+            .all(|x| x["tags"] == serde_json::json!(["storage"])));
+        let (status, body) = get(address, "/alarms/config?tag=minmon-test-missing").await;
+        assert_eq!((status, body.as_str()), (200, "[]"));
+        assert_eq!(get(address, "/alarms/config?tag=a&tag=b").await.0, 400);
+    }
+
+    #[tokio::test]
+    async fn test_missing_placeholders() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let action = HttpHealth::new(listener, Vec::new()).unwrap();
+        assert!(action.trigger(PlaceholderMap::new()).await.is_err());
+    }
+}
@@ -0,0 +1,196 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+pub struct JsonLines {
+    path: std::path::PathBuf,
+    rotated_path: std::path::PathBuf,
+    max_file_size_bytes: Option<u64>,
+    // Serializes the rotation and appending of concurrent triggers of the same action.
+    lock: tokio::sync::Mutex<()>,
+}
+
+impl JsonLines {
+    fn new(path: std::path::PathBuf, max_file_size_bytes: Option<u64>) -> Result<Self> {
+        if path.as_os_str().is_empty() {
+            Err(Error::invalid_config("path", "cannot be empty."))
+        } else if max_file_size_bytes == Some(0) {
+            Err(Error::invalid_config("max_file_size_bytes", "cannot be 0."))
+        } else {
+            let mut rotated_path = path.clone().into_os_string();
+            rotated_path.push(".1");
+            Ok(Self {
+                path,
+                rotated_path: rotated_path.into(),
+                max_file_size_bytes,
+                lock: tokio::sync::Mutex::new(()),
+            })
+        }
+    }
+
+    fn line(placeholders: &PlaceholderMap) -> Result<String> {
+        use serde_json::Value;
+        let get = |key: &str| {
+            placeholders
+                .get(key)
+                .map_or(Value::Null, |x| Value::String(x.clone()))
+        };
+        let event_type = if placeholders.contains_key("event_name") {
+            Value::String(String::from("report"))
+        } else {
+            get("alarm_event")
+        };
+        let mut object: serde_json::Map<String, Value> = placeholders
+            .iter()
+            .map(|(k, v)| (k.clone(), Value::String(v.clone())))
+            .collect();
+        object.insert(String::from("event_type"), event_type);
+        object.insert(String::from("alarm_id"), get("check_id"));
+        object.insert(String::from("alarm_name"), get("alarm_name"));
+        object.insert(
+            String::from("event_timestamp"),
+            Value::String(crate::datetime_iso8601(std::time::SystemTime::now())),
+        );
+        let mut line = serde_json::to_string(&object)
+            .map_err(|x| Error::Message(format!("Could not serialize event: {x}")))?;
+        line.push('\n');
+        Ok(line)
+    }
+
+    async fn rotate(&self, bytes_to_write: u64) -> Result<()> {
+        if let Some(max_file_size_bytes) = self.max_file_size_bytes {
+            let size = match tokio::fs::metadata(&self.path).await {
+                Ok(metadata) => metadata.len(),
+                Err(_) => return Ok(()),
+            };
+            if size > 0 && size + bytes_to_write > max_file_size_bytes {
+                tokio::fs::rename(&self.path, &self.rotated_path)
+                    .await
+                    .map_err(|x| {
+                        Error::Message(format!("Could not rotate {}: {x}", self.path.display()))
+                    })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&config::Action> for JsonLines {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::JsonLines(json_lines) = &action.type_ {
+            Self::new(json_lines.path.clone(), json_lines.max_file_size_bytes)
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for JsonLines {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+        let line = Self::line(&placeholders)?;
+        let _guard = self.lock.lock().await;
+        self.rotate(line.len() as u64).await?;
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|x| {
+                Error::Message(format!(
+                    "Could not open {} for appending: {x}",
+                    self.path.display()
+                ))
+            })?;
+        file.write_all(line.as_bytes()).await.map_err(|x| {
+            Error::Message(format!("Could not write to {}: {x}", self.path.display()))
+        })?;
+        file.flush()
+            .await
+            .map_err(|x| Error::Message(format!("Could not write to {}: {x}", self.path.display())))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "minmon-test-json-lines-{}.jsonl",
+            rand::random::<u64>()
+        ))
+    }
+
+    fn alarm_placeholders(alarm_event: &str) -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("Name")),
+            (String::from("check_id"), String::from("ID")),
+            (String::from("alarm_event"), String::from(alarm_event)),
+        ])
+    }
+
+    fn read_lines(path: &std::path::Path) -> Vec<serde_json::Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|x| serde_json::from_str(x).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(JsonLines::new(std::path::PathBuf::new(), None).is_err());
+        assert!(JsonLines::new(temp_path(), Some(0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_append() {
+        let path = temp_path();
+        let action = JsonLines::new(path.clone(), None).unwrap();
+        action.trigger(alarm_placeholders("trigger")).await.unwrap();
+        action.trigger(alarm_placeholders("recover")).await.unwrap();
+        action
+            .trigger(PlaceholderMap::from([(
+                String::from("event_name"),
+                String::from("Event"),
+            )]))
+            .await
+            .unwrap();
+        let lines = read_lines(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["event_type"], "trigger");
+        assert_eq!(lines[0]["alarm_id"], "ID");
+        assert_eq!(lines[0]["alarm_name"], "Name");
+        assert_eq!(lines[0]["check_id"], "ID");
+        use std::str::FromStr;
+        chrono::DateTime::<chrono::Utc>::from_str(lines[0]["event_timestamp"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(lines[1]["event_type"], "recover");
+        assert_eq!(lines[2]["event_type"], "report");
+        assert_eq!(lines[2]["event_name"], "Event");
+        assert!(lines[2]["alarm_name"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_rotation() {
+        let path = temp_path();
+        let action = JsonLines::new(path.clone(), Some(1)).unwrap();
+        action.trigger(alarm_placeholders("trigger")).await.unwrap();
+        action.trigger(alarm_placeholders("recover")).await.unwrap();
+        action.trigger(alarm_placeholders("error")).await.unwrap();
+        let lines = read_lines(&path);
+        let rotated_lines = read_lines(&action.rotated_path);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&action.rotated_path).unwrap();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["event_type"], "error");
+        assert_eq!(rotated_lines.len(), 1);
+        assert_eq!(rotated_lines[0]["event_type"], "recover");
+    }
+}
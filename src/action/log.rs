@@ -14,7 +14,7 @@ impl TryFrom<&config::Action> for Log {
     fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
         if let config::ActionType::Log(log) = &action.type_ {
             if log.template.is_empty() {
-                Err(Error(String::from("'template' cannot be empty.")))
+                Err(Error::invalid_config("template", "cannot be empty."))
             } else {
                 Ok(Self {
                     level: log.level.into(),
@@ -4,23 +4,52 @@ use crate::{Error, PlaceholderMap, Result};
 use async_trait::async_trait;
 extern crate log as log_ext;
 
+mod digest;
 #[cfg(feature = "smtp")]
 mod email;
+#[cfg(feature = "grpc")]
+mod grpc_health;
+#[cfg(feature = "http-health")]
+mod http_health;
+mod json_lines;
 mod log;
 mod process;
+#[cfg(feature = "syslog")]
+mod syslog;
+#[cfg(test)]
+mod test_action;
 #[cfg(feature = "http")]
 mod webhook;
+#[cfg(feature = "websocket")]
+mod websocket;
 pub use self::log::Log;
+#[cfg(feature = "syslog")]
+pub use self::syslog::Syslog;
+pub use digest::Digest;
 #[cfg(feature = "smtp")]
 pub use email::Email;
+#[cfg(feature = "grpc")]
+pub use grpc_health::GrpcHealth;
+#[cfg(feature = "http-health")]
+pub use http_health::HttpHealth;
+pub use json_lines::JsonLines;
 pub use process::Process;
+#[cfg(test)]
+pub use test_action::TestAction;
 #[cfg(feature = "http")]
 pub use webhook::Webhook;
+#[cfg(feature = "websocket")]
+pub use websocket::WebSocket;
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait Action: Send + Sync {
     async fn trigger(&self, mut placeholders: PlaceholderMap) -> Result<()>;
+
+    // Name of the action as given in the config. Only `ActionBase` has one.
+    fn name(&self) -> Option<String> {
+        None
+    }
 }
 
 pub struct ActionBase<T>
@@ -44,9 +73,9 @@ where
         action: T,
     ) -> Result<Self> {
         if name.is_empty() {
-            Err(Error(String::from("'name' cannot be empty.")))
+            Err(Error::invalid_config("name", "cannot be empty."))
         } else if timeout.is_zero() {
-            Err(Error(String::from("'timeout' cannot be 0.")))
+            Err(Error::invalid_config("timeout", "cannot be 0."))
         } else {
             Ok(Self {
                 name,
@@ -76,6 +105,12 @@ where
                 placeholders.get("action_name").unwrap(),
                 placeholders.get("event_name").unwrap()
             );
+        } else if placeholders.contains_key("digest_count") {
+            log_ext::info!(
+                "Action '{}' triggered for a digest of {} trigger(s).",
+                placeholders.get("action_name").unwrap(),
+                placeholders.get("digest_count").unwrap()
+            );
         } else {
             log_ext::info!(
                 "Action '{}' triggered for alarm '{}', id '{}' from check '{}'.",
@@ -87,14 +122,21 @@ where
         }
         let res = tokio::time::timeout(self.timeout, self.action.trigger(placeholders)).await;
         match res {
-            Ok(inner) => inner,
-            Err(_) => Err(Error(format!(
+            Ok(inner) => inner.map_err(|x| Error::ActionFailed {
+                action_name: self.name.clone(),
+                source: std::sync::Arc::new(x),
+            }),
+            Err(_) => Err(Error::Message(format!(
                 "Action '{}' timed out after {} seconds.",
                 self.name,
                 self.timeout.as_secs()
             ))),
         }
     }
+
+    fn name(&self) -> Option<String> {
+        Some(self.name.clone())
+    }
 }
 
 struct DisabledAction {}
@@ -108,6 +150,12 @@ impl Action for DisabledAction {
                 placeholders.get("action_name").unwrap(),
                 placeholders.get("event_name").unwrap()
             );
+        } else if placeholders.contains_key("digest_count") {
+            log_ext::debug!(
+                "Disabled action '{}' triggered for a digest of {} trigger(s).",
+                placeholders.get("action_name").unwrap(),
+                placeholders.get("digest_count").unwrap()
+            );
         } else {
             log_ext::debug!(
                 "Disabled action '{}' triggered for alarm '{}', id '{}' from check '{}'.",
@@ -121,7 +169,10 @@ impl Action for DisabledAction {
     }
 }
 
-pub fn from_action_config(action_config: &config::Action) -> Result<std::sync::Arc<dyn Action>> {
+pub fn from_action_config(
+    action_config: &config::Action,
+    actions: &ActionMap,
+) -> Result<std::sync::Arc<dyn Action>> {
     if action_config.disable {
         log_ext::info!("Action '{}' is disabled.", action_config.name);
         Ok(std::sync::Arc::new(ActionBase::new(
@@ -132,6 +183,12 @@ pub fn from_action_config(action_config: &config::Action) -> Result<std::sync::A
         )?))
     } else {
         Ok(match &action_config.type_ {
+            config::ActionType::Digest(_) => std::sync::Arc::new(ActionBase::new(
+                action_config.name.clone(),
+                std::time::Duration::from_secs(action_config.timeout as u64),
+                action_config.placeholders.clone(),
+                Digest::new(action_config, actions)?,
+            )?),
             #[cfg(feature = "smtp")]
             config::ActionType::Email(_) => std::sync::Arc::new(ActionBase::new(
                 action_config.name.clone(),
@@ -139,6 +196,26 @@ pub fn from_action_config(action_config: &config::Action) -> Result<std::sync::A
                 action_config.placeholders.clone(),
                 Email::try_from(action_config)?,
             )?),
+            #[cfg(feature = "grpc")]
+            config::ActionType::GrpcHealth(_) => std::sync::Arc::new(ActionBase::new(
+                action_config.name.clone(),
+                std::time::Duration::from_secs(action_config.timeout as u64),
+                action_config.placeholders.clone(),
+                GrpcHealth::try_from(action_config)?,
+            )?),
+            #[cfg(feature = "http-health")]
+            config::ActionType::HttpHealth(_) => std::sync::Arc::new(ActionBase::new(
+                action_config.name.clone(),
+                std::time::Duration::from_secs(action_config.timeout as u64),
+                action_config.placeholders.clone(),
+                HttpHealth::try_from(action_config)?,
+            )?),
+            config::ActionType::JsonLines(_) => std::sync::Arc::new(ActionBase::new(
+                action_config.name.clone(),
+                std::time::Duration::from_secs(action_config.timeout as u64),
+                action_config.placeholders.clone(),
+                JsonLines::try_from(action_config)?,
+            )?),
             config::ActionType::Log(_) => std::sync::Arc::new(ActionBase::new(
                 action_config.name.clone(),
                 std::time::Duration::from_secs(action_config.timeout as u64),
@@ -151,6 +228,13 @@ pub fn from_action_config(action_config: &config::Action) -> Result<std::sync::A
                 action_config.placeholders.clone(),
                 Process::try_from(action_config)?,
             )?),
+            #[cfg(feature = "syslog")]
+            config::ActionType::Syslog(_) => std::sync::Arc::new(ActionBase::new(
+                action_config.name.clone(),
+                std::time::Duration::from_secs(action_config.timeout as u64),
+                action_config.placeholders.clone(),
+                Syslog::try_from(action_config)?,
+            )?),
             #[cfg(feature = "http")]
             config::ActionType::Webhook(_) => std::sync::Arc::new(ActionBase::new(
                 action_config.name.clone(),
@@ -158,17 +242,38 @@ pub fn from_action_config(action_config: &config::Action) -> Result<std::sync::A
                 action_config.placeholders.clone(),
                 Webhook::try_from(action_config)?,
             )?),
+            #[cfg(feature = "websocket")]
+            config::ActionType::WebSocket(_) => std::sync::Arc::new(ActionBase::new(
+                action_config.name.clone(),
+                std::time::Duration::from_secs(action_config.timeout as u64),
+                action_config.placeholders.clone(),
+                WebSocket::try_from(action_config)?,
+            )?),
         })
     }
 }
 
+// Actions that run a server on the `bind_address`. The socket stays bound as long as the action
+// exists.
+pub fn is_listener(action_type: &config::ActionType) -> bool {
+    match action_type {
+        #[cfg(feature = "grpc")]
+        config::ActionType::GrpcHealth(_) => true,
+        #[cfg(feature = "http-health")]
+        config::ActionType::HttpHealth(_) => true,
+        #[cfg(feature = "websocket")]
+        config::ActionType::WebSocket(_) => true,
+        _ => false,
+    }
+}
+
 pub fn get_action(action: &String, actions: &ActionMap) -> Result<std::sync::Arc<dyn Action>> {
     if action.is_empty() {
-        Err(Error(String::from("'name' cannot be empty.")))
+        Err(Error::invalid_config("name", "cannot be empty."))
     } else {
         Ok(actions
             .get(action)
-            .ok_or_else(|| Error(format!("Action '{action}' not found.")))?
+            .ok_or_else(|| Error::Message(format!("Action '{action}' not found.")))?
             .clone())
     }
 }
@@ -223,9 +328,6 @@ mod test {
             TimeoutMockAction {},
         )
         .unwrap();
-        assert!(matches!(
-            action.trigger(PlaceholderMap::new()).await,
-            Err(_)
-        ));
+        assert!(action.trigger(PlaceholderMap::new()).await.is_err());
     }
 }
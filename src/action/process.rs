@@ -28,8 +28,10 @@ impl Action for Process {
         let (code, stderr) = self.process_config.run(Some(placeholders)).await?;
         if code != 0 {
             return match stderr {
-                None => Err(Error(format!("Process failed with code {code}."))),
-                Some(stderr) => Err(Error(format!("Process failed with code {code}: {stderr}"))),
+                None => Err(Error::Message(format!("Process failed with code {code}."))),
+                Some(stderr) => Err(Error::Message(format!(
+                    "Process failed with code {code}: {stderr}"
+                ))),
             };
         }
         Ok(())
@@ -0,0 +1,186 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+type Logger = ::syslog::Logger<::syslog::LoggerBackend, ::syslog::Formatter3164>;
+
+#[cfg_attr(test, mockall::automock)]
+pub trait SyslogWriter: Send {
+    fn write(&mut self, severity: ::syslog::Severity, message: &str) -> Result<()>;
+}
+
+// The connection is established lazily and dropped on errors so it is re-established on the next
+// trigger, e.g. after the syslog daemon was restarted.
+pub struct UnixSyslogWriter {
+    formatter: ::syslog::Formatter3164,
+    logger: Option<Logger>,
+}
+
+impl UnixSyslogWriter {
+    fn new(facility: config::SyslogFacility) -> Self {
+        Self {
+            formatter: ::syslog::Formatter3164 {
+                facility: facility.into(),
+                hostname: None,
+                process: String::from("minmon"),
+                pid: std::process::id(),
+            },
+            logger: None,
+        }
+    }
+}
+
+impl SyslogWriter for UnixSyslogWriter {
+    fn write(&mut self, severity: ::syslog::Severity, message: &str) -> Result<()> {
+        use ::syslog::LogFormat;
+        let logger = match &mut self.logger {
+            Some(logger) => logger,
+            None => self.logger.insert(
+                ::syslog::unix(self.formatter.clone())
+                    .map_err(|x| Error::Message(format!("Could not connect to syslog: {x}")))?,
+            ),
+        };
+        let res = logger
+            .formatter
+            .format(&mut logger.backend, severity, message)
+            .map_err(|x| Error::Message(format!("Could not write to syslog: {x}")));
+        if res.is_err() {
+            self.logger = None;
+        }
+        res
+    }
+}
+
+pub struct Syslog<T = UnixSyslogWriter>
+where
+    T: SyslogWriter,
+{
+    severity: ::syslog::Severity,
+    template: String,
+    writer: std::sync::Mutex<T>,
+}
+
+impl<T> Syslog<T>
+where
+    T: SyslogWriter,
+{
+    fn new(severity: config::Severity, template: String, writer: T) -> Result<Self> {
+        if template.is_empty() {
+            Err(Error::invalid_config("template", "cannot be empty."))
+        } else {
+            Ok(Self {
+                severity: severity.into(),
+                template,
+                writer: std::sync::Mutex::new(writer),
+            })
+        }
+    }
+}
+
+impl TryFrom<&config::Action> for Syslog {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::Syslog(syslog) = &action.type_ {
+            Self::new(
+                syslog.severity,
+                syslog.template.clone(),
+                UnixSyslogWriter::new(syslog.facility),
+            )
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Action for Syslog<T>
+where
+    T: SyslogWriter,
+{
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let text = crate::fill_placeholders(self.template.as_str(), &placeholders);
+        self.writer
+            .lock()
+            .map_err(|x| Error::Message(format!("Could not lock syslog writer: {x}")))?
+            .write(self.severity, &text)
+    }
+}
+
+impl From<config::Severity> for ::syslog::Severity {
+    fn from(severity: config::Severity) -> Self {
+        match severity {
+            config::Severity::Critical => ::syslog::Severity::LOG_CRIT,
+            config::Severity::High => ::syslog::Severity::LOG_ERR,
+            config::Severity::Medium => ::syslog::Severity::LOG_WARNING,
+            config::Severity::Low => ::syslog::Severity::LOG_NOTICE,
+            config::Severity::Info => ::syslog::Severity::LOG_INFO,
+        }
+    }
+}
+
+impl From<config::SyslogFacility> for ::syslog::Facility {
+    fn from(facility: config::SyslogFacility) -> Self {
+        match facility {
+            config::SyslogFacility::User => ::syslog::Facility::LOG_USER,
+            config::SyslogFacility::Daemon => ::syslog::Facility::LOG_DAEMON,
+            config::SyslogFacility::Local0 => ::syslog::Facility::LOG_LOCAL0,
+            config::SyslogFacility::Local1 => ::syslog::Facility::LOG_LOCAL1,
+            config::SyslogFacility::Local2 => ::syslog::Facility::LOG_LOCAL2,
+            config::SyslogFacility::Local3 => ::syslog::Facility::LOG_LOCAL3,
+            config::SyslogFacility::Local4 => ::syslog::Facility::LOG_LOCAL4,
+            config::SyslogFacility::Local5 => ::syslog::Facility::LOG_LOCAL5,
+            config::SyslogFacility::Local6 => ::syslog::Facility::LOG_LOCAL6,
+            config::SyslogFacility::Local7 => ::syslog::Facility::LOG_LOCAL7,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validation() {
+        assert!(Syslog::new(
+            config::Severity::Info,
+            String::new(),
+            MockSyslogWriter::new()
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_severity_mapping() {
+        for (severity, expected) in [
+            (config::Severity::Critical, ::syslog::Severity::LOG_CRIT),
+            (config::Severity::High, ::syslog::Severity::LOG_ERR),
+            (config::Severity::Medium, ::syslog::Severity::LOG_WARNING),
+            (config::Severity::Low, ::syslog::Severity::LOG_NOTICE),
+            (config::Severity::Info, ::syslog::Severity::LOG_INFO),
+        ] {
+            let mut mock_writer = MockSyslogWriter::new();
+            mock_writer
+                .expect_write()
+                .once()
+                .withf(move |severity, message| {
+                    *severity as u8 == expected as u8 && message == "Alarm 'Foo' triggered."
+                })
+                .returning(|_, _| Ok(()));
+            let action = Syslog::new(
+                severity,
+                String::from("Alarm '{{alarm_name}}' triggered."),
+                mock_writer,
+            )
+            .unwrap();
+            action
+                .trigger(PlaceholderMap::from([(
+                    String::from("alarm_name"),
+                    String::from("Foo"),
+                )]))
+                .await
+                .unwrap();
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use super::Action;
+use crate::{PlaceholderMap, Result};
+use async_trait::async_trait;
+
+#[derive(Clone, Debug)]
+pub struct TriggerRecord {
+    pub placeholders: PlaceholderMap,
+    pub called_at: std::time::SystemTime,
+}
+
+// Records every trigger so that tests can inspect them afterwards. Clones share the records, so a
+// clone can be handed to the code under test.
+#[derive(Clone, Default)]
+pub struct TestAction {
+    records: std::sync::Arc<std::sync::Mutex<Vec<TriggerRecord>>>,
+}
+
+impl TestAction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn records(&self) -> Vec<TriggerRecord> {
+        self.records.lock().unwrap().clone()
+    }
+
+    pub fn was_triggered(&self) -> bool {
+        self.trigger_count() > 0
+    }
+
+    pub fn trigger_count(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    pub fn last_placeholders(&self) -> Option<PlaceholderMap> {
+        self.records
+            .lock()
+            .unwrap()
+            .last()
+            .map(|record| record.placeholders.clone())
+    }
+
+    #[track_caller]
+    pub fn assert_triggered_once(&self) {
+        assert_eq!(self.trigger_count(), 1, "Expected exactly one trigger.");
+    }
+
+    #[track_caller]
+    pub fn assert_not_triggered(&self) {
+        assert_eq!(self.trigger_count(), 0, "Expected no trigger.");
+    }
+}
+
+#[async_trait]
+impl Action for TestAction {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        self.records.lock().unwrap().push(TriggerRecord {
+            placeholders,
+            called_at: std::time::SystemTime::now(),
+        });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_records() {
+        let action = TestAction::new();
+        action.assert_not_triggered();
+        assert!(action.last_placeholders().is_none());
+        action
+            .trigger(PlaceholderMap::from([(
+                String::from("alarm_event"),
+                String::from("trigger"),
+            )]))
+            .await
+            .unwrap();
+        action.assert_triggered_once();
+        assert!(action.was_triggered());
+        assert_eq!(
+            action.last_placeholders().unwrap()["alarm_event"],
+            "trigger"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_triggers() {
+        let action = TestAction::new();
+        let tasks: Vec<_> = (0..16)
+            .map(|task| {
+                let action = action.clone();
+                tokio::spawn(async move {
+                    for i in 0..100 {
+                        action
+                            .trigger(PlaceholderMap::from([(
+                                String::from("trigger"),
+                                format!("{task}-{i}"),
+                            )]))
+                            .await
+                            .unwrap();
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let records = action.records();
+        assert_eq!(action.trigger_count(), 1600);
+        let triggers: std::collections::HashSet<_> = records
+            .iter()
+            .map(|record| record.placeholders["trigger"].clone())
+            .collect();
+        assert_eq!(triggers.len(), 1600);
+        // The records are in the order of the triggers.
+        assert!(records.windows(2).all(|x| x[0].called_at <= x[1].called_at));
+    }
+}
@@ -21,9 +21,9 @@ impl Webhook {
             .iter()
             .map(|(k, v)| {
                 let name = reqwest::header::HeaderName::from_str(k)
-                    .map_err(|x| Error(format!("Could not parse header name: {x}")))?;
+                    .map_err(|x| Error::Message(format!("Could not parse header name: {x}")))?;
                 let value = reqwest::header::HeaderValue::from_str(v)
-                    .map_err(|x| Error(format!("Could not parse header value: {x}")))?;
+                    .map_err(|x| Error::Message(format!("Could not parse header value: {x}")))?;
                 Ok((name, value))
             })
             .collect()
@@ -40,7 +40,7 @@ impl TryFrom<&config::Action> for Webhook {
                 headers.insert(String::from("User-Agent"), crate::user_agent());
             }
             if web_hook.url.is_empty() {
-                Err(Error(String::from("'url' cannot be empty.")))
+                Err(Error::invalid_config("url", "cannot be empty."))
             } else {
                 Ok(Self {
                     url: web_hook.url.clone(),
@@ -67,12 +67,12 @@ impl Action for Webhook {
             .body(body)
             .send()
             .await
-            .map_err(|x| Error(format!("HTTP request failed: {x}")))?;
+            .map_err(|x| Error::Message(format!("HTTP request failed: {x}")))?;
         let status = response.status();
         if status.is_success() {
             Ok(())
         } else {
-            Err(Error(format!(
+            Err(Error::Message(format!(
                 "HTTP status code {} indicates error.",
                 status.as_u16()
             )))
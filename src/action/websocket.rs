@@ -0,0 +1,191 @@
+use super::Action;
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite;
+
+const PATH: &str = "/ws/events";
+
+// Every connected client holds its own receiver of the broadcast channel. Clients that disconnect
+// drop their receiver, so there is no list of clients to clean up.
+pub struct WebSocket {
+    sender: broadcast::Sender<String>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+// Frees the socket, e.g. if the action was removed from the config on reload.
+impl Drop for WebSocket {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl WebSocket {
+    fn new(listener: std::net::TcpListener, channel_capacity: usize) -> Result<Self> {
+        if channel_capacity == 0 {
+            return Err(Error::invalid_config("channel_capacity", "cannot be 0."));
+        }
+        let address = listener
+            .local_addr()
+            .map_err(|x| Error::Message(format!("Could not get WebSocket server address: {x}")))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|x| Error::Message(format!("Could not set up WebSocket server: {x}")))?;
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|x| Error::Message(format!("Could not start WebSocket server: {x}")))?;
+        let listener = {
+            let _guard = runtime.enter();
+            tokio::net::TcpListener::from_std(listener)
+                .map_err(|x| Error::Message(format!("Could not start WebSocket server: {x}")))?
+        };
+        let (sender, _) = broadcast::channel(channel_capacity);
+        let server = runtime.spawn(Self::serve(listener, sender.clone()));
+        log::info!("WebSocket server listening on {address}{PATH}.");
+        Ok(Self { sender, server })
+    }
+
+    async fn serve(listener: tokio::net::TcpListener, sender: broadcast::Sender<String>) {
+        loop {
+            match listener.accept().await {
+                Ok((stream, address)) => {
+                    tokio::spawn(Self::handle_client(stream, address, sender.subscribe()));
+                }
+                Err(err) => log::error!("WebSocket server could not accept connection: {err}"),
+            }
+        }
+    }
+
+    async fn handle_client(
+        stream: tokio::net::TcpStream,
+        address: std::net::SocketAddr,
+        mut receiver: broadcast::Receiver<String>,
+    ) {
+        use futures_util::{SinkExt, StreamExt};
+        use tungstenite::handshake::server::{ErrorResponse, Request, Response};
+        // The signature of the callback is dictated by tungstenite.
+        #[allow(clippy::result_large_err)]
+        let callback = |request: &Request, response: Response| {
+            if request.uri().path() == PATH {
+                Ok(response)
+            } else {
+                let mut response = ErrorResponse::new(None);
+                *response.status_mut() = tungstenite::http::StatusCode::NOT_FOUND;
+                Err(response)
+            }
+        };
+        let mut websocket = match tokio_tungstenite::accept_hdr_async(stream, callback).await {
+            Ok(websocket) => websocket,
+            Err(err) => {
+                log::warn!("WebSocket handshake with {address} failed: {err}");
+                return;
+            }
+        };
+        log::debug!("WebSocket client {address} connected.");
+        loop {
+            tokio::select! {
+                message = receiver.recv() => match message {
+                    Ok(message) => {
+                        if websocket.send(tungstenite::Message::Text(message)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(count)) => {
+                        log::warn!("WebSocket client {address} missed {count} events.");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                message = websocket.next() => match message {
+                    Some(Ok(tungstenite::Message::Close(_))) | Some(Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+        log::debug!("WebSocket client {address} disconnected.");
+    }
+}
+
+impl TryFrom<&config::Action> for WebSocket {
+    type Error = Error;
+
+    fn try_from(action: &config::Action) -> std::result::Result<Self, Self::Error> {
+        if let config::ActionType::WebSocket(websocket) = &action.type_ {
+            let listener = std::net::TcpListener::bind(websocket.bind_address).map_err(|x| {
+                Error::Message(format!(
+                    "Could not bind WebSocket server to {}: {x}",
+                    websocket.bind_address
+                ))
+            })?;
+            Self::new(listener, websocket.channel_capacity)
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl Action for WebSocket {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let message = serde_json::to_string(&placeholders)
+            .map_err(|x| Error::Message(format!("Could not serialize event: {x}")))?;
+        // Sending only fails if no client is connected in which case the event is dropped.
+        let _ = self.sender.send(message);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures_util::StreamExt;
+
+    fn bind() -> (std::net::TcpListener, std::net::SocketAddr) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        (listener, address)
+    }
+
+    fn placeholders(alarm_event: &str) -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("alarm_name"), String::from("Name")),
+            (String::from("alarm_event"), String::from(alarm_event)),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_validation() {
+        let (listener, _) = bind();
+        assert!(WebSocket::new(listener, 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast() {
+        let (listener, address) = bind();
+        let action = WebSocket::new(listener, 16).unwrap();
+        action.trigger(placeholders("dropped")).await.unwrap();
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{address}{PATH}"))
+            .await
+            .unwrap();
+        for alarm_event in ["trigger", "recover", "error"] {
+            action.trigger(placeholders(alarm_event)).await.unwrap();
+        }
+        for alarm_event in ["trigger", "recover", "error"] {
+            let message = client.next().await.unwrap().unwrap().into_text().unwrap();
+            let received: PlaceholderMap = serde_json::from_str(&message).unwrap();
+            assert_eq!(received, placeholders(alarm_event));
+        }
+        drop(client);
+        action.trigger(placeholders("trigger")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wrong_path() {
+        let (listener, address) = bind();
+        let _action = WebSocket::new(listener, 16).unwrap();
+        assert!(
+            tokio_tungstenite::connect_async(format!("ws://{address}/foobar"))
+                .await
+                .is_err()
+        );
+    }
+}
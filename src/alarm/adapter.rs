@@ -0,0 +1,196 @@
+use crate::measurement::Measurement;
+use crate::{PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+
+// Adapters to reuse a data sink for another item type, similar to the iterator adapters. The
+// placeholders of the inner data sink are based on the mapped data.
+
+pub struct MappedDataSink<U, T, F>
+where
+    T: DataSink,
+    F: Fn(&U) -> T::Item + Send + Sync,
+{
+    inner: T,
+    f: F,
+    _item: std::marker::PhantomData<fn(&U)>,
+}
+
+pub fn map_sink<U, T, F>(inner: T, f: F) -> MappedDataSink<U, T, F>
+where
+    T: DataSink,
+    F: Fn(&U) -> T::Item + Send + Sync,
+{
+    MappedDataSink {
+        inner,
+        f,
+        _item: std::marker::PhantomData,
+    }
+}
+
+impl<U, T, F> DataSink for MappedDataSink<U, T, F>
+where
+    U: Send + Sync + Measurement,
+    T: DataSink,
+    F: Fn(&U) -> T::Item + Send + Sync,
+{
+    type Item = U;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        self.inner.put_data(&(self.f)(data))
+    }
+
+    fn name(&self) -> String {
+        format!("map({})", self.inner.name())
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        self.inner.format_data(&(self.f)(data))
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.inner.validate()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn describe_threshold(&self) -> String {
+        self.inner.describe_threshold()
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        self.inner.add_placeholders(&(self.f)(data), placeholders);
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        self.inner.add_state_placeholders(placeholders);
+    }
+}
+
+// Data for which the mapping returns `None` is good and does not reach the inner data sink.
+pub struct FilterMapDataSink<U, T, F>
+where
+    T: DataSink,
+    F: Fn(&U) -> Option<T::Item> + Send + Sync,
+{
+    inner: T,
+    f: F,
+    _item: std::marker::PhantomData<fn(&U)>,
+}
+
+// Not used by any alarm yet.
+#[allow(dead_code)]
+pub fn filter_map_sink<U, T, F>(inner: T, f: F) -> FilterMapDataSink<U, T, F>
+where
+    T: DataSink,
+    F: Fn(&U) -> Option<T::Item> + Send + Sync,
+{
+    FilterMapDataSink {
+        inner,
+        f,
+        _item: std::marker::PhantomData,
+    }
+}
+
+impl<U, T, F> DataSink for FilterMapDataSink<U, T, F>
+where
+    U: Send + Sync + Measurement,
+    T: DataSink,
+    F: Fn(&U) -> Option<T::Item> + Send + Sync,
+{
+    type Item = U;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        match (self.f)(data) {
+            Some(data) => self.inner.put_data(&data),
+            None => Ok(SinkDecision::Good),
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("filter_map({})", self.inner.name())
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        match (self.f)(data) {
+            Some(mapped) => self.inner.format_data(&mapped),
+            None => data.format(),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.inner.validate()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn describe_threshold(&self) -> String {
+        self.inner.describe_threshold()
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        if let Some(data) = (self.f)(data) {
+            self.inner.add_placeholders(&data, placeholders);
+        }
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        self.inner.add_state_placeholders(placeholders);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::MockDataSink;
+    use super::*;
+    use mockall::predicate::*;
+
+    #[test]
+    fn test_map() {
+        let mut inner = MockDataSink::new();
+        inner
+            .expect_add_placeholders()
+            .with(eq(21), always())
+            .returning(|data, placeholders| {
+                placeholders.insert(String::from("data"), data.to_string());
+            });
+        inner
+            .expect_put_data()
+            .with(eq(21))
+            .once()
+            .returning(|_| Ok(SinkDecision::Bad));
+        inner.expect_name().return_const(String::from("mock"));
+        let mut sink = map_sink(inner, |x: &u32| (x / 2) as u8);
+        assert_eq!(sink.name(), "map(mock)");
+        assert!(matches!(sink.put_data(&42).unwrap(), SinkDecision::Bad));
+        let mut placeholders = PlaceholderMap::new();
+        sink.add_placeholders(&42, &mut placeholders);
+        assert_eq!(placeholders["data"], "21");
+    }
+
+    #[test]
+    fn test_filter_map() {
+        let mut inner = MockDataSink::new();
+        inner
+            .expect_add_placeholders()
+            .with(eq(7), always())
+            .once()
+            .return_const(());
+        inner
+            .expect_put_data()
+            .with(eq(7))
+            .once()
+            .returning(|_| Ok(SinkDecision::Bad));
+        let mut sink = filter_map_sink(inner, |x: &i32| u8::try_from(*x).ok());
+        assert!(matches!(sink.put_data(&-1).unwrap(), SinkDecision::Good));
+        sink.add_placeholders(&-1, &mut PlaceholderMap::new());
+        assert!(matches!(sink.put_data(&7).unwrap(), SinkDecision::Bad));
+        sink.add_placeholders(&7, &mut PlaceholderMap::new());
+        assert!(matches!(sink.put_data(&300).unwrap(), SinkDecision::Good));
+        sink.add_placeholders(&300, &mut PlaceholderMap::new());
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+type Item = measurement::AlarmSummary;
+
+// Bad if at least `min_bad_count` alarms of the summary are bad.
+pub struct AlarmSummary {
+    min_bad_count: usize,
+}
+
+impl TryFrom<&config::Alarm> for AlarmSummary {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::Default(_) => Ok(Self { min_bad_count: 1 }),
+            config::AlarmType::AlarmSummary(alarm_summary) => {
+                if alarm_summary.min_bad_count == 0 {
+                    Err(Error::invalid_config("min_bad_count", "cannot be 0."))
+                } else {
+                    Ok(Self {
+                        min_bad_count: alarm_summary.min_bad_count as usize,
+                    })
+                }
+            }
+            _ => Err(Error::Message(String::from(
+                "Expected alarm summary alarm config.",
+            ))),
+        }
+    }
+}
+
+impl DataSink for AlarmSummary {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        Ok(if data.bad_alarms().len() >= self.min_bad_count {
+            SinkDecision::Bad
+        } else {
+            SinkDecision::Good
+        })
+    }
+
+    fn describe_threshold(&self) -> String {
+        format!("Bad when at least {} alarm(s) are bad", self.min_bad_count)
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("child_alarm_names"),
+            data.bad_alarms().join(", "),
+        );
+        placeholders.insert(
+            String::from("bad_child_alarm_count"),
+            data.bad_alarms().len().to_string(),
+        );
+        placeholders.insert(
+            String::from("child_alarm_count"),
+            data.alarm_count().to_string(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::measurement::Measurement;
+
+    fn summary(bad_alarms: &[&str]) -> Item {
+        Item::new((3, bad_alarms.iter().map(|x| String::from(*x)).collect())).unwrap()
+    }
+
+    #[test]
+    fn test_min_bad_count() {
+        let alarm: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nmin_bad_count = 2").unwrap();
+        let mut sink = AlarmSummary::try_from(&alarm).unwrap();
+        assert!(matches!(
+            sink.put_data(&summary(&["A/Foo"])).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            sink.put_data(&summary(&["A/Foo", "B/Bar"])).unwrap(),
+            SinkDecision::Bad
+        ));
+
+        // Without `min_bad_count`, any bad alarm is bad.
+        let alarm: config::Alarm = toml::from_str("name = \"Alarm\"\naction = \"Action\"").unwrap();
+        let mut sink = AlarmSummary::try_from(&alarm).unwrap();
+        assert!(matches!(
+            sink.put_data(&summary(&[])).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            sink.put_data(&summary(&["A/Foo"])).unwrap(),
+            SinkDecision::Bad
+        ));
+        let alarm: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nmin_bad_count = 0").unwrap();
+        assert!(AlarmSummary::try_from(&alarm).is_err());
+        assert!(Item::new((1, vec![String::new(), String::new()])).is_err());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let alarm: config::Alarm = toml::from_str("name = \"Alarm\"\naction = \"Action\"").unwrap();
+        let sink = AlarmSummary::try_from(&alarm).unwrap();
+        let mut placeholders = PlaceholderMap::new();
+        sink.add_placeholders(&summary(&["A/Foo", "B/Bar"]), &mut placeholders);
+        assert_eq!(placeholders["child_alarm_names"], "A/Foo, B/Bar");
+        assert_eq!(placeholders["bad_child_alarm_count"], "2");
+        assert_eq!(placeholders["child_alarm_count"], "3");
+    }
+}
@@ -1,36 +1,47 @@
 use crate::measurement::Measurement;
-use crate::{measurement, Error, PlaceholderMap, Result};
+use crate::{measurement, Error};
 
-use super::{DataSink, SinkDecision};
+use super::{map_sink, MappedDataSink, TwoState};
 use crate::config;
 
 type Item = measurement::BinaryState;
 
-pub struct BinaryState {}
+// A two state alarm where `false` is bad and the `state` placeholder is `true` or `false`.
+pub type BinaryState = MappedDataSink<Item, TwoState, fn(&Item) -> bool>;
 
 impl TryFrom<&config::Alarm> for BinaryState {
     type Error = Error;
 
     fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
         if let config::AlarmType::Default(_) = &alarm.type_ {
-            Ok(Self {})
+            Ok(map_sink(
+                TwoState::new(true, true.to_string(), false.to_string())?,
+                |x: &Item| x.data(),
+            ))
         } else {
-            Err(Error(String::from("Did not expect any alarm config.")))
+            Err(Error::Message(String::from(
+                "Did not expect any alarm config.",
+            )))
         }
     }
 }
 
-impl DataSink for BinaryState {
-    type Item = Item;
-
-    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
-        Ok(match data.data() {
-            true => SinkDecision::Good,
-            false => SinkDecision::Bad,
-        })
-    }
-
-    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
-        placeholders.insert(String::from("state"), data.to_string());
+#[cfg(test)]
+mod test {
+    use super::super::{DataSink, SinkDecision};
+    use super::*;
+    use crate::PlaceholderMap;
+
+    #[test]
+    fn test_binary_state() {
+        let alarm: config::Alarm = toml::from_str("name = \"Name\"\naction = \"Action\"").unwrap();
+        let mut binary_state = BinaryState::try_from(&alarm).unwrap();
+        for (data, bad) in [(true, false), (false, true)] {
+            let decision = binary_state.put_data(&Item::new(data).unwrap()).unwrap();
+            assert_eq!(matches!(decision, SinkDecision::Bad), bad);
+            let mut placeholders = PlaceholderMap::new();
+            binary_state.add_state_placeholders(&mut placeholders);
+            assert_eq!(placeholders["state"], data.to_string());
+        }
     }
 }
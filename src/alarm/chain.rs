@@ -0,0 +1,197 @@
+use crate::config;
+use crate::{Error, Result};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum StateKind {
+    Good,
+    Bad,
+    Error,
+}
+
+impl StateKind {
+    pub fn from_state_name(state_name: &str) -> Self {
+        match state_name {
+            "Bad" => Self::Bad,
+            "Error" => Self::Error,
+            _ => Self::Good,
+        }
+    }
+}
+
+// Current state of every alarm instance, shared by all checks. The key is the check name, the alarm
+// name and the ID joined with '/' (see `registry_key`). The lock is only held for single lookups and
+// updates, never across an await, so a `std::sync::RwLock` does not block the runtime.
+pub type AlarmRegistry =
+    std::sync::Arc<std::sync::RwLock<std::collections::HashMap<String, StateKind>>>;
+
+pub fn registry_key(check_name: &str, alarm_name: &str, id: &str) -> String {
+    format!("{check_name}/{alarm_name}/{id}")
+}
+
+// Suppresses the action of an alarm unless the alarm it depends on is bad. A dependency with
+// multiple IDs is bad if it is bad for any ID. A dependency without any state yet is not bad.
+#[derive(Clone)]
+pub struct AlarmChain {
+    depends_on_alarm_id: String,
+    registry: AlarmRegistry,
+}
+
+impl AlarmChain {
+    // `depends_on_alarm_id` is the check name and the alarm name joined with '/'.
+    pub fn new(depends_on_alarm_id: String, registry: AlarmRegistry) -> Self {
+        Self {
+            depends_on_alarm_id,
+            registry,
+        }
+    }
+
+    pub fn depends_on_alarm_id(&self) -> &str {
+        &self.depends_on_alarm_id
+    }
+
+    pub fn dependency_bad(&self) -> bool {
+        let prefix = format!("{}/", self.depends_on_alarm_id);
+        self.registry
+            .read()
+            .unwrap()
+            .iter()
+            .any(|(key, state)| key.starts_with(&prefix) && *state == StateKind::Bad)
+    }
+}
+
+// Makes sure that every `depends_on_alarm_id` refers to an existing alarm and that there are no
+// circular dependencies.
+pub fn validate_dependencies(checks: &[config::Check]) -> Result<()> {
+    let dependencies: std::collections::HashMap<String, Option<&String>> = checks
+        .iter()
+        .flat_map(|check| {
+            check.alarms.iter().map(|alarm| {
+                (
+                    format!("{}/{}", check.name, alarm.name),
+                    alarm.depends_on_alarm_id.as_ref(),
+                )
+            })
+        })
+        .collect();
+    for (alarm_id, depends_on_alarm_id) in dependencies.iter() {
+        if let Some(depends_on_alarm_id) = depends_on_alarm_id {
+            if !dependencies.contains_key(*depends_on_alarm_id) {
+                return Err(Error::Message(format!(
+                    "Alarm '{depends_on_alarm_id}' that alarm '{alarm_id}' depends on not found."
+                )));
+            }
+        }
+        // Every alarm has at most one dependency, so following them either ends or runs in a
+        // circle.
+        let mut path = vec![alarm_id];
+        let mut current = alarm_id;
+        while let Some(Some(next)) = dependencies.get(current) {
+            if path.contains(next) {
+                path.push(next);
+                let path: Vec<_> = path.iter().map(|x| format!("'{x}'")).collect();
+                return Err(Error::Message(format!(
+                    "Found circular alarm dependency: {}",
+                    path.join(" -> ")
+                )));
+            }
+            path.push(next);
+            current = next;
+        }
+    }
+    for check in checks.iter() {
+        if let config::CheckType::AlarmSummary(alarm_summary) = &check.type_ {
+            for alarm_id in alarm_summary.alarm_ids.iter() {
+                if !dependencies.contains_key(alarm_id) {
+                    return Err(Error::Message(format!(
+                        "Alarm '{alarm_id}' of alarm summary '{}' not found.",
+                        check.name
+                    )));
+                }
+                // The summary would follow its own state.
+                if alarm_id.starts_with(&format!("{}/", check.name)) {
+                    return Err(Error::Message(format!(
+                        "Alarm summary '{}' cannot contain its own alarm '{alarm_id}'.",
+                        check.name
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checks(dependencies: &[(&str, &str, Option<&str>)]) -> Vec<config::Check> {
+        let mut toml = String::new();
+        for (check, alarm, depends_on_alarm_id) in dependencies {
+            toml.push_str(&format!(
+                "[[checks]]\nname = \"{check}\"\ntype = \"MemoryUsage\"\n[[checks.alarms]]\nname = \"{alarm}\"\naction = \"Foo\"\nlevel = 90\n"
+            ));
+            if let Some(depends_on_alarm_id) = depends_on_alarm_id {
+                toml.push_str(&format!(
+                    "depends_on_alarm_id = \"{depends_on_alarm_id}\"\n"
+                ));
+            }
+        }
+        toml::from_str::<config::Config>(&toml).unwrap().checks
+    }
+
+    #[test]
+    fn test_validate_dependencies() {
+        assert!(validate_dependencies(&checks(&[
+            ("A", "Foo", None),
+            ("B", "Bar", Some("A/Foo")),
+            ("C", "Baz", Some("B/Bar")),
+        ]))
+        .is_ok());
+        assert!(validate_dependencies(&checks(&[("A", "Foo", Some("B/Bar"))])).is_err());
+        assert!(validate_dependencies(&checks(&[("A", "Foo", Some("A/Foo"))])).is_err());
+        assert!(validate_dependencies(&checks(&[
+            ("A", "Foo", Some("C/Baz")),
+            ("B", "Bar", Some("A/Foo")),
+            ("C", "Baz", Some("B/Bar")),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_validate_alarm_summary() {
+        let validate = |alarm_ids: &str| {
+            let toml = format!(
+                "[[checks]]\nname = \"A\"\ntype = \"MemoryUsage\"\n[[checks.alarms]]\nname = \"Foo\"\naction = \"Foo\"\nlevel = 90\n\
+                 [[checks]]\nname = \"Service\"\ntype = \"AlarmSummary\"\nalarm_ids = [{alarm_ids}]\n[[checks.alarms]]\nname = \"Health\"\naction = \"Foo\"\n"
+            );
+            validate_dependencies(&toml::from_str::<config::Config>(&toml).unwrap().checks)
+        };
+        assert!(validate(r#""A/Foo""#).is_ok());
+        assert_eq!(
+            validate(r#""A/Foo", "B/Bar""#).unwrap_err().to_string(),
+            "Alarm 'B/Bar' of alarm summary 'Service' not found."
+        );
+        assert!(validate(r#""Service/Health""#).is_err());
+    }
+
+    #[test]
+    fn test_dependency_bad() {
+        let registry = AlarmRegistry::default();
+        let chain = AlarmChain::new(String::from("A/Foo"), registry.clone());
+        assert!(!chain.dependency_bad());
+        registry
+            .write()
+            .unwrap()
+            .insert(registry_key("A", "Foo", "/srv"), StateKind::Good);
+        registry
+            .write()
+            .unwrap()
+            .insert(registry_key("A", "FooBar", "/srv"), StateKind::Bad);
+        assert!(!chain.dependency_bad());
+        registry
+            .write()
+            .unwrap()
+            .insert(registry_key("A", "Foo", "/home"), StateKind::Bad);
+        assert!(chain.dependency_bad());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, MinMaxTracking, NumericSink, SinkDecision};
+use crate::config;
+
+type Item = measurement::ConsumerLag;
+
+type Inner = MinMaxTracking<NumericSink<u64>>;
+
+// Checks the total lag of all partitions like any other number. The lag per partition is only
+// added to the placeholders.
+pub struct ConsumerLag {
+    inner: Inner,
+}
+
+impl TryFrom<&config::Alarm> for ConsumerLag {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        Ok(Self {
+            inner: Inner::try_from(alarm)?,
+        })
+    }
+}
+
+impl DataSink for ConsumerLag {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        self.inner.put_data(&data.total())
+    }
+
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn unit(&self) -> &str {
+        self.inner.unit()
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        self.inner.format_data(&data.total())
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.inner.validate()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn describe_threshold(&self) -> String {
+        self.inner.describe_threshold()
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        self.inner.add_placeholders(&data.total(), placeholders);
+        let partitions: Vec<String> = data
+            .partitions()
+            .iter()
+            .map(|(partition, lag)| format!("{partition}: {lag}"))
+            .collect();
+        placeholders.insert(String::from("partition_lag"), partitions.join(", "));
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        self.inner.add_state_placeholders(placeholders);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::measurement::Measurement;
+
+    #[test]
+    fn test_consumer_lag() {
+        let alarm: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nlevel = 100").unwrap();
+        let mut alarm = ConsumerLag::try_from(&alarm).unwrap();
+        let lag = Item::new(vec![(1, 70), (0, 20)]).unwrap();
+        assert!(matches!(alarm.put_data(&lag).unwrap(), SinkDecision::Good));
+        let lag = Item::new(vec![(1, 70), (0, 40)]).unwrap();
+        assert!(matches!(alarm.put_data(&lag).unwrap(), SinkDecision::Bad));
+        assert_eq!(alarm.format_data(&lag), "110");
+        let mut placeholders = PlaceholderMap::new();
+        alarm.add_placeholders(&lag, &mut placeholders);
+        assert_eq!(placeholders["level"], "110");
+        assert_eq!(placeholders["partition_lag"], "0: 40, 1: 70");
+    }
+}
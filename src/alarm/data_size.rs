@@ -19,7 +19,9 @@ impl TryFrom<&config::Alarm> for DataSize {
                 data_size: Item::new(data_size.bytes())?,
             })
         } else {
-            Err(Error(String::from("Expected data size alarm config.")))
+            Err(Error::Message(String::from(
+                "Expected data size alarm config.",
+            )))
         }
     }
 }
@@ -35,7 +37,11 @@ impl DataSink for DataSize {
         })
     }
 
-    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
+    fn describe_threshold(&self) -> String {
+        format!("Bad when data size > {}", self.data_size)
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
         placeholders.insert(String::from("data_size"), data.to_string());
         placeholders.insert(String::from("data_size_bin"), data.as_string_binary());
         placeholders.insert(String::from("data_size_dec"), data.as_string_decimal());
@@ -0,0 +1,271 @@
+use crate::measurement::Measurement;
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+// Absolute difference that cannot overflow. `abs_diff` of the integer types would return the
+// unsigned type for signed integers.
+pub trait AbsDifference: Copy {
+    fn abs_difference(self, other: Self) -> Self;
+}
+
+macro_rules! impl_AbsDifference_unsigned {
+    ($T:ty) => {
+        impl AbsDifference for $T {
+            fn abs_difference(self, other: Self) -> Self {
+                self.saturating_sub(other).max(other.saturating_sub(self))
+            }
+        }
+    };
+}
+
+macro_rules! impl_AbsDifference_signed {
+    ($T:ty) => {
+        impl AbsDifference for $T {
+            fn abs_difference(self, other: Self) -> Self {
+                self.saturating_sub(other).saturating_abs()
+            }
+        }
+    };
+}
+
+macro_rules! impl_AbsDifference_float {
+    ($T:ty) => {
+        impl AbsDifference for $T {
+            fn abs_difference(self, other: Self) -> Self {
+                (self - other).abs()
+            }
+        }
+    };
+}
+
+impl_AbsDifference_unsigned!(u8);
+impl_AbsDifference_unsigned!(u16);
+impl_AbsDifference_unsigned!(u32);
+impl_AbsDifference_unsigned!(u64);
+impl_AbsDifference_signed!(i16);
+impl_AbsDifference_signed!(i32);
+impl_AbsDifference_signed!(i64);
+impl_AbsDifference_float!(f32);
+impl_AbsDifference_float!(f64);
+
+#[derive(Clone)]
+pub struct Delta<T = measurement::Level>
+where
+    T: Measurement + Copy + Send + Sync,
+    T::Data: AbsDifference + PartialOrd + std::fmt::Display,
+{
+    delta: T::Data,
+    last: Option<T>,
+    previous: Option<T>,
+}
+
+impl<T> Delta<T>
+where
+    T: Measurement + Copy + Send + Sync,
+    T::Data: AbsDifference + PartialOrd + std::fmt::Display + num_traits::NumCast,
+{
+    fn new(delta: f64) -> Result<Self> {
+        if super::needs_integer::<T::Data>(delta) {
+            return Err(Error::invalid_config(
+                "delta",
+                format!("{delta} needs to be an integer."),
+            ));
+        }
+        let delta: T::Data = num_traits::cast(delta)
+            .ok_or_else(|| Error::invalid_config("delta", format!("{delta} is out of range.")))?;
+        if delta.partial_cmp(&T::Data::default()) != Some(std::cmp::Ordering::Greater) {
+            return Err(Error::invalid_config("delta", "must be greater than 0."));
+        }
+        Ok(Self {
+            delta,
+            last: None,
+            previous: None,
+        })
+    }
+}
+
+impl<T> TryFrom<&config::Alarm> for Delta<T>
+where
+    T: Measurement + Copy + Send + Sync,
+    T::Data: AbsDifference + PartialOrd + std::fmt::Display + num_traits::NumCast,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        if let config::AlarmType::Delta(delta) = &alarm.type_ {
+            Self::new(delta.delta)
+        } else {
+            Err(Error::Message(String::from("Expected delta alarm config.")))
+        }
+    }
+}
+
+impl<T> DataSink for Delta<T>
+where
+    T: Measurement + Copy + Send + Sync,
+    T::Data: AbsDifference + PartialOrd + std::fmt::Display + Send + Sync,
+{
+    type Item = T;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        self.previous = self.last.replace(*data);
+        match self.previous {
+            // There is nothing to compare the first data point to.
+            None => Ok(SinkDecision::Good),
+            Some(previous) => match data
+                .data()
+                .abs_difference(previous.data())
+                .partial_cmp(&self.delta)
+            {
+                Some(std::cmp::Ordering::Greater) => Ok(SinkDecision::Bad),
+                Some(_) => Ok(SinkDecision::Good),
+                None => Err(Error::Message(format!(
+                    "Cannot compare {} to previous value {}.",
+                    data.format(),
+                    previous.format()
+                ))),
+            },
+        }
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+        self.previous = None;
+    }
+
+    fn describe_threshold(&self) -> String {
+        let delta = T::new(self.delta).map_or_else(|_| self.delta.to_string(), |x| x.format());
+        format!("Bad when level changes by more than {delta}")
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("level"), data.format());
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let (Some(last), Some(previous)) = (self.last, self.previous) {
+            let delta = last.data().abs_difference(previous.data());
+            placeholders.insert(String::from("level_previous"), previous.format());
+            placeholders.insert(
+                String::from("level_delta"),
+                T::new(delta).map_or_else(|_| delta.to_string(), |x| x.format()),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decisions<T>(delta: f64, data: &[T]) -> Vec<bool>
+    where
+        T: Measurement + Copy + Send + Sync,
+        T::Data: AbsDifference + PartialOrd + std::fmt::Display + num_traits::NumCast + Send + Sync,
+    {
+        let mut alarm = Delta::<T>::new(delta).unwrap();
+        data.iter()
+            .map(|x| matches!(alarm.put_data(x).unwrap(), SinkDecision::Bad))
+            .collect()
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(Delta::<u8>::new(0.).is_err());
+        assert!(Delta::<i32>::new(-1.).is_err());
+        assert!(Delta::<u8>::new(256.).is_err());
+        assert!(Delta::<f64>::new(f64::NAN).is_err());
+        assert!(matches!(
+            Delta::<u64>::new(1.5),
+            Err(Error::InvalidConfig { field, message })
+                if field == "delta" && message == "1.5 needs to be an integer."
+        ));
+        assert!(matches!(
+            Delta::<u64>::new(0.5),
+            Err(Error::InvalidConfig { field, message })
+                if field == "delta" && message == "0.5 needs to be an integer."
+        ));
+        assert!(Delta::<f32>::new(0.5).is_ok());
+    }
+
+    #[test]
+    fn test_first_call() {
+        assert_eq!(decisions(1., &[100u8]), [false]);
+        assert_eq!(decisions(1., &[-100i64]), [false]);
+        assert_eq!(decisions(0.5, &[1000f64]), [false]);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut alarm = Delta::<u8>::new(10.).unwrap();
+        alarm.put_data(&10).unwrap();
+        alarm.reset();
+        // Compared to nothing instead of the value before the reset.
+        assert!(matches!(alarm.put_data(&50).unwrap(), SinkDecision::Good));
+        let mut placeholders = PlaceholderMap::new();
+        alarm.add_state_placeholders(&mut placeholders);
+        assert!(placeholders.is_empty());
+        assert!(matches!(alarm.put_data(&80).unwrap(), SinkDecision::Bad));
+    }
+
+    #[test]
+    fn test_unsigned() {
+        assert_eq!(
+            decisions(10., &[50u8, 60, 71, 60, 0, 255]),
+            [false, false, true, true, true, true]
+        );
+        assert_eq!(
+            decisions(10., &[u64::MAX, 0, u64::MAX - 10]),
+            [false, true, true]
+        );
+        assert_eq!(
+            decisions(
+                10.,
+                &[
+                    measurement::Level::new(50).unwrap(),
+                    measurement::Level::new(39).unwrap()
+                ]
+            ),
+            [false, true]
+        );
+    }
+
+    #[test]
+    fn test_signed() {
+        assert_eq!(
+            decisions(10., &[0i32, -10, -21, -5, 5]),
+            [false, false, true, true, false]
+        );
+        assert_eq!(decisions(10., &[i64::MIN, i64::MAX]), [false, true]);
+    }
+
+    #[test]
+    fn test_float() {
+        assert_eq!(
+            decisions(0.5, &[1f64, 1.5, 0.9, -0.5]),
+            [false, false, true, true]
+        );
+        let mut alarm = Delta::<f64>::new(0.5).unwrap();
+        alarm.put_data(&1.).unwrap();
+        assert!(alarm.put_data(&f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut alarm = Delta::<measurement::Level>::new(10.).unwrap();
+        let mut placeholders = PlaceholderMap::new();
+        alarm
+            .put_data(&measurement::Level::new(50).unwrap())
+            .unwrap();
+        alarm.add_state_placeholders(&mut placeholders);
+        assert!(placeholders.is_empty());
+        alarm
+            .put_data(&measurement::Level::new(35).unwrap())
+            .unwrap();
+        alarm.add_state_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("level_previous").unwrap(), "50%");
+        assert_eq!(placeholders.get("level_delta").unwrap(), "15%");
+    }
+}
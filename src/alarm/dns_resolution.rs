@@ -0,0 +1,200 @@
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, Level, SinkDecision};
+use crate::config;
+
+type Item = measurement::DnsResolution;
+
+// Lets the alarms of the same check either watch the resolved addresses or the resolve time.
+pub enum DnsResolution {
+    // Bad if the name could not be resolved.
+    Resolve,
+    // Sorted and without duplicates, like the resolved addresses.
+    ExpectedIps(Vec<std::net::IpAddr>),
+    ResolveTime(Level<f64>),
+}
+
+impl TryFrom<&config::Alarm> for DnsResolution {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::Default(_) => Ok(Self::Resolve),
+            config::AlarmType::DnsResolution(dns_resolution) => {
+                if dns_resolution.expected_ips.is_empty() {
+                    return Err(Error::invalid_config("expected_ips", "cannot be empty."));
+                }
+                let mut expected_ips = dns_resolution.expected_ips.clone();
+                expected_ips.sort();
+                expected_ips.dedup();
+                Ok(Self::ExpectedIps(expected_ips))
+            }
+            config::AlarmType::Level(_) => Ok(Self::ResolveTime(Level::try_from(alarm)?)),
+            _ => Err(Error::Message(String::from(
+                "Expected DNS resolution or level alarm config.",
+            ))),
+        }
+    }
+}
+
+impl DataSink for DnsResolution {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        match self {
+            Self::Resolve => Ok(if data.addresses().is_empty() {
+                SinkDecision::Bad
+            } else {
+                SinkDecision::Good
+            }),
+            // The order of the addresses returned by resolvers is not stable, so the sets are
+            // compared.
+            Self::ExpectedIps(expected_ips) => Ok(if data.addresses() == &expected_ips[..] {
+                SinkDecision::Good
+            } else {
+                SinkDecision::Bad
+            }),
+            Self::ResolveTime(level) => level.put_data(&data.resolve_time()),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Resolve => String::from("dns_resolve"),
+            Self::ExpectedIps(_) => String::from("dns_expected_ips"),
+            Self::ResolveTime(level) => level.name(),
+        }
+    }
+
+    fn unit(&self) -> &str {
+        match self {
+            Self::Resolve | Self::ExpectedIps(_) => "",
+            Self::ResolveTime(level) => level.unit(),
+        }
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        match self {
+            Self::Resolve | Self::ExpectedIps(_) => data.to_string(),
+            Self::ResolveTime(level) => level.format_data(&data.resolve_time()),
+        }
+    }
+
+    fn describe_threshold(&self) -> String {
+        match self {
+            Self::Resolve => String::from("Bad when the name cannot be resolved"),
+            Self::ExpectedIps(expected_ips) => {
+                let expected_ips: Vec<String> =
+                    expected_ips.iter().map(|x| x.to_string()).collect();
+                format!("Bad when the addresses are not {}", expected_ips.join(", "))
+            }
+            Self::ResolveTime(level) => level.describe_threshold(),
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        let addresses: Vec<String> = data.addresses().iter().map(|x| x.to_string()).collect();
+        placeholders.insert(String::from("addresses"), addresses.join(", "));
+        placeholders.insert(
+            String::from("resolve_time"),
+            format!("{:.2}", data.resolve_time()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::measurement::Measurement;
+
+    fn resolution(addresses: &[&str], resolve_time: f64) -> Item {
+        Item::new((
+            addresses.iter().map(|x| x.parse().unwrap()).collect(),
+            resolve_time,
+        ))
+        .unwrap()
+    }
+
+    fn dns_resolution(config: &str) -> DnsResolution {
+        let config = format!("name = \"Alarm\"\naction = \"Action\"\n{config}");
+        DnsResolution::try_from(&toml::from_str::<config::Alarm>(&config).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut alarm = dns_resolution("");
+        assert!(matches!(
+            alarm.put_data(&resolution(&["127.0.0.1"], 1.0)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.put_data(&resolution(&[], 1.0)).unwrap(),
+            SinkDecision::Bad
+        ));
+    }
+
+    #[test]
+    fn test_expected_ips() {
+        let mut alarm = dns_resolution("expected_ips = [\"10.0.0.2\", \"::1\", \"10.0.0.1\"]");
+        assert!(matches!(
+            alarm
+                .put_data(&resolution(
+                    &["::1", "10.0.0.1", "10.0.0.2", "10.0.0.1"],
+                    1.0
+                ))
+                .unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm
+                .put_data(&resolution(&["10.0.0.1", "10.0.0.2"], 1.0))
+                .unwrap(),
+            SinkDecision::Bad
+        ));
+        assert!(matches!(
+            alarm
+                .put_data(&resolution(
+                    &["10.0.0.1", "10.0.0.2", "10.0.0.3", "::1"],
+                    1.0
+                ))
+                .unwrap(),
+            SinkDecision::Bad
+        ));
+        assert!(matches!(
+            alarm.put_data(&resolution(&[], 1.0)).unwrap(),
+            SinkDecision::Bad
+        ));
+        assert_eq!(
+            alarm.describe_threshold(),
+            "Bad when the addresses are not 10.0.0.1, 10.0.0.2, ::1"
+        );
+    }
+
+    #[test]
+    fn test_resolve_time() {
+        let mut alarm = dns_resolution("level = 100\nunit = \" ms\"");
+        assert!(matches!(
+            alarm.put_data(&resolution(&[], 50.0)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.put_data(&resolution(&["127.0.0.1"], 150.0)).unwrap(),
+            SinkDecision::Bad
+        ));
+        assert_eq!(
+            alarm.format_data(&resolution(&["127.0.0.1"], 150.0)),
+            "150.00 ms"
+        );
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut placeholders = PlaceholderMap::new();
+        dns_resolution("").add_placeholders(
+            &resolution(&["10.0.0.2", "10.0.0.1"], 12.345),
+            &mut placeholders,
+        );
+        assert_eq!(placeholders["addresses"], "10.0.0.1, 10.0.0.2");
+        assert_eq!(placeholders["resolve_time"], "12.35");
+    }
+}
@@ -0,0 +1,176 @@
+use crate::measurement::Measurement;
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+// Exponential moving average of the data. Smooths out short spikes like a moving average without
+// keeping the previous values. A higher `alpha` gives more weight to the latest value.
+#[derive(Clone)]
+pub struct Ema<T = measurement::Level>
+where
+    T: Measurement + Send + Sync,
+    T::Data: num_traits::ToPrimitive,
+{
+    alpha: f64,
+    ema_level: f64,
+    ema: Option<f64>,
+    _measurement: std::marker::PhantomData<T>,
+}
+
+impl<T> Ema<T>
+where
+    T: Measurement + Send + Sync,
+    T::Data: num_traits::ToPrimitive,
+{
+    fn new(alpha: f64, ema_level: f64) -> Result<Self> {
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            Err(Error::invalid_config(
+                "alpha",
+                "must be greater than 0 and at most 1.",
+            ))
+        } else if !ema_level.is_finite() {
+            Err(Error::invalid_config("ema_level", "must be finite."))
+        } else {
+            Ok(Self {
+                alpha,
+                ema_level,
+                ema: None,
+                _measurement: std::marker::PhantomData,
+            })
+        }
+    }
+}
+
+impl<T> TryFrom<&config::Alarm> for Ema<T>
+where
+    T: Measurement + Send + Sync,
+    T::Data: num_traits::ToPrimitive,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        if let config::AlarmType::Ema(ema) = &alarm.type_ {
+            Self::new(ema.alpha, ema.ema_level)
+        } else {
+            Err(Error::Message(String::from("Expected EMA alarm config.")))
+        }
+    }
+}
+
+impl<T> DataSink for Ema<T>
+where
+    T: Measurement + Send + Sync,
+    T::Data: num_traits::ToPrimitive + Send + Sync,
+{
+    type Item = T;
+
+    // The first value initializes the average.
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        let value = num_traits::ToPrimitive::to_f64(&data.data())
+            .filter(|x| x.is_finite())
+            .ok_or_else(|| Error::Message(format!("Cannot average {}.", data.format())))?;
+        let ema = match self.ema {
+            Some(ema) => self.alpha * value + (1.0 - self.alpha) * ema,
+            None => value,
+        };
+        self.ema = Some(ema);
+        Ok(if ema >= self.ema_level {
+            SinkDecision::Bad
+        } else {
+            SinkDecision::Good
+        })
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        match self.ema {
+            Some(ema) => format!("{} (EMA {ema:.2})", data.format()),
+            None => data.format(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.ema = None;
+    }
+
+    fn describe_threshold(&self) -> String {
+        format!(
+            "Bad when the exponential moving average (alpha {}) of level >= {}",
+            self.alpha, self.ema_level
+        )
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("level"), data.format());
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Some(ema) = self.ema {
+            placeholders.insert(String::from("level_ema"), format!("{ema:.2}"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn is_bad(ema: &mut Ema<f64>, value: f64) -> bool {
+        matches!(ema.put_data(&value).unwrap(), SinkDecision::Bad)
+    }
+
+    #[test]
+    fn test_ema() {
+        let mut ema = Ema::<f64>::new(0.5, 50.0).unwrap();
+        let mut averages = Vec::new();
+        for value in [10.0, 30.0, 80.0, 0.0] {
+            ema.put_data(&value).unwrap();
+            averages.push(ema.ema.unwrap());
+        }
+        // 10, 0.5 * 30 + 0.5 * 10, 0.5 * 80 + 0.5 * 20, 0.5 * 0 + 0.5 * 50
+        assert_eq!(averages, [10.0, 20.0, 50.0, 25.0]);
+        assert_eq!(ema.format_data(&0.0), "0.00 (EMA 25.00)");
+        let mut placeholders = PlaceholderMap::new();
+        ema.add_state_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("level_ema").unwrap(), "25.00");
+    }
+
+    #[test]
+    fn test_threshold() {
+        let mut ema = Ema::<f64>::new(0.5, 50.0).unwrap();
+        assert!(!is_bad(&mut ema, 40.0));
+        // A single spike is smoothed out.
+        assert!(!is_bad(&mut ema, 58.0));
+        // The average reaches the level exactly.
+        assert!(is_bad(&mut ema, 51.0));
+        assert!(is_bad(&mut ema, 60.0));
+        assert!(!is_bad(&mut ema, 20.0));
+        // After a reset, the next value starts over.
+        ema.reset();
+        assert!(ema.ema.is_none());
+        assert!(is_bad(&mut ema, 90.0));
+    }
+
+    #[test]
+    fn test_validation() {
+        for alpha in [0.0, -0.1, 1.1, f64::NAN] {
+            assert!(Ema::<f64>::new(alpha, 50.0).is_err());
+        }
+        assert!(Ema::<f64>::new(0.1, f64::NAN).is_err());
+        assert!(Ema::<f64>::new(1.0, 50.0).is_ok());
+        let mut ema = Ema::<f64>::new(0.1, 50.0).unwrap();
+        assert!(ema.put_data(&f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_config() {
+        let alarm: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nema_level = 80\nalpha = 0.25")
+                .unwrap();
+        let ema = Ema::<measurement::Level>::try_from(&alarm).unwrap();
+        assert_eq!(
+            ema.describe_threshold(),
+            "Bad when the exponential moving average (alpha 0.25) of level >= 80"
+        );
+    }
+}
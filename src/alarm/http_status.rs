@@ -0,0 +1,222 @@
+use crate::measurement::Measurement;
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, Level, Regex, SinkDecision};
+use crate::config;
+
+type Item = measurement::HttpResponse;
+
+// Lets the alarms of the same check watch different parts of the same response: the status code,
+// the response time or the body snippet.
+pub enum HttpStatus {
+    // Inclusive range of the good status codes.
+    StatusCodes(u16, u16),
+    ResponseTime(Level<f64>),
+    BodyPattern(Regex),
+}
+
+impl HttpStatus {
+    fn body(data: &Item) -> measurement::Text {
+        // Never fails for text.
+        measurement::Text::new(String::from(data.body_snippet())).unwrap()
+    }
+}
+
+impl TryFrom<&config::Alarm> for HttpStatus {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::Default(_) => {
+                let [min, max] = config::default::ALARM_HTTP_STATUS_STATUS_RANGE;
+                Ok(Self::StatusCodes(min, max))
+            }
+            config::AlarmType::HttpStatus(http_status) => {
+                let [min, max] = http_status.status_range;
+                if min > max {
+                    Err(Error::invalid_config(
+                        "status_range",
+                        "must be a range from the lowest to the highest good status code.",
+                    ))
+                } else {
+                    Ok(Self::StatusCodes(min, max))
+                }
+            }
+            config::AlarmType::Level(_) => Ok(Self::ResponseTime(Level::try_from(alarm)?)),
+            config::AlarmType::Regex(_) => Ok(Self::BodyPattern(Regex::try_from(alarm)?)),
+            _ => Err(Error::Message(String::from(
+                "Expected HTTP status, level or regex alarm config.",
+            ))),
+        }
+    }
+}
+
+impl DataSink for HttpStatus {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        match self {
+            Self::StatusCodes(min, max) => Ok(if (*min..=*max).contains(&data.status_code()) {
+                SinkDecision::Good
+            } else {
+                SinkDecision::Bad
+            }),
+            Self::ResponseTime(level) => level.put_data(&data.response_time()),
+            Self::BodyPattern(regex) => regex.put_data(&Self::body(data)),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::StatusCodes(..) => String::from("http_status"),
+            Self::ResponseTime(level) => level.name(),
+            Self::BodyPattern(regex) => regex.name(),
+        }
+    }
+
+    fn unit(&self) -> &str {
+        match self {
+            Self::StatusCodes(..) | Self::BodyPattern(_) => "",
+            Self::ResponseTime(level) => level.unit(),
+        }
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        match self {
+            Self::StatusCodes(..) => data.status_code().to_string(),
+            Self::ResponseTime(level) => level.format_data(&data.response_time()),
+            Self::BodyPattern(regex) => regex.format_data(&Self::body(data)),
+        }
+    }
+
+    fn reset(&mut self) {
+        if let Self::BodyPattern(regex) = self {
+            regex.reset();
+        }
+    }
+
+    fn describe_threshold(&self) -> String {
+        match self {
+            Self::StatusCodes(min, max) => {
+                format!("Bad when status code is not in {min}..={max}")
+            }
+            Self::ResponseTime(level) => level.describe_threshold(),
+            Self::BodyPattern(regex) => regex.describe_threshold().replacen("text", "body", 1),
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("status_code"), data.status_code().to_string());
+        placeholders.insert(
+            String::from("response_time"),
+            format!("{:.2}", data.response_time()),
+        );
+        placeholders.insert(
+            String::from("body_snippet"),
+            String::from(data.body_snippet()),
+        );
+    }
+
+    // The capture group `alarm_value` of the body pattern.
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Self::BodyPattern(regex) = self {
+            regex.add_state_placeholders(placeholders);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn response(status_code: u16, response_time: f64) -> Item {
+        Item::new((status_code, response_time, String::from("body"))).unwrap()
+    }
+
+    fn http_status(config: &str) -> HttpStatus {
+        let config = format!("name = \"Alarm\"\naction = \"Action\"\n{config}");
+        HttpStatus::try_from(&toml::from_str::<config::Alarm>(&config).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_status_codes() {
+        let mut alarm = http_status("");
+        assert!(matches!(
+            alarm.put_data(&response(200, 1.0)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.put_data(&response(299, 1.0)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.put_data(&response(500, 1.0)).unwrap(),
+            SinkDecision::Bad
+        ));
+        let mut alarm = http_status("status_range = [200, 399]");
+        assert!(matches!(
+            alarm.put_data(&response(301, 1.0)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert_eq!(
+            alarm.describe_threshold(),
+            "Bad when status code is not in 200..=399"
+        );
+    }
+
+    #[test]
+    fn test_response_time() {
+        let mut alarm = http_status("level = 100\nunit = \" ms\"");
+        assert!(matches!(
+            alarm.put_data(&response(500, 50.0)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.put_data(&response(200, 150.0)).unwrap(),
+            SinkDecision::Bad
+        ));
+        assert_eq!(alarm.format_data(&response(200, 150.0)), "150.00 ms");
+    }
+
+    #[test]
+    fn test_body_pattern() {
+        let mut alarm = http_status("pattern = 'maintenance until (?P<alarm_value>\\d+:\\d+)'");
+        let response = |body: &str| Item::new((200, 1.0, String::from(body))).unwrap();
+        assert!(matches!(
+            alarm.put_data(&response("OK")).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm
+                .put_data(&response("Down for maintenance until 14:30"))
+                .unwrap(),
+            SinkDecision::Bad
+        ));
+        let mut placeholders = PlaceholderMap::new();
+        alarm.add_state_placeholders(&mut placeholders);
+        assert_eq!(placeholders["alarm_value"], "14:30");
+        assert_eq!(alarm.format_data(&response("OK")), "OK");
+        assert_eq!(
+            alarm.describe_threshold(),
+            "Bad when body matches maintenance until (?P<alarm_value>\\d+:\\d+)"
+        );
+        let mut alarm = http_status("pattern = '^OK$'\nmatch_is_bad = false");
+        assert!(matches!(
+            alarm.put_data(&response("OK")).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.put_data(&response("starting")).unwrap(),
+            SinkDecision::Bad
+        ));
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut placeholders = PlaceholderMap::new();
+        http_status("").add_placeholders(&response(404, 12.345), &mut placeholders);
+        assert_eq!(placeholders["status_code"], "404");
+        assert_eq!(placeholders["response_time"], "12.35");
+        assert_eq!(placeholders["body_snippet"], "body");
+    }
+}
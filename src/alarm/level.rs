@@ -4,38 +4,193 @@ use crate::{measurement, Error, PlaceholderMap, Result};
 use super::{DataSink, SinkDecision};
 use crate::config;
 
-type Item = measurement::Level;
+#[derive(Clone)]
+pub struct Level<T = measurement::Level>
+where
+    T: Measurement + PartialOrd + Send + Sync,
+{
+    level: T,
+    unit: String,
+}
 
-pub struct Level {
-    level: Item,
+impl<T> Level<T>
+where
+    T: Measurement + PartialOrd + Send + Sync,
+    T::Data: num_traits::NumCast,
+{
+    fn new(level: f64) -> Result<Self> {
+        if super::needs_integer::<T::Data>(level) {
+            return Err(Error::invalid_config(
+                "level",
+                format!("{level} needs to be an integer."),
+            ));
+        }
+        let level = num_traits::cast(level)
+            .ok_or_else(|| Error::invalid_config("level", format!("{level} is out of range.")))?;
+        Ok(Self {
+            level: T::new(level)?,
+            unit: String::new(),
+        })
+    }
 }
 
-impl TryFrom<&config::Alarm> for Level {
+impl<T> TryFrom<&config::Alarm> for Level<T>
+where
+    T: Measurement + PartialOrd + Send + Sync,
+    T::Data: num_traits::NumCast,
+{
     type Error = Error;
 
     fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
         if let config::AlarmType::Level(level) = &alarm.type_ {
-            Ok(Self {
-                level: Item::new(level.level)?,
-            })
+            let mut sink = Self::new(level.level)?;
+            sink.unit = level.unit.clone();
+            Ok(sink)
         } else {
-            Err(Error(String::from("Expected level alarm config.")))
+            Err(Error::Message(String::from("Expected level alarm config.")))
         }
     }
 }
 
-impl DataSink for Level {
-    type Item = Item;
+impl<T> DataSink for Level<T>
+where
+    T: Measurement + PartialOrd + Send + Sync,
+{
+    type Item = T;
 
     fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
-        Ok(if *data > self.level {
-            SinkDecision::Bad
-        } else {
-            SinkDecision::Good
-        })
+        // Data that reaches the level is bad. -0.0 and 0.0 compare equal (IEEE 754), so -0.0 is bad
+        // for a level of 0.0 and vice versa.
+        match data.partial_cmp(&self.level) {
+            Some(std::cmp::Ordering::Greater | std::cmp::Ordering::Equal) => Ok(SinkDecision::Bad),
+            Some(_) => Ok(SinkDecision::Good),
+            None => Err(Error::Message(format!(
+                "Cannot compare {} to level {}.",
+                data.format(),
+                self.level.format()
+            ))),
+        }
+    }
+
+    fn describe_threshold(&self) -> String {
+        format!("Bad when level >= {}", self.format_data(&self.level))
+    }
+
+    fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("level"), data.format());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_level<T>(level: f64, good: T, bad: T)
+    where
+        T: Measurement + PartialOrd + Send + Sync,
+        T::Data: num_traits::NumCast,
+    {
+        let mut alarm = Level::<T>::new(level).unwrap();
+        assert!(matches!(alarm.put_data(&good).unwrap(), SinkDecision::Good));
+        let level = alarm.level.clone();
+        assert!(matches!(alarm.put_data(&level).unwrap(), SinkDecision::Bad));
+        assert!(matches!(alarm.put_data(&bad).unwrap(), SinkDecision::Bad));
+    }
+
+    #[test]
+    fn test_numeric_types() {
+        test_level(
+            50.,
+            measurement::Level::new(10).unwrap(),
+            measurement::Level::new(90).unwrap(),
+        );
+        test_level(50., 10u8, 90u8);
+        test_level(50., 10u16, 90u16);
+        test_level(50., 10u32, 90u32);
+        test_level(50., 10u64, 90u64);
+        test_level(-50., -90i32, -10i32);
+        test_level(-50., -90i64, -10i64);
+        test_level(0.5, 0.25f32, 0.75f32);
+        test_level(0.5, 0.25f64, 0.75f64);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(Level::<measurement::Level>::new(101.).is_err());
+        assert!(Level::<u8>::new(-1.).is_err());
+        assert!(Level::<u8>::new(256.).is_err());
+        assert!(Level::<f64>::new(f64::NAN).is_err());
+        assert!(matches!(
+            Level::<u8>::new(90.5),
+            Err(Error::InvalidConfig { field, message })
+                if field == "level" && message == "90.5 needs to be an integer."
+        ));
+        assert!(Level::<i64>::new(-0.1).is_err());
+        assert!(Level::<measurement::Level>::new(90.5).is_err());
+        assert!(Level::<f32>::new(0.1).is_ok());
+    }
+
+    #[test]
+    fn test_nan() {
+        let mut alarm = Level::<f64>::new(0.).unwrap();
+        assert!(alarm.put_data(&f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_negative_zero() {
+        // IEEE 754: -0.0 == 0.0, so -0.0 >= 0.0 holds.
+        let mut alarm = Level::<f64>::new(0.).unwrap();
+        assert!(matches!(alarm.put_data(&-0.).unwrap(), SinkDecision::Bad));
+        let mut alarm = Level::<f64>::new(-0.).unwrap();
+        assert!(matches!(alarm.put_data(&0.).unwrap(), SinkDecision::Bad));
+        assert!(matches!(alarm.put_data(&-0.1).unwrap(), SinkDecision::Good));
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut placeholders = PlaceholderMap::new();
+        Level::<f64>::new(10.)
+            .unwrap()
+            .add_placeholders(&12.3456, &mut placeholders);
+        assert_eq!(placeholders.get("level").unwrap(), "12.35");
+        Level::<measurement::Level>::new(90.)
+            .unwrap()
+            .add_placeholders(&measurement::Level::new(42).unwrap(), &mut placeholders);
+        assert_eq!(placeholders.get("level").unwrap(), "42%");
+    }
+
+    #[test]
+    fn test_describe_threshold() {
+        let alarm = Level::<measurement::Level>::new(90.).unwrap();
+        assert_eq!(alarm.describe_threshold(), "Bad when level >= 90%");
+    }
+
+    #[test]
+    fn test_unit() {
+        let config: config::Alarm = toml::from_str(
+            r#"
+            name = "Alarm"
+            action = "Action"
+            level = 70
+            unit = "°C"
+            "#,
+        )
+        .unwrap();
+        let alarm = Level::<f64>::try_from(&config).unwrap();
+        assert_eq!(alarm.format_data(&72.3), "72.30°C");
+        assert_eq!(alarm.describe_threshold(), "Bad when level >= 70.00°C");
+        assert_eq!(Level::<u8>::new(90.).unwrap().format_data(&10), "10");
     }
 
-    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
-        placeholders.insert(String::from("level"), data.to_string());
+    #[test]
+    fn test_name() {
+        assert_eq!(
+            Level::<measurement::Level>::new(90.).unwrap().name(),
+            "level"
+        );
     }
 }
@@ -0,0 +1,226 @@
+use crate::{Error, PlaceholderMap, Result};
+
+use super::{DataSink, DataSinkPlaceholders, SinkDecision};
+use crate::config;
+
+// Records the all-time minimum and maximum of the data passed to the wrapped data sink. The first
+// data point only initializes them, so it is never a new maximum.
+#[derive(Clone)]
+pub struct MinMaxTracking<S>
+where
+    S: DataSink,
+    S::Item: PartialOrd + Copy + std::fmt::Display,
+{
+    inner: S,
+    alert_on_new_max: bool,
+    min: Option<S::Item>,
+    max: Option<S::Item>,
+    last: Option<S::Item>,
+}
+
+impl<S> MinMaxTracking<S>
+where
+    S: DataSink,
+    S::Item: PartialOrd + Copy + std::fmt::Display,
+{
+    fn new(inner: S, alert_on_new_max: bool) -> Self {
+        Self {
+            inner,
+            alert_on_new_max,
+            min: None,
+            max: None,
+            last: None,
+        }
+    }
+}
+
+impl<S> TryFrom<&config::Alarm> for MinMaxTracking<S>
+where
+    S: DataSink + for<'a> TryFrom<&'a config::Alarm, Error = Error>,
+    S::Item: PartialOrd + Copy + std::fmt::Display,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        let alert_on_new_max = match &alarm.type_ {
+            config::AlarmType::Level(level) => level.alert_on_new_max,
+            config::AlarmType::Delta(delta) => delta.alert_on_new_max,
+            _ => false,
+        };
+        Ok(Self::new(S::try_from(alarm)?, alert_on_new_max))
+    }
+}
+
+impl<S> DataSinkPlaceholders<S::Item> for MinMaxTracking<S>
+where
+    S: DataSink,
+    S::Item: PartialOrd + Copy + std::fmt::Display,
+{
+    fn extra_placeholders(&self, data: &S::Item) -> PlaceholderMap {
+        let min = match self.min {
+            Some(min) if min < *data => min,
+            _ => *data,
+        };
+        let max = match self.max {
+            Some(max) if max > *data => max,
+            _ => *data,
+        };
+        PlaceholderMap::from([
+            (String::from("alarm_all_time_min"), min.to_string()),
+            (String::from("alarm_all_time_max"), max.to_string()),
+        ])
+    }
+}
+
+impl<S> DataSink for MinMaxTracking<S>
+where
+    S: DataSink,
+    S::Item: PartialOrd + Copy + std::fmt::Display,
+{
+    type Item = S::Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        let decision = self.inner.put_data(data)?;
+        let new_max = matches!(self.max, Some(max) if *data > max);
+        if self.min.is_none_or(|min| *data < min) {
+            self.min = Some(*data);
+        }
+        if self.max.is_none_or(|max| *data > max) {
+            self.max = Some(*data);
+        }
+        self.last = Some(*data);
+        Ok(if new_max && self.alert_on_new_max {
+            SinkDecision::Bad
+        } else {
+            decision
+        })
+    }
+
+    fn name(&self) -> String {
+        format!("min_max_tracking({})", self.inner.name())
+    }
+
+    fn unit(&self) -> &str {
+        self.inner.unit()
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        self.inner.format_data(data)
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.inner.validate()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.min = None;
+        self.max = None;
+        self.last = None;
+    }
+
+    fn describe_threshold(&self) -> String {
+        if self.alert_on_new_max {
+            format!(
+                "{} or on a new all-time maximum",
+                self.inner.describe_threshold()
+            )
+        } else {
+            self.inner.describe_threshold()
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        self.inner.add_placeholders(data, placeholders);
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        self.inner.add_state_placeholders(placeholders);
+        if let Some(last) = &self.last {
+            crate::merge_placeholders(placeholders, &self.extra_placeholders(last));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::MockDataSink;
+    use super::*;
+
+    fn tracking(alert_on_new_max: bool) -> MinMaxTracking<MockDataSink> {
+        let mut inner = MockDataSink::new();
+        inner.expect_put_data().returning(|x| {
+            Ok(if *x > 50 {
+                SinkDecision::Bad
+            } else {
+                SinkDecision::Good
+            })
+        });
+        inner.expect_add_state_placeholders().return_const(());
+        MinMaxTracking::new(inner, alert_on_new_max)
+    }
+
+    #[test]
+    fn test_min_max() {
+        let mut tracking = tracking(false);
+        let mut placeholders = PlaceholderMap::new();
+        tracking.add_state_placeholders(&mut placeholders);
+        assert!(placeholders.is_empty());
+        for (data, min, max) in [
+            (30, "30", "30"),
+            (20, "20", "30"),
+            (40, "20", "40"),
+            (35, "20", "40"),
+            (10, "10", "40"),
+            (90, "10", "90"),
+            (50, "10", "90"),
+        ] {
+            let decision = tracking.put_data(&data).unwrap();
+            assert_eq!(matches!(decision, SinkDecision::Bad), data > 50);
+            let mut placeholders = PlaceholderMap::new();
+            tracking.add_state_placeholders(&mut placeholders);
+            assert_eq!(placeholders.get("alarm_all_time_min").unwrap(), min);
+            assert_eq!(placeholders.get("alarm_all_time_max").unwrap(), max);
+        }
+        let placeholders = tracking.extra_placeholders(&5);
+        assert_eq!(placeholders.get("alarm_all_time_min").unwrap(), "5");
+        assert_eq!(placeholders.get("alarm_all_time_max").unwrap(), "90");
+    }
+
+    #[test]
+    fn test_alert_on_new_max() {
+        let mut tracking = tracking(true);
+        for (data, bad) in [
+            (30, false),
+            (30, false),
+            (40, true),
+            (20, false),
+            (45, true),
+            (60, true),
+            (55, true),
+            (10, false),
+        ] {
+            let decision = tracking.put_data(&data).unwrap();
+            assert_eq!(matches!(decision, SinkDecision::Bad), bad, "data {data}");
+        }
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut tracking = tracking(true);
+        tracking.inner.expect_reset().times(1).return_const(());
+        tracking.put_data(&40).unwrap();
+        tracking.reset();
+        let mut placeholders = PlaceholderMap::new();
+        tracking.add_state_placeholders(&mut placeholders);
+        assert!(placeholders.is_empty());
+        // The first value after the reset is not a new maximum, the next higher one is.
+        assert!(matches!(
+            tracking.put_data(&30).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(tracking.put_data(&35).unwrap(), SinkDecision::Bad));
+        let placeholders = tracking.extra_placeholders(&35);
+        assert_eq!(placeholders.get("alarm_all_time_min").unwrap(), "30");
+    }
+}
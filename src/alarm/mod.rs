@@ -1,36 +1,223 @@
 use crate::action;
+use crate::config;
+use crate::measurement;
+use crate::measurement::Measurement;
+use crate::ActionMap;
 use crate::{Error, PlaceholderMap, Result};
 use async_trait::async_trait;
 
+// Logs with the alarm's configured log level. Falls back to the given default level if the alarm
+// does not override it.
+macro_rules! alarm_log {
+    ($log_level:expr, $default:expr, $($arg:tt)+) => {
+        if let Some(level) = $log_level.unwrap_or($default.to_level_filter()).to_level() {
+            log::log!(level, $($arg)+);
+        }
+    };
+}
+
+mod adapter;
+mod alarm_summary;
 mod binary_state;
+mod chain;
+#[cfg(feature = "kafka")]
+mod consumer_lag;
 mod data_size;
+mod delta;
+mod dns_resolution;
+mod ema;
+#[cfg(feature = "http")]
+mod http_status;
 mod level;
+mod min_max_tracking;
+mod multi_threshold;
+mod numeric_sink;
+mod placeholder_source;
+mod port_scan;
+mod process_value;
+mod regex;
+mod router;
+mod sampling;
+mod snapshot;
 mod state_machine;
 mod status_code;
 #[cfg(feature = "sensors")]
 mod temperature;
+mod topology;
+mod trend;
+mod two_state;
+#[cfg(feature = "http")]
+mod value_pair;
+mod weighted_voting;
 
+pub use adapter::{map_sink, MappedDataSink};
+pub use alarm_summary::AlarmSummary;
 pub use binary_state::BinaryState;
+pub use chain::{registry_key, validate_dependencies, AlarmChain, AlarmRegistry, StateKind};
+#[cfg(feature = "kafka")]
+pub use consumer_lag::ConsumerLag;
 pub use data_size::DataSize;
+pub use delta::Delta;
+pub use dns_resolution::DnsResolution;
+pub use ema::Ema;
+#[cfg(feature = "http")]
+pub use http_status::HttpStatus;
 pub use level::Level;
-pub use state_machine::{StateHandler, StateMachine};
+pub use min_max_tracking::MinMaxTracking;
+pub use multi_threshold::MultiThreshold;
+pub use numeric_sink::NumericSink;
+pub use placeholder_source::{from_placeholder_source_config, PlaceholderSource};
+pub use port_scan::PortScan;
+pub use process_value::ProcessValue;
+pub use regex::Regex;
+pub use router::AlarmRouter;
+pub use sampling::Sampling;
+pub use snapshot::{AlarmSnapshot, ReloadState};
+pub use state_machine::{RepeatSchedule, StateHandler, StateMachine, StateMachineSnapshot};
 pub use status_code::StatusCode;
 #[cfg(feature = "sensors")]
 pub use temperature::Temperature;
+pub use topology::Topology;
+pub use trend::Trend;
+pub use two_state::TwoState;
+#[cfg(feature = "http")]
+pub use value_pair::ValuePair;
+pub use weighted_voting::WeightedVoting;
+
+// Whether the threshold of a sink for data of type `T` needs to be an integer. Casting a fraction
+// to an integer type truncates it, which would silently change the threshold.
+fn needs_integer<T: num_traits::NumCast>(threshold: f64) -> bool {
+    let integral = num_traits::cast::<f64, T>(0.5).and_then(num_traits::cast::<T, f64>) == Some(0.);
+    integral && threshold.fract() != 0.
+}
 
 #[cfg_attr(test, mockall::automock(type Item=u8;))]
-pub trait DataSink: Send + Sync + Sized {
-    type Item: Send + Sync;
+pub trait DataSink: Send + Sync {
+    type Item: Send + Sync + measurement::Measurement;
 
     fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision>;
-    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap);
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap);
+    // Human-readable description of when the data is bad, e.g. "Bad when level >= 90%".
+    fn describe_threshold(&self) -> String;
+
+    // Appended to the formatted data, e.g. "%" or "°C".
+    fn unit(&self) -> &str {
+        ""
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("{}{}", data.format(), self.unit())
+    }
+
+    // Placeholders that depend on the state of the data sink. Called after `put_data`.
+    fn add_state_placeholders(&self, _placeholders: &mut PlaceholderMap) {}
+
+    // Rejects settings that can never work, e.g. a NaN level. Called when the alarm is created.
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    // Forgets the data seen so far, e.g. the previous value of a delta. Called when the alarm
+    // starts over in the good state.
+    fn reset(&mut self) {}
+
+    // Name for log messages. Defaults to the type name in snake case without the module path and
+    // the generic parameters. Data sinks wrapping another one include its name, e.g. `map(level)`.
+    fn name(&self) -> String {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        let name = name.rsplit("::").next().unwrap_or(name);
+        let mut snake_case = String::new();
+        for (i, c) in name.chars().enumerate() {
+            if c.is_uppercase() {
+                if i > 0 {
+                    snake_case.push('_');
+                }
+                snake_case.extend(c.to_lowercase());
+            } else {
+                snake_case.push(c);
+            }
+        }
+        snake_case
+    }
+}
+
+// Data sink that is chosen at runtime, e.g. to swap the data sink of an alarm for another one with
+// the same item type.
+pub type BoxedDataSink<I> = Box<dyn DataSink<Item = I>>;
+
+// Not used by any check yet.
+#[allow(dead_code)]
+pub type DynAlarmBase<I> = AlarmBase<BoxedDataSink<I>>;
+
+impl<I> DataSink for BoxedDataSink<I>
+where
+    I: Send + Sync + measurement::Measurement,
+{
+    type Item = I;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        self.as_mut().put_data(data)
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        self.as_ref().add_placeholders(data, placeholders);
+    }
+
+    fn describe_threshold(&self) -> String {
+        self.as_ref().describe_threshold()
+    }
+
+    fn unit(&self) -> &str {
+        self.as_ref().unit()
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        self.as_ref().format_data(data)
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        self.as_ref().add_state_placeholders(placeholders);
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.as_ref().validate()
+    }
+
+    fn reset(&mut self) {
+        self.as_mut().reset();
+    }
+
+    fn name(&self) -> String {
+        self.as_ref().name()
+    }
+}
+
+// Placeholders of a data sink that are derived from its history and the given data.
+pub trait DataSinkPlaceholders<T> {
+    fn extra_placeholders(&self, data: &T) -> PlaceholderMap;
 }
 
+#[derive(Clone, Copy, Debug)]
 pub enum SinkDecision {
     Good,
     Bad,
 }
 
+impl std::str::FromStr for SinkDecision {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        match text.to_lowercase().as_str() {
+            "good" => Ok(SinkDecision::Good),
+            "bad" => Ok(SinkDecision::Bad),
+            _ => Err(Error::Message(format!(
+                "Expected 'good' or 'bad', got '{text}'."
+            ))),
+        }
+    }
+}
+
 impl std::ops::Not for SinkDecision {
     type Output = Self;
 
@@ -42,15 +229,217 @@ impl std::ops::Not for SinkDecision {
     }
 }
 
+// One cycle of a simulated alarm. `event` is the value of the `alarm_event` placeholder if the
+// transition has an event. `would_trigger` is only true if there is an action for the event.
+#[derive(Debug, PartialEq)]
+pub struct SimulationEvent {
+    pub cycle: usize,
+    pub state_before: &'static str,
+    pub state_after: &'static str,
+    pub event: Option<&'static str>,
+    pub would_trigger: bool,
+}
+
+impl std::fmt::Display for SimulationEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cycle {}: {} -> {}",
+            self.cycle, self.state_before, self.state_after
+        )?;
+        match (self.event, self.would_trigger) {
+            (Some(event), true) => write!(f, " ({event}, triggers action)"),
+            (Some(event), false) => write!(f, " ({event}, no action)"),
+            (None, _) => Ok(()),
+        }
+    }
+}
+
+// Recorded data for a replay, see `Alarm::replay`.
+pub struct HistoricalDataPoint<T> {
+    pub timestamp: std::time::SystemTime,
+    pub data: T,
+}
+
+// Data point of a replay that changed the state or triggered actions. `events` holds the value of
+// the `alarm_event` placeholder of each action that would have been triggered.
+#[derive(Debug, PartialEq)]
+pub struct ReplayEvent {
+    pub timestamp: std::time::SystemTime,
+    pub state_before: &'static str,
+    pub state_after: &'static str,
+    pub events: Vec<String>,
+    // Why the action was not triggered, only if nothing was triggered in the bad or error state.
+    pub reason: Option<String>,
+}
+
+impl std::fmt::Display for ReplayEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            crate::datetime_iso8601(self.timestamp),
+            self.state_before,
+            self.state_after
+        )?;
+        if let Some(reason) = &self.reason {
+            write!(f, " (no action: {reason})")
+        } else if self.events.is_empty() {
+            write!(f, " (no action)")
+        } else {
+            write!(f, " (triggers {})", self.events.join(", "))
+        }
+    }
+}
+
+// Counts how often the alarm changed to the bad state on a single bad cycle and recovered on the
+// very next cycle, which hints at a threshold that is too tight or at short spikes in the data.
+#[derive(Clone)]
+struct FastRecovery {
+    // Warns if there are more fast recoveries than this within an hour.
+    warn_count: u32,
+    recoveries: std::collections::VecDeque<tokio::time::Instant>,
+    // The decision of the previous data was bad.
+    previous_bad: bool,
+    // The previous data changed the state to bad after a single bad cycle.
+    single_cycle_bad: bool,
+}
+
+impl FastRecovery {
+    const PERIOD: std::time::Duration = std::time::Duration::from_secs(3600);
+
+    fn new(warn_count: u32) -> Self {
+        Self {
+            warn_count,
+            recoveries: std::collections::VecDeque::new(),
+            previous_bad: false,
+            single_cycle_bad: false,
+        }
+    }
+
+    // Returns the number of fast recoveries within the last hour if that is more than
+    // `warn_count`. The count starts over after a warning.
+    fn put_data(
+        &mut self,
+        decision: SinkDecision,
+        was_bad: bool,
+        is_bad: bool,
+        now: tokio::time::Instant,
+    ) -> Option<usize> {
+        let recovered = was_bad && !is_bad && self.single_cycle_bad;
+        self.single_cycle_bad = !was_bad && is_bad && !self.previous_bad;
+        self.previous_bad = matches!(decision, SinkDecision::Bad);
+        if !recovered {
+            return None;
+        }
+        self.recoveries.push_back(now);
+        while matches!(self.recoveries.front(), Some(x) if now.duration_since(*x) > Self::PERIOD) {
+            self.recoveries.pop_front();
+        }
+        let count = self.recoveries.len();
+        if count > self.warn_count as usize {
+            self.recoveries.clear();
+            Some(count)
+        } else {
+            None
+        }
+    }
+}
+
+// Set while an alarm replays recorded data. The clock replaces the current time and the actions
+// are recorded instead of being triggered.
+struct Replay {
+    clock: tokio::time::Instant,
+    events: std::sync::Mutex<Vec<String>>,
+}
+
+// State change of an alarm, passed to its state change callbacks. `alarm_id` is the ID of the
+// check that the alarm is instantiated for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StateTransition<'a> {
+    pub from: StateKind,
+    pub to: StateKind,
+    pub alarm_id: &'a str,
+    pub alarm_name: &'a str,
+    pub timestamp: std::time::SystemTime,
+}
+
+// Called synchronously after every state change, also if no action is triggered. Meant for cheap
+// local bookkeeping, anything slow belongs into an action.
+pub type StateChangeCallback = std::sync::Arc<dyn Fn(StateTransition<'_>) + Send + Sync>;
+
+// Documentation of an alarm from the config. It does not change how the alarm behaves.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AlarmMetadata {
+    pub description: String,
+    pub severity: config::Severity,
+    pub runbook_url: Option<String>,
+    pub owner: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl TryFrom<&config::Alarm> for AlarmMetadata {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        if let Some(tag) = alarm.tags.iter().find(|x| !config::is_valid_tag(x)) {
+            return Err(Error::Message(format!(
+                "Tag '{tag}' may only contain letters, digits and hyphens."
+            )));
+        }
+        Ok(Self {
+            description: alarm.description.clone(),
+            severity: alarm.severity,
+            runbook_url: alarm.runbook_url.clone(),
+            owner: alarm.owner.clone(),
+            tags: alarm.tags.clone(),
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AlarmDescriptor {
+    pub name: String,
+    pub id: String,
+    pub description: String,
+    pub severity: config::Severity,
+    pub runbook_url: Option<String>,
+    pub owner: Option<String>,
+    pub tags: Vec<String>,
+    pub threshold_summary: String,
+}
+
 #[async_trait]
 pub trait Alarm: Send + Sync + Sized {
     type Item: Send + Sync;
 
     fn log_id(&self) -> &str;
+    fn to_dot_graph(&self) -> String;
+    fn describe(&self) -> AlarmDescriptor;
+    // Runs the decisions through a copy of the state machine in its initial state. No actions are
+    // triggered and the alarm's own state is not changed.
+    fn simulate(&self, decisions: &[SinkDecision]) -> Vec<SimulationEvent>;
+    // Feeds the data through the data sink and the state machine as if it had arrived at the
+    // recorded times. No actions are triggered. Unlike `simulate`, this changes the state of the
+    // alarm and its data sink, so it is only meant for alarms that are not run afterwards.
+    async fn replay(
+        &mut self,
+        data: &[HistoricalDataPoint<Self::Item>],
+    ) -> Result<Vec<ReplayEvent>>;
+    fn to_snapshot(&self) -> Result<AlarmSnapshot>;
+    // Replaces the alarm by the one described by the snapshot keeping the data sink.
+    fn restore(self, snapshot: AlarmSnapshot, actions: &ActionMap) -> Result<Self>;
 
+    // The placeholder map passed by the check already holds the global placeholders and the ones of
+    // the check (`check_name` and the custom placeholders of the check). The alarm adds the ones
+    // of the data sink, `alarm_name`, `check_id` (the alarm is instantiated per ID) and its custom
+    // placeholders. `put_error` gets `check_error` instead of the data sink's placeholders.
     async fn put_data(&mut self, data: &Self::Item, mut placeholders: PlaceholderMap)
         -> Result<()>;
     async fn put_error(&mut self, error: &Error, mut placeholders: PlaceholderMap) -> Result<()>;
+    // Called when there is neither data nor an error, e.g. while the check is warming up. Only
+    // time based transitions (the auto recovery) can happen.
+    async fn tick(&mut self, mut placeholders: PlaceholderMap) -> Result<()>;
 }
 
 pub struct AlarmBase<T, U = StateMachine>
@@ -62,6 +451,7 @@ where
     id: String,
     action: std::sync::Arc<dyn action::Action>,
     placeholders: PlaceholderMap,
+    escalation: Vec<(u32, std::sync::Arc<dyn action::Action>)>,
     recover_action: Option<std::sync::Arc<dyn action::Action>>,
     recover_placeholders: PlaceholderMap,
     error_action: Option<std::sync::Arc<dyn action::Action>>,
@@ -69,9 +459,39 @@ where
     error_recover_action: Option<std::sync::Arc<dyn action::Action>>,
     error_recover_placeholders: PlaceholderMap,
     invert: bool,
+    disabled: bool,
+    cooldown: Option<std::time::Duration>,
+    last_trigger: Option<tokio::time::Instant>,
+    last_error_trigger: Option<tokio::time::Instant>,
+    auto_recovery: Option<std::time::Duration>,
+    bad_since: Option<tokio::time::Instant>,
     state_machine: U,
     data_sink: T,
     log_id: String,
+    log_level: Option<log::LevelFilter>,
+    metadata: AlarmMetadata,
+    warn_if_never_bad_cycles: Option<u32>,
+    warn_action: Option<std::sync::Arc<dyn action::Action>>,
+    // Consecutive good cycles since the last bad cycle.
+    never_bad_cycles: u32,
+    max_error_cycles: Option<u32>,
+    fast_recovery: Option<FastRecovery>,
+    // Actions are suppressed during the first `warm_up_cycles` cycles (data or error).
+    warm_up_cycles: u32,
+    cycles: u32,
+    // The trigger action was suppressed by the warm-up and the alarm is still bad.
+    trigger_suppressed: bool,
+    // Why the last data or error did not trigger the action or the error action.
+    last_non_trigger_reason: Option<String>,
+    // The last data formatted by the data sink, also used for the placeholders of errors.
+    last_value: Option<String>,
+    chain: Option<AlarmChain>,
+    // Called for the placeholders of every action that is triggered.
+    placeholder_sources: Vec<std::sync::Arc<dyn PlaceholderSource>>,
+    // The registry and the key under which the state of the alarm is published.
+    registry: Option<(AlarmRegistry, String)>,
+    state_change_callbacks: Vec<StateChangeCallback>,
+    replay: Option<Replay>,
 }
 
 impl<T, U> AlarmBase<T, U>
@@ -79,56 +499,577 @@ where
     T: DataSink,
     U: StateHandler,
 {
-    #[allow(clippy::too_many_arguments)]
+    // The optional settings have their own setters, e.g. `set_recover_action`.
     pub fn new(
         name: String,
         id: String,
         action: std::sync::Arc<dyn action::Action>,
         placeholders: PlaceholderMap,
-        recover_action: Option<std::sync::Arc<dyn action::Action>>,
-        recover_placeholders: PlaceholderMap,
-        error_action: Option<std::sync::Arc<dyn action::Action>>,
-        error_placeholders: PlaceholderMap,
-        error_recover_action: Option<std::sync::Arc<dyn action::Action>>,
-        error_recover_placeholders: PlaceholderMap,
-        invert: bool,
         state_machine: U,
         data_sink: T,
         log_id: String,
     ) -> Result<Self> {
         if name.is_empty() {
-            Err(Error(String::from("'name' cannot be empty.")))
+            Err(Error::invalid_config("name", "cannot be empty."))
+        } else if let Err(err) = data_sink.validate() {
+            Err(Error::Message(format!("Alarm '{name}': {err}")))
         } else {
             Ok(Self {
                 name,
                 id,
                 action,
                 placeholders,
-                recover_action,
-                recover_placeholders,
-                error_action,
-                error_placeholders,
-                error_recover_action,
-                error_recover_placeholders,
-                invert,
+                escalation: Vec::new(),
+                recover_action: None,
+                recover_placeholders: PlaceholderMap::new(),
+                error_action: None,
+                error_placeholders: PlaceholderMap::new(),
+                error_recover_action: None,
+                error_recover_placeholders: PlaceholderMap::new(),
+                invert: false,
+                disabled: false,
+                cooldown: None,
+                last_trigger: None,
+                last_error_trigger: None,
+                auto_recovery: None,
+                bad_since: None,
                 state_machine,
                 data_sink,
                 log_id,
+                log_level: None,
+                metadata: AlarmMetadata::default(),
+                warn_if_never_bad_cycles: None,
+                warn_action: None,
+                never_bad_cycles: 0,
+                max_error_cycles: None,
+                fast_recovery: None,
+                warm_up_cycles: 0,
+                cycles: 0,
+                trigger_suppressed: false,
+                last_non_trigger_reason: None,
+                last_value: None,
+                chain: None,
+                placeholder_sources: Vec::new(),
+                registry: None,
+                state_change_callbacks: Vec::new(),
+                replay: None,
             })
         }
     }
 
+    pub fn from_snapshot(
+        snapshot: AlarmSnapshot,
+        data_sink: T,
+        actions: &ActionMap,
+    ) -> Result<Self> {
+        snapshot.check_version()?;
+        let get_optional_action = |action: &Option<String>| match action {
+            Some(action) => action::get_action(action, actions).map(Some),
+            None => Ok(None),
+        };
+        let mut alarm = Self::new(
+            snapshot.name,
+            snapshot.id,
+            action::get_action(&snapshot.action, actions)?,
+            snapshot.placeholders,
+            U::from_snapshot(
+                snapshot.state_machine,
+                snapshot.log_id.clone(),
+                snapshot.log_level,
+            )?,
+            data_sink,
+            snapshot.log_id,
+        )?;
+        alarm.set_escalation(
+            snapshot
+                .escalation
+                .iter()
+                .map(|(cycles, action)| Ok((*cycles, action::get_action(action, actions)?)))
+                .collect::<Result<_>>()?,
+        )?;
+        alarm.set_recover_action(
+            get_optional_action(&snapshot.recover_action)?,
+            snapshot.recover_placeholders,
+        );
+        alarm.set_error_action(
+            get_optional_action(&snapshot.error_action)?,
+            snapshot.error_placeholders,
+        );
+        alarm.set_error_recover_action(
+            get_optional_action(&snapshot.error_recover_action)?,
+            snapshot.error_recover_placeholders,
+        );
+        alarm.set_invert(snapshot.invert);
+        alarm.set_cooldown(snapshot.cooldown)?;
+        alarm.set_auto_recovery(snapshot.auto_recovery)?;
+        alarm.set_log_level(snapshot.log_level);
+        alarm.disabled = snapshot.disabled;
+        Ok(alarm)
+    }
+
+    // Copy of the alarm with the same configuration but in the initial state, e.g. for another ID
+    // of the same check.
+    pub fn clone_reset(&self, name: String, id: String, log_id: String, data_sink: T) -> Self {
+        Self {
+            name,
+            id,
+            action: self.action.clone(),
+            placeholders: self.placeholders.clone(),
+            escalation: self.escalation.clone(),
+            recover_action: self.recover_action.clone(),
+            recover_placeholders: self.recover_placeholders.clone(),
+            error_action: self.error_action.clone(),
+            error_placeholders: self.error_placeholders.clone(),
+            error_recover_action: self.error_recover_action.clone(),
+            error_recover_placeholders: self.error_recover_placeholders.clone(),
+            invert: self.invert,
+            disabled: self.disabled,
+            cooldown: self.cooldown,
+            last_trigger: None,
+            last_error_trigger: None,
+            auto_recovery: self.auto_recovery,
+            bad_since: None,
+            state_machine: self.state_machine.clone_reset(log_id.clone()),
+            data_sink,
+            log_id,
+            log_level: self.log_level,
+            metadata: self.metadata.clone(),
+            warn_if_never_bad_cycles: self.warn_if_never_bad_cycles,
+            warn_action: self.warn_action.clone(),
+            never_bad_cycles: 0,
+            max_error_cycles: self.max_error_cycles,
+            fast_recovery: self
+                .fast_recovery
+                .as_ref()
+                .map(|x| FastRecovery::new(x.warn_count)),
+            warm_up_cycles: self.warm_up_cycles,
+            cycles: 0,
+            trigger_suppressed: false,
+            last_non_trigger_reason: None,
+            last_value: None,
+            chain: self.chain.clone(),
+            placeholder_sources: self.placeholder_sources.clone(),
+            // The registry key depends on the ID.
+            registry: None,
+            // The callbacks are registered per ID, like the registry.
+            state_change_callbacks: Vec::new(),
+            replay: None,
+        }
+    }
+
+    // The placeholders of the alarm override the ones of its group.
+    pub fn set_group_placeholders(&mut self, group_placeholders: PlaceholderMap) {
+        for (key, value) in group_placeholders {
+            self.placeholders.entry(key).or_insert(value);
+        }
+    }
+
+    pub fn set_escalation(
+        &mut self,
+        mut escalation: Vec<(u32, std::sync::Arc<dyn action::Action>)>,
+    ) -> Result<()> {
+        escalation.sort_by_key(|(cycles, _)| *cycles);
+        if escalation.iter().any(|(cycles, _)| *cycles <= 1) {
+            Err(Error::invalid_config(
+                "cycles",
+                "of escalation must be greater than 1.",
+            ))
+        } else if escalation.windows(2).any(|x| x[0].0 == x[1].0) {
+            Err(Error::invalid_config(
+                "cycles",
+                "of escalation must be unique.",
+            ))
+        } else {
+            self.escalation = escalation;
+            Ok(())
+        }
+    }
+
+    pub fn set_recover_action(
+        &mut self,
+        action: Option<std::sync::Arc<dyn action::Action>>,
+        placeholders: PlaceholderMap,
+    ) {
+        self.recover_action = action;
+        self.recover_placeholders = placeholders;
+    }
+
+    pub fn set_error_action(
+        &mut self,
+        action: Option<std::sync::Arc<dyn action::Action>>,
+        placeholders: PlaceholderMap,
+    ) {
+        self.error_action = action;
+        self.error_placeholders = placeholders;
+    }
+
+    pub fn set_error_recover_action(
+        &mut self,
+        action: Option<std::sync::Arc<dyn action::Action>>,
+        placeholders: PlaceholderMap,
+    ) {
+        self.error_recover_action = action;
+        self.error_recover_placeholders = placeholders;
+    }
+
+    pub fn set_invert(&mut self, invert: bool) {
+        self.invert = invert;
+    }
+
+    pub fn set_cooldown(&mut self, cooldown: Option<std::time::Duration>) -> Result<()> {
+        if matches!(cooldown, Some(cooldown) if cooldown.is_zero()) {
+            Err(Error::invalid_config("cooldown", "cannot be 0."))
+        } else {
+            self.cooldown = cooldown;
+            Ok(())
+        }
+    }
+
+    pub fn set_auto_recovery(&mut self, auto_recovery: Option<std::time::Duration>) -> Result<()> {
+        if matches!(auto_recovery, Some(auto_recovery) if auto_recovery.is_zero()) {
+            Err(Error::invalid_config("auto_recovery", "cannot be 0."))
+        } else {
+            self.auto_recovery = auto_recovery;
+            Ok(())
+        }
+    }
+
+    pub fn set_log_level(&mut self, log_level: Option<log::LevelFilter>) {
+        self.log_level = log_level;
+    }
+
+    // A disabled alarm ignores all data and errors. When it is enabled again, it starts over in the
+    // good state because the state it was in before is most likely outdated.
+    pub fn set_disabled(&mut self, disabled: bool) {
+        if self.disabled && !disabled {
+            self.reset_state();
+        }
+        self.disabled = disabled;
+        self.publish_state();
+    }
+
+    // Starts over in the good state, including the data sink, so that data from before does not
+    // influence the next decisions.
+    fn reset_state(&mut self) {
+        self.state_machine.reset();
+        self.bad_since = None;
+        self.data_sink.reset();
+    }
+
+    pub fn set_metadata(&mut self, metadata: AlarmMetadata) {
+        self.metadata = metadata;
+    }
+
+    // Warns if the data has not been bad for the given number of consecutive cycles, e.g. because
+    // the level of the alarm is too high to ever be reached.
+    pub fn set_never_bad_warning(
+        &mut self,
+        cycles: Option<u32>,
+        action: Option<std::sync::Arc<dyn action::Action>>,
+    ) -> Result<()> {
+        match (cycles, &action) {
+            (Some(0), _) => Err(Error::invalid_config(
+                "warn_if_never_bad_cycles",
+                "cannot be 0.",
+            )),
+            (None, Some(_)) => Err(Error::invalid_config(
+                "warn_action",
+                "requires 'warn_if_never_bad_cycles'.",
+            )),
+            _ => {
+                self.warn_if_never_bad_cycles = cycles;
+                self.warn_action = action;
+                Ok(())
+            }
+        }
+    }
+
+    // Fails open: after the given number of error cycles, the alarm goes back to the good state
+    // regardless of the data, e.g. for a check that is known to fail temporarily.
+    pub fn set_max_error_cycles(&mut self, max_error_cycles: Option<u32>) -> Result<()> {
+        if max_error_cycles == Some(0) {
+            Err(Error::invalid_config("max_error_cycles", "cannot be 0."))
+        } else {
+            self.max_error_cycles = max_error_cycles;
+            Ok(())
+        }
+    }
+
+    // Warns if the alarm recovers on the cycle after it changed to the bad state on a single bad
+    // cycle more than `warn_count` times within an hour.
+    pub fn set_fast_recovery_warning(&mut self, warn_count: Option<u32>) {
+        self.fast_recovery = warn_count.map(FastRecovery::new);
+    }
+
+    // Lets data sinks with a window fill it before their decisions lead to actions. The state
+    // changes as usual during the warm-up.
+    pub fn set_warm_up_cycles(&mut self, warm_up_cycles: u32) {
+        self.warm_up_cycles = warm_up_cycles;
+    }
+
+    fn warming_up(&self) -> bool {
+        self.warm_up_cycles > 0 && self.cycles <= self.warm_up_cycles
+    }
+
+    fn warm_up_reason(&self) -> String {
+        format!(
+            "Alarm is in warm-up period (cycle {} of {})",
+            self.cycles, self.warm_up_cycles
+        )
+    }
+
+    // Meant for finding out why an alarm did not notify. `None` if the last data or error
+    // triggered the action or the error action.
+    pub fn explain_last_non_trigger(&self) -> Option<String> {
+        self.last_non_trigger_reason.clone()
+    }
+
+    // Remaining time of the cooldown, which has to be active.
+    fn cooldown_remaining(&self, error: bool) -> String {
+        let last_trigger = if error {
+            self.last_error_trigger
+        } else {
+            self.last_trigger
+        };
+        let remaining = match (self.cooldown, last_trigger) {
+            (Some(cooldown), Some(last)) => {
+                cooldown.saturating_sub(self.now().duration_since(last))
+            }
+            _ => std::time::Duration::ZERO,
+        };
+        format!(
+            "Action suppressed by cooldown (next eligible in {})",
+            crate::format_duration(remaining.as_secs())
+        )
+    }
+
+    pub fn set_chain(&mut self, chain: Option<AlarmChain>) {
+        self.chain = chain;
+    }
+
+    // The initial state is published right away, the state changes by a callback.
+    pub fn set_registry(&mut self, registry: AlarmRegistry, key: String) {
+        self.registry = Some((registry.clone(), key.clone()));
+        self.publish_state();
+        self.on_state_change(std::sync::Arc::new(move |transition| {
+            registry.write().unwrap().insert(key.clone(), transition.to);
+        }));
+    }
+
+    pub fn on_state_change(&mut self, callback: StateChangeCallback) {
+        self.state_change_callbacks.push(callback);
+    }
+
+    // None without callbacks, so that the state is only looked up if somebody is interested.
+    fn observed_state(&self) -> Option<StateKind> {
+        (!self.state_change_callbacks.is_empty())
+            .then(|| StateKind::from_state_name(self.state_machine.state_name()))
+    }
+
+    // Calls the state change callbacks if the state is not `from` anymore. Returns the current
+    // state.
+    fn notify_state_change(&self, from: Option<StateKind>) -> Option<StateKind> {
+        let (from, to) = (from?, self.observed_state()?);
+        if to != from {
+            let transition = StateTransition {
+                from,
+                to,
+                alarm_id: &self.id,
+                alarm_name: &self.name,
+                timestamp: std::time::SystemTime::now(),
+            };
+            for callback in self.state_change_callbacks.iter() {
+                callback(transition);
+            }
+        }
+        Some(to)
+    }
+
+    // Disabled alarms are removed from the registry so that they do not count as bad.
+    fn publish_state(&self) {
+        if let Some((registry, key)) = &self.registry {
+            let mut registry = registry.write().unwrap();
+            if self.disabled {
+                registry.remove(key);
+            } else {
+                registry.insert(
+                    key.clone(),
+                    StateKind::from_state_name(self.state_machine.state_name()),
+                );
+            }
+        }
+    }
+
+    // The replay clock while replaying, the current time otherwise.
+    fn now(&self) -> tokio::time::Instant {
+        self.replay
+            .as_ref()
+            .map_or_else(tokio::time::Instant::now, |replay| replay.clock)
+    }
+
+    pub fn add_placeholder_source(&mut self, source: std::sync::Arc<dyn PlaceholderSource>) {
+        self.placeholder_sources.push(source);
+    }
+
+    // Initial state for stepping through a simulation with `simulate_cycle`.
+    pub fn simulation_state(&self) -> U {
+        self.state_machine
+            .clone_reset(format!("{} (simulation)", self.log_id))
+    }
+
+    // Advances the given simulation state by one cycle with the decision of the data sink. Neither
+    // the alarm nor its state machine are changed and no action is triggered, so stepping through
+    // the same decisions always gives the same events. The state machine only counts cycles, so
+    // the result does not depend on the time.
+    pub fn simulate_cycle(
+        &self,
+        state_machine: &mut U,
+        cycle: usize,
+        decision: SinkDecision,
+    ) -> SimulationEvent {
+        let state_before = state_machine.state_name();
+        let decision = if self.invert { !decision } else { decision };
+        let (event, would_trigger) = match decision {
+            SinkDecision::Good => match state_machine.good() {
+                (true, _) => (Some("recover"), self.recover_action.is_some()),
+                _ => (None, false),
+            },
+            SinkDecision::Bad => match state_machine.bad() {
+                (true, _) => (Some("trigger"), true),
+                _ => (None, false),
+            },
+        };
+        SimulationEvent {
+            cycle,
+            state_before,
+            state_after: state_machine.state_name(),
+            event,
+            would_trigger,
+        }
+    }
+
+    async fn trigger_action(
+        &self,
+        action: &std::sync::Arc<dyn action::Action>,
+        mut placeholders: PlaceholderMap,
+    ) -> Result<()> {
+        if self.warming_up() {
+            alarm_log!(
+                self.log_level,
+                log::Level::Info,
+                "{}: Warming up, not triggering {} action.",
+                self.log_id,
+                placeholders["alarm_event"]
+            );
+            return Ok(());
+        }
+        match &self.replay {
+            Some(replay) => {
+                replay
+                    .events
+                    .lock()
+                    .unwrap()
+                    .push(placeholders["alarm_event"].clone());
+                Ok(())
+            }
+            None => {
+                // A missing placeholder is better than a missing action.
+                for source in self.placeholder_sources.iter() {
+                    if let Err(err) = source.provide(&mut placeholders) {
+                        alarm_log!(
+                            self.log_level,
+                            log::Level::Warn,
+                            "{}: Could not provide placeholders: {}",
+                            self.log_id,
+                            err
+                        );
+                    }
+                }
+                action.trigger(placeholders).await
+            }
+        }
+    }
+
+    fn warn_fast_recovery(&mut self, decision: SinkDecision, was_bad: bool) {
+        let now = self.now();
+        let Some(fast_recovery) = &mut self.fast_recovery else {
+            return;
+        };
+        let is_bad = self.state_machine.bad_cycles().is_some();
+        if let Some(count) = fast_recovery.put_data(decision, was_bad, is_bad, now) {
+            alarm_log!(
+                self.log_level,
+                log::Level::Warn,
+                "{}: Recovered on the cycle after triggering {} times within an hour. Consider increasing 'cycles' or 'recover_cycles'.",
+                self.log_id,
+                count
+            );
+        }
+    }
+
+    async fn count_never_bad(
+        &mut self,
+        decision: SinkDecision,
+        mut placeholders: PlaceholderMap,
+    ) -> Result<()> {
+        let Some(warn_if_never_bad_cycles) = self.warn_if_never_bad_cycles else {
+            return Ok(());
+        };
+        self.never_bad_cycles = match decision {
+            SinkDecision::Good => self.never_bad_cycles.saturating_add(1),
+            SinkDecision::Bad => 0,
+        };
+        if self.never_bad_cycles != warn_if_never_bad_cycles {
+            return Ok(());
+        }
+        alarm_log!(
+            self.log_level,
+            log::Level::Warn,
+            "{}: Data has not been bad for {} cycles. The threshold might never be reached.",
+            self.log_id,
+            self.never_bad_cycles
+        );
+        self.state_machine.add_placeholders(&mut placeholders);
+        placeholders.insert(String::from("alarm_event"), String::from("never_bad"));
+        placeholders.insert(
+            String::from("alarm_never_bad_cycles"),
+            self.never_bad_cycles.to_string(),
+        );
+        match &self.warn_action {
+            Some(action) => self.trigger_action(action, placeholders).await,
+            None => Ok(()),
+        }
+    }
+
     async fn error(&mut self, placeholders: PlaceholderMap) -> Result<()> {
         if self.state_machine.error() {
-            self.trigger_error(placeholders).await?;
+            self.trigger_error(placeholders.clone()).await?;
+        } else {
+            self.last_non_trigger_reason = Some(self.state_machine.explain_no_trigger());
+        }
+        let Some(max_error_cycles) = self.max_error_cycles else {
+            return Ok(());
+        };
+        if matches!(self.state_machine.error_cycles(), Some(cycles) if cycles >= max_error_cycles)
+            && self.state_machine.recover_error()
+        {
+            self.bad_since = None;
+            self.trigger_error_recover(placeholders.clone()).await?;
+            self.trigger_recover(placeholders).await?;
         }
         Ok(())
     }
 
     async fn bad(&mut self, placeholders: PlaceholderMap) -> Result<()> {
         let (trigger, trigger_error_recover) = self.state_machine.bad();
-        if trigger {
+        self.update_bad_since();
+        // The trigger that was suppressed during the warm-up is caught up on.
+        if trigger || (self.trigger_suppressed && !self.warming_up()) {
             self.trigger(placeholders.clone()).await?;
+        } else if self.trigger_suppressed {
+            self.last_non_trigger_reason = Some(self.warm_up_reason());
+        } else {
+            self.last_non_trigger_reason = Some(self.state_machine.explain_no_trigger());
         }
         if trigger_error_recover {
             self.trigger_error_recover(placeholders).await?;
@@ -137,7 +1078,9 @@ where
     }
 
     async fn good(&mut self, placeholders: PlaceholderMap) -> Result<()> {
+        self.last_non_trigger_reason = Some(String::from("Data sink returned good"));
         let (trigger_recover, trigger_error_recover) = self.state_machine.good();
+        self.update_bad_since();
         if trigger_recover {
             self.trigger_recover(placeholders.clone()).await?;
         }
@@ -147,34 +1090,155 @@ where
         Ok(())
     }
 
-    async fn trigger(&self, mut placeholders: PlaceholderMap) -> Result<()> {
+    // Keeps track of when the alarm changed to the bad state. Only needed for the auto recovery. An
+    // error keeps the time because the bad state is restored after the error recovered.
+    fn update_bad_since(&mut self) {
+        if self.auto_recovery.is_none() {
+            return;
+        }
+        match self.state_machine.state_name() {
+            "Bad" => {
+                let now = self.now();
+                self.bad_since.get_or_insert(now);
+            }
+            "Good" => self.bad_since = None,
+            _ => {}
+        }
+    }
+
+    // Forces the recovery if the alarm has been in the bad state for at least `auto_recovery`.
+    async fn auto_recover(&mut self, placeholders: PlaceholderMap) -> Result<()> {
+        self.update_bad_since();
+        match (self.auto_recovery, self.bad_since) {
+            (Some(auto_recovery), Some(bad_since))
+                if self.now().duration_since(bad_since) >= auto_recovery =>
+            {
+                if self.state_machine.recover() {
+                    self.bad_since = None;
+                    self.trigger_recover(placeholders).await?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    // Returns true if the action has to be suppressed because the last trigger of the same event
+    // type is less than `cooldown` ago. Otherwise the last trigger is set to now, so it must not be
+    // called for actions that are suppressed for other reasons, e.g. during the warm-up.
+    fn cooldown_active(&mut self, error: bool, placeholders: &mut PlaceholderMap) -> bool {
+        let now = self.now();
+        let last_trigger = if error {
+            &mut self.last_error_trigger
+        } else {
+            &mut self.last_trigger
+        };
+        let cooldown_active = match (self.cooldown, *last_trigger) {
+            (Some(cooldown), Some(last)) => now.duration_since(last) < cooldown,
+            _ => false,
+        };
+        if self.cooldown.is_some() {
+            placeholders.insert(
+                String::from("alarm_cooldown_active"),
+                cooldown_active.to_string(),
+            );
+        }
+        if cooldown_active {
+            alarm_log!(
+                self.log_level,
+                log::Level::Info,
+                "{}: Cooldown is active, not triggering {} action.",
+                self.log_id,
+                placeholders["alarm_event"]
+            );
+        } else {
+            *last_trigger = Some(now);
+        }
+        cooldown_active
+    }
+
+    async fn trigger(&mut self, mut placeholders: PlaceholderMap) -> Result<()> {
         self.state_machine.add_placeholders(&mut placeholders);
-        self.action.trigger(placeholders).await
+        placeholders.insert(String::from("alarm_event"), String::from("trigger"));
+        self.trigger_suppressed = self.warming_up();
+        if self.trigger_suppressed {
+            self.last_non_trigger_reason = Some(self.warm_up_reason());
+        }
+        if let Some(chain) = &self.chain {
+            if !chain.dependency_bad() {
+                alarm_log!(
+                    self.log_level,
+                    log::Level::Info,
+                    "{}: Alarm '{}' is not bad, not triggering action.",
+                    self.log_id,
+                    chain.depends_on_alarm_id()
+                );
+                self.last_non_trigger_reason = Some(format!(
+                    "Dependency '{}' is not bad",
+                    chain.depends_on_alarm_id()
+                ));
+                return Ok(());
+            }
+        }
+        if !self.trigger_suppressed && self.cooldown_active(false, &mut placeholders) {
+            self.last_non_trigger_reason = Some(self.cooldown_remaining(false));
+            return Ok(());
+        }
+        self.trigger_action(self.escalated_action(), placeholders)
+            .await
+    }
+
+    // The action with the highest escalation cycles not exceeding the cycles in the bad state.
+    fn escalated_action(&self) -> &std::sync::Arc<dyn action::Action> {
+        if self.escalation.is_empty() {
+            return &self.action;
+        }
+        let bad_cycles = self.state_machine.bad_cycles().unwrap_or(1);
+        self.escalation
+            .iter()
+            .rev()
+            .find(|(cycles, _)| *cycles <= bad_cycles)
+            .map_or(&self.action, |(_, action)| action)
     }
 
-    async fn trigger_recover(&self, mut placeholders: PlaceholderMap) -> Result<()> {
+    async fn trigger_recover(&mut self, mut placeholders: PlaceholderMap) -> Result<()> {
+        // Nobody was told about the bad state, so there is nothing to recover from.
+        if std::mem::take(&mut self.trigger_suppressed) {
+            return Ok(());
+        }
         self.state_machine.add_placeholders(&mut placeholders);
+        placeholders.insert(String::from("alarm_event"), String::from("recover"));
         crate::merge_placeholders(&mut placeholders, &self.recover_placeholders);
         match &self.recover_action {
-            Some(action) => action.trigger(placeholders).await,
+            Some(action) => self.trigger_action(action, placeholders).await,
             None => Ok(()),
         }
     }
 
-    async fn trigger_error(&self, mut placeholders: PlaceholderMap) -> Result<()> {
+    async fn trigger_error(&mut self, mut placeholders: PlaceholderMap) -> Result<()> {
         self.state_machine.add_placeholders(&mut placeholders);
+        placeholders.insert(String::from("alarm_event"), String::from("error"));
+        if self.error_action.is_none() {
+            self.last_non_trigger_reason = Some(String::from("No error action configured"));
+            return Ok(());
+        }
+        if !self.warming_up() && self.cooldown_active(true, &mut placeholders) {
+            self.last_non_trigger_reason = Some(self.cooldown_remaining(true));
+            return Ok(());
+        }
         crate::merge_placeholders(&mut placeholders, &self.error_placeholders);
         match &self.error_action {
-            Some(action) => action.trigger(placeholders).await,
+            Some(action) => self.trigger_action(action, placeholders).await,
             None => Ok(()),
         }
     }
 
     async fn trigger_error_recover(&self, mut placeholders: PlaceholderMap) -> Result<()> {
         self.state_machine.add_placeholders(&mut placeholders);
+        placeholders.insert(String::from("alarm_event"), String::from("error_recover"));
         crate::merge_placeholders(&mut placeholders, &self.error_recover_placeholders);
         match &self.error_recover_action {
-            Some(action) => action.trigger(placeholders).await,
+            Some(action) => self.trigger_action(action, placeholders).await,
             None => Ok(()),
         }
     }
@@ -182,6 +1246,15 @@ where
     fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
         placeholders.insert(String::from("alarm_name"), self.name.clone());
         placeholders.insert(String::from("check_id"), self.id.clone());
+        if self.warm_up_cycles > 0 {
+            placeholders.insert(
+                String::from("alarm_warming_up"),
+                self.warming_up().to_string(),
+            );
+        }
+        if !self.metadata.tags.is_empty() {
+            placeholders.insert(String::from("alarm_tags"), self.metadata.tags.join(","));
+        }
         crate::merge_placeholders(placeholders, &self.placeholders);
     }
 }
@@ -198,30 +1271,266 @@ where
         &self.log_id
     }
 
-    async fn put_data(
+    fn simulate(&self, decisions: &[SinkDecision]) -> Vec<SimulationEvent> {
+        let mut state_machine = self.simulation_state();
+        decisions
+            .iter()
+            .enumerate()
+            .map(|(i, decision)| self.simulate_cycle(&mut state_machine, i + 1, *decision))
+            .collect()
+    }
+
+    async fn replay(
         &mut self,
-        data: &Self::Item,
-        mut placeholders: PlaceholderMap,
-    ) -> Result<()> {
-        T::add_placeholders(data, &mut placeholders);
-        self.add_placeholders(&mut placeholders);
-        let mut decision = self.data_sink.put_data(data)?;
-        if self.invert {
-            decision = !decision;
+        data: &[HistoricalDataPoint<Self::Item>],
+    ) -> Result<Vec<ReplayEvent>> {
+        if data.windows(2).any(|x| x[1].timestamp < x[0].timestamp) {
+            return Err(Error::Message(String::from(
+                "The data is not sorted by timestamp.",
+            )));
         }
-        match decision {
-            SinkDecision::Good => self.good(placeholders).await,
-            SinkDecision::Bad => {
-                log::warn!("{}: Data is bad.", self.log_id);
-                self.bad(placeholders).await
+        let Some(first) = data.first() else {
+            return Ok(Vec::new());
+        };
+        let start = tokio::time::Instant::now();
+        let mut events = Vec::new();
+        for point in data {
+            let offset = point
+                .timestamp
+                .duration_since(first.timestamp)
+                .unwrap_or_default();
+            self.replay = Some(Replay {
+                clock: start + offset,
+                events: std::sync::Mutex::default(),
+            });
+            let state_before = self.state_machine.state_name();
+            let result = self.put_data(&point.data, PlaceholderMap::new()).await;
+            let replay = self.replay.take().unwrap();
+            result.map_err(|x| {
+                Error::Message(format!(
+                    "{}: {}",
+                    crate::datetime_iso8601(point.timestamp),
+                    x
+                ))
+            })?;
+            let triggered = replay.events.into_inner().unwrap();
+            let state_after = self.state_machine.state_name();
+            if state_before != state_after || !triggered.is_empty() {
+                let reason = if triggered.is_empty() && state_after != "Good" {
+                    self.explain_last_non_trigger()
+                } else {
+                    None
+                };
+                events.push(ReplayEvent {
+                    timestamp: point.timestamp,
+                    state_before,
+                    state_after,
+                    events: triggered,
+                    reason,
+                });
             }
         }
+        Ok(events)
+    }
+
+    fn to_snapshot(&self) -> Result<AlarmSnapshot> {
+        let action_name = |action: &std::sync::Arc<dyn action::Action>| {
+            action.name().ok_or_else(|| {
+                Error::Message(String::from("Action without a name cannot be saved."))
+            })
+        };
+        let optional_action_name = |action: &Option<std::sync::Arc<dyn action::Action>>| {
+            action.as_ref().map(action_name).transpose()
+        };
+        Ok(AlarmSnapshot {
+            version: snapshot::SNAPSHOT_VERSION,
+            name: self.name.clone(),
+            id: self.id.clone(),
+            action: action_name(&self.action)?,
+            placeholders: self.placeholders.clone(),
+            escalation: self
+                .escalation
+                .iter()
+                .map(|(cycles, action)| Ok((*cycles, action_name(action)?)))
+                .collect::<Result<_>>()?,
+            recover_action: optional_action_name(&self.recover_action)?,
+            recover_placeholders: self.recover_placeholders.clone(),
+            error_action: optional_action_name(&self.error_action)?,
+            error_placeholders: self.error_placeholders.clone(),
+            error_recover_action: optional_action_name(&self.error_recover_action)?,
+            error_recover_placeholders: self.error_recover_placeholders.clone(),
+            invert: self.invert,
+            disabled: self.disabled,
+            cooldown: self.cooldown,
+            auto_recovery: self.auto_recovery,
+            log_id: self.log_id.clone(),
+            log_level: self.log_level,
+            state_machine: self.state_machine.snapshot(),
+        })
+    }
+
+    fn restore(self, snapshot: AlarmSnapshot, actions: &ActionMap) -> Result<Self> {
+        // The metadata is not part of the snapshot because it does not belong to the state.
+        let mut alarm = Self::from_snapshot(snapshot, self.data_sink, actions)?;
+        alarm.metadata = self.metadata;
+        alarm.warn_if_never_bad_cycles = self.warn_if_never_bad_cycles;
+        alarm.warn_action = self.warn_action;
+        alarm.max_error_cycles = self.max_error_cycles;
+        alarm.fast_recovery = self.fast_recovery;
+        alarm.warm_up_cycles = self.warm_up_cycles;
+        alarm.cycles = self.cycles;
+        alarm.chain = self.chain;
+        alarm.placeholder_sources = self.placeholder_sources;
+        alarm.registry = self.registry;
+        // Restoring is not a state change, the registry is updated directly.
+        alarm.state_change_callbacks = self.state_change_callbacks;
+        alarm.publish_state();
+        Ok(alarm)
+    }
+
+    fn describe(&self) -> AlarmDescriptor {
+        AlarmDescriptor {
+            name: self.name.clone(),
+            id: self.id.clone(),
+            description: self.metadata.description.clone(),
+            severity: self.metadata.severity,
+            runbook_url: self.metadata.runbook_url.clone(),
+            owner: self.metadata.owner.clone(),
+            tags: self.metadata.tags.clone(),
+            threshold_summary: self.data_sink.describe_threshold(),
+        }
+    }
+
+    fn to_dot_graph(&self) -> String {
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let action_name = |event: &str| match event {
+            "trigger" => Some("action"),
+            "recover" => self.recover_action.as_ref().map(|_| "recover_action"),
+            "error" => self.error_action.as_ref().map(|_| "error_action"),
+            "error_recover" => self
+                .error_recover_action
+                .as_ref()
+                .map(|_| "error_recover_action"),
+            _ => None,
+        };
+        let mut label = self.log_id.clone();
+        if self.invert {
+            label.push_str(" (inverted)");
+        }
+        if self.disabled {
+            label.push_str(" (disabled)");
+        }
+        let current_state = self.state_machine.state_name();
+        let mut graph = format!("digraph {} {{\n", quote(&label));
+        for state in ["Good", "Bad", "Error"] {
+            if state == current_state {
+                graph.push_str(&format!(
+                    "    {} [label={} style=filled fillcolor=lightgrey penwidth=2];\n",
+                    quote(state),
+                    quote(&format!("{state} (current)"))
+                ));
+            } else {
+                graph.push_str(&format!("    {};\n", quote(state)));
+            }
+        }
+        for transition in self.state_machine.transitions() {
+            let mut label = transition.guard;
+            if let Some(action_name) = transition.event.and_then(action_name) {
+                label.push_str(&format!(" / {action_name}"));
+            }
+            graph.push_str(&format!(
+                "    {} -> {} [label={}];\n",
+                quote(transition.from),
+                quote(transition.to),
+                quote(&label)
+            ));
+        }
+        graph.push_str("}\n");
+        graph
+    }
+
+    async fn put_data(
+        &mut self,
+        data: &Self::Item,
+        mut placeholders: PlaceholderMap,
+    ) -> Result<()> {
+        if self.disabled {
+            self.last_non_trigger_reason = Some(String::from("Alarm is disabled"));
+            return Ok(());
+        }
+        self.cycles = self.cycles.saturating_add(1);
+        self.last_non_trigger_reason = None;
+        let from = self.observed_state();
+        self.data_sink.add_placeholders(data, &mut placeholders);
+        // Formatted after the data was put, so that stateful sinks (e.g. the EMA) show the state
+        // including this data.
+        let decision = self.data_sink.put_data(data);
+        let value = self.data_sink.format_data(data);
+        placeholders.insert(String::from("alarm_value"), value.clone());
+        self.last_value = Some(value.clone());
+        self.add_placeholders(&mut placeholders);
+        let auto_recovery = self.auto_recover(placeholders.clone()).await;
+        let from = self.notify_state_change(from);
+        auto_recovery?;
+        let mut decision = decision?;
+        self.data_sink.add_state_placeholders(&mut placeholders);
+        if self.invert {
+            decision = !decision;
+        }
+        self.count_never_bad(decision, placeholders.clone()).await?;
+        let was_bad = self.fast_recovery.is_some() && self.state_machine.bad_cycles().is_some();
+        let result = match decision {
+            SinkDecision::Good => self.good(placeholders).await,
+            SinkDecision::Bad => {
+                alarm_log!(
+                    self.log_level,
+                    log::Level::Warn,
+                    "{}: Data is bad ({}): {}",
+                    self.log_id,
+                    self.data_sink.name(),
+                    value
+                );
+                self.bad(placeholders).await
+            }
+        };
+        self.warn_fast_recovery(decision, was_bad);
+        self.notify_state_change(from);
+        result
     }
 
     async fn put_error(&mut self, error: &Error, mut placeholders: PlaceholderMap) -> Result<()> {
-        log::error!("{} got an error: {}", self.log_id, error);
+        if self.disabled {
+            self.last_non_trigger_reason = Some(String::from("Alarm is disabled"));
+            return Ok(());
+        }
+        self.cycles = self.cycles.saturating_add(1);
+        self.last_non_trigger_reason = None;
+        alarm_log!(
+            self.log_level,
+            log::Level::Error,
+            "{} got an error: {}",
+            self.log_id,
+            error
+        );
+        let from = self.observed_state();
+        if let Some(value) = &self.last_value {
+            placeholders.insert(String::from("alarm_value"), value.clone());
+        }
+        self.add_placeholders(&mut placeholders);
+        let result = self.error(placeholders).await;
+        self.notify_state_change(from);
+        result
+    }
+
+    async fn tick(&mut self, mut placeholders: PlaceholderMap) -> Result<()> {
+        if self.disabled {
+            return Ok(());
+        }
+        let from = self.observed_state();
         self.add_placeholders(&mut placeholders);
-        self.error(placeholders).await
+        let result = self.auto_recover(placeholders).await;
+        self.notify_state_change(from);
+        result
     }
 }
 
@@ -230,8 +1539,6 @@ mod test {
     use super::*;
     use mockall::predicate::*;
 
-    static SEMAPHORE: tokio::sync::Semaphore = tokio::sync::Semaphore::const_new(1);
-
     fn times_action(times: usize) -> std::sync::Arc<dyn action::Action> {
         let mut mock_action = action::MockAction::new();
         mock_action
@@ -241,8 +1548,36 @@ mod test {
         std::sync::Arc::new(mock_action)
     }
 
-    fn mock_data_sink() -> MockDataSink {
+    // Alarm without any of the optional settings.
+    fn alarm_base<T, U>(
+        action: std::sync::Arc<dyn action::Action>,
+        state_machine: U,
+        data_sink: T,
+    ) -> AlarmBase<T, U>
+    where
+        T: DataSink,
+        U: StateHandler,
+    {
+        AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            action,
+            PlaceholderMap::new(),
+            state_machine,
+            data_sink,
+            String::new(),
+        )
+        .unwrap()
+    }
+
+    fn valid_mock_data_sink() -> MockDataSink {
         let mut mock_data_sink = MockDataSink::new();
+        mock_data_sink.expect_validate().returning(|| Ok(()));
+        mock_data_sink
+    }
+
+    fn base_mock_data_sink() -> MockDataSink {
+        let mut mock_data_sink = valid_mock_data_sink();
         mock_data_sink
             .expect_put_data()
             .with(eq(10))
@@ -252,18 +1587,37 @@ mod test {
             .with(eq(20))
             .returning(|_| Ok(SinkDecision::Bad));
         mock_data_sink
+            .expect_format_data()
+            .returning(|data| format!("{data}%"));
+        mock_data_sink
+            .expect_add_state_placeholders()
+            .return_const(());
+        mock_data_sink
+            .expect_name()
+            .return_const(String::from("mock"));
+        mock_data_sink
+    }
+
+    fn mock_data_sink() -> MockDataSink {
+        let mut mock_data_sink = base_mock_data_sink();
+        mock_data_sink.expect_add_placeholders().return_const(());
+        mock_data_sink
+    }
+
+    // Like `mock_data_sink`, but the data is added as the `data` placeholder.
+    fn data_mock_data_sink() -> MockDataSink {
+        let mut mock_data_sink = base_mock_data_sink();
+        mock_data_sink.expect_add_placeholders().returning(
+            |data: &u8, placeholders: &mut PlaceholderMap| {
+                placeholders.insert(String::from("data"), data.to_string());
+            },
+        );
+        mock_data_sink
     }
 
     #[tokio::test]
     async fn test_trigger_action() {
-        let _permit = SEMAPHORE.acquire().await.unwrap();
-        let data_sink_ctx = MockDataSink::add_placeholders_context();
-        data_sink_ctx
-            .expect()
-            .returning(|data: &u8, placeholders: &mut PlaceholderMap| {
-                placeholders.insert(String::from("data"), data.to_string());
-            });
-        let mock_data_sink = mock_data_sink();
+        let mock_data_sink = data_mock_data_sink();
         let mut mock_action = action::MockAction::new();
         mock_action
             .expect_trigger()
@@ -274,7 +1628,9 @@ mod test {
                 assert_eq!(placeholders.get("Hello").unwrap(), "World");
                 assert_eq!(placeholders.get("Foo").unwrap(), "Bar");
                 assert_eq!(placeholders.get("data").unwrap(), "20");
-                assert_eq!(placeholders.len(), 5);
+                assert_eq!(placeholders.get("alarm_value").unwrap(), "20%");
+                assert_eq!(placeholders.get("alarm_event").unwrap(), "trigger");
+                assert_eq!(placeholders.len(), 7);
                 true
             }))
             .returning(|_| Ok(()));
@@ -296,18 +1652,14 @@ mod test {
             String::from("ID"),
             std::sync::Arc::new(mock_action),
             PlaceholderMap::from([(String::from("Hello"), String::from("World"))]),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
-            false,
             mock_state_machine,
             mock_data_sink,
             String::from(""),
         )
         .unwrap();
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_recover_action(Some(times_action(0)), PlaceholderMap::new());
         alarm
             .put_data(
                 &20,
@@ -319,14 +1671,7 @@ mod test {
 
     #[tokio::test]
     async fn test_trigger_recover_action() {
-        let _permit = SEMAPHORE.acquire().await.unwrap();
-        let data_sink_ctx = MockDataSink::add_placeholders_context();
-        data_sink_ctx
-            .expect()
-            .returning(|data: &u8, placeholders: &mut PlaceholderMap| {
-                placeholders.insert(String::from("data"), data.to_string());
-            });
-        let mock_data_sink = mock_data_sink();
+        let mock_data_sink = data_mock_data_sink();
         let mut mock_state_machine = state_machine::MockStateHandler::new();
         mock_state_machine
             .expect_good()
@@ -350,27 +1695,19 @@ mod test {
                 assert_eq!(placeholders.get("Hello").unwrap(), "World");
                 assert_eq!(placeholders.get("Foo").unwrap(), "Bar");
                 assert_eq!(placeholders.get("data").unwrap(), "10");
-                assert_eq!(placeholders.len(), 5);
+                assert_eq!(placeholders.get("alarm_value").unwrap(), "10%");
+                assert_eq!(placeholders.get("alarm_event").unwrap(), "recover");
+                assert_eq!(placeholders.len(), 7);
                 true
             }))
             .returning(|_| Ok(()));
-        let mut alarm = AlarmBase::new(
-            String::from("Name"),
-            String::from("ID"),
-            times_action(0),
-            PlaceholderMap::new(),
+        let mut alarm = alarm_base(times_action(0), mock_state_machine, mock_data_sink);
+        alarm.set_recover_action(
             Some(std::sync::Arc::new(mock_recover_action)),
             PlaceholderMap::from([(String::from("Hello"), String::from("World"))]),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
-            false,
-            mock_state_machine,
-            mock_data_sink,
-            String::from(""),
-        )
-        .unwrap();
+        );
+        alarm.set_error_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_recover_action(Some(times_action(0)), PlaceholderMap::new());
         alarm
             .put_data(
                 &10,
@@ -382,7 +1719,7 @@ mod test {
 
     #[tokio::test]
     async fn test_trigger_error_action() {
-        let mut mock_data_sink = MockDataSink::new();
+        let mut mock_data_sink = valid_mock_data_sink();
         mock_data_sink.expect_put_data().never();
         let mut mock_error_action = action::MockAction::new();
         mock_error_action
@@ -393,7 +1730,8 @@ mod test {
                 assert_eq!(placeholders.get("check_id").unwrap(), "ID");
                 assert_eq!(placeholders.get("Hello").unwrap(), "World");
                 assert_eq!(placeholders.get("Foo").unwrap(), "Bar");
-                assert_eq!(placeholders.len(), 4);
+                assert_eq!(placeholders.get("alarm_event").unwrap(), "error");
+                assert_eq!(placeholders.len(), 5);
                 true
             }))
             .returning(|_| Ok(()));
@@ -407,26 +1745,16 @@ mod test {
                 true
             }))
             .return_const(());
-        let mut alarm = AlarmBase::new(
-            String::from("Name"),
-            String::from("ID"),
-            times_action(0),
-            PlaceholderMap::new(),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
+        let mut alarm = alarm_base(times_action(0), mock_state_machine, mock_data_sink);
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_action(
             Some(std::sync::Arc::new(mock_error_action)),
             PlaceholderMap::from([(String::from("Hello"), String::from("World"))]),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
-            false,
-            mock_state_machine,
-            mock_data_sink,
-            String::from(""),
-        )
-        .unwrap();
+        );
+        alarm.set_error_recover_action(Some(times_action(0)), PlaceholderMap::new());
         alarm
             .put_error(
-                &Error(String::from("Error")),
+                &Error::Message(String::from("Error")),
                 PlaceholderMap::from([(String::from("Foo"), String::from("Bar"))]),
             )
             .await
@@ -435,18 +1763,22 @@ mod test {
 
     #[tokio::test]
     async fn test_trigger_error_recover_action() {
-        let _permit = SEMAPHORE.acquire().await.unwrap();
-        let data_sink_ctx = MockDataSink::add_placeholders_context();
-        data_sink_ctx
-            .expect()
-            .returning(|data: &u8, placeholders: &mut PlaceholderMap| {
+        let mut mock_data_sink = valid_mock_data_sink();
+        mock_data_sink.expect_add_placeholders().returning(
+            |data: &u8, placeholders: &mut PlaceholderMap| {
                 placeholders.insert(String::from("data"), data.to_string());
-            });
-        let mut mock_data_sink = MockDataSink::new();
+            },
+        );
         mock_data_sink
             .expect_put_data()
             .with(eq(10))
             .returning(|_| Ok(SinkDecision::Good));
+        mock_data_sink
+            .expect_add_state_placeholders()
+            .return_const(());
+        mock_data_sink
+            .expect_format_data()
+            .returning(|data| data.to_string());
         let mut mock_action = action::MockAction::new();
         mock_action.expect_trigger().never();
         let mut mock_error_action = action::MockAction::new();
@@ -464,7 +1796,9 @@ mod test {
                 assert_eq!(placeholders.get("Hello").unwrap(), "World");
                 assert_eq!(placeholders.get("Foo").unwrap(), "Bar");
                 assert_eq!(placeholders.get("data").unwrap(), "10");
-                assert_eq!(placeholders.len(), 5);
+                assert_eq!(placeholders.get("alarm_value").unwrap(), "10");
+                assert_eq!(placeholders.get("alarm_event").unwrap(), "error_recover");
+                assert_eq!(placeholders.len(), 7);
                 true
             }))
             .returning(|_| Ok(()));
@@ -482,26 +1816,19 @@ mod test {
             .expect_good()
             .once()
             .return_const((false, true));
-        let mut alarm = AlarmBase::new(
-            String::from("Name"),
-            String::from("ID"),
-            times_action(0),
-            PlaceholderMap::new(),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
+        let mut alarm = alarm_base(times_action(0), mock_state_machine, mock_data_sink);
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_action(
             Some(std::sync::Arc::new(mock_error_action)),
             PlaceholderMap::new(),
+        );
+        alarm.set_error_recover_action(
             Some(std::sync::Arc::new(mock_error_recover_action)),
             PlaceholderMap::from([(String::from("Hello"), String::from("World"))]),
-            false,
-            mock_state_machine,
-            mock_data_sink,
-            String::from(""),
-        )
-        .unwrap();
+        );
         alarm
             .put_error(
-                &Error(String::from("Error")),
+                &Error::Message(String::from("Error")),
                 PlaceholderMap::from([(String::from("Foo"), String::from("Bar"))]),
             )
             .await
@@ -515,16 +1842,385 @@ mod test {
             .unwrap();
     }
 
+    // Actions of an alarm with the real state machine, see `state_machine_alarm`.
+    #[derive(Default)]
+    struct StateMachineActions {
+        trigger: action::TestAction,
+        recover: action::TestAction,
+        error: action::TestAction,
+        error_recover: action::TestAction,
+    }
+
+    fn state_machine_alarm(
+        actions: &StateMachineActions,
+        cycles: u32,
+        repeat_cycles: u32,
+        recover_cycles: u32,
+        error_repeat_cycles: u32,
+    ) -> AlarmBase<MockDataSink> {
+        state_machine_alarm_with_action(
+            std::sync::Arc::new(actions.trigger.clone()),
+            actions,
+            cycles,
+            repeat_cycles,
+            recover_cycles,
+            error_repeat_cycles,
+        )
+    }
+
+    // Like `state_machine_alarm`, but triggers `action` instead of `actions.trigger`.
+    fn state_machine_alarm_with_action(
+        action: std::sync::Arc<dyn action::Action>,
+        actions: &StateMachineActions,
+        cycles: u32,
+        repeat_cycles: u32,
+        recover_cycles: u32,
+        error_repeat_cycles: u32,
+    ) -> AlarmBase<MockDataSink> {
+        let mut alarm = alarm_base(
+            action,
+            StateMachine::new(
+                cycles,
+                RepeatSchedule::Fixed(repeat_cycles),
+                recover_cycles,
+                error_repeat_cycles,
+                String::new(),
+                None,
+            )
+            .unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(
+            Some(std::sync::Arc::new(actions.recover.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm.set_error_action(
+            Some(std::sync::Arc::new(actions.error.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm.set_error_recover_action(
+            Some(std::sync::Arc::new(actions.error_recover.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm
+    }
+
+    async fn put_data_times(alarm: &mut AlarmBase<MockDataSink>, data: u8, times: usize) {
+        for _ in 0..times {
+            alarm
+                .put_data(
+                    &data,
+                    PlaceholderMap::from([(String::from("Foo"), String::from("Bar"))]),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn put_error_times(alarm: &mut AlarmBase<MockDataSink>, times: usize) {
+        for _ in 0..times {
+            alarm
+                .put_error(
+                    &Error::Message(String::from("Error")),
+                    PlaceholderMap::from([(String::from("Foo"), String::from("Bar"))]),
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    // Sorted placeholder keys of the last trigger of the action.
+    fn placeholder_keys(action: &action::TestAction) -> Vec<String> {
+        let mut keys: Vec<String> = action.last_placeholders().unwrap().into_keys().collect();
+        keys.sort();
+        keys
+    }
+
+    #[tokio::test]
+    async fn test_good_stays_good() {
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 1, 0, 1, 0);
+        put_data_times(&mut alarm, 10, 5).await;
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        actions.trigger.assert_not_triggered();
+        actions.recover.assert_not_triggered();
+        actions.error.assert_not_triggered();
+        actions.error_recover.assert_not_triggered();
+    }
+
+    #[tokio::test]
+    async fn test_good_accumulates_bad_cycles() {
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 3, 0, 1, 0);
+        put_data_times(&mut alarm, 20, 2).await;
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        actions.trigger.assert_not_triggered();
+        // Good data in between does not reset the bad cycles.
+        put_data_times(&mut alarm, 10, 1).await;
+        put_data_times(&mut alarm, 20, 1).await;
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        actions.trigger.assert_triggered_once();
+    }
+
+    #[tokio::test]
+    async fn test_bad_triggers_on_threshold() {
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 3, 0, 1, 0);
+        put_data_times(&mut alarm, 20, 3).await;
+        actions.trigger.assert_triggered_once();
+        assert_eq!(
+            placeholder_keys(&actions.trigger),
+            [
+                "Foo",
+                "alarm_bad_cycles",
+                "alarm_bad_duration_human",
+                "alarm_consecutive_bad_count",
+                "alarm_event",
+                "alarm_good_cycles",
+                "alarm_name",
+                "alarm_state",
+                "alarm_timestamp",
+                "alarm_value",
+                "check_id"
+            ]
+        );
+        let placeholders = actions.trigger.last_placeholders().unwrap();
+        assert_eq!(placeholders["alarm_event"], "trigger");
+        assert_eq!(placeholders["alarm_value"], "20%");
+        // Without repetitions, further bad data does not trigger again.
+        put_data_times(&mut alarm, 20, 5).await;
+        actions.trigger.assert_triggered_once();
+    }
+
+    #[tokio::test]
+    async fn test_bad_repeats_on_repeat_cycles() {
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 1, 2, 1, 0);
+        put_data_times(&mut alarm, 20, 5).await;
+        // Cycles 1, 3 and 5.
+        assert_eq!(actions.trigger.trigger_count(), 3);
+        assert_eq!(
+            placeholder_keys(&actions.trigger),
+            [
+                "Foo",
+                "alarm_bad_cycles",
+                "alarm_bad_duration_human",
+                "alarm_consecutive_bad_count",
+                "alarm_event",
+                "alarm_good_cycles",
+                "alarm_name",
+                "alarm_state",
+                "alarm_timestamp",
+                "alarm_value",
+                "check_id"
+            ]
+        );
+        assert_eq!(
+            actions.trigger.last_placeholders().unwrap()["alarm_event"],
+            "trigger"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bad_recovers_on_recover_cycles() {
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 1, 0, 3, 0);
+        put_data_times(&mut alarm, 20, 1).await;
+        put_data_times(&mut alarm, 10, 2).await;
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        actions.recover.assert_not_triggered();
+        // Bad data starts the recovery over.
+        put_data_times(&mut alarm, 20, 1).await;
+        put_data_times(&mut alarm, 10, 2).await;
+        actions.recover.assert_not_triggered();
+        put_data_times(&mut alarm, 10, 1).await;
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        actions.recover.assert_triggered_once();
+        assert_eq!(
+            placeholder_keys(&actions.recover),
+            [
+                "Foo",
+                "alarm_bad_cycles_before_recovery",
+                "alarm_event",
+                "alarm_good_duration_human",
+                "alarm_name",
+                "alarm_state",
+                "alarm_timestamp",
+                "alarm_value",
+                "check_id"
+            ]
+        );
+        let placeholders = actions.recover.last_placeholders().unwrap();
+        assert_eq!(placeholders["alarm_event"], "recover");
+        assert_eq!(placeholders["alarm_value"], "10%");
+    }
+
+    #[tokio::test]
+    async fn test_error_on_first_error() {
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 3, 0, 1, 0);
+        put_error_times(&mut alarm, 1).await;
+        assert_eq!(alarm.state_machine.state_name(), "Error");
+        actions.error.assert_triggered_once();
+        assert_eq!(
+            placeholder_keys(&actions.error),
+            [
+                "Foo",
+                "alarm_error_duration_human",
+                "alarm_event",
+                "alarm_name",
+                "alarm_state",
+                "alarm_timestamp",
+                "check_id",
+                "error_cycles"
+            ]
+        );
+        let placeholders = actions.error.last_placeholders().unwrap();
+        assert_eq!(placeholders["alarm_event"], "error");
+        put_error_times(&mut alarm, 3).await;
+        actions.error.assert_triggered_once();
+        actions.trigger.assert_not_triggered();
+    }
+
+    #[tokio::test]
+    async fn test_error_repeats_on_error_repeat_cycles() {
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 1, 0, 1, 2);
+        put_error_times(&mut alarm, 5).await;
+        // Cycles 1, 3 and 5.
+        assert_eq!(actions.error.trigger_count(), 3);
+        assert_eq!(
+            placeholder_keys(&actions.error),
+            [
+                "Foo",
+                "alarm_error_duration_human",
+                "alarm_event",
+                "alarm_name",
+                "alarm_state",
+                "alarm_timestamp",
+                "check_id",
+                "error_cycles"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_clears_on_good_data() {
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 1, 0, 1, 0);
+        put_error_times(&mut alarm, 2).await;
+        put_data_times(&mut alarm, 10, 1).await;
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        actions.error_recover.assert_triggered_once();
+        assert_eq!(
+            placeholder_keys(&actions.error_recover),
+            [
+                "Foo",
+                "alarm_event",
+                "alarm_good_duration_human",
+                "alarm_name",
+                "alarm_state",
+                "alarm_timestamp",
+                "alarm_value",
+                "check_id"
+            ]
+        );
+        assert_eq!(
+            actions.error_recover.last_placeholders().unwrap()["alarm_event"],
+            "error_recover"
+        );
+        actions.recover.assert_not_triggered();
+    }
+
+    // Action that takes a while, e.g. a slow webhook.
+    struct SlowAction {
+        delay: std::time::Duration,
+        action: action::TestAction,
+    }
+
+    #[async_trait]
+    impl action::Action for SlowAction {
+        async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+            tokio::time::sleep(self.delay).await;
+            self.action.trigger(placeholders).await
+        }
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    // No lock is held while an action runs, so a slow action of one alarm does not hold up the
+    // others, even on a single thread. `tokio::spawn` also requires the future of `put_data` to be
+    // `Send`, which it would not be if a `std::sync` guard were held across an await.
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_action_does_not_block() {
+        assert_send_sync::<AlarmBase<MockDataSink>>();
+        assert_send_sync::<AlarmRegistry>();
+        assert_send_sync::<AlarmChain>();
+
+        let slow = action::TestAction::new();
+        let mut slow_alarm = state_machine_alarm_with_action(
+            std::sync::Arc::new(SlowAction {
+                delay: std::time::Duration::from_secs(60),
+                action: slow.clone(),
+            }),
+            &StateMachineActions::default(),
+            1,
+            0,
+            1,
+            0,
+        );
+        let slow_put_data =
+            tokio::spawn(async move { put_data_times(&mut slow_alarm, 20, 1).await });
+        // Let the slow alarm start its action.
+        tokio::task::yield_now().await;
+
+        let start = tokio::time::Instant::now();
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 1, 0, 1, 0);
+        put_data_times(&mut alarm, 20, 1).await;
+        put_data_times(&mut alarm, 10, 1).await;
+        assert_eq!(start.elapsed(), std::time::Duration::ZERO);
+        actions.trigger.assert_triggered_once();
+        actions.recover.assert_triggered_once();
+        slow.assert_not_triggered();
+        assert!(!slow_put_data.is_finished());
+
+        slow_put_data.await.unwrap();
+        slow.assert_triggered_once();
+        assert_eq!(start.elapsed(), std::time::Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_alarm_tags() {
+        let mut config: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nlevel = 1").unwrap();
+        let actions = StateMachineActions::default();
+        let mut alarm = state_machine_alarm(&actions, 1, 0, 1, 0);
+        put_data_times(&mut alarm, 20, 1).await;
+        assert!(!actions
+            .trigger
+            .last_placeholders()
+            .unwrap()
+            .contains_key("alarm_tags"));
+
+        config.tags = vec![String::from("database"), String::from("non-critical")];
+        alarm.set_metadata(AlarmMetadata::try_from(&config).unwrap());
+        put_data_times(&mut alarm, 10, 1).await;
+        put_data_times(&mut alarm, 20, 1).await;
+        assert_eq!(
+            actions.trigger.last_placeholders().unwrap()["alarm_tags"],
+            "database,non-critical"
+        );
+        config.tags.push(String::from("not/valid"));
+        assert_eq!(
+            AlarmMetadata::try_from(&config).err().unwrap().to_string(),
+            "Tag 'not/valid' may only contain letters, digits and hyphens."
+        );
+    }
+
     #[tokio::test]
     async fn test_invert() {
-        let _permit = SEMAPHORE.acquire().await.unwrap();
-        let data_sink_ctx = MockDataSink::add_placeholders_context();
-        data_sink_ctx
-            .expect()
-            .returning(|data: &u8, placeholders: &mut PlaceholderMap| {
-                placeholders.insert(String::from("data"), data.to_string());
-            });
-        let mock_data_sink = mock_data_sink();
+        let mock_data_sink = data_mock_data_sink();
         let mut mock_state_machine = state_machine::MockStateHandler::new();
         mock_state_machine
             .expect_bad()
@@ -538,26 +2234,1603 @@ mod test {
             .expect_add_placeholders()
             .times(2)
             .return_const(());
+        let mut alarm = alarm_base(times_action(1), mock_state_machine, mock_data_sink);
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_invert(true);
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm.action = times_action(0);
+        alarm.recover_action = Some(times_action(1));
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+    }
+
+    struct CaptureLogger {
+        records: std::sync::Mutex<Vec<(log::Level, String)>>,
+    }
+
+    impl log::Log for CaptureLogger {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            self.records
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CaptureLogger = CaptureLogger {
+        records: std::sync::Mutex::new(Vec::new()),
+    };
+
+    // Throughput of `put_data` with the real state machine and a level data sink, so that a cycle
+    // that gets slower than O(1) shows up. It needs an optimized build and is ignored by default,
+    // run it with `cargo test --release --lib -- --ignored test_put_data_throughput`. The number of
+    // calls per case and the minimum throughput can be set with `MINMON_THROUGHPUT_CALLS` and
+    // `MINMON_THROUGHPUT_MIN_CALLS_PER_SEC`. For detailed numbers use
+    // `cargo bench --bench alarm_throughput`.
+    #[tokio::test]
+    #[ignore]
+    async fn test_put_data_throughput() {
+        let env = |name, default| std::env::var(name).map_or(default, |x| x.parse().unwrap());
+        let calls = env("MINMON_THROUGHPUT_CALLS", 1_000_000);
+        let min_calls_per_sec = env("MINMON_THROUGHPUT_MIN_CALLS_PER_SEC", 100_000);
+        let mut too_slow = Vec::new();
+        for (data_name, data) in [("good", &[10][..]), ("bad", &[20]), ("mixed", &[10, 20])] {
+            for cycles in [1, 100, 1000] {
+                let mut alarm = crate::bench::LevelAlarm::new(cycles);
+                let start = std::time::Instant::now();
+                for i in 0..calls {
+                    alarm.put_data(data[i % data.len()]).await;
+                }
+                let calls_per_sec = calls as f64 / start.elapsed().as_secs_f64();
+                if calls_per_sec < min_calls_per_sec as f64 {
+                    too_slow.push(format!("{data_name} data with cycles = {cycles}"));
+                }
+            }
+        }
+        assert!(
+            too_slow.is_empty(),
+            "Less than {min_calls_per_sec} calls/s for {}.",
+            too_slow.join(", ")
+        );
+    }
+
+    fn fast_recovery_warnings(log_id: &str) -> usize {
+        LOGGER
+            .records
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(level, message)| {
+                *level == log::Level::Warn
+                    && message.starts_with(&format!("{log_id}:"))
+                    && message.contains("Consider increasing 'cycles' or 'recover_cycles'.")
+            })
+            .count()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fast_recovery_warning() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+        let alarm = |log_id: &str, cycles| {
+            let actions = StateMachineActions::default();
+            let mut alarm = state_machine_alarm(&actions, cycles, 0, 1, 0);
+            alarm.log_id = String::from(log_id);
+            alarm.set_fast_recovery_warning(Some(3));
+            alarm
+        };
+
+        let log_id = "Alarm test_fast_recovery_warning";
+        let mut fast = alarm(log_id, 1);
+        for _ in 0..3 {
+            put_data_times(&mut fast, 20, 1).await;
+            put_data_times(&mut fast, 10, 1).await;
+        }
+        assert_eq!(fast_recovery_warnings(log_id), 0);
+        // The fourth fast recovery within an hour warns.
+        put_data_times(&mut fast, 20, 1).await;
+        put_data_times(&mut fast, 10, 1).await;
+        assert_eq!(fast_recovery_warnings(log_id), 1);
+        // The count starts over after the warning and only the last hour counts.
+        for _ in 0..3 {
+            put_data_times(&mut fast, 20, 1).await;
+            put_data_times(&mut fast, 10, 1).await;
+        }
+        tokio::time::advance(std::time::Duration::from_secs(3601)).await;
+        put_data_times(&mut fast, 20, 1).await;
+        put_data_times(&mut fast, 10, 1).await;
+        assert_eq!(fast_recovery_warnings(log_id), 1);
+
+        // Staying bad for more than one cycle is not a fast recovery.
+        let log_id = "Alarm test_fast_recovery_warning slow";
+        let mut slow = alarm(log_id, 1);
+        for _ in 0..5 {
+            put_data_times(&mut slow, 20, 2).await;
+            put_data_times(&mut slow, 10, 1).await;
+        }
+        assert_eq!(fast_recovery_warnings(log_id), 0);
+
+        // Neither is triggering after more than one bad cycle.
+        let log_id = "Alarm test_fast_recovery_warning cycles";
+        let mut cycles = alarm(log_id, 2);
+        for _ in 0..5 {
+            put_data_times(&mut cycles, 20, 2).await;
+            put_data_times(&mut cycles, 10, 1).await;
+        }
+        assert_eq!(fast_recovery_warnings(log_id), 0);
+
+        // Without the warning, nothing is logged.
+        let log_id = "Alarm test_fast_recovery_warning disabled";
+        let mut disabled = alarm(log_id, 1);
+        disabled.set_fast_recovery_warning(None);
+        for _ in 0..5 {
+            put_data_times(&mut disabled, 20, 1).await;
+            put_data_times(&mut disabled, 10, 1).await;
+        }
+        assert_eq!(fast_recovery_warnings(log_id), 0);
+    }
+
+    #[tokio::test]
+    async fn test_log_level() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+        use log::Level::{Debug, Error as Err, Warn};
+        // Each test case logs "data is bad", "good to bad", "got an error" and "bad to error".
+        for (i, (log_level, expected_levels)) in [
+            (None, vec![Warn, Warn, Err, Warn]),
+            (Some(log::LevelFilter::Debug), vec![Debug; 4]),
+            (Some(log::LevelFilter::Warn), vec![Warn; 4]),
+            (Some(log::LevelFilter::Off), vec![]),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let log_id = format!("Alarm test_log_level {i}");
+            let mut alarm = AlarmBase::new(
+                String::from("Name"),
+                String::from("ID"),
+                times_action(1),
+                PlaceholderMap::new(),
+                StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, log_id.clone(), log_level)
+                    .unwrap(),
+                mock_data_sink(),
+                log_id.clone(),
+            )
+            .unwrap();
+            alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+            alarm.set_error_action(Some(times_action(1)), PlaceholderMap::new());
+            alarm.set_error_recover_action(Some(times_action(0)), PlaceholderMap::new());
+            alarm.set_log_level(log_level);
+            alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+            alarm
+                .put_error(
+                    &Error::Message(String::from("Error")),
+                    PlaceholderMap::new(),
+                )
+                .await
+                .unwrap();
+            let levels: Vec<log::Level> = LOGGER
+                .records
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, message)| message.starts_with(&log_id))
+                .map(|(level, _)| *level)
+                .collect();
+            assert_eq!(levels, expected_levels);
+        }
+        assert!(LOGGER
+            .records
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(_, message)| message == "Alarm test_log_level 0: Data is bad (mock): 20%"));
+    }
+
+    // Minimal parser for the edges generated by `to_dot_graph`.
+    fn parse_dot_edges(graph: &str) -> Vec<(String, String, String)> {
+        graph
+            .lines()
+            .filter_map(|line| {
+                let (from, rest) = line.trim().split_once(" -> ")?;
+                let (to, rest) = rest.split_once(" [label=")?;
+                let label = rest.strip_suffix("];")?;
+                let unquote = |x: &str| x.trim_matches('"').to_string();
+                Some((unquote(from), unquote(to), unquote(label)))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_to_dot_graph() {
+        let mut mock_data_sink = valid_mock_data_sink();
+        mock_data_sink.expect_put_data().never();
         let mut alarm = AlarmBase::new(
             String::from("Name"),
             String::from("ID"),
-            times_action(1),
-            PlaceholderMap::new(),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
-            Some(times_action(0)),
-            PlaceholderMap::new(),
-            Some(times_action(0)),
+            times_action(0),
             PlaceholderMap::new(),
-            true,
-            mock_state_machine,
+            StateMachine::new(3, RepeatSchedule::Fixed(100), 4, 0, String::new(), None).unwrap(),
             mock_data_sink,
-            String::from(""),
+            String::from("Alarm 'Name'"),
         )
         .unwrap();
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_invert(true);
+        let graph = alarm.to_dot_graph();
+        assert!(graph.starts_with("digraph \"Alarm 'Name' (inverted)\" {\n"));
+        assert!(graph.contains("\"Good\" [label=\"Good (current)\" style=filled"));
+        assert!(graph.contains("    \"Bad\";\n"));
+        let edges = parse_dot_edges(&graph);
+        let expected = [
+            ("Good", "Good", "good data or < 3 bad cycles"),
+            ("Good", "Bad", "3 bad cycles / action"),
+            (
+                "Bad",
+                "Bad",
+                "bad data or < 4 good cycles, repeat every 100 cycles / action",
+            ),
+            ("Bad", "Good", "4 good cycles / recover_action"),
+            ("Good", "Error", "error / error_action"),
+            ("Bad", "Error", "error / error_action"),
+            ("Error", "Error", "error"),
+            ("Error", "Good", "good data"),
+            ("Error", "Bad", "bad data"),
+        ];
+        assert_eq!(edges.len(), expected.len());
+        for (edge, (from, to, label)) in edges.iter().zip(expected) {
+            assert_eq!(edge, &(from.to_string(), to.to_string(), label.to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_escalation() {
+        let triggered = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let named_action = |name: &'static str| -> std::sync::Arc<dyn action::Action> {
+            let triggered = triggered.clone();
+            let mut mock_action = action::MockAction::new();
+            mock_action.expect_trigger().returning(move |_| {
+                triggered.lock().unwrap().push(name);
+                Ok(())
+            });
+            std::sync::Arc::new(mock_action)
+        };
+        let mut alarm = alarm_base(
+            named_action("first"),
+            StateMachine::new(1, RepeatSchedule::Fixed(1), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm
+            .set_escalation(vec![
+                (5, named_action("third")),
+                (3, named_action("second")),
+            ])
+            .unwrap();
+        alarm.set_recover_action(Some(named_action("recover")), PlaceholderMap::new());
+        for _ in 0..6 {
+            alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        }
         alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
-        alarm.action = times_action(0);
-        alarm.recover_action = Some(times_action(1));
         alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(
+            *triggered.lock().unwrap(),
+            ["first", "first", "second", "second", "third", "third", "recover", "first"]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cooldown() {
+        let triggered = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let named_action = |name: &'static str| -> std::sync::Arc<dyn action::Action> {
+            let triggered = triggered.clone();
+            let mut mock_action = action::MockAction::new();
+            mock_action.expect_trigger().returning(move |placeholders| {
+                assert_ne!(
+                    placeholders
+                        .get("alarm_cooldown_active")
+                        .map(String::as_str),
+                    Some("true")
+                );
+                triggered.lock().unwrap().push(name);
+                Ok(())
+            });
+            std::sync::Arc::new(mock_action)
+        };
+        let mut alarm = alarm_base(
+            named_action("trigger"),
+            StateMachine::new(1, RepeatSchedule::Fixed(1), 1, 1, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(Some(named_action("recover")), PlaceholderMap::new());
+        alarm.set_error_action(Some(named_action("error")), PlaceholderMap::new());
+        alarm.set_error_recover_action(Some(named_action("error_recover")), PlaceholderMap::new());
+        alarm
+            .set_cooldown(Some(std::time::Duration::from_secs(60)))
+            .unwrap();
+        let error = Error::Message(String::from("Error"));
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        tokio::time::advance(std::time::Duration::from_secs(59)).await;
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        tokio::time::advance(std::time::Duration::from_secs(1)).await;
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(
+            *triggered.lock().unwrap(),
+            [
+                "trigger",
+                "recover",
+                "error",
+                "error_recover",
+                "trigger",
+                "error",
+                "error_recover"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cooldown_validation() {
+        let mut alarm = alarm_base(
+            times_action(0),
+            StateMachine::new(1, RepeatSchedule::Fixed(1), 1, 0, String::new(), None).unwrap(),
+            valid_mock_data_sink(),
+        );
+        assert!(alarm.set_cooldown(Some(std::time::Duration::ZERO)).is_err());
+    }
+
+    #[test]
+    fn test_data_sink_validation() {
+        let mut mock_data_sink = MockDataSink::new();
+        mock_data_sink
+            .expect_validate()
+            .returning(|| Err(Error::invalid_config("level", "is invalid.")));
+        let result = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            times_action(0),
+            PlaceholderMap::new(),
+            StateMachine::new(1, RepeatSchedule::Fixed(1), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink,
+            String::new(),
+        );
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Alarm 'Name': 'level' is invalid."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disabled() {
+        let mut data_sink = mock_data_sink();
+        // The data sink starts over whenever the alarm is enabled again.
+        data_sink.expect_reset().times(2).return_const(());
+        let mut alarm = alarm_base(
+            times_action(2),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            data_sink,
+        );
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_error_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm.set_disabled(true);
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm
+            .put_error(
+                &Error::Message(String::from("Error")),
+                PlaceholderMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        assert!(alarm.to_dot_graph().contains("(disabled)"));
+        alarm.set_disabled(false);
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        // The bad state is discarded when the alarm is enabled again.
+        alarm.set_disabled(true);
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        alarm.set_disabled(false);
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+    }
+
+    #[tokio::test]
+    async fn test_snapshot() {
+        let actions: ActionMap = ["Action", "Escalation", "Recover", "Error", "ErrorRecover"]
+            .into_iter()
+            .map(|name| {
+                let mut mock_action = action::MockAction::new();
+                mock_action.expect_trigger().returning(|_| Ok(()));
+                let action: std::sync::Arc<dyn action::Action> = std::sync::Arc::new(
+                    action::ActionBase::new(
+                        String::from(name),
+                        std::time::Duration::from_secs(1),
+                        PlaceholderMap::new(),
+                        mock_action,
+                    )
+                    .unwrap(),
+                );
+                (String::from(name), action)
+            })
+            .collect();
+        let placeholders = |value: &str| PlaceholderMap::from([(String::from("P"), value.into())]);
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            actions["Action"].clone(),
+            placeholders("A"),
+            StateMachine::new(
+                2,
+                RepeatSchedule::Fixed(3),
+                4,
+                5,
+                String::from("LogID"),
+                None,
+            )
+            .unwrap(),
+            mock_data_sink(),
+            String::from("LogID"),
+        )
+        .unwrap();
+        alarm
+            .set_escalation(vec![(5, actions["Escalation"].clone())])
+            .unwrap();
+        alarm.set_recover_action(Some(actions["Recover"].clone()), placeholders("R"));
+        alarm.set_error_action(Some(actions["Error"].clone()), placeholders("E"));
+        alarm.set_error_recover_action(Some(actions["ErrorRecover"].clone()), placeholders("ER"));
+        alarm.set_invert(true);
+        alarm
+            .set_cooldown(Some(std::time::Duration::from_secs(10)))
+            .unwrap();
+        alarm.set_log_level(Some(log::LevelFilter::Debug));
+        // inverted, so this is bad data
+        let check_placeholders =
+            PlaceholderMap::from([(String::from("check_name"), String::from("Check"))]);
+        for _ in 0..3 {
+            alarm
+                .put_data(&10, check_placeholders.clone())
+                .await
+                .unwrap();
+        }
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        let snapshot = alarm.to_snapshot().unwrap();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: AlarmSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, snapshot);
+        assert_eq!(restored.version, snapshot::SNAPSHOT_VERSION);
+        assert_eq!(restored.name, "Name");
+        assert_eq!(restored.id, "ID");
+        assert_eq!(restored.action, "Action");
+        assert_eq!(restored.placeholders, placeholders("A"));
+        assert_eq!(restored.escalation, [(5, String::from("Escalation"))]);
+        assert_eq!(restored.recover_action.as_deref(), Some("Recover"));
+        assert_eq!(restored.recover_placeholders, placeholders("R"));
+        assert_eq!(restored.error_action.as_deref(), Some("Error"));
+        assert_eq!(restored.error_placeholders, placeholders("E"));
+        assert_eq!(
+            restored.error_recover_action.as_deref(),
+            Some("ErrorRecover")
+        );
+        assert_eq!(restored.error_recover_placeholders, placeholders("ER"));
+        assert!(restored.invert);
+        assert!(!restored.disabled);
+        assert_eq!(restored.cooldown, Some(std::time::Duration::from_secs(10)));
+        assert_eq!(restored.log_id, "LogID");
+        assert_eq!(restored.log_level, Some(log::LevelFilter::Debug));
+        let alarm = alarm.restore(restored, &actions).unwrap();
+        assert_eq!(alarm.to_snapshot().unwrap(), snapshot);
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        assert_eq!(alarm.state_machine.bad_cycles(), Some(2));
+        assert_eq!(alarm.escalation[0].0, 5);
+        assert!(std::sync::Arc::ptr_eq(&alarm.action, &actions["Action"]));
+    }
+
+    #[test]
+    fn test_snapshot_version() {
+        let alarm = alarm_base(
+            std::sync::Arc::new(
+                action::ActionBase::new(
+                    String::from("Action"),
+                    std::time::Duration::from_secs(1),
+                    PlaceholderMap::new(),
+                    action::MockAction::new(),
+                )
+                .unwrap(),
+            ),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            valid_mock_data_sink(),
+        );
+        let actions = ActionMap::from([(String::from("Action"), alarm.action.clone())]);
+        let mut snapshot = alarm.to_snapshot().unwrap();
+        snapshot.version += 1;
+        assert!(AlarmBase::<_, StateMachine>::from_snapshot(
+            snapshot.clone(),
+            valid_mock_data_sink(),
+            &actions
+        )
+        .is_err());
+        snapshot.version -= 1;
+        assert!(snapshot.same_config(&alarm.to_snapshot().unwrap()));
+        snapshot.invert = true;
+        assert!(!snapshot.same_config(&alarm.to_snapshot().unwrap()));
+        assert!(AlarmBase::<_, StateMachine>::from_snapshot(
+            snapshot,
+            valid_mock_data_sink(),
+            &actions
+        )
+        .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_clone_reset() {
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            times_action(2),
+            PlaceholderMap::from([(String::from("Hello"), String::from("World"))]),
+            StateMachine::new(
+                1,
+                RepeatSchedule::Fixed(0),
+                1,
+                0,
+                String::from("LogID"),
+                None,
+            )
+            .unwrap(),
+            mock_data_sink(),
+            String::from("LogID"),
+        )
+        .unwrap();
+        alarm.set_invert(true);
+        // inverted, so this is bad data
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        let mut clone = alarm.clone_reset(
+            String::from("Clone"),
+            String::from("CloneID"),
+            String::from("CloneLogID"),
+            mock_data_sink(),
+        );
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        assert_eq!(clone.state_machine.state_name(), "Good");
+        assert_eq!(clone.name, "Clone");
+        assert_eq!(clone.id, "CloneID");
+        assert_eq!(clone.log_id, "CloneLogID");
+        assert_eq!(clone.placeholders, alarm.placeholders);
+        assert!(clone.invert);
+        assert!(std::sync::Arc::ptr_eq(&clone.action, &alarm.action));
+        // The state machines are independent.
+        clone.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        clone.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(clone.state_machine.state_name(), "Good");
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_auto_recovery() {
+        let triggered = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let named_action = |name: &'static str| -> std::sync::Arc<dyn action::Action> {
+            let triggered = triggered.clone();
+            let mut mock_action = action::MockAction::new();
+            mock_action.expect_trigger().returning(move |placeholders| {
+                triggered
+                    .lock()
+                    .unwrap()
+                    .push((name, placeholders["alarm_state"].clone()));
+                Ok(())
+            });
+            std::sync::Arc::new(mock_action)
+        };
+        let mut alarm = alarm_base(
+            named_action("trigger"),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(Some(named_action("recover")), PlaceholderMap::new());
+        alarm
+            .set_auto_recovery(Some(std::time::Duration::from_secs(60)))
+            .unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        tokio::time::advance(std::time::Duration::from_secs(59)).await;
+        alarm.tick(PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        tokio::time::advance(std::time::Duration::from_secs(1)).await;
+        alarm.tick(PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        // Bad data keeps the alarm in the bad state but does not extend the timeout.
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        tokio::time::advance(std::time::Duration::from_secs(30)).await;
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        tokio::time::advance(std::time::Duration::from_secs(30)).await;
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        assert_eq!(
+            *triggered.lock().unwrap(),
+            [
+                ("trigger", String::from("Bad")),
+                ("recover", String::from("Good")),
+                ("trigger", String::from("Bad")),
+                ("recover", String::from("Good")),
+                ("trigger", String::from("Bad")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auto_recovery_validation() {
+        let mut alarm = alarm_base(
+            times_action(0),
+            state_machine::MockStateHandler::new(),
+            valid_mock_data_sink(),
+        );
+        assert!(alarm
+            .set_auto_recovery(Some(std::time::Duration::ZERO))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_placeholder_precedence() {
+        let placeholders = |level: &str, keys: &[&str]| -> PlaceholderMap {
+            keys.iter()
+                .map(|key| (String::from(*key), String::from(level)))
+                .collect()
+        };
+        let mut mock_action = action::MockAction::new();
+        mock_action
+            .expect_trigger()
+            .once()
+            .with(function(|placeholders: &PlaceholderMap| {
+                placeholders["A"] == "call"
+                    && placeholders["B"] == "alarm"
+                    && placeholders["C"] == "group"
+                    && placeholders["D"] == "global"
+            }))
+            .returning(|_| Ok(()));
+        let mut alarm = AlarmBase::new(
+            String::from("Name"),
+            String::from("ID"),
+            times_action(1),
+            placeholders("alarm", &["A", "B"]),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+            String::new(),
+        )
+        .unwrap();
+        alarm.set_group_placeholders(placeholders("group", &["A", "B", "C"]));
+        alarm.set_recover_action(
+            Some(std::sync::Arc::new(mock_action)),
+            placeholders("call", &["A"]),
+        );
+        let global = placeholders("global", &["A", "B", "C", "D"]);
+        alarm.put_data(&20, global.clone()).await.unwrap();
+        alarm.put_data(&10, global).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_simulate() {
+        let mut alarm = alarm_base(
+            times_action(1),
+            StateMachine::new(2, RepeatSchedule::Fixed(3), 2, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        // The simulation starts in the initial state regardless of the alarm's state.
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        let (g, b) = (SinkDecision::Good, SinkDecision::Bad);
+        let events = alarm.simulate(&[b, b, b, b, b, g, b, g, g, b]);
+        let event =
+            |cycle, state_before, state_after, event: Option<&'static str>| SimulationEvent {
+                cycle,
+                state_before,
+                state_after,
+                event,
+                would_trigger: event.is_some(),
+            };
+        assert_eq!(
+            events,
+            [
+                event(1, "Good", "Good", None),
+                event(2, "Good", "Bad", Some("trigger")),
+                event(3, "Bad", "Bad", None),
+                event(4, "Bad", "Bad", None),
+                event(5, "Bad", "Bad", Some("trigger")),
+                event(6, "Bad", "Bad", None),
+                event(7, "Bad", "Bad", None),
+                event(8, "Bad", "Bad", None),
+                event(9, "Bad", "Good", Some("recover")),
+                event(10, "Good", "Good", None),
+            ]
+        );
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        assert_eq!(alarm.state_machine.bad_cycles(), Some(1));
+        assert_eq!(
+            events[1].to_string(),
+            "cycle 2: Good -> Bad (trigger, triggers action)"
+        );
+        alarm.recover_action = None;
+        assert!(!alarm.simulate(&[b, b, g, g])[3].would_trigger);
+    }
+
+    #[tokio::test]
+    async fn test_simulate_cycle() {
+        let alarm = alarm_base(
+            times_action(0),
+            StateMachine::new(5, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        let mut state = alarm.simulation_state();
+        let fired: Vec<bool> = (1..=5)
+            .map(|cycle| {
+                alarm
+                    .simulate_cycle(&mut state, cycle, SinkDecision::Bad)
+                    .would_trigger
+            })
+            .collect();
+        assert_eq!(fired, [false, false, false, false, true]);
+        assert_eq!(state.state_name(), "Bad");
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        // The same steps from a fresh state give the same events.
+        let mut other_state = alarm.simulation_state();
+        for cycle in 1..=4 {
+            alarm.simulate_cycle(&mut other_state, cycle, SinkDecision::Bad);
+        }
+        assert_eq!(
+            alarm.simulate_cycle(&mut other_state, 5, SinkDecision::Bad),
+            alarm.simulate(&[SinkDecision::Bad; 5])[4]
+        );
+    }
+
+    // Configuration of an alarm for the randomized tests of the state machine.
+    #[derive(Clone, Copy, Debug)]
+    struct RandomConfig {
+        trigger_cycles: u32,
+        repeat_cycles: u32,
+        recover_cycles: u32,
+    }
+
+    fn random_config_alarm(config: RandomConfig) -> AlarmBase<MockDataSink> {
+        let mut alarm = alarm_base(
+            times_action(0),
+            StateMachine::new(
+                config.trigger_cycles,
+                RepeatSchedule::Fixed(config.repeat_cycles),
+                config.recover_cycles,
+                0,
+                String::new(),
+                None,
+            )
+            .unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm
+    }
+
+    // Describes the first invariant of the state machine that is violated by the decisions.
+    fn check_invariants(
+        config: RandomConfig,
+        decisions: &[SinkDecision],
+    ) -> std::result::Result<(), String> {
+        let events = random_config_alarm(config).simulate(decisions);
+        if events != random_config_alarm(config).simulate(decisions) {
+            return Err(String::from("The same decisions gave different events."));
+        }
+        let bad_cycles = decisions
+            .iter()
+            .filter(|x| matches!(x, SinkDecision::Bad))
+            .count();
+        let transitions = events
+            .iter()
+            .filter(|x| x.event == Some("trigger") && x.state_before == "Good")
+            .count();
+        if transitions > bad_cycles / config.trigger_cycles as usize {
+            return Err(format!(
+                "{transitions} transition(s) to the bad state with {bad_cycles} bad cycle(s)."
+            ));
+        }
+        let mut last_trigger = None;
+        let mut good_cycles = 0;
+        for (event, decision) in events.iter().zip(decisions) {
+            if event.event == Some("trigger") {
+                if matches!(decision, SinkDecision::Good) {
+                    return Err(format!("Cycle {}: Trigger without bad data.", event.cycle));
+                }
+                // Repetitions have to wait for `repeat_cycles` cycles since the last trigger.
+                if let (Some(last_trigger), "Bad") = (last_trigger, event.state_before) {
+                    if event.cycle - last_trigger < config.repeat_cycles as usize {
+                        return Err(format!(
+                            "Cycle {}: Repeated {} cycle(s) after the last trigger.",
+                            event.cycle,
+                            event.cycle - last_trigger
+                        ));
+                    }
+                }
+                last_trigger = Some(event.cycle);
+            }
+            match (event.state_before, decision) {
+                ("Bad", SinkDecision::Good) => {
+                    good_cycles += 1;
+                    let recovered = event.state_after == "Good";
+                    if recovered != (good_cycles == config.recover_cycles) {
+                        return Err(format!(
+                            "Cycle {}: {} after {good_cycles} good cycle(s).",
+                            event.cycle,
+                            if recovered { "Recovered" } else { "Still bad" }
+                        ));
+                    }
+                    if recovered != (event.event == Some("recover")) {
+                        return Err(format!(
+                            "Cycle {}: Recover event does not match the state change.",
+                            event.cycle
+                        ));
+                    }
+                }
+                _ => good_cycles = 0,
+            }
+        }
+        Ok(())
+    }
+
+    fn random_config() -> impl proptest::strategy::Strategy<Value = RandomConfig> {
+        use proptest::strategy::Strategy;
+        (1..=4u32, 0..=4u32, 1..=4u32).prop_map(
+            |(trigger_cycles, repeat_cycles, recover_cycles)| RandomConfig {
+                trigger_cycles,
+                repeat_cycles,
+                recover_cycles,
+            },
+        )
+    }
+
+    // Varying the probability gives long streaks of good or bad data, too.
+    fn random_decisions() -> impl proptest::strategy::Strategy<Value = Vec<SinkDecision>> {
+        use proptest::strategy::Strategy;
+        (0.1..0.9f64).prop_flat_map(|bad_probability| {
+            proptest::collection::vec(
+                proptest::bool::weighted(bad_probability).prop_map(|bad| {
+                    if bad {
+                        SinkDecision::Bad
+                    } else {
+                        SinkDecision::Good
+                    }
+                }),
+                0..64,
+            )
+        })
+    }
+
+    // A failure is reported with the minimal decisions proptest shrank it to.
+    #[tokio::test]
+    async fn test_random_decisions() {
+        let mut runner = proptest::test_runner::TestRunner::new(proptest::test_runner::Config {
+            cases: 256,
+            failure_persistence: None,
+            ..Default::default()
+        });
+        if let Err(err) = runner.run(
+            &(random_config(), random_decisions()),
+            |(config, decisions)| {
+                check_invariants(config, &decisions)
+                    .map_err(proptest::test_runner::TestCaseError::fail)
+            },
+        ) {
+            panic!("{err}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay() {
+        let mut alarm = alarm_base(
+            times_action(0),
+            StateMachine::new(2, RepeatSchedule::Fixed(6), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(Some(times_action(0)), PlaceholderMap::new());
+        alarm
+            .set_cooldown(Some(std::time::Duration::from_secs(3600)))
+            .unwrap();
+        // One data point every 5 minutes for 24 hours. Bad from 02:00 to 03:55, from 12:00 to
+        // 12:55 and once at 18:00.
+        let start = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_006_400);
+        let data: Vec<_> = (0..288)
+            .map(|i| {
+                let minutes = i * 5;
+                let bad = (120..240).contains(&minutes)
+                    || (720..780).contains(&minutes)
+                    || minutes == 1080;
+                HistoricalDataPoint {
+                    timestamp: start + std::time::Duration::from_secs(minutes * 60),
+                    data: if bad { 20 } else { 10 },
+                }
+            })
+            .collect();
+        let events = alarm.replay(&data).await.unwrap();
+        let count = |event: &str| {
+            events
+                .iter()
+                .flat_map(|x| x.events.iter())
+                .filter(|x| *x == event)
+                .count()
+        };
+        // The repetitions every 30 minutes are only triggered if the 1 hour cooldown has passed.
+        assert_eq!(count("trigger"), 3);
+        assert_eq!(count("recover"), 2);
+        assert_eq!(
+            events[0].to_string(),
+            "2023-11-15T02:05:00.000Z: Good -> Bad (triggers trigger)"
+        );
+        assert!(alarm.replay.is_none());
+
+        let unsorted = [
+            HistoricalDataPoint {
+                timestamp: start,
+                data: 10,
+            },
+            HistoricalDataPoint {
+                timestamp: start - std::time::Duration::from_secs(1),
+                data: 10,
+            },
+        ];
+        assert!(alarm.replay(&unsorted).await.is_err());
+    }
+
+    #[test]
+    fn test_escalation_validation() {
+        let mut alarm = alarm_base(
+            times_action(0),
+            StateMachine::new(1, RepeatSchedule::Fixed(1), 1, 0, String::new(), None).unwrap(),
+            valid_mock_data_sink(),
+        );
+        assert!(alarm.set_escalation(vec![(1, times_action(0))]).is_err());
+        assert!(alarm
+            .set_escalation(vec![(3, times_action(0)), (3, times_action(0))])
+            .is_err());
+        assert!(alarm
+            .set_escalation(vec![(3, times_action(0)), (2, times_action(0))])
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_never_bad_warning() {
+        let mut mock_warn_action = action::MockAction::new();
+        mock_warn_action
+            .expect_trigger()
+            .times(2)
+            .with(function(|placeholders: &PlaceholderMap| {
+                placeholders["alarm_event"] == "never_bad"
+                    && placeholders["alarm_never_bad_cycles"] == "3"
+            }))
+            .returning(|_| Ok(()));
+        let mut alarm = alarm_base(
+            times_action(1),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(Some(times_action(1)), PlaceholderMap::new());
+        alarm
+            .set_never_bad_warning(Some(3), Some(std::sync::Arc::new(mock_warn_action)))
+            .unwrap();
+        // 2 good cycles, the 3rd one warns.
+        for _ in 0..3 {
+            alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        }
+        // The bad cycle resets the counter. Another 3 good cycles warn again.
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        for _ in 0..3 {
+            alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        }
+        assert_eq!(alarm.never_bad_cycles, 3);
+    }
+
+    #[test]
+    fn test_never_bad_warning_validation() {
+        let alarm = || {
+            alarm_base(
+                times_action(0),
+                StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+                valid_mock_data_sink(),
+            )
+        };
+        assert!(alarm().set_never_bad_warning(Some(0), None).is_err());
+        assert!(alarm()
+            .set_never_bad_warning(None, Some(times_action(0)))
+            .is_err());
+        assert!(alarm().set_never_bad_warning(Some(1), None).is_ok());
+    }
+
+    fn max_error_cycles_alarm(
+        max_error_cycles: Option<u32>,
+        recover_action: &action::TestAction,
+        error_recover_action: &action::TestAction,
+    ) -> AlarmBase<MockDataSink> {
+        let mut alarm = alarm_base(
+            times_action(0),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(
+            Some(std::sync::Arc::new(recover_action.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm.set_error_action(Some(times_action(1)), PlaceholderMap::new());
+        alarm.set_error_recover_action(
+            Some(std::sync::Arc::new(error_recover_action.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm.set_max_error_cycles(max_error_cycles).unwrap();
+        alarm
+    }
+
+    #[tokio::test]
+    async fn test_max_error_cycles() {
+        let recover_action = action::TestAction::new();
+        let error_recover_action = action::TestAction::new();
+        let mut alarm = max_error_cycles_alarm(Some(3), &recover_action, &error_recover_action);
+        for _ in 0..2 {
+            alarm
+                .put_error(
+                    &Error::Message(String::from("Error")),
+                    PlaceholderMap::new(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(alarm.state_machine.state_name(), "Error");
+        }
+        recover_action.assert_not_triggered();
+        alarm
+            .put_error(
+                &Error::Message(String::from("Error")),
+                PlaceholderMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        recover_action.assert_triggered_once();
+        assert_eq!(
+            recover_action.last_placeholders().unwrap()["alarm_event"],
+            "recover"
+        );
+        error_recover_action.assert_triggered_once();
+    }
+
+    #[tokio::test]
+    async fn test_max_error_cycles_unset() {
+        let recover_action = action::TestAction::new();
+        let error_recover_action = action::TestAction::new();
+        let mut alarm = max_error_cycles_alarm(None, &recover_action, &error_recover_action);
+        for _ in 0..100 {
+            alarm
+                .put_error(
+                    &Error::Message(String::from("Error")),
+                    PlaceholderMap::new(),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(alarm.state_machine.state_name(), "Error");
+        assert!(!recover_action.was_triggered());
+        error_recover_action.assert_not_triggered();
+        assert!(alarm.set_max_error_cycles(Some(0)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_error_shadowed_state() {
+        let error = Error::Message(String::from("Error"));
+        // The trigger and error actions are not of interest here.
+        let other_action = action::TestAction::new();
+        let shadowing_alarm =
+            |recover_action: &action::TestAction, error_recover_action: &action::TestAction| {
+                let mut alarm = alarm_base(
+                    std::sync::Arc::new(other_action.clone()),
+                    StateMachine::new(2, RepeatSchedule::Fixed(0), 2, 0, String::new(), None)
+                        .unwrap(),
+                    mock_data_sink(),
+                );
+                alarm.set_recover_action(
+                    Some(std::sync::Arc::new(recover_action.clone())),
+                    PlaceholderMap::new(),
+                );
+                alarm.set_error_action(
+                    Some(std::sync::Arc::new(other_action.clone())),
+                    PlaceholderMap::new(),
+                );
+                alarm.set_error_recover_action(
+                    Some(std::sync::Arc::new(error_recover_action.clone())),
+                    PlaceholderMap::new(),
+                );
+                alarm
+            };
+
+        // The error shadows a good state with pending bad cycles. The good data after the error
+        // goes to the good state, there is nothing to recover from.
+        let recover_action = action::TestAction::new();
+        let error_recover_action = action::TestAction::new();
+        let mut alarm = shadowing_alarm(&recover_action, &error_recover_action);
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+            assert_eq!(alarm.state_machine.state_name(), "Good");
+        }
+        recover_action.assert_not_triggered();
+        error_recover_action.assert_triggered_once();
+
+        // The error shadows the bad state. The good data after the error counts towards
+        // `recover_cycles` of the bad state.
+        let recover_action = action::TestAction::new();
+        let error_recover_action = action::TestAction::new();
+        let mut alarm = shadowing_alarm(&recover_action, &error_recover_action);
+        for _ in 0..2 {
+            alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        }
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+        error_recover_action.assert_triggered_once();
+        recover_action.assert_not_triggered();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        recover_action.assert_triggered_once();
+
+        // A second error in between keeps the good cycles of the shadowed bad state.
+        let recover_action = action::TestAction::new();
+        let error_recover_action = action::TestAction::new();
+        let mut alarm = shadowing_alarm(&recover_action, &error_recover_action);
+        for _ in 0..2 {
+            alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        }
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        recover_action.assert_not_triggered();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        recover_action.assert_triggered_once();
+        assert_eq!(error_recover_action.trigger_count(), 2);
+    }
+
+    // Counts its calls and provides the count as placeholder.
+    struct CountingSource(std::sync::atomic::AtomicUsize);
+
+    impl PlaceholderSource for CountingSource {
+        fn provide(&self, placeholders: &mut PlaceholderMap) -> Result<()> {
+            let calls = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            placeholders.insert(String::from("source_calls"), calls.to_string());
+            Ok(())
+        }
+    }
+
+    struct FailingSource;
+
+    impl PlaceholderSource for FailingSource {
+        fn provide(&self, _placeholders: &mut PlaceholderMap) -> Result<()> {
+            Err(Error::Message(String::from("Error")))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_placeholder_sources() {
+        let action = action::TestAction::new();
+        let mut alarm = alarm_base(
+            std::sync::Arc::new(action.clone()),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(
+            Some(std::sync::Arc::new(action.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm.set_error_action(
+            Some(std::sync::Arc::new(action.clone())),
+            PlaceholderMap::new(),
+        );
+        let source = std::sync::Arc::new(CountingSource(Default::default()));
+        alarm.add_placeholder_source(source.clone());
+        // A failing source does not keep the others from providing their placeholders.
+        alarm.add_placeholder_source(std::sync::Arc::new(FailingSource));
+
+        // Good data does not trigger any action, so the source is not called.
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(source.0.load(std::sync::atomic::Ordering::SeqCst), 0);
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm
+            .put_error(
+                &Error::Message(String::from("Error")),
+                PlaceholderMap::new(),
+            )
+            .await
+            .unwrap();
+        let records = action.records();
+        assert_eq!(records.len(), 3);
+        for (i, event) in ["trigger", "recover", "error"].iter().enumerate() {
+            assert_eq!(records[i].placeholders["alarm_event"], *event);
+            assert_eq!(records[i].placeholders["source_calls"], (i + 1).to_string());
+        }
+        assert_eq!(source.0.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_alarm_value() {
+        let action = action::TestAction::new();
+        let mut alarm = alarm_base(
+            std::sync::Arc::new(action.clone()),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(
+            Some(std::sync::Arc::new(action.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm.set_error_action(
+            Some(std::sync::Arc::new(action.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        // Errors have no data, they get the value of the last data.
+        alarm
+            .put_error(
+                &Error::Message(String::from("Error")),
+                PlaceholderMap::new(),
+            )
+            .await
+            .unwrap();
+        let values: Vec<_> = action
+            .records()
+            .into_iter()
+            .map(|x| {
+                (
+                    x.placeholders["alarm_event"].clone(),
+                    x.placeholders["alarm_value"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            values,
+            [("trigger", "20%"), ("recover", "10%"), ("error", "10%")]
+                .map(|(event, value)| (String::from(event), String::from(value)))
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_explain_last_non_trigger() {
+        let action = action::TestAction::new();
+        let mut alarm = alarm_base(
+            std::sync::Arc::new(action.clone()),
+            StateMachine::new(2, RepeatSchedule::Fixed(3), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm
+            .set_cooldown(Some(std::time::Duration::from_secs(600)))
+            .unwrap();
+        assert_eq!(alarm.explain_last_non_trigger(), None);
+        let error = Error::Message(String::from("Error"));
+        let mut reasons = Vec::new();
+        for data in [10, 20, 20, 20, 20, 20] {
+            alarm.put_data(&data, PlaceholderMap::new()).await.unwrap();
+            reasons.push(alarm.explain_last_non_trigger());
+        }
+        for _ in 0..2 {
+            alarm
+                .put_error(&error, PlaceholderMap::new())
+                .await
+                .unwrap();
+            reasons.push(alarm.explain_last_non_trigger());
+        }
+        alarm.set_disabled(true);
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        reasons.push(alarm.explain_last_non_trigger());
+        action.assert_triggered_once();
+        assert_eq!(
+            reasons,
+            [
+                Some("Data sink returned good"),
+                Some("Bad for 1 of 2 cycles needed for the bad state"),
+                None,
+                Some("In bad state but repeat_cycles not reached (cycle 2 of 3)"),
+                Some("In bad state but repeat_cycles not reached (cycle 3 of 3)"),
+                Some("Action suppressed by cooldown (next eligible in 10m 0s)"),
+                Some("No error action configured"),
+                Some("In error state and error_repeat_cycles is not set"),
+                Some("Alarm is disabled"),
+            ]
+            .map(|x| x.map(String::from))
+        );
+    }
+
+    fn warm_up_alarm(trigger_action: &action::TestAction) -> AlarmBase<MockDataSink> {
+        let mut alarm = alarm_base(
+            std::sync::Arc::new(trigger_action.clone()),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(
+            Some(std::sync::Arc::new(trigger_action.clone())),
+            PlaceholderMap::new(),
+        );
+        alarm.set_warm_up_cycles(2);
+        alarm
+    }
+
+    #[tokio::test]
+    async fn test_warm_up_cycles() {
+        // Bad during the warm-up, the trigger is caught up on the first bad cycle after it.
+        let action = action::TestAction::new();
+        let mut alarm = warm_up_alarm(&action);
+        for _ in 0..2 {
+            alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+            assert_eq!(alarm.state_machine.state_name(), "Bad");
+        }
+        action.assert_not_triggered();
+        assert_eq!(
+            alarm.explain_last_non_trigger().unwrap(),
+            "Alarm is in warm-up period (cycle 2 of 2)"
+        );
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        action.assert_triggered_once();
+        let placeholders = action.last_placeholders().unwrap();
+        assert_eq!(placeholders["alarm_event"], "trigger");
+        assert_eq!(placeholders["alarm_warming_up"], "false");
+
+        // Recovered during the warm-up, nothing is ever triggered.
+        let action = action::TestAction::new();
+        let mut alarm = warm_up_alarm(&action);
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Good");
+        action.assert_not_triggered();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_warm_up_cycles_cooldown() {
+        // The suppressed triggers during the warm-up don't start the cooldown.
+        let action = action::TestAction::new();
+        let mut alarm = warm_up_alarm(&action);
+        alarm
+            .set_cooldown(Some(std::time::Duration::from_secs(60)))
+            .unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        action.assert_triggered_once();
+        assert_eq!(
+            action.last_placeholders().unwrap()["alarm_event"],
+            "trigger"
+        );
+        assert_eq!(
+            action.last_placeholders().unwrap()["alarm_cooldown_active"],
+            "false"
+        );
+
+        // The caught up trigger does start it.
+        let action = action::TestAction::new();
+        let mut alarm = warm_up_alarm(&action);
+        alarm
+            .set_cooldown(Some(std::time::Duration::from_secs(60)))
+            .unwrap();
+        for _ in 0..3 {
+            alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        }
+        action.assert_triggered_once();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(action.trigger_count(), 2);
+        assert_eq!(
+            action.last_placeholders().unwrap()["alarm_event"],
+            "recover"
+        );
+        assert_eq!(
+            alarm.explain_last_non_trigger().unwrap(),
+            "Action suppressed by cooldown (next eligible in 1m 0s)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_alarm_value_of_stateful_sink() {
+        let action = action::TestAction::new();
+        let mut alarm = alarm_base(
+            std::sync::Arc::new(action.clone()),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            Ema::<f64>::try_from(
+                &toml::from_str::<config::Alarm>(
+                    "name = \"Alarm\"\naction = \"Action\"\nema_level = 50\nalpha = 0.5",
+                )
+                .unwrap(),
+            )
+            .unwrap(),
+        );
+        alarm.put_data(&40.0, PlaceholderMap::new()).await.unwrap();
+        action.assert_not_triggered();
+        alarm.put_data(&80.0, PlaceholderMap::new()).await.unwrap();
+        action.assert_triggered_once();
+        // The average includes the data that triggered the alarm.
+        assert_eq!(
+            action.last_placeholders().unwrap()["alarm_value"],
+            "80.00 (EMA 60.00)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chain() {
+        let registry = AlarmRegistry::default();
+        let chained_alarm = |trigger_times| {
+            let mut alarm = alarm_base(
+                times_action(trigger_times),
+                StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+                mock_data_sink(),
+            );
+            alarm.set_chain(Some(AlarmChain::new(
+                String::from("Check/Dependency"),
+                registry.clone(),
+            )));
+            alarm
+        };
+        let mut dependency = chained_alarm(1);
+        dependency.set_chain(None);
+        dependency.set_registry(registry.clone(), registry_key("Check", "Dependency", "ID"));
+        assert_eq!(
+            registry.read().unwrap()[&registry_key("Check", "Dependency", "ID")],
+            StateKind::Good
+        );
+
+        // The dependency is good, the trigger is suppressed.
+        let mut alarm = chained_alarm(0);
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(alarm.state_machine.state_name(), "Bad");
+
+        // The dependency is bad, the action is triggered.
+        dependency
+            .put_data(&20, PlaceholderMap::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            registry.read().unwrap()[&registry_key("Check", "Dependency", "ID")],
+            StateKind::Bad
+        );
+        let mut alarm = chained_alarm(1);
+        alarm.put_data(&20, PlaceholderMap::new()).await.unwrap();
+
+        // Disabled alarms do not count as bad.
+        dependency.set_disabled(true);
+        assert!(registry.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_state_change_callbacks() {
+        let mut alarm = alarm_base(
+            times_action(1),
+            StateMachine::new(2, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            mock_data_sink(),
+        );
+        alarm.set_recover_action(Some(times_action(1)), PlaceholderMap::new());
+        let transitions = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let transitions_clone = transitions.clone();
+        alarm.on_state_change(std::sync::Arc::new(move |transition| {
+            assert_eq!(transition.alarm_id, "ID");
+            assert_eq!(transition.alarm_name, "Name");
+            transitions_clone
+                .lock()
+                .unwrap()
+                .push((transition.from, transition.to));
+        }));
+        for data in [10, 20, 20, 20, 10] {
+            alarm.put_data(&data, PlaceholderMap::new()).await.unwrap();
+        }
+        // The error action is not configured, the callbacks are called anyway.
+        alarm
+            .put_error(
+                &Error::Message(String::from("Error")),
+                PlaceholderMap::new(),
+            )
+            .await
+            .unwrap();
+        alarm.tick(PlaceholderMap::new()).await.unwrap();
+        alarm.put_data(&10, PlaceholderMap::new()).await.unwrap();
+        assert_eq!(
+            *transitions.lock().unwrap(),
+            [
+                (StateKind::Good, StateKind::Bad),
+                (StateKind::Bad, StateKind::Good),
+                (StateKind::Good, StateKind::Error),
+                (StateKind::Error, StateKind::Good),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_name() {
+        // The type name in snake case, wrappers add their own name around it.
+        let config: config::Alarm = toml::from_str(
+            r#"
+            name = "Alarm"
+            action = "Action"
+            data_size = 1024
+            "#,
+        )
+        .unwrap();
+        assert_eq!(DataSize::try_from(&config).unwrap().name(), "data_size");
+        let sink = Sampling::<DataSize>::try_from(&config).unwrap();
+        assert_eq!(sink.name(), "sampling(data_size)");
+    }
+
+    #[tokio::test]
+    async fn test_dyn_data_sink() {
+        let sink = |threshold: &str| -> BoxedDataSink<f64> {
+            let config: config::Alarm = toml::from_str(&format!(
+                "name = \"Alarm\"\naction = \"Action\"\n{threshold}"
+            ))
+            .unwrap();
+            match threshold.split_once(' ').unwrap().0 {
+                "level" => Box::new(Level::<f64>::try_from(&config).unwrap()),
+                _ => Box::new(Delta::<f64>::try_from(&config).unwrap()),
+            }
+        };
+        let action = action::TestAction::new();
+        let mut alarm: DynAlarmBase<f64> = alarm_base(
+            std::sync::Arc::new(action.clone()),
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap(),
+            sink("level = 10"),
+        );
+        assert_eq!(alarm.data_sink.name(), "level");
+        alarm.put_data(&12., PlaceholderMap::new()).await.unwrap();
+        action.assert_triggered_once();
+        assert_eq!(action.last_placeholders().unwrap()["level"], "12.00");
+        // The delta that replaces the level has no previous value for the first data.
+        alarm.data_sink = sink("delta = 5");
+        assert_eq!(alarm.data_sink.name(), "delta");
+        assert_eq!(
+            alarm.data_sink.describe_threshold(),
+            "Bad when level changes by more than 5.00"
+        );
+        assert!(matches!(
+            alarm.data_sink.put_data(&12.).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.data_sink.put_data(&20.).unwrap(),
+            SinkDecision::Bad
+        ));
     }
 }
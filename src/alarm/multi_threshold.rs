@@ -0,0 +1,226 @@
+use crate::measurement::Measurement;
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, DataSinkPlaceholders, SinkDecision};
+use crate::config;
+
+// Several levels with a severity each, e.g. a yellow and a red level for the same data. The data
+// is bad if it exceeds any of the levels. The `alarm_severity` placeholder is the severity of the
+// highest level it exceeds.
+#[derive(Clone)]
+pub struct MultiThreshold<T = measurement::Level>
+where
+    T: Measurement + PartialOrd + Copy + Send + Sync,
+{
+    thresholds: Vec<(T, config::Severity)>,
+    last: Option<T>,
+}
+
+impl<T> MultiThreshold<T>
+where
+    T: Measurement + PartialOrd + Copy + Send + Sync,
+{
+    fn new(thresholds: Vec<(T, config::Severity)>) -> Result<Self> {
+        if thresholds.is_empty() {
+            Err(Error::invalid_config("thresholds", "cannot be empty."))
+        } else if thresholds
+            .iter()
+            .any(|(level, _)| level.partial_cmp(level).is_none())
+        {
+            Err(Error::invalid_config("level", "must be a number."))
+        } else if thresholds
+            .windows(2)
+            .any(|x| x[0].0.partial_cmp(&x[1].0) != Some(std::cmp::Ordering::Less))
+        {
+            Err(Error::invalid_config(
+                "thresholds",
+                "must be sorted by 'level' in ascending order without duplicates.",
+            ))
+        } else {
+            Ok(Self {
+                thresholds,
+                last: None,
+            })
+        }
+    }
+
+    // The levels are sorted, so the last one exceeded is the highest one.
+    fn severity(&self, data: &T) -> Option<config::Severity> {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|(level, _)| data > level)
+            .map(|(_, severity)| *severity)
+    }
+}
+
+impl<T> TryFrom<&config::Alarm> for MultiThreshold<T>
+where
+    T: Measurement + PartialOrd + Copy + Send + Sync,
+    T::Data: num_traits::NumCast,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        if let config::AlarmType::MultiThreshold(multi_threshold) = &alarm.type_ {
+            let thresholds = multi_threshold
+                .thresholds
+                .iter()
+                .map(|threshold| {
+                    let level = num_traits::cast(threshold.level).ok_or_else(|| {
+                        Error::invalid_config(
+                            "level",
+                            format!("{} is out of range.", threshold.level),
+                        )
+                    })?;
+                    Ok((T::new(level)?, threshold.severity))
+                })
+                .collect::<Result<_>>()?;
+            Self::new(thresholds)
+        } else {
+            Err(Error::Message(String::from(
+                "Expected multi threshold alarm config.",
+            )))
+        }
+    }
+}
+
+impl<T> DataSinkPlaceholders<T> for MultiThreshold<T>
+where
+    T: Measurement + PartialOrd + Copy + Send + Sync,
+{
+    fn extra_placeholders(&self, data: &T) -> PlaceholderMap {
+        match self.severity(data) {
+            Some(severity) => {
+                PlaceholderMap::from([(String::from("alarm_severity"), format!("{severity:?}"))])
+            }
+            None => PlaceholderMap::new(),
+        }
+    }
+}
+
+impl<T> DataSink for MultiThreshold<T>
+where
+    T: Measurement + PartialOrd + Copy + Send + Sync,
+{
+    type Item = T;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        let lowest = &self.thresholds[0].0;
+        if data.partial_cmp(lowest).is_none() {
+            return Err(Error::Message(format!(
+                "Cannot compare {} to level {}.",
+                data.format(),
+                lowest.format()
+            )));
+        }
+        self.last = Some(*data);
+        Ok(match self.severity(data) {
+            Some(_) => SinkDecision::Bad,
+            None => SinkDecision::Good,
+        })
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+
+    fn describe_threshold(&self) -> String {
+        let levels: Vec<String> = self
+            .thresholds
+            .iter()
+            .map(|(level, severity)| format!("> {} ({severity:?})", level.format()))
+            .collect();
+        format!("Bad when level {}", levels.join(", "))
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("level"), data.format());
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Some(last) = &self.last {
+            crate::merge_placeholders(placeholders, &self.extra_placeholders(last));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn multi_threshold() -> MultiThreshold<f64> {
+        MultiThreshold::new(vec![
+            (70.0, config::Severity::Low),
+            (85.0, config::Severity::Medium),
+            (95.0, config::Severity::High),
+        ])
+        .unwrap()
+    }
+
+    // Decision and `alarm_severity` placeholder for the data.
+    fn severity(data: f64) -> (bool, Option<String>) {
+        let mut sink = multi_threshold();
+        let decision = sink.put_data(&data).unwrap();
+        let mut placeholders = PlaceholderMap::new();
+        sink.add_state_placeholders(&mut placeholders);
+        (
+            matches!(decision, SinkDecision::Bad),
+            placeholders.remove("alarm_severity"),
+        )
+    }
+
+    #[test]
+    fn test_multi_threshold() {
+        assert_eq!(severity(50.0), (false, None));
+        // The levels themselves are not exceeded.
+        assert_eq!(severity(70.0), (false, None));
+        assert_eq!(severity(75.0), (true, Some(String::from("Low"))));
+        assert_eq!(severity(90.0), (true, Some(String::from("Medium"))));
+        assert_eq!(severity(99.0), (true, Some(String::from("High"))));
+        assert_eq!(
+            multi_threshold().describe_threshold(),
+            "Bad when level > 70.00 (Low), > 85.00 (Medium), > 95.00 (High)"
+        );
+        assert!(multi_threshold().put_data(&f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(MultiThreshold::<f64>::new(Vec::new()).is_err());
+        assert!(MultiThreshold::new(vec![
+            (85.0, config::Severity::Medium),
+            (70.0, config::Severity::Low)
+        ])
+        .is_err());
+        assert!(MultiThreshold::new(vec![
+            (70.0, config::Severity::Low),
+            (70.0, config::Severity::High)
+        ])
+        .is_err());
+        assert!(MultiThreshold::new(vec![(f64::NAN, config::Severity::Low)]).is_err());
+    }
+
+    #[test]
+    fn test_config() {
+        let alarm: config::Alarm = toml::from_str(
+            r#"
+            name = "Alarm"
+            action = "Action"
+            thresholds = [{ level = 80, severity = "Low" }, { level = 90, severity = "Critical" }]
+            "#,
+        )
+        .unwrap();
+        let sink = MultiThreshold::<measurement::Level>::try_from(&alarm).unwrap();
+        assert_eq!(sink.thresholds[1].1, config::Severity::Critical);
+        let alarm: config::Alarm = toml::from_str(
+            r#"
+            name = "Alarm"
+            action = "Action"
+            thresholds = [{ level = 180, severity = "Low" }]
+            "#,
+        )
+        .unwrap();
+        assert!(MultiThreshold::<measurement::Level>::try_from(&alarm).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::measurement::Measurement;
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::delta::AbsDifference;
+use super::{DataSink, Delta, Ema, Level, MultiThreshold, SinkDecision, Trend};
+use crate::config;
+
+// Lets the alarms of the same check use an absolute level, several levels, a delta, a trend or a
+// moving average.
+#[derive(Clone)]
+pub enum NumericSink<T = measurement::Level>
+where
+    T: Measurement + PartialOrd + Copy + Send + Sync,
+    T::Data: AbsDifference + PartialOrd + std::fmt::Display + num_traits::ToPrimitive,
+{
+    Level(Level<T>),
+    Delta(Delta<T>),
+    Trend(Trend<T>),
+    Ema(Ema<T>),
+    MultiThreshold(MultiThreshold<T>),
+}
+
+impl<T> TryFrom<&config::Alarm> for NumericSink<T>
+where
+    T: Measurement + PartialOrd + Copy + Send + Sync,
+    T::Data: AbsDifference + PartialOrd + std::fmt::Display + num_traits::NumCast,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::Delta(_) => Ok(Self::Delta(Delta::try_from(alarm)?)),
+            config::AlarmType::Trend(_) => Ok(Self::Trend(Trend::try_from(alarm)?)),
+            config::AlarmType::Ema(_) => Ok(Self::Ema(Ema::try_from(alarm)?)),
+            config::AlarmType::MultiThreshold(_) => {
+                Ok(Self::MultiThreshold(MultiThreshold::try_from(alarm)?))
+            }
+            _ => Ok(Self::Level(Level::try_from(alarm)?)),
+        }
+    }
+}
+
+impl<T> DataSink for NumericSink<T>
+where
+    T: Measurement + PartialOrd + Copy + Send + Sync,
+    T::Data: AbsDifference + PartialOrd + std::fmt::Display + num_traits::ToPrimitive + Send + Sync,
+{
+    type Item = T;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        match self {
+            Self::Level(level) => level.put_data(data),
+            Self::Delta(delta) => delta.put_data(data),
+            Self::Trend(trend) => trend.put_data(data),
+            Self::Ema(ema) => ema.put_data(data),
+            Self::MultiThreshold(multi_threshold) => multi_threshold.put_data(data),
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Level(level) => level.name(),
+            Self::Delta(delta) => delta.name(),
+            Self::Trend(trend) => trend.name(),
+            Self::Ema(ema) => ema.name(),
+            Self::MultiThreshold(multi_threshold) => multi_threshold.name(),
+        }
+    }
+
+    fn unit(&self) -> &str {
+        match self {
+            Self::Level(level) => level.unit(),
+            Self::Delta(delta) => delta.unit(),
+            Self::Trend(trend) => trend.unit(),
+            Self::Ema(ema) => ema.unit(),
+            Self::MultiThreshold(multi_threshold) => multi_threshold.unit(),
+        }
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        match self {
+            Self::Level(level) => level.format_data(data),
+            Self::Delta(delta) => delta.format_data(data),
+            Self::Trend(trend) => trend.format_data(data),
+            Self::Ema(ema) => ema.format_data(data),
+            Self::MultiThreshold(multi_threshold) => multi_threshold.format_data(data),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        match self {
+            Self::Level(level) => level.validate(),
+            Self::Delta(delta) => delta.validate(),
+            Self::Trend(trend) => trend.validate(),
+            Self::Ema(ema) => ema.validate(),
+            Self::MultiThreshold(multi_threshold) => multi_threshold.validate(),
+        }
+    }
+
+    fn reset(&mut self) {
+        match self {
+            Self::Level(level) => level.reset(),
+            Self::Delta(delta) => delta.reset(),
+            Self::Trend(trend) => trend.reset(),
+            Self::Ema(ema) => ema.reset(),
+            Self::MultiThreshold(multi_threshold) => multi_threshold.reset(),
+        }
+    }
+
+    fn describe_threshold(&self) -> String {
+        match self {
+            Self::Level(level) => level.describe_threshold(),
+            Self::Delta(delta) => delta.describe_threshold(),
+            Self::Trend(trend) => trend.describe_threshold(),
+            Self::Ema(ema) => ema.describe_threshold(),
+            Self::MultiThreshold(multi_threshold) => multi_threshold.describe_threshold(),
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        match self {
+            Self::Level(level) => level.add_placeholders(data, placeholders),
+            Self::Delta(delta) => delta.add_placeholders(data, placeholders),
+            Self::Trend(trend) => trend.add_placeholders(data, placeholders),
+            Self::Ema(ema) => ema.add_placeholders(data, placeholders),
+            Self::MultiThreshold(multi_threshold) => {
+                multi_threshold.add_placeholders(data, placeholders)
+            }
+        }
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        match self {
+            Self::Level(level) => level.add_state_placeholders(placeholders),
+            Self::Delta(delta) => delta.add_state_placeholders(placeholders),
+            Self::Trend(trend) => trend.add_state_placeholders(placeholders),
+            Self::Ema(ema) => ema.add_state_placeholders(placeholders),
+            Self::MultiThreshold(multi_threshold) => {
+                multi_threshold.add_state_placeholders(placeholders)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_name() {
+        let config: config::Alarm = toml::from_str(
+            r#"
+            name = "Alarm"
+            action = "Action"
+            level = 90
+            "#,
+        )
+        .unwrap();
+        let sink = NumericSink::<measurement::Level>::try_from(&config).unwrap();
+        assert_eq!(sink.name(), "level");
+        let config: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\ndelta = 10").unwrap();
+        let sink = NumericSink::<measurement::Level>::try_from(&config).unwrap();
+        assert_eq!(sink.name(), "delta");
+    }
+}
@@ -0,0 +1,100 @@
+use crate::config;
+use crate::{Error, PlaceholderMap, Result};
+
+// Provides placeholders that are only computed when an action is actually triggered, e.g. because
+// they are expensive or change over time.
+pub trait PlaceholderSource: Send + Sync {
+    fn provide(&self, placeholders: &mut PlaceholderMap) -> Result<()>;
+}
+
+// Host name of the machine MinMon runs on.
+pub struct HostnameSource;
+
+impl PlaceholderSource for HostnameSource {
+    fn provide(&self, placeholders: &mut PlaceholderMap) -> Result<()> {
+        let hostname = nix::unistd::gethostname()
+            .map_err(|x| Error::Message(format!("Could not get host name: {x}")))?
+            .into_string()
+            .map_err(|_| Error::Message(String::from("Host name is not valid UTF-8.")))?;
+        placeholders.insert(String::from("hostname"), hostname);
+        Ok(())
+    }
+}
+
+// Value of an environment variable at the time of the trigger.
+pub struct EnvVarSource {
+    variable: String,
+    placeholder: String,
+}
+
+impl EnvVarSource {
+    pub fn new(variable: String, placeholder: Option<String>) -> Result<Self> {
+        if variable.is_empty() {
+            Err(Error::invalid_config("variable", "cannot be empty."))
+        } else if placeholder.as_ref().is_some_and(|x| x.is_empty()) {
+            Err(Error::invalid_config("placeholder", "cannot be empty."))
+        } else {
+            Ok(Self {
+                placeholder: placeholder.unwrap_or_else(|| variable.clone()),
+                variable,
+            })
+        }
+    }
+}
+
+impl PlaceholderSource for EnvVarSource {
+    fn provide(&self, placeholders: &mut PlaceholderMap) -> Result<()> {
+        let value = std::env::var(&self.variable).map_err(|x| {
+            Error::Message(format!(
+                "Could not read environment variable '{}': {x}",
+                self.variable
+            ))
+        })?;
+        placeholders.insert(self.placeholder.clone(), value);
+        Ok(())
+    }
+}
+
+pub fn from_placeholder_source_config(
+    source: &config::PlaceholderSource,
+) -> Result<std::sync::Arc<dyn PlaceholderSource>> {
+    Ok(match source {
+        config::PlaceholderSource::Hostname => std::sync::Arc::new(HostnameSource),
+        config::PlaceholderSource::EnvVar(env_var) => std::sync::Arc::new(EnvVarSource::new(
+            env_var.variable.clone(),
+            env_var.placeholder.clone(),
+        )?),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hostname_source() {
+        let mut placeholders = PlaceholderMap::new();
+        HostnameSource.provide(&mut placeholders).unwrap();
+        assert!(!placeholders["hostname"].is_empty());
+    }
+
+    #[test]
+    fn test_env_var_source() {
+        let mut placeholders = PlaceholderMap::new();
+        std::env::set_var("MINMON_TEST_PLACEHOLDER_SOURCE", "staging");
+        EnvVarSource::new(
+            String::from("MINMON_TEST_PLACEHOLDER_SOURCE"),
+            Some(String::from("environment")),
+        )
+        .unwrap()
+        .provide(&mut placeholders)
+        .unwrap();
+        assert_eq!(placeholders["environment"], "staging");
+        let source =
+            EnvVarSource::new(String::from("MINMON_TEST_PLACEHOLDER_SOURCE_UNSET"), None).unwrap();
+        assert_eq!(source.placeholder, "MINMON_TEST_PLACEHOLDER_SOURCE_UNSET");
+        assert!(source.provide(&mut placeholders).is_err());
+        assert!(EnvVarSource::new(String::new(), None).is_err());
+        assert!(EnvVarSource::new(String::from("FOO"), Some(String::new())).is_err());
+    }
+}
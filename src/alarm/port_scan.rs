@@ -0,0 +1,69 @@
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+type Item = measurement::PortScan;
+
+// Bad if any port is not in the state the check expects.
+pub struct PortScan;
+
+impl TryFrom<&config::Alarm> for PortScan {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::Default(_) => Ok(Self),
+            _ => Err(Error::Message(String::from(
+                "Expected port scan alarm config.",
+            ))),
+        }
+    }
+}
+
+impl DataSink for PortScan {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        Ok(if data.offending_ports().is_empty() {
+            SinkDecision::Good
+        } else {
+            SinkDecision::Bad
+        })
+    }
+
+    fn describe_threshold(&self) -> String {
+        String::from("Bad when any port is not in the expected state")
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        let ports: Vec<String> = data
+            .offending_ports()
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        placeholders.insert(String::from("alarm_offending_ports"), ports.join(", "));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::measurement::Measurement;
+
+    #[test]
+    fn test_port_scan() {
+        let mut alarm = PortScan;
+        assert!(matches!(
+            alarm.put_data(&Item::new(vec![]).unwrap()).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.put_data(&Item::new(vec![443, 22]).unwrap()).unwrap(),
+            SinkDecision::Bad
+        ));
+        let mut placeholders = PlaceholderMap::new();
+        alarm.add_placeholders(&Item::new(vec![443, 22, 443]).unwrap(), &mut placeholders);
+        assert_eq!(placeholders["alarm_offending_ports"], "22, 443");
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, MinMaxTracking, NumericSink, SinkDecision};
+use crate::config;
+
+type Item = measurement::ProcessValue;
+type Inner = MinMaxTracking<NumericSink<f64>>;
+
+// A numeric alarm (level, delta, trend, ...) on the value. The placeholders printed by the process
+// are added to the ones of the inner alarm, which take precedence.
+#[derive(Clone)]
+pub struct ProcessValue {
+    inner: Inner,
+}
+
+impl TryFrom<&config::Alarm> for ProcessValue {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        Ok(Self {
+            inner: Inner::try_from(alarm)?,
+        })
+    }
+}
+
+impl DataSink for ProcessValue {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        self.inner.put_data(&data.value())
+    }
+
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn unit(&self) -> &str {
+        self.inner.unit()
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        self.inner.format_data(&data.value())
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.inner.validate()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn describe_threshold(&self) -> String {
+        self.inner.describe_threshold()
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        crate::merge_placeholders(placeholders, data.placeholders());
+        self.inner.add_placeholders(&data.value(), placeholders);
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        self.inner.add_state_placeholders(placeholders);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::measurement::Measurement;
+
+    #[test]
+    fn test_placeholders() {
+        let alarm: config::Alarm =
+            toml::from_str("name = \"Name\"\naction = \"Action\"\nlevel = 10").unwrap();
+        let mut sink = ProcessValue::try_from(&alarm).unwrap();
+        let data = Item::new((
+            12.5,
+            PlaceholderMap::from([
+                (String::from("load"), String::from("0.5")),
+                (String::from("level"), String::from("spoofed")),
+            ]),
+        ))
+        .unwrap();
+        assert!(matches!(sink.put_data(&data).unwrap(), SinkDecision::Bad));
+        let mut placeholders = PlaceholderMap::new();
+        sink.add_placeholders(&data, &mut placeholders);
+        assert_eq!(placeholders["load"], "0.5");
+        assert_eq!(placeholders["level"], "12.50");
+    }
+}
@@ -0,0 +1,135 @@
+use crate::measurement::Measurement;
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+type Item = measurement::Text;
+
+const VALUE_GROUP: &str = "alarm_value";
+
+pub struct Regex {
+    regex: ::regex::Regex,
+    match_is_bad: bool,
+    value: Option<String>,
+}
+
+impl Regex {
+    fn new(pattern: &str, match_is_bad: bool) -> Result<Self> {
+        if pattern.is_empty() {
+            return Err(Error::invalid_config("pattern", "cannot be empty."));
+        }
+        Ok(Self {
+            regex: ::regex::Regex::new(pattern).map_err(|x| {
+                Error::invalid_config("pattern", format!("is not a valid regex: {x}"))
+            })?,
+            match_is_bad,
+            value: None,
+        })
+    }
+}
+
+impl TryFrom<&config::Alarm> for Regex {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        if let config::AlarmType::Regex(regex) = &alarm.type_ {
+            Self::new(&regex.pattern, regex.match_is_bad)
+        } else {
+            Err(Error::Message(String::from("Expected regex alarm config.")))
+        }
+    }
+}
+
+impl DataSink for Regex {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        let captures = self.regex.captures(data.as_str());
+        self.value = captures
+            .as_ref()
+            .and_then(|x| x.name(VALUE_GROUP))
+            .map(|x| x.as_str().to_string());
+        Ok(if captures.is_some() == self.match_is_bad {
+            SinkDecision::Bad
+        } else {
+            SinkDecision::Good
+        })
+    }
+
+    fn reset(&mut self) {
+        self.value = None;
+    }
+
+    fn describe_threshold(&self) -> String {
+        if self.match_is_bad {
+            format!("Bad when text matches {}", self.regex)
+        } else {
+            format!("Bad when text does not match {}", self.regex)
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("text"), data.format());
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Some(value) = &self.value {
+            placeholders.insert(String::from(VALUE_GROUP), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(text: &str) -> Item {
+        Item::new(String::from(text)).unwrap()
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(Regex::new("", true).is_err());
+        assert!(Regex::new("(unclosed", true).is_err());
+    }
+
+    #[test]
+    fn test_match_is_bad() {
+        let mut regex = Regex::new("ERROR", true).unwrap();
+        assert!(matches!(
+            regex.put_data(&text("everything is fine")).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            regex.put_data(&text("ERROR: disk on fire")).unwrap(),
+            SinkDecision::Bad
+        ));
+    }
+
+    #[test]
+    fn test_no_match_is_bad() {
+        let mut regex = Regex::new("^OK$", false).unwrap();
+        assert!(matches!(
+            regex.put_data(&text("OK")).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            regex.put_data(&text("NOT OK")).unwrap(),
+            SinkDecision::Bad
+        ));
+    }
+
+    #[test]
+    fn test_value_group() {
+        let mut regex = Regex::new(r"temperature: (?P<alarm_value>\d+)", true).unwrap();
+        let mut placeholders = PlaceholderMap::new();
+        regex.put_data(&text("temperature: 42 degrees")).unwrap();
+        regex.add_state_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("alarm_value").unwrap(), "42");
+        let mut placeholders = PlaceholderMap::new();
+        regex.put_data(&text("no temperature")).unwrap();
+        regex.add_state_placeholders(&mut placeholders);
+        assert!(placeholders.is_empty());
+    }
+}
@@ -0,0 +1,298 @@
+use super::{
+    Alarm, AlarmBase, AlarmDescriptor, AlarmRegistry, AlarmSnapshot, DataSink, HistoricalDataPoint,
+    ReplayEvent, SimulationEvent, SinkDecision,
+};
+use crate::{ActionMap, Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+type RoutingKey<T> = Box<dyn Fn(&<T as DataSink>::Item) -> Result<String> + Send + Sync>;
+
+// Instantiates the template alarm per routing key of the data, e.g. per device reported by a
+// script. The template itself never gets data, it only describes the alarm.
+pub struct AlarmRouter<T>
+where
+    T: DataSink + Clone,
+{
+    template: AlarmBase<T>,
+    check_name: String,
+    routing_key: RoutingKey<T>,
+    max_routes: Option<usize>,
+    registry: Option<AlarmRegistry>,
+    routes: std::collections::HashMap<String, AlarmBase<T>>,
+}
+
+impl<T> AlarmRouter<T>
+where
+    T: DataSink + Clone,
+{
+    pub fn new(
+        template: AlarmBase<T>,
+        check_name: String,
+        routing_key: RoutingKey<T>,
+        max_routes: Option<usize>,
+    ) -> Result<Self> {
+        if max_routes == Some(0) {
+            return Err(Error::invalid_config("max_routes", "cannot be 0."));
+        }
+        Ok(Self {
+            template,
+            check_name,
+            routing_key,
+            max_routes,
+            registry: None,
+            routes: std::collections::HashMap::new(),
+        })
+    }
+
+    // The routes are published under the name of the template, so that alarms depending on it
+    // see the state of every route.
+    pub fn set_registry(&mut self, registry: AlarmRegistry) {
+        self.registry = Some(registry);
+    }
+
+    fn route(&mut self, key: &str) -> Result<&mut AlarmBase<T>> {
+        if !self.routes.contains_key(key) {
+            if let Some(max_routes) = self.max_routes {
+                if self.routes.len() >= max_routes {
+                    return Err(Error::Message(format!(
+                        "Cannot add route '{key}', 'max_routes' ({max_routes}) is reached."
+                    )));
+                }
+            }
+            let name = format!("{} ({})", self.template.name, key);
+            let id = format!("{}-{}", self.template.id, key);
+            let log_id = format!(
+                "Alarm '{}', id '{}' from check '{}'",
+                name, id, self.check_name
+            );
+            let mut alarm = self.template.clone_reset(
+                name,
+                id.clone(),
+                log_id,
+                self.template.data_sink.clone(),
+            );
+            if let Some(registry) = &self.registry {
+                alarm.set_registry(
+                    registry.clone(),
+                    super::registry_key(&self.check_name, &self.template.name, &id),
+                );
+            }
+            log::info!("{} was added.", alarm.log_id());
+            self.routes.insert(String::from(key), alarm);
+        }
+        Ok(self.routes.get_mut(key).unwrap())
+    }
+
+    pub async fn put_data_routed(
+        &mut self,
+        key: &str,
+        data: &T::Item,
+        placeholders: PlaceholderMap,
+    ) -> Result<()> {
+        self.route(key)?.put_data(data, placeholders).await
+    }
+}
+
+#[async_trait]
+impl<T> Alarm for AlarmRouter<T>
+where
+    T: DataSink + Clone,
+{
+    type Item = T::Item;
+
+    fn log_id(&self) -> &str {
+        self.template.log_id()
+    }
+
+    fn to_dot_graph(&self) -> String {
+        self.template.to_dot_graph()
+    }
+
+    fn describe(&self) -> AlarmDescriptor {
+        self.template.describe()
+    }
+
+    fn simulate(&self, decisions: &[SinkDecision]) -> Vec<SimulationEvent> {
+        self.template.simulate(decisions)
+    }
+
+    async fn replay(
+        &mut self,
+        _data: &[HistoricalDataPoint<Self::Item>],
+    ) -> Result<Vec<ReplayEvent>> {
+        Err(Error::Message(String::from(
+            "Replaying is not supported for routed alarms.",
+        )))
+    }
+
+    // Only the template is saved, the routes start over after a restart.
+    fn to_snapshot(&self) -> Result<AlarmSnapshot> {
+        self.template.to_snapshot()
+    }
+
+    fn restore(self, snapshot: AlarmSnapshot, actions: &ActionMap) -> Result<Self> {
+        Ok(Self {
+            template: self.template.restore(snapshot, actions)?,
+            ..self
+        })
+    }
+
+    async fn put_data(&mut self, data: &Self::Item, placeholders: PlaceholderMap) -> Result<()> {
+        let key = (self.routing_key)(data)?;
+        self.put_data_routed(&key, data, placeholders).await
+    }
+
+    // There is no routing key without data, so all routes get the error.
+    async fn put_error(&mut self, error: &Error, placeholders: PlaceholderMap) -> Result<()> {
+        let mut result = Ok(());
+        for alarm in self.routes.values_mut() {
+            result = result.and(alarm.put_error(error, placeholders.clone()).await);
+        }
+        result
+    }
+
+    async fn tick(&mut self, placeholders: PlaceholderMap) -> Result<()> {
+        let mut result = Ok(());
+        for alarm in self.routes.values_mut() {
+            result = result.and(alarm.tick(placeholders.clone()).await);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{ProcessValue, RepeatSchedule, StateHandler, StateMachine};
+    use super::*;
+    use crate::action::TestAction;
+    use crate::measurement::{self, Measurement};
+
+    fn router(action: &TestAction, max_routes: Option<usize>) -> AlarmRouter<ProcessValue> {
+        let alarm_config: crate::config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nlevel = 10").unwrap();
+        let mut template = AlarmBase::new(
+            String::from("Alarm"),
+            String::from("script"),
+            std::sync::Arc::new(action.clone()),
+            PlaceholderMap::new(),
+            StateMachine::new(
+                1,
+                RepeatSchedule::Fixed(0),
+                1,
+                0,
+                String::from("LogID"),
+                None,
+            )
+            .unwrap(),
+            ProcessValue::try_from(&alarm_config).unwrap(),
+            String::from("LogID"),
+        )
+        .unwrap();
+        template.set_recover_action(
+            Some(std::sync::Arc::new(action.clone())),
+            PlaceholderMap::new(),
+        );
+        AlarmRouter::new(
+            template,
+            String::from("Check"),
+            Box::new(|data: &measurement::ProcessValue| {
+                data.placeholders()
+                    .get("device")
+                    .cloned()
+                    .ok_or_else(|| Error::Message(String::from("No device.")))
+            }),
+            max_routes,
+        )
+        .unwrap()
+    }
+
+    fn data(device: &str, value: f64) -> measurement::ProcessValue {
+        measurement::ProcessValue::new((
+            value,
+            PlaceholderMap::from([(String::from("device"), String::from(device))]),
+        ))
+        .unwrap()
+    }
+
+    fn state(router: &AlarmRouter<ProcessValue>, key: &str) -> &'static str {
+        router.routes[key].state_machine.state_name()
+    }
+
+    #[tokio::test]
+    async fn test_routing() {
+        let action = TestAction::new();
+        let mut router = router(&action, None);
+        for (device, value) in [("a", 20.), ("b", 5.), ("c", 30.), ("c", 1.)] {
+            router
+                .put_data(&data(device, value), PlaceholderMap::new())
+                .await
+                .unwrap();
+        }
+        assert_eq!(state(&router, "a"), "Bad");
+        assert_eq!(state(&router, "b"), "Good");
+        assert_eq!(state(&router, "c"), "Good");
+        // Triggered for 'a' and 'c', recovered for 'c'.
+        let records = action.records();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].placeholders["alarm_name"], "Alarm (a)");
+        assert_eq!(records[0].placeholders["check_id"], "script-a");
+        assert_eq!(records[1].placeholders["check_id"], "script-c");
+        assert_eq!(records[2].placeholders["alarm_event"], "recover");
+        assert_eq!(
+            router.routes["b"].log_id(),
+            "Alarm 'Alarm (b)', id 'script-b' from check 'Check'"
+        );
+
+        let error = Error::Message(String::from("Error"));
+        // Every route gets the error.
+        router
+            .put_error(&error, PlaceholderMap::new())
+            .await
+            .unwrap();
+        for key in ["a", "b", "c"] {
+            assert_eq!(state(&router, key), "Error");
+        }
+        assert!(router
+            .put_data(
+                &measurement::ProcessValue::new((1., PlaceholderMap::new())).unwrap(),
+                PlaceholderMap::new()
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_max_routes() {
+        let action = TestAction::new();
+        let mut router = router(&action, Some(2));
+        router
+            .put_data(&data("a", 1.), PlaceholderMap::new())
+            .await
+            .unwrap();
+        router
+            .put_data(&data("b", 1.), PlaceholderMap::new())
+            .await
+            .unwrap();
+        assert_eq!(
+            router
+                .put_data(&data("c", 1.), PlaceholderMap::new())
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Cannot add route 'c', 'max_routes' (2) is reached."
+        );
+        // Known routes still get data.
+        router
+            .put_data(&data("a", 20.), PlaceholderMap::new())
+            .await
+            .unwrap();
+        assert_eq!(state(&router, "a"), "Bad");
+        assert!(AlarmRouter::new(
+            router.template,
+            String::from("Check"),
+            router.routing_key,
+            Some(0)
+        )
+        .is_err());
+    }
+}
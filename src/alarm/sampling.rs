@@ -0,0 +1,144 @@
+use crate::{Error, PlaceholderMap, Result};
+use rand::{Rng, SeedableRng};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+// Passes only a random fraction of the data to the wrapped data sink. Skipped data is good.
+#[derive(Clone)]
+pub struct Sampling<S>
+where
+    S: DataSink,
+{
+    inner: S,
+    sample_rate: f64,
+    rng: rand::rngs::StdRng,
+}
+
+impl<S> Sampling<S>
+where
+    S: DataSink,
+{
+    fn new(inner: S, sample_rate: f64, seed: Option<u64>) -> Result<Self> {
+        if !(sample_rate > 0.0 && sample_rate <= 1.0) {
+            return Err(Error::invalid_config(
+                "sample_rate",
+                "must be greater than 0 and at most 1.",
+            ));
+        }
+        Ok(Self {
+            inner,
+            sample_rate,
+            rng: match seed {
+                Some(seed) => rand::rngs::StdRng::seed_from_u64(seed),
+                None => rand::rngs::StdRng::from_entropy(),
+            },
+        })
+    }
+}
+
+impl<'a, S> TryFrom<&'a config::Alarm> for Sampling<S>
+where
+    S: DataSink + TryFrom<&'a config::Alarm, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &'a config::Alarm) -> std::result::Result<Self, Self::Error> {
+        Self::new(S::try_from(alarm)?, alarm.sample_rate.unwrap_or(1.0), None)
+    }
+}
+
+impl<S> DataSink for Sampling<S>
+where
+    S: DataSink,
+{
+    type Item = S::Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        if self.sample_rate < 1.0 && self.rng.gen::<f64>() >= self.sample_rate {
+            Ok(SinkDecision::Good)
+        } else {
+            self.inner.put_data(data)
+        }
+    }
+
+    fn name(&self) -> String {
+        format!("sampling({})", self.inner.name())
+    }
+
+    fn unit(&self) -> &str {
+        self.inner.unit()
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        self.inner.format_data(data)
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.inner.validate()
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn describe_threshold(&self) -> String {
+        if self.sample_rate < 1.0 {
+            format!(
+                "{} (sampled at {}%)",
+                self.inner.describe_threshold(),
+                self.sample_rate * 100.0
+            )
+        } else {
+            self.inner.describe_threshold()
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        self.inner.add_placeholders(data, placeholders);
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        self.inner.add_state_placeholders(placeholders);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::MockDataSink;
+    use super::*;
+
+    // Returns for each data point whether it was passed to the inner data sink.
+    fn passed(sample_rate: f64, seed: u64, count: usize) -> Vec<bool> {
+        let mut inner = MockDataSink::new();
+        inner.expect_put_data().returning(|_| Ok(SinkDecision::Bad));
+        let mut sampling = Sampling::new(inner, sample_rate, Some(seed)).unwrap();
+        (0..count)
+            .map(|_| matches!(sampling.put_data(&0).unwrap(), SinkDecision::Bad))
+            .collect()
+    }
+
+    #[test]
+    fn test_validation() {
+        for sample_rate in [0.0, -0.5, 1.5, f64::NAN] {
+            assert!(Sampling::new(MockDataSink::new(), sample_rate, None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_all_samples() {
+        assert!(passed(1.0, 42, 100).into_iter().all(|x| x));
+    }
+
+    #[test]
+    fn test_fraction_of_samples() {
+        let passed = passed(0.25, 42, 10000).into_iter().filter(|x| *x).count();
+        assert!((2300..2700).contains(&passed), "passed {passed}");
+    }
+
+    #[test]
+    fn test_seed() {
+        assert_eq!(passed(0.5, 1, 100), passed(0.5, 1, 100));
+        assert_ne!(passed(0.5, 1, 100), passed(0.5, 2, 100));
+    }
+}
@@ -0,0 +1,81 @@
+use super::StateMachineSnapshot;
+use crate::{Error, PlaceholderMap, Result};
+use serde::{Deserialize, Serialize};
+
+// Increment this whenever the format changes in an incompatible way.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+// Configuration and current state of an alarm. Actions are referenced by their names. The data sink
+// is not part of the snapshot. The cooldown timers and the time since the transition to the bad
+// state used for the auto recovery are not part of the snapshot either because they are based on a
+// monotonic clock that does not survive restarts.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct AlarmSnapshot {
+    pub version: u32,
+    pub name: String,
+    pub id: String,
+    pub action: String,
+    pub placeholders: PlaceholderMap,
+    pub escalation: Vec<(u32, String)>,
+    pub recover_action: Option<String>,
+    pub recover_placeholders: PlaceholderMap,
+    pub error_action: Option<String>,
+    pub error_placeholders: PlaceholderMap,
+    pub error_recover_action: Option<String>,
+    pub error_recover_placeholders: PlaceholderMap,
+    pub invert: bool,
+    pub disabled: bool,
+    pub cooldown: Option<std::time::Duration>,
+    #[serde(default)]
+    pub auto_recovery: Option<std::time::Duration>,
+    pub log_id: String,
+    pub log_level: Option<log::LevelFilter>,
+    pub state_machine: StateMachineSnapshot,
+}
+
+impl AlarmSnapshot {
+    pub fn check_version(&self) -> Result<()> {
+        if self.version == SNAPSHOT_VERSION {
+            Ok(())
+        } else {
+            Err(Error::Message(format!(
+                "Unsupported snapshot version {} (expected {}).",
+                self.version, SNAPSHOT_VERSION
+            )))
+        }
+    }
+
+    // True if both snapshots only differ in their state.
+    pub fn same_config(&self, other: &Self) -> bool {
+        self.state_machine.same_config(&other.state_machine)
+            && Self {
+                state_machine: other.state_machine.clone(),
+                ..self.clone()
+            } == *other
+    }
+}
+
+// State of a running alarm that is handed over to the alarm with the same log ID when the config
+// is reloaded. The threshold summary describes the data sink, see `AlarmDescriptor`.
+#[derive(Clone, Debug)]
+pub struct ReloadState {
+    pub snapshot: AlarmSnapshot,
+    pub threshold_summary: String,
+}
+
+impl ReloadState {
+    // True if the alarm of the reloaded config can continue from this state, i.e. its data sink,
+    // inversion and state machine did not change. Everything else, e.g. the actions, may change.
+    pub fn applies_to(&self, snapshot: &AlarmSnapshot, threshold_summary: &str) -> bool {
+        self.threshold_summary == threshold_summary
+            && self.snapshot.invert == snapshot.invert
+            && self
+                .snapshot
+                .state_machine
+                .same_config(&snapshot.state_machine)
+    }
+
+    pub fn is_bad(&self) -> bool {
+        self.snapshot.state_machine.is_bad()
+    }
+}
@@ -1,5 +1,7 @@
+use crate::config;
 use crate::PlaceholderMap;
 use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
 
 #[cfg_attr(test, mockall::automock)]
 pub trait StateHandler: Send + Sync + Sized {
@@ -8,18 +10,206 @@ pub trait StateHandler: Send + Sync + Sized {
     fn error(&mut self) -> bool;
     fn bad(&mut self) -> (bool, bool);
     fn good(&mut self) -> (bool, bool);
+
+    fn state_name(&self) -> &'static str;
+    fn transitions(&self) -> Vec<Transition>;
+
+    // Why the last call of `bad` or `error` did not return a trigger, e.g. because the bad state
+    // is only reached after more cycles.
+    fn explain_no_trigger(&self) -> String;
+
+    // Number of cycles since the transition to the bad state or `None` if not in the bad state.
+    fn bad_cycles(&self) -> Option<u32>;
+    // Number of cycles since the transition to the error state or `None` if not in the error state.
+    fn error_cycles(&self) -> Option<u32>;
+
+    // Goes back to the initial good state.
+    fn reset(&mut self);
+    // Forces the transition from the bad to the good state. Returns false if not in the bad state.
+    fn recover(&mut self) -> bool;
+    // Forces the transition from the error to the good state. Returns false if not in the error
+    // state.
+    fn recover_error(&mut self) -> bool;
+    // Copy with the same configuration in the initial good state.
+    fn clone_reset(&self, log_id: String) -> Self;
+
+    fn snapshot(&self) -> StateMachineSnapshot;
+    fn from_snapshot(
+        snapshot: StateMachineSnapshot,
+        log_id: String,
+        log_level: Option<log::LevelFilter>,
+    ) -> Result<Self>;
+}
+
+// Number of cycles between repetitions of the action in the bad state. The schedule starts over
+// when the alarm changes to the bad state again. Serialized untagged so that a fixed schedule looks
+// the same as the plain `u32` of older state files.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum RepeatSchedule {
+    // A value of 0 means the action is never repeated.
+    Fixed(u32),
+    Exponential { initial: u32, factor: f64, max: u32 },
+    // The last value is used for all following repetitions.
+    Sequence(Vec<u32>),
+}
+
+impl RepeatSchedule {
+    fn validate(&self) -> Result<()> {
+        match self {
+            Self::Fixed(_) => Ok(()),
+            Self::Exponential {
+                initial,
+                factor,
+                max,
+            } => {
+                if *initial == 0 {
+                    Err(Error::invalid_config(
+                        "initial",
+                        "of 'repeat_cycles' cannot be 0.",
+                    ))
+                } else if !(factor.is_finite() && *factor >= 1.0) {
+                    Err(Error::invalid_config(
+                        "factor",
+                        "of 'repeat_cycles' must be at least 1.",
+                    ))
+                } else if max < initial {
+                    Err(Error::invalid_config(
+                        "max",
+                        "of 'repeat_cycles' cannot be less than 'initial'.",
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            Self::Sequence(sequence) => {
+                if sequence.is_empty() {
+                    Err(Error::invalid_config("repeat_cycles", "cannot be empty."))
+                } else if sequence.contains(&0) {
+                    Err(Error::invalid_config("repeat_cycles", "cannot contain 0."))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    // Number of cycles until the repetition with the given index (starting at 0) or 0 if the action
+    // is never repeated.
+    fn cycles(&self, index: u32) -> u32 {
+        match self {
+            Self::Fixed(cycles) => *cycles,
+            Self::Exponential {
+                initial,
+                factor,
+                max,
+            } => (f64::from(*initial) * factor.powf(f64::from(index))).min(f64::from(*max)) as u32,
+            Self::Sequence(sequence) => {
+                let index = usize::try_from(index).unwrap_or(usize::MAX);
+                sequence[index.min(sequence.len() - 1)]
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for RepeatSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Fixed(cycles) => write!(f, "repeat every {cycles} cycles"),
+            Self::Exponential {
+                initial,
+                factor,
+                max,
+            } => write!(
+                f,
+                "repeat after {initial} cycles, then {factor} times as many up to {max}"
+            ),
+            Self::Sequence(sequence) => {
+                let sequence: Vec<_> = sequence.iter().map(|x| x.to_string()).collect();
+                write!(f, "repeat after {} cycles", sequence.join(", "))
+            }
+        }
+    }
+}
+
+impl From<&config::RepeatCycles> for RepeatSchedule {
+    fn from(repeat_cycles: &config::RepeatCycles) -> Self {
+        match repeat_cycles {
+            config::RepeatCycles::Fixed(cycles) => Self::Fixed(*cycles),
+            config::RepeatCycles::Sequence(sequence) => Self::Sequence(sequence.clone()),
+            config::RepeatCycles::Exponential(exponential) => Self::Exponential {
+                initial: exponential.initial,
+                factor: exponential.factor,
+                max: exponential.max,
+            },
+        }
+    }
+}
+
+// Configuration and current state of a state machine.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct StateMachineSnapshot {
+    cycles: u32,
+    repeat_cycles: RepeatSchedule,
+    recover_cycles: u32,
+    error_repeat_cycles: u32,
+    #[serde(default)]
+    stabilize_cycles: u32,
+    state: State,
+}
+
+impl StateMachineSnapshot {
+    pub fn same_config(&self, other: &Self) -> bool {
+        self.cycles == other.cycles
+            && self.repeat_cycles == other.repeat_cycles
+            && self.recover_cycles == other.recover_cycles
+            && self.error_repeat_cycles == other.error_repeat_cycles
+            && self.stabilize_cycles == other.stabilize_cycles
+    }
+
+    pub fn is_bad(&self) -> bool {
+        matches!(self.state, State::Bad(_))
+    }
+}
+
+// Edge of the state graph. `event` is the value of the `alarm_event` placeholder of the action
+// that is triggered by the transition, if any.
+#[derive(Debug, PartialEq)]
+pub struct Transition {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub guard: String,
+    pub event: Option<&'static str>,
+}
+
+impl Transition {
+    fn new(
+        from: &'static str,
+        to: &'static str,
+        guard: String,
+        event: Option<&'static str>,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            guard,
+            event,
+        }
+    }
 }
 
 pub struct StateMachine {
     cycles: u32,
-    repeat_cycles: u32,
+    repeat_cycles: RepeatSchedule,
     recover_cycles: u32,
     error_repeat_cycles: u32,
+    stabilize_cycles: u32,
     state: State,
     log_id: String,
+    log_level: Option<log::LevelFilter>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 enum State {
     Good(GoodState),
     Bad(BadState),
@@ -32,10 +222,14 @@ impl Default for State {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 struct GoodState {
     timestamp: std::time::SystemTime,
     bad_cycles: u32,
+    bad_cycles_before_recovery: Option<u32>,
+    // Good cycles since the last bad cycle, only counted while `bad_cycles` is not 0.
+    #[serde(default)]
+    consecutive_good_cycles: u32,
 }
 
 impl Default for GoodState {
@@ -43,47 +237,71 @@ impl Default for GoodState {
         Self {
             timestamp: std::time::SystemTime::now(),
             bad_cycles: 0,
+            bad_cycles_before_recovery: None,
+            consecutive_good_cycles: 0,
         }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 struct BadState {
     timestamp: std::time::SystemTime,
     cycles: u32,
     good_cycles: u32,
+    // Unlike `cycles`, this is not reset when the action is repeated.
+    total_cycles: u32,
+    // Consecutive bad cycles including the ones that led to the bad state. Reset by good cycles.
+    #[serde(default)]
+    consecutive_bad_total: u32,
+    // Index of the next repetition in the repeat schedule.
+    #[serde(default)]
+    repeat_index: u32,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize, PartialEq, Debug)]
 struct ErrorState {
     timestamp: std::time::SystemTime,
     shadowed_state: Box<State>,
     cycles: u32,
+    // Unlike `cycles`, this is not reset when the error action is repeated.
+    #[serde(default)]
+    total_cycles: u32,
 }
 
 impl StateMachine {
     pub fn new(
         cycles: u32,
-        repeat_cycles: u32,
+        repeat_cycles: RepeatSchedule,
         recover_cycles: u32,
         error_repeat_cycles: u32,
         log_id: String,
+        log_level: Option<log::LevelFilter>,
     ) -> Result<Self> {
         if cycles == 0 {
-            Err(Error(String::from("'cycles' cannot be 0.")))
+            Err(Error::invalid_config("cycles", "cannot be 0."))
         } else if recover_cycles == 0 {
-            Err(Error(String::from("'recover_cycles' cannot be 0.")))
+            Err(Error::invalid_config("recover_cycles", "cannot be 0."))
         } else {
+            repeat_cycles.validate()?;
             Ok(Self {
                 cycles,
                 repeat_cycles,
                 recover_cycles,
                 error_repeat_cycles,
+                stabilize_cycles: 0,
                 state: State::default(),
                 log_id,
+                log_level,
             })
         }
     }
+
+    // Number of consecutive good cycles after which the bad cycles counted in the good state are
+    // reset. A value of 0 means they are never reset, i.e. they add up until the bad state is
+    // reached.
+    pub fn set_stabilize_cycles(&mut self, stabilize_cycles: u32) {
+        self.stabilize_cycles = stabilize_cycles;
+    }
 }
 
 impl StateHandler for StateMachine {
@@ -95,6 +313,19 @@ impl StateHandler for StateMachine {
                     String::from("alarm_timestamp"),
                     crate::datetime_iso8601(bad.timestamp),
                 );
+                placeholders.insert(
+                    String::from("alarm_bad_duration_human"),
+                    crate::format_duration_since(bad.timestamp),
+                );
+                placeholders.insert(String::from("alarm_bad_cycles"), bad.cycles.to_string());
+                placeholders.insert(
+                    String::from("alarm_good_cycles"),
+                    bad.good_cycles.to_string(),
+                );
+                placeholders.insert(
+                    String::from("alarm_consecutive_bad_count"),
+                    bad.consecutive_bad_total.to_string(),
+                );
             }
 
             State::Good(good) => {
@@ -103,6 +334,16 @@ impl StateHandler for StateMachine {
                     String::from("alarm_timestamp"),
                     crate::datetime_iso8601(good.timestamp),
                 );
+                placeholders.insert(
+                    String::from("alarm_good_duration_human"),
+                    crate::format_duration_since(good.timestamp),
+                );
+                if let Some(bad_cycles) = good.bad_cycles_before_recovery {
+                    placeholders.insert(
+                        String::from("alarm_bad_cycles_before_recovery"),
+                        bad_cycles.to_string(),
+                    );
+                }
             }
 
             State::Error(error) => {
@@ -111,6 +352,11 @@ impl StateHandler for StateMachine {
                     String::from("alarm_timestamp"),
                     crate::datetime_iso8601(error.timestamp),
                 );
+                placeholders.insert(
+                    String::from("alarm_error_duration_human"),
+                    crate::format_duration_since(error.timestamp),
+                );
+                placeholders.insert(String::from("error_cycles"), error.cycles.to_string());
             }
         }
     }
@@ -120,35 +366,50 @@ impl StateHandler for StateMachine {
         self.state = match &self.state {
             State::Good(_) => {
                 trigger = true;
-                log::warn!("{} changing from good to error state.", self.log_id);
+                alarm_log!(
+                    self.log_level,
+                    log::Level::Warn,
+                    "{} changing from good to error state.",
+                    self.log_id
+                );
                 State::Error(ErrorState {
                     timestamp: std::time::SystemTime::now(),
                     shadowed_state: Box::new(self.state.clone()),
                     cycles: 1,
+                    total_cycles: 1,
                 })
             }
 
             State::Bad(_) => {
                 trigger = true;
-                log::warn!("{} changing from bad to error state.", self.log_id);
+                alarm_log!(
+                    self.log_level,
+                    log::Level::Warn,
+                    "{} changing from bad to error state.",
+                    self.log_id
+                );
                 State::Error(ErrorState {
                     timestamp: std::time::SystemTime::now(),
                     shadowed_state: Box::new(self.state.clone()),
                     cycles: 1,
+                    total_cycles: 1,
                 })
             }
 
             State::Error(error) => {
-                let cycles = if error.cycles == self.error_repeat_cycles {
-                    trigger = true;
-                    1
-                } else {
-                    error.cycles + 1
-                };
+                // A value of 0 means the error action is never repeated.
+                let cycles =
+                    if self.error_repeat_cycles != 0 && error.cycles == self.error_repeat_cycles {
+                        trigger = true;
+                        1
+                    } else {
+                        error.cycles.saturating_add(1)
+                    };
                 State::Error(ErrorState {
                     timestamp: error.timestamp,
                     shadowed_state: error.shadowed_state.clone(),
                     cycles,
+                    total_cycles: error.total_cycles.saturating_add(1),
                 })
             }
         };
@@ -160,33 +421,47 @@ impl StateHandler for StateMachine {
         let mut trigger_error_recover = false;
         self.state = match &self.state {
             State::Good(good) => {
-                if good.bad_cycles + 1 == self.cycles {
+                if good.bad_cycles.saturating_add(1) == self.cycles {
                     trigger = true;
-                    log::warn!("{} changing from good to bad state.", self.log_id);
+                    alarm_log!(
+                        self.log_level,
+                        log::Level::Warn,
+                        "{} changing from good to bad state.",
+                        self.log_id
+                    );
                     State::Bad(BadState {
                         timestamp: std::time::SystemTime::now(),
                         cycles: 1,
                         good_cycles: 0,
+                        total_cycles: 1,
+                        consecutive_bad_total: self.cycles,
+                        repeat_index: 0,
                     })
                 } else {
                     State::Good(GoodState {
                         timestamp: good.timestamp,
-                        bad_cycles: good.bad_cycles + 1,
+                        bad_cycles: good.bad_cycles.saturating_add(1),
+                        bad_cycles_before_recovery: good.bad_cycles_before_recovery,
+                        consecutive_good_cycles: 0,
                     })
                 }
             }
 
             State::Bad(bad) => {
-                let cycles = if bad.cycles == self.repeat_cycles {
+                let repeat_cycles = self.repeat_cycles.cycles(bad.repeat_index);
+                let (cycles, repeat_index) = if repeat_cycles != 0 && bad.cycles == repeat_cycles {
                     trigger = true;
-                    1
+                    (1, bad.repeat_index.saturating_add(1))
                 } else {
-                    bad.cycles + 1
+                    (bad.cycles.saturating_add(1), bad.repeat_index)
                 };
                 State::Bad(BadState {
                     timestamp: bad.timestamp,
                     cycles,
                     good_cycles: 0,
+                    total_cycles: bad.total_cycles.saturating_add(1),
+                    consecutive_bad_total: bad.consecutive_bad_total.saturating_add(1),
+                    repeat_index,
                 })
             }
 
@@ -195,7 +470,12 @@ impl StateHandler for StateMachine {
                 let (shadowed_trigger, _) = self.bad();
                 trigger = shadowed_trigger;
                 trigger_error_recover = true;
-                log::warn!("{} changing from error to bad state.", self.log_id);
+                alarm_log!(
+                    self.log_level,
+                    log::Level::Warn,
+                    "{} changing from error to bad state.",
+                    self.log_id
+                );
                 self.state.clone()
             }
         };
@@ -206,21 +486,49 @@ impl StateHandler for StateMachine {
         let mut trigger = false;
         let mut trigger_error_recover = false;
         self.state = match &self.state {
-            State::Good(good) => State::Good(good.clone()),
+            State::Good(good) => {
+                if self.stabilize_cycles != 0 && good.bad_cycles != 0 {
+                    let consecutive_good_cycles = good.consecutive_good_cycles.saturating_add(1);
+                    if consecutive_good_cycles == self.stabilize_cycles {
+                        State::Good(GoodState {
+                            bad_cycles: 0,
+                            consecutive_good_cycles: 0,
+                            ..good.clone()
+                        })
+                    } else {
+                        State::Good(GoodState {
+                            consecutive_good_cycles,
+                            ..good.clone()
+                        })
+                    }
+                } else {
+                    State::Good(good.clone())
+                }
+            }
 
             State::Bad(bad) => {
-                if bad.good_cycles + 1 == self.recover_cycles {
+                if bad.good_cycles.saturating_add(1) == self.recover_cycles {
                     trigger = true;
-                    log::info!("{} changing from bad to good state.", self.log_id);
+                    alarm_log!(
+                        self.log_level,
+                        log::Level::Info,
+                        "{} changing from bad to good state.",
+                        self.log_id
+                    );
                     State::Good(GoodState {
                         timestamp: std::time::SystemTime::now(),
                         bad_cycles: 0,
+                        bad_cycles_before_recovery: Some(bad.cycles),
+                        consecutive_good_cycles: 0,
                     })
                 } else {
                     State::Bad(BadState {
                         timestamp: bad.timestamp,
-                        cycles: bad.cycles + 1,
-                        good_cycles: bad.good_cycles + 1,
+                        cycles: bad.cycles.saturating_add(1),
+                        good_cycles: bad.good_cycles.saturating_add(1),
+                        total_cycles: bad.total_cycles.saturating_add(1),
+                        consecutive_bad_total: 0,
+                        repeat_index: bad.repeat_index,
                     })
                 }
             }
@@ -230,12 +538,204 @@ impl StateHandler for StateMachine {
                 let (shadowed_trigger, _) = self.good();
                 trigger = shadowed_trigger;
                 trigger_error_recover = true;
-                log::info!("{} changing from error to good state.", self.log_id);
+                alarm_log!(
+                    self.log_level,
+                    log::Level::Info,
+                    "{} changing from error to good state.",
+                    self.log_id
+                );
                 self.state.clone()
             }
         };
         (trigger, trigger_error_recover)
     }
+
+    fn explain_no_trigger(&self) -> String {
+        match &self.state {
+            State::Good(good) => format!(
+                "Bad for {} of {} cycles needed for the bad state",
+                good.bad_cycles, self.cycles
+            ),
+            State::Bad(bad) => match self.repeat_cycles.cycles(bad.repeat_index) {
+                0 => String::from("In bad state and repeat_cycles is not set"),
+                repeat_cycles => format!(
+                    "In bad state but repeat_cycles not reached (cycle {} of {})",
+                    bad.cycles, repeat_cycles
+                ),
+            },
+            State::Error(error) => match self.error_repeat_cycles {
+                0 => String::from("In error state and error_repeat_cycles is not set"),
+                error_repeat_cycles => format!(
+                    "In error state but error_repeat_cycles not reached (cycle {} of {})",
+                    error.cycles, error_repeat_cycles
+                ),
+            },
+        }
+    }
+
+    fn bad_cycles(&self) -> Option<u32> {
+        match &self.state {
+            State::Bad(bad) => Some(bad.total_cycles),
+            _ => None,
+        }
+    }
+
+    fn error_cycles(&self) -> Option<u32> {
+        match &self.state {
+            State::Error(error) => Some(error.total_cycles),
+            _ => None,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state = State::default();
+    }
+
+    fn recover(&mut self) -> bool {
+        if let State::Bad(bad) = &self.state {
+            alarm_log!(
+                self.log_level,
+                log::Level::Info,
+                "{} changing from bad to good state (auto recovery).",
+                self.log_id
+            );
+            self.state = State::Good(GoodState {
+                timestamp: std::time::SystemTime::now(),
+                bad_cycles: 0,
+                bad_cycles_before_recovery: Some(bad.cycles),
+                consecutive_good_cycles: 0,
+            });
+            true
+        } else {
+            false
+        }
+    }
+
+    fn recover_error(&mut self) -> bool {
+        if let State::Error(_) = &self.state {
+            alarm_log!(
+                self.log_level,
+                log::Level::Info,
+                "{} changing from error to good state (max error cycles).",
+                self.log_id
+            );
+            self.state = State::default();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn clone_reset(&self, log_id: String) -> Self {
+        Self {
+            cycles: self.cycles,
+            repeat_cycles: self.repeat_cycles.clone(),
+            recover_cycles: self.recover_cycles,
+            error_repeat_cycles: self.error_repeat_cycles,
+            stabilize_cycles: self.stabilize_cycles,
+            state: State::default(),
+            log_id,
+            log_level: self.log_level,
+        }
+    }
+
+    fn snapshot(&self) -> StateMachineSnapshot {
+        StateMachineSnapshot {
+            cycles: self.cycles,
+            repeat_cycles: self.repeat_cycles.clone(),
+            recover_cycles: self.recover_cycles,
+            error_repeat_cycles: self.error_repeat_cycles,
+            stabilize_cycles: self.stabilize_cycles,
+            state: self.state.clone(),
+        }
+    }
+
+    fn from_snapshot(
+        snapshot: StateMachineSnapshot,
+        log_id: String,
+        log_level: Option<log::LevelFilter>,
+    ) -> Result<Self> {
+        let mut state_machine = Self::new(
+            snapshot.cycles,
+            snapshot.repeat_cycles,
+            snapshot.recover_cycles,
+            snapshot.error_repeat_cycles,
+            log_id,
+            log_level,
+        )?;
+        state_machine.set_stabilize_cycles(snapshot.stabilize_cycles);
+        state_machine.state = snapshot.state;
+        Ok(state_machine)
+    }
+
+    fn state_name(&self) -> &'static str {
+        match &self.state {
+            State::Good(_) => "Good",
+            State::Bad(_) => "Bad",
+            State::Error(_) => "Error",
+        }
+    }
+
+    fn transitions(&self) -> Vec<Transition> {
+        let repeat = |guard: &str, repeat_cycles: &RepeatSchedule, event: &'static str| {
+            if *repeat_cycles == RepeatSchedule::Fixed(0) {
+                (String::from(guard), None)
+            } else {
+                (format!("{guard}, {repeat_cycles}"), Some(event))
+            }
+        };
+        let mut good_guard = String::from("good data");
+        if self.cycles > 1 {
+            good_guard.push_str(&format!(" or < {} bad cycles", self.cycles));
+            if self.stabilize_cycles != 0 {
+                good_guard.push_str(&format!(
+                    " (reset by {} good cycles)",
+                    self.stabilize_cycles
+                ));
+            }
+        }
+        let mut bad_guard = String::from("bad data");
+        if self.recover_cycles > 1 {
+            bad_guard.push_str(&format!(" or < {} good cycles", self.recover_cycles));
+        }
+        let (bad_guard, bad_event) = repeat(&bad_guard, &self.repeat_cycles, "trigger");
+        let (error_guard, error_event) = repeat(
+            "error",
+            &RepeatSchedule::Fixed(self.error_repeat_cycles),
+            "error",
+        );
+        vec![
+            Transition::new("Good", "Good", good_guard, None),
+            Transition::new(
+                "Good",
+                "Bad",
+                format!("{} bad cycles", self.cycles),
+                Some("trigger"),
+            ),
+            Transition::new("Bad", "Bad", bad_guard, bad_event),
+            Transition::new(
+                "Bad",
+                "Good",
+                format!("{} good cycles", self.recover_cycles),
+                Some("recover"),
+            ),
+            Transition::new("Good", "Error", String::from("error"), Some("error")),
+            Transition::new("Bad", "Error", String::from("error"), Some("error")),
+            Transition::new("Error", "Error", error_guard, error_event),
+            Transition::new(
+                "Error",
+                "Good",
+                String::from("good data"),
+                Some("error_recover"),
+            ),
+            Transition::new(
+                "Error",
+                "Bad",
+                String::from("bad data"),
+                Some("error_recover"),
+            ),
+        ]
+    }
 }
 
 #[cfg(test)]
@@ -244,25 +744,101 @@ mod test {
 
     #[test]
     fn test_validation() {
-        assert!(matches!(
-            StateMachine::new(0, 0, 1, 0, String::from("")),
-            Err(Error(_))
-        ));
-        assert!(matches!(
-            StateMachine::new(1, 0, 0, 0, String::from("")),
-            Err(Error(_))
-        ));
+        assert!(
+            StateMachine::new(0, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).is_err()
+        );
+        assert!(
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 0, 0, String::from(""), None).is_err()
+        );
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut state_machine =
+            StateMachine::new(2, RepeatSchedule::Fixed(3), 4, 5, String::from("ID"), None).unwrap();
+        state_machine.bad();
+        state_machine.bad();
+        state_machine.bad();
+        state_machine.good();
+        let snapshot = state_machine.snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let snapshot: StateMachineSnapshot = serde_json::from_str(&json).unwrap();
+        let restored = StateMachine::from_snapshot(snapshot, String::from("ID"), None).unwrap();
+        assert_eq!(restored.cycles, 2);
+        assert_eq!(restored.repeat_cycles, RepeatSchedule::Fixed(3));
+        assert_eq!(restored.recover_cycles, 4);
+        assert_eq!(restored.error_repeat_cycles, 5);
+        assert_eq!(restored.state, state_machine.state);
+        match restored.state {
+            State::Bad(bad) => {
+                assert_eq!(bad.cycles, 3);
+                assert_eq!(bad.good_cycles, 1);
+                assert_eq!(bad.total_cycles, 3);
+            }
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_recover() {
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap();
+        assert!(!state_machine.recover());
+        state_machine.bad();
+        state_machine.bad();
+        assert!(state_machine.recover());
+        assert_eq!(state_machine.state_name(), "Good");
+        let mut placeholders = PlaceholderMap::new();
+        state_machine.add_placeholders(&mut placeholders);
+        assert_eq!(placeholders["alarm_bad_cycles_before_recovery"], "2");
+        state_machine.error();
+        assert!(!state_machine.recover());
+        assert_eq!(state_machine.state_name(), "Error");
+    }
+
+    #[test]
+    fn test_recover_error() {
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 2, String::new(), None).unwrap();
+        assert!(!state_machine.recover_error());
+        assert_eq!(state_machine.error_cycles(), None);
+        state_machine.bad();
+        for _ in 0..3 {
+            state_machine.error();
+        }
+        // Unlike the placeholder, the error cycles are not reset by the repeated error action.
+        assert_eq!(state_machine.error_cycles(), Some(3));
+        assert!(state_machine.recover_error());
+        assert_eq!(state_machine.state_name(), "Good");
+        assert_eq!(state_machine.error_cycles(), None);
+    }
+
+    #[test]
+    fn test_clone_reset() {
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from("A"), None).unwrap();
+        state_machine.bad();
+        let mut clone = state_machine.clone_reset(String::from("B"));
+        assert_eq!(clone.state_name(), "Good");
+        assert_eq!(clone.log_id, "B");
+        assert!(clone.snapshot().same_config(&state_machine.snapshot()));
+        assert_eq!(state_machine.state_name(), "Bad");
+        clone.error();
+        assert_eq!(clone.state_name(), "Error");
+        assert_eq!(state_machine.state_name(), "Bad");
     }
 
     #[test]
     fn test_trigger_action() {
-        let mut state_machine = StateMachine::new(1, 0, 1, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
         assert_eq!((true, false), state_machine.bad());
     }
 
     #[test]
     fn test_trigger_action_repeat() {
-        let mut state_machine = StateMachine::new(1, 7, 1, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(7), 1, 0, String::from(""), None).unwrap();
         assert_eq!((true, false), state_machine.bad());
         for _ in 0..6 {
             assert_eq!((false, false), state_machine.bad());
@@ -270,9 +846,184 @@ mod test {
         assert_eq!((true, false), state_machine.bad());
     }
 
+    // Returns the cycles (starting at 1) in which the action is triggered.
+    fn trigger_cycles(repeat_cycles: RepeatSchedule, count: u32) -> Vec<u32> {
+        let mut state_machine =
+            StateMachine::new(1, repeat_cycles, 1, 0, String::new(), None).unwrap();
+        (1..=count).filter(|_| state_machine.bad().0).collect()
+    }
+
+    #[test]
+    fn test_repeat_fixed() {
+        assert_eq!(
+            trigger_cycles(RepeatSchedule::Fixed(3), 12),
+            vec![1, 4, 7, 10]
+        );
+    }
+
+    #[test]
+    fn test_repeat_exponential() {
+        let repeat_cycles = RepeatSchedule::Exponential {
+            initial: 1,
+            factor: 2.0,
+            max: 5,
+        };
+        // Repeated after 1, 2, 4, 5, 5 cycles.
+        assert_eq!(trigger_cycles(repeat_cycles, 18), vec![1, 2, 4, 8, 13, 18]);
+        let repeat_cycles = RepeatSchedule::Exponential {
+            initial: 2,
+            factor: 1.5,
+            max: 100,
+        };
+        // Repeated after 2, 3, 4, 6 cycles.
+        assert_eq!(trigger_cycles(repeat_cycles, 16), vec![1, 3, 6, 10, 16]);
+    }
+
+    #[test]
+    fn test_repeat_sequence() {
+        let repeat_cycles = RepeatSchedule::Sequence(vec![1, 5, 2]);
+        assert_eq!(trigger_cycles(repeat_cycles, 12), vec![1, 2, 7, 9, 11]);
+    }
+
+    #[test]
+    fn test_repeat_schedule_restarts() {
+        let repeat_cycles = RepeatSchedule::Sequence(vec![1, 3]);
+        let mut state_machine =
+            StateMachine::new(1, repeat_cycles, 1, 0, String::new(), None).unwrap();
+        assert_eq!(state_machine.bad(), (true, false));
+        assert_eq!(state_machine.bad(), (true, false));
+        assert_eq!(state_machine.bad(), (false, false));
+        state_machine.good();
+        assert_eq!(state_machine.bad(), (true, false));
+        assert_eq!(state_machine.bad(), (true, false));
+    }
+
+    #[test]
+    fn test_repeat_schedule_validation() {
+        for repeat_cycles in [
+            RepeatSchedule::Sequence(Vec::new()),
+            RepeatSchedule::Sequence(vec![1, 0]),
+            RepeatSchedule::Exponential {
+                initial: 0,
+                factor: 2.0,
+                max: 10,
+            },
+            RepeatSchedule::Exponential {
+                initial: 1,
+                factor: 0.5,
+                max: 10,
+            },
+            RepeatSchedule::Exponential {
+                initial: 1,
+                factor: f64::INFINITY,
+                max: 10,
+            },
+            RepeatSchedule::Exponential {
+                initial: 5,
+                factor: 2.0,
+                max: 4,
+            },
+        ] {
+            assert!(StateMachine::new(1, repeat_cycles, 1, 0, String::new(), None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_repeat_schedule_serialization() {
+        for (repeat_cycles, json) in [
+            (RepeatSchedule::Fixed(3), "3"),
+            (RepeatSchedule::Sequence(vec![1, 2]), "[1,2]"),
+            (
+                RepeatSchedule::Exponential {
+                    initial: 1,
+                    factor: 2.0,
+                    max: 8,
+                },
+                r#"{"initial":1,"factor":2.0,"max":8}"#,
+            ),
+        ] {
+            assert_eq!(serde_json::to_string(&repeat_cycles).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<RepeatSchedule>(json).unwrap(),
+                repeat_cycles
+            );
+        }
+    }
+
+    #[test]
+    fn test_trigger_action_no_repeat() {
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
+        assert_eq!((true, false), state_machine.bad());
+        for _ in 0..100 {
+            assert_eq!((false, false), state_machine.bad());
+        }
+        if let State::Bad(bad) = &mut state_machine.state {
+            bad.cycles = u32::MAX;
+            bad.total_cycles = u32::MAX;
+        }
+        assert_eq!((false, false), state_machine.bad());
+        assert_eq!(Some(u32::MAX), state_machine.bad_cycles());
+    }
+
+    #[test]
+    fn test_saturating_cycles() {
+        let mut state_machine =
+            StateMachine::new(2, RepeatSchedule::Fixed(0), 3, 0, String::new(), None).unwrap();
+        state_machine.bad();
+        if let State::Good(good) = &mut state_machine.state {
+            good.bad_cycles = u32::MAX;
+        }
+        assert_eq!((false, false), state_machine.bad());
+        assert_eq!(state_machine.state_name(), "Good");
+
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 3, 0, String::new(), None).unwrap();
+        state_machine.bad();
+        if let State::Bad(bad) = &mut state_machine.state {
+            bad.cycles = u32::MAX;
+            bad.good_cycles = u32::MAX;
+            bad.total_cycles = u32::MAX;
+        }
+        assert_eq!((false, false), state_machine.good());
+        match &state_machine.state {
+            State::Bad(bad) => {
+                assert_eq!(bad.cycles, u32::MAX);
+                assert_eq!(bad.good_cycles, u32::MAX);
+                assert_eq!(bad.total_cycles, u32::MAX);
+            }
+            _ => panic!(),
+        }
+
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap();
+        state_machine.set_stabilize_cycles(2);
+        state_machine.bad();
+        state_machine.good();
+        if let State::Good(good) = &mut state_machine.state {
+            good.bad_cycles = 1;
+            good.consecutive_good_cycles = u32::MAX;
+        }
+        state_machine.good();
+        match &state_machine.state {
+            State::Good(good) => assert_eq!(good.consecutive_good_cycles, u32::MAX),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_trigger_action_repeat_every_cycle() {
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(1), 1, 0, String::from(""), None).unwrap();
+        for _ in 0..5 {
+            assert_eq!((true, false), state_machine.bad());
+        }
+    }
+
     #[test]
     fn test_trigger_recover_action() {
-        let mut state_machine = StateMachine::new(1, 0, 5, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 5, 0, String::from(""), None).unwrap();
         assert_eq!((true, false), state_machine.bad());
         for _ in 0..4 {
             assert_eq!((false, false), state_machine.good());
@@ -282,13 +1033,15 @@ mod test {
 
     #[test]
     fn test_trigger_error_action() {
-        let mut state_machine = StateMachine::new(1, 0, 1, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
         assert!(state_machine.error());
     }
 
     #[test]
     fn test_trigger_error_action_repeat() {
-        let mut state_machine = StateMachine::new(1, 0, 1, 7, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 7, String::from(""), None).unwrap();
         assert!(state_machine.error());
         for _ in 0..6 {
             assert!(!state_machine.error());
@@ -296,16 +1049,32 @@ mod test {
         assert!(state_machine.error());
     }
 
+    #[test]
+    fn test_trigger_error_action_no_repeat() {
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
+        assert!(state_machine.error());
+        for _ in 0..100 {
+            assert!(!state_machine.error());
+        }
+        if let State::Error(error) = &mut state_machine.state {
+            error.cycles = u32::MAX;
+        }
+        assert!(!state_machine.error());
+    }
+
     #[test]
     fn test_trigger_error_recover_action() {
-        let mut state_machine = StateMachine::new(1, 0, 1, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
         state_machine.error();
         assert_eq!((false, true), state_machine.good());
     }
 
     #[test]
     fn test_add_placeholders_good() {
-        let mut state_machine = StateMachine::new(1, 0, 1, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
         let mut placeholders = PlaceholderMap::new();
         // starts in good state without "last alarm"
         state_machine.bad();
@@ -315,12 +1084,20 @@ mod test {
         chrono::DateTime::<chrono::Utc>::from_str(placeholders.get("alarm_timestamp").unwrap())
             .unwrap();
         assert_eq!(placeholders.get("alarm_state").unwrap(), "Good");
-        assert_eq!(placeholders.len(), 2);
+        assert_eq!(
+            placeholders
+                .get("alarm_bad_cycles_before_recovery")
+                .unwrap(),
+            "1"
+        );
+        assert_eq!(placeholders.get("alarm_good_duration_human").unwrap(), "0s");
+        assert_eq!(placeholders.len(), 4);
     }
 
     #[test]
     fn test_add_placeholders_bad() {
-        let mut state_machine = StateMachine::new(1, 0, 1, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
         let mut placeholders = PlaceholderMap::new();
         state_machine.bad();
         state_machine.add_placeholders(&mut placeholders);
@@ -328,12 +1105,20 @@ mod test {
         chrono::DateTime::<chrono::Utc>::from_str(placeholders.get("alarm_timestamp").unwrap())
             .unwrap();
         assert_eq!(placeholders.get("alarm_state").unwrap(), "Bad");
-        assert_eq!(placeholders.len(), 2);
+        assert_eq!(placeholders.get("alarm_bad_cycles").unwrap(), "1");
+        assert_eq!(placeholders.get("alarm_good_cycles").unwrap(), "0");
+        assert_eq!(
+            placeholders.get("alarm_consecutive_bad_count").unwrap(),
+            "1"
+        );
+        assert_eq!(placeholders.get("alarm_bad_duration_human").unwrap(), "0s");
+        assert_eq!(placeholders.len(), 6);
     }
 
     #[test]
     fn test_add_placeholders_error_without_bad() {
-        let mut state_machine = StateMachine::new(1, 0, 1, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
         let mut placeholders = PlaceholderMap::new();
         state_machine.error();
         state_machine.add_placeholders(&mut placeholders);
@@ -341,12 +1126,129 @@ mod test {
         chrono::DateTime::<chrono::Utc>::from_str(placeholders.get("alarm_timestamp").unwrap())
             .unwrap();
         assert_eq!(placeholders.get("alarm_state").unwrap(), "Error");
-        assert_eq!(placeholders.len(), 2);
+        assert_eq!(placeholders.get("error_cycles").unwrap(), "1");
+        assert_eq!(
+            placeholders.get("alarm_error_duration_human").unwrap(),
+            "0s"
+        );
+        assert_eq!(placeholders.len(), 4);
+    }
+
+    #[test]
+    fn test_add_placeholders_cycles() {
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 3, 3, String::from(""), None).unwrap();
+        let placeholders = |state_machine: &StateMachine| {
+            let mut placeholders = PlaceholderMap::new();
+            state_machine.add_placeholders(&mut placeholders);
+            placeholders
+        };
+        assert!(!placeholders(&state_machine).contains_key("alarm_bad_cycles_before_recovery"));
+        for _ in 0..5 {
+            state_machine.bad();
+        }
+        state_machine.good();
+        state_machine.good();
+        let bad = placeholders(&state_machine);
+        assert_eq!(bad.get("alarm_bad_cycles").unwrap(), "7");
+        assert_eq!(bad.get("alarm_good_cycles").unwrap(), "2");
+        state_machine.good();
+        let good = placeholders(&state_machine);
+        assert_eq!(good.get("alarm_state").unwrap(), "Good");
+        assert_eq!(good.get("alarm_bad_cycles_before_recovery").unwrap(), "7");
+        assert!(!good.contains_key("alarm_bad_cycles"));
+        state_machine.error();
+        state_machine.error();
+        let error = placeholders(&state_machine);
+        assert_eq!(error.get("error_cycles").unwrap(), "2");
+        assert!(!error.contains_key("alarm_bad_cycles_before_recovery"));
+    }
+
+    #[test]
+    fn test_consecutive_bad_count() {
+        let mut state_machine =
+            StateMachine::new(3, RepeatSchedule::Fixed(2), 3, 0, String::from(""), None).unwrap();
+        let count = |state_machine: &StateMachine| {
+            let mut placeholders = PlaceholderMap::new();
+            state_machine.add_placeholders(&mut placeholders);
+            placeholders.remove("alarm_consecutive_bad_count")
+        };
+        assert_eq!(state_machine.bad(), (false, false));
+        assert_eq!(state_machine.bad(), (false, false));
+        assert_eq!(count(&state_machine), None);
+        assert_eq!(state_machine.bad(), (true, false));
+        assert_eq!(count(&state_machine).unwrap(), "3");
+        assert_eq!(state_machine.bad(), (false, false));
+        assert_eq!(state_machine.bad(), (true, false));
+        assert_eq!(count(&state_machine).unwrap(), "5");
+        state_machine.good();
+        assert_eq!(count(&state_machine).unwrap(), "0");
+        state_machine.bad();
+        assert_eq!(count(&state_machine).unwrap(), "1");
+    }
+
+    #[test]
+    fn test_bad_cycles() {
+        let mut state_machine =
+            StateMachine::new(2, RepeatSchedule::Fixed(2), 2, 0, String::from(""), None).unwrap();
+        state_machine.bad();
+        assert_eq!(state_machine.bad_cycles(), None);
+        for total_cycles in 1..=5 {
+            state_machine.bad();
+            assert_eq!(state_machine.bad_cycles(), Some(total_cycles));
+        }
+        state_machine.good();
+        assert_eq!(state_machine.bad_cycles(), Some(6));
+        state_machine.error();
+        assert_eq!(state_machine.bad_cycles(), None);
+        state_machine.bad();
+        assert_eq!(state_machine.bad_cycles(), Some(7));
+        state_machine.good();
+        state_machine.good();
+        assert_eq!(state_machine.bad_cycles(), None);
+    }
+
+    #[test]
+    fn test_stabilize_cycles() {
+        let bad_cycles = |state_machine: &StateMachine| match &state_machine.state {
+            State::Good(good) => good.bad_cycles,
+            _ => panic!(),
+        };
+        // Without `stabilize_cycles`, the bad cycles add up.
+        let mut state_machine =
+            StateMachine::new(4, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap();
+        state_machine.bad();
+        state_machine.good();
+        state_machine.good();
+        state_machine.bad();
+        assert_eq!(bad_cycles(&state_machine), 2);
+
+        let mut state_machine =
+            StateMachine::new(4, RepeatSchedule::Fixed(0), 1, 0, String::new(), None).unwrap();
+        state_machine.set_stabilize_cycles(2);
+        // A single good cycle does not reset the bad cycles.
+        state_machine.bad();
+        state_machine.good();
+        state_machine.bad();
+        assert_eq!(bad_cycles(&state_machine), 2);
+        state_machine.good();
+        state_machine.bad();
+        assert_eq!(bad_cycles(&state_machine), 3);
+        // Two consecutive good cycles do.
+        state_machine.good();
+        state_machine.good();
+        assert_eq!(bad_cycles(&state_machine), 0);
+        for _ in 0..3 {
+            assert!(!state_machine.bad().0);
+        }
+        assert!(state_machine.bad().0);
+        assert_eq!(state_machine.clone_reset(String::new()).stabilize_cycles, 2);
     }
 
     #[test]
     fn test_trigger_error_shadowed_good() {
-        let mut state_machine = StateMachine::new(2, 0, 1, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(2, RepeatSchedule::Fixed(0), 1, 0, String::from(""), None).unwrap();
         assert!(matches!(state_machine.state, State::Good(_)));
         state_machine.error();
         assert!(matches!(state_machine.state, State::Error(_)));
@@ -356,7 +1258,8 @@ mod test {
 
     #[test]
     fn test_trigger_error_shadowed_bad() {
-        let mut state_machine = StateMachine::new(1, 0, 2, 0, String::from("")).unwrap();
+        let mut state_machine =
+            StateMachine::new(1, RepeatSchedule::Fixed(0), 2, 0, String::from(""), None).unwrap();
         state_machine.bad();
         assert!(matches!(state_machine.state, State::Bad(_)));
         state_machine.error();
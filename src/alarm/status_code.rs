@@ -27,7 +27,9 @@ impl TryFrom<&config::Alarm> for StatusCode {
             config::AlarmType::Default(_) => Ok(Self {
                 status_codes: vec![Item::new(0)?],
             }),
-            _ => Err(Error(String::from("Expected status code alarm config."))),
+            _ => Err(Error::Message(String::from(
+                "Expected status code alarm config.",
+            ))),
         }
     }
 }
@@ -43,7 +45,38 @@ impl DataSink for StatusCode {
         })
     }
 
-    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
+    // Without status codes, all data would be bad.
+    fn validate(&self) -> Result<()> {
+        if self.status_codes.is_empty() {
+            Err(Error::invalid_config("status_codes", "cannot be empty."))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn describe_threshold(&self) -> String {
+        let status_codes: Vec<_> = self.status_codes.iter().map(|x| x.to_string()).collect();
+        format!("Bad when status code is not {}", status_codes.join(", "))
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
         placeholders.insert(String::from("status_code"), data.to_string());
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let alarm = StatusCode {
+            status_codes: Vec::new(),
+        };
+        assert!(alarm.validate().is_err());
+        let alarm = StatusCode {
+            status_codes: vec![Item::new(0).unwrap()],
+        };
+        assert!(alarm.validate().is_ok());
+    }
+}
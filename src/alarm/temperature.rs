@@ -19,7 +19,9 @@ impl TryFrom<&config::Alarm> for Temperature {
                 temperature: Item::new(temperature.temperature)?,
             })
         } else {
-            Err(Error(String::from("Expected temperature alarm config.")))
+            Err(Error::Message(String::from(
+                "Expected temperature alarm config.",
+            )))
         }
     }
 }
@@ -35,7 +37,11 @@ impl DataSink for Temperature {
         })
     }
 
-    fn add_placeholders(data: &Self::Item, placeholders: &mut PlaceholderMap) {
+    fn describe_threshold(&self) -> String {
+        format!("Bad when temperature > {}", self.temperature)
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
         placeholders.insert(String::from("temperature"), data.to_string());
     }
 }
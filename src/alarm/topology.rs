@@ -0,0 +1,147 @@
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+type Item = measurement::Topology;
+
+// Bad if more than `max_failures` connections failed.
+pub struct Topology {
+    max_failures: usize,
+}
+
+impl TryFrom<&config::Alarm> for Topology {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::Default(_) => Ok(Self { max_failures: 0 }),
+            config::AlarmType::Topology(topology) => Ok(Self {
+                max_failures: topology.max_failures as usize,
+            }),
+            _ => Err(Error::Message(String::from(
+                "Expected topology alarm config.",
+            ))),
+        }
+    }
+}
+
+impl DataSink for Topology {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        Ok(if data.failures().len() > self.max_failures {
+            SinkDecision::Bad
+        } else {
+            SinkDecision::Good
+        })
+    }
+
+    fn describe_threshold(&self) -> String {
+        format!(
+            "Bad when more than {} connection(s) fail",
+            self.max_failures
+        )
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        let failed: Vec<String> = data
+            .failures()
+            .iter()
+            .map(|x| format!("{} ({})", x.connection, x.error))
+            .collect();
+        placeholders.insert(String::from("failed_connections"), failed.join(", "));
+        placeholders.insert(
+            String::from("failed_connection_count"),
+            data.failures().len().to_string(),
+        );
+        placeholders.insert(
+            String::from("connection_count"),
+            data.connection_count().to_string(),
+        );
+        let traceroutes: Vec<String> = data
+            .failures()
+            .iter()
+            .filter_map(|x| {
+                x.traceroute
+                    .as_ref()
+                    .map(|traceroute| format!("{}:\n{}", x.connection, traceroute.trim_end()))
+            })
+            .collect();
+        if !traceroutes.is_empty() {
+            placeholders.insert(String::from("traceroute"), traceroutes.join("\n"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::measurement::Measurement;
+
+    fn failures(count: usize) -> Item {
+        Item::new((
+            5,
+            (0..count)
+                .map(|i| measurement::ConnectionFailure {
+                    connection: format!("10.0.0.1 -> db{i}:5432"),
+                    error: String::from("Connection refused"),
+                    traceroute: None,
+                })
+                .collect(),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_max_failures() {
+        let alarm: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nmax_failures = 2").unwrap();
+        let mut sink = Topology::try_from(&alarm).unwrap();
+        assert!(matches!(
+            sink.put_data(&failures(0)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            sink.put_data(&failures(2)).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            sink.put_data(&failures(3)).unwrap(),
+            SinkDecision::Bad
+        ));
+
+        // Without `max_failures`, any failure is bad.
+        let alarm: config::Alarm = toml::from_str("name = \"Alarm\"\naction = \"Action\"").unwrap();
+        let mut sink = Topology::try_from(&alarm).unwrap();
+        assert!(matches!(
+            sink.put_data(&failures(1)).unwrap(),
+            SinkDecision::Bad
+        ));
+        assert!(Item::new((1, failures(2).failures().to_vec())).is_err());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let mut data = failures(2).data();
+        data.1[1].traceroute = Some(String::from(" 1  10.0.0.254\n 2  *\n"));
+        let alarm: config::Alarm = toml::from_str("name = \"Alarm\"\naction = \"Action\"").unwrap();
+        let sink = Topology::try_from(&alarm).unwrap();
+        let mut placeholders = PlaceholderMap::new();
+        sink.add_placeholders(&Item::new(data).unwrap(), &mut placeholders);
+        assert_eq!(
+            placeholders["failed_connections"],
+            "10.0.0.1 -> db0:5432 (Connection refused), 10.0.0.1 -> db1:5432 (Connection refused)"
+        );
+        assert_eq!(placeholders["failed_connection_count"], "2");
+        assert_eq!(placeholders["connection_count"], "5");
+        assert_eq!(
+            placeholders["traceroute"],
+            "10.0.0.1 -> db1:5432:\n 1  10.0.0.254\n 2  *"
+        );
+        let mut placeholders = PlaceholderMap::new();
+        sink.add_placeholders(&failures(0), &mut placeholders);
+        assert_eq!(placeholders["failed_connections"], "");
+        assert!(!placeholders.contains_key("traceroute"));
+    }
+}
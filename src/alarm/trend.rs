@@ -0,0 +1,286 @@
+use crate::measurement::Measurement;
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TrendDirection {
+    Increasing,
+    Decreasing,
+    // Least-squares slope per cycle.
+    SteepIncrease { slope_threshold: f64 },
+}
+
+// Detects a steady trend in the last `window` values, e.g. the growing memory usage of a leak.
+// The data is always good until the window is filled.
+#[derive(Clone)]
+pub struct Trend<T = measurement::Level>
+where
+    T: Measurement + Copy + Send + Sync,
+    T::Data: PartialOrd + num_traits::ToPrimitive,
+{
+    direction: TrendDirection,
+    window: usize,
+    samples: std::collections::VecDeque<T>,
+}
+
+impl<T> Trend<T>
+where
+    T: Measurement + Copy + Send + Sync,
+    T::Data: PartialOrd + num_traits::ToPrimitive,
+{
+    fn new(direction: TrendDirection, window: u32) -> Result<Self> {
+        if window < 2 {
+            return Err(Error::invalid_config("window", "must be at least 2."));
+        }
+        if matches!(direction, TrendDirection::SteepIncrease { slope_threshold } if !slope_threshold.is_finite())
+        {
+            return Err(Error::invalid_config("slope_threshold", "must be finite."));
+        }
+        Ok(Self {
+            direction,
+            window: window as usize,
+            samples: std::collections::VecDeque::with_capacity(window as usize),
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        self.samples.len() == self.window
+    }
+
+    fn is_monotonic(&self, ordering: std::cmp::Ordering) -> bool {
+        self.samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .all(|(a, b)| a.data().partial_cmp(&b.data()) == Some(ordering))
+    }
+
+    // The x values are the indices of the samples, so the slope is the change per cycle.
+    fn slope(&self) -> Result<f64> {
+        let values = self
+            .samples
+            .iter()
+            .map(|x| {
+                num_traits::ToPrimitive::to_f64(&x.data()).ok_or_else(|| {
+                    Error::Message(format!("Cannot convert {} to a number.", x.format()))
+                })
+            })
+            .collect::<Result<Vec<f64>>>()?;
+        let count = values.len() as f64;
+        let mean_x = (count - 1.0) / 2.0;
+        let mean_y = values.iter().sum::<f64>() / count;
+        let mut covariance = 0.0;
+        let mut variance = 0.0;
+        for (i, y) in values.iter().enumerate() {
+            let dx = i as f64 - mean_x;
+            covariance += dx * (y - mean_y);
+            variance += dx * dx;
+        }
+        Ok(covariance / variance)
+    }
+}
+
+impl<T> TryFrom<&config::Alarm> for Trend<T>
+where
+    T: Measurement + Copy + Send + Sync,
+    T::Data: PartialOrd + num_traits::ToPrimitive,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        if let config::AlarmType::Trend(trend) = &alarm.type_ {
+            let direction = match (trend.trend, trend.slope_threshold) {
+                (config::TrendDirection::Increasing, None) => TrendDirection::Increasing,
+                (config::TrendDirection::Decreasing, None) => TrendDirection::Decreasing,
+                (config::TrendDirection::SteepIncrease, Some(slope_threshold)) => {
+                    TrendDirection::SteepIncrease { slope_threshold }
+                }
+                (config::TrendDirection::SteepIncrease, None) => {
+                    return Err(Error::Message(String::from(
+                        "'SteepIncrease' requires 'slope_threshold'.",
+                    )))
+                }
+                (_, Some(_)) => {
+                    return Err(Error::invalid_config(
+                        "slope_threshold",
+                        "is only used by 'SteepIncrease'.",
+                    ))
+                }
+            };
+            Self::new(direction, trend.window)
+        } else {
+            Err(Error::Message(String::from("Expected trend alarm config.")))
+        }
+    }
+}
+
+impl<T> DataSink for Trend<T>
+where
+    T: Measurement + Copy + Send + Sync,
+    T::Data: PartialOrd + num_traits::ToPrimitive + Send + Sync,
+{
+    type Item = T;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        if self.is_full() {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(*data);
+        if !self.is_full() {
+            return Ok(SinkDecision::Good);
+        }
+        let bad = match self.direction {
+            TrendDirection::Increasing => self.is_monotonic(std::cmp::Ordering::Less),
+            TrendDirection::Decreasing => self.is_monotonic(std::cmp::Ordering::Greater),
+            TrendDirection::SteepIncrease { slope_threshold } => self.slope()? > slope_threshold,
+        };
+        Ok(if bad {
+            SinkDecision::Bad
+        } else {
+            SinkDecision::Good
+        })
+    }
+
+    fn reset(&mut self) {
+        self.samples.clear();
+    }
+
+    fn describe_threshold(&self) -> String {
+        match self.direction {
+            TrendDirection::Increasing => format!(
+                "Bad when level increases in each of the last {} measurements",
+                self.window
+            ),
+            TrendDirection::Decreasing => format!(
+                "Bad when level decreases in each of the last {} measurements",
+                self.window
+            ),
+            TrendDirection::SteepIncrease { slope_threshold } => format!(
+                "Bad when level rises by more than {slope_threshold} per cycle over the last {} measurements",
+                self.window
+            ),
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(String::from("level"), data.format());
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if self.is_full() {
+            if let Ok(slope) = self.slope() {
+                placeholders.insert(String::from("trend_slope"), format!("{slope:.2}"));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INCREASING: [f64; 4] = [1.0, 2.0, 4.0, 7.0];
+    const DECREASING: [f64; 4] = [7.0, 4.0, 2.0, 1.0];
+    const FLAT: [f64; 4] = [3.0, 3.0, 3.0, 3.0];
+    // Rising by 2 per cycle on average, but not in every cycle.
+    const NOISY: [f64; 4] = [1.0, 5.0, 4.0, 8.0];
+
+    // Decision after the last value.
+    fn decision(direction: TrendDirection, values: &[f64]) -> SinkDecision {
+        let mut trend = Trend::<f64>::new(direction, 4).unwrap();
+        let mut decision = SinkDecision::Good;
+        for value in values {
+            decision = trend.put_data(value).unwrap();
+        }
+        decision
+    }
+
+    fn is_bad(direction: TrendDirection, values: &[f64]) -> bool {
+        matches!(decision(direction, values), SinkDecision::Bad)
+    }
+
+    #[test]
+    fn test_increasing() {
+        let direction = TrendDirection::Increasing;
+        assert!(is_bad(direction, &INCREASING));
+        assert!(!is_bad(direction, &DECREASING));
+        assert!(!is_bad(direction, &FLAT));
+        assert!(!is_bad(direction, &NOISY));
+    }
+
+    #[test]
+    fn test_decreasing() {
+        let direction = TrendDirection::Decreasing;
+        assert!(!is_bad(direction, &INCREASING));
+        assert!(is_bad(direction, &DECREASING));
+        assert!(!is_bad(direction, &FLAT));
+        assert!(!is_bad(direction, &NOISY));
+    }
+
+    #[test]
+    fn test_steep_increase() {
+        let direction = TrendDirection::SteepIncrease {
+            slope_threshold: 1.5,
+        };
+        // The slope of `INCREASING` is 2.
+        assert!(is_bad(direction, &INCREASING));
+        assert!(!is_bad(direction, &DECREASING));
+        assert!(!is_bad(direction, &FLAT));
+        assert!(is_bad(direction, &NOISY));
+        assert!(!is_bad(
+            TrendDirection::SteepIncrease {
+                slope_threshold: 2.5
+            },
+            &NOISY
+        ));
+    }
+
+    #[test]
+    fn test_window() {
+        let mut trend = Trend::<f64>::new(TrendDirection::Increasing, 4).unwrap();
+        for value in &INCREASING[..3] {
+            assert!(matches!(trend.put_data(value).unwrap(), SinkDecision::Good));
+        }
+        assert!(matches!(trend.put_data(&7.0).unwrap(), SinkDecision::Bad));
+        let mut placeholders = PlaceholderMap::new();
+        trend.add_state_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("trend_slope").unwrap(), "2.00");
+        // The oldest value is dropped.
+        assert!(matches!(trend.put_data(&11.0).unwrap(), SinkDecision::Bad));
+        assert!(matches!(trend.put_data(&10.0).unwrap(), SinkDecision::Good));
+        trend.reset();
+        assert!(matches!(trend.put_data(&20.0).unwrap(), SinkDecision::Good));
+        let mut placeholders = PlaceholderMap::new();
+        trend.add_state_placeholders(&mut placeholders);
+        assert!(placeholders.is_empty());
+    }
+
+    #[test]
+    fn test_config() {
+        let trend = |options: &str| {
+            let alarm: config::Alarm =
+                toml::from_str(&format!("name = \"Alarm\"\naction = \"Action\"\n{options}"))
+                    .unwrap();
+            Trend::<measurement::Level>::try_from(&alarm)
+        };
+        assert_eq!(
+            trend("trend = \"Decreasing\"\nwindow = 5")
+                .unwrap()
+                .describe_threshold(),
+            "Bad when level decreases in each of the last 5 measurements"
+        );
+        assert_eq!(
+            trend("trend = \"SteepIncrease\"\nwindow = 5\nslope_threshold = 0.5")
+                .unwrap()
+                .direction,
+            TrendDirection::SteepIncrease {
+                slope_threshold: 0.5
+            }
+        );
+        assert!(trend("trend = \"Increasing\"\nwindow = 1").is_err());
+        assert!(trend("trend = \"SteepIncrease\"\nwindow = 5").is_err());
+        assert!(trend("trend = \"Increasing\"\nwindow = 5\nslope_threshold = 0.5").is_err());
+        assert!(trend("trend = \"SteepIncrease\"\nwindow = 5\nslope_threshold = nan").is_err());
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+type Item = bool;
+
+pub struct TwoState {
+    bad_on_false: bool,
+    true_state: String,
+    false_state: String,
+    last: Option<bool>,
+}
+
+impl TwoState {
+    pub(super) fn new(bad_on_false: bool, true_state: String, false_state: String) -> Result<Self> {
+        if true_state.is_empty() {
+            Err(Error::invalid_config("true_state", "cannot be empty."))
+        } else if false_state.is_empty() {
+            Err(Error::invalid_config("false_state", "cannot be empty."))
+        } else {
+            Ok(Self {
+                bad_on_false,
+                true_state,
+                false_state,
+                last: None,
+            })
+        }
+    }
+}
+
+impl TryFrom<&config::Alarm> for TwoState {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::Default(_) => Self::new(
+                config::default::alarm_two_state_bad_on_false(),
+                config::default::alarm_two_state_true_state(),
+                config::default::alarm_two_state_false_state(),
+            ),
+            config::AlarmType::TwoState(two_state) => Self::new(
+                two_state.bad_on_false,
+                two_state.true_state.clone(),
+                two_state.false_state.clone(),
+            ),
+            _ => Err(Error::Message(String::from(
+                "Expected two state alarm config.",
+            ))),
+        }
+    }
+}
+
+impl DataSink for TwoState {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        self.last = Some(*data);
+        Ok(if *data != self.bad_on_false {
+            SinkDecision::Bad
+        } else {
+            SinkDecision::Good
+        })
+    }
+
+    fn reset(&mut self) {
+        self.last = None;
+    }
+
+    fn describe_threshold(&self) -> String {
+        let bad_state = if self.bad_on_false {
+            &self.false_state
+        } else {
+            &self.true_state
+        };
+        format!("Bad when state is {bad_state}")
+    }
+
+    fn add_placeholders(&self, _data: &Self::Item, _placeholders: &mut PlaceholderMap) {}
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        if let Some(last) = self.last {
+            let state = if last {
+                &self.true_state
+            } else {
+                &self.false_state
+            };
+            placeholders.insert(String::from("state"), state.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn two_state(bad_on_false: bool) -> TwoState {
+        TwoState::new(bad_on_false, String::from("up"), String::from("down")).unwrap()
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(TwoState::new(true, String::new(), String::from("down")).is_err());
+        assert!(TwoState::new(true, String::from("up"), String::new()).is_err());
+    }
+
+    #[test]
+    fn test_bad_on_false() {
+        let mut alarm = two_state(true);
+        assert!(matches!(alarm.put_data(&true).unwrap(), SinkDecision::Good));
+        assert!(matches!(alarm.put_data(&false).unwrap(), SinkDecision::Bad));
+    }
+
+    #[test]
+    fn test_bad_on_true() {
+        let mut alarm = two_state(false);
+        assert!(matches!(alarm.put_data(&true).unwrap(), SinkDecision::Bad));
+        assert!(matches!(
+            alarm.put_data(&false).unwrap(),
+            SinkDecision::Good
+        ));
+    }
+
+    #[test]
+    fn test_state_placeholder() {
+        let mut alarm =
+            TwoState::new(true, String::from("present"), String::from("missing")).unwrap();
+        let mut placeholders = PlaceholderMap::new();
+        alarm.add_state_placeholders(&mut placeholders);
+        assert!(placeholders.is_empty());
+        alarm.put_data(&false).unwrap();
+        alarm.add_state_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("state").unwrap(), "missing");
+        alarm.put_data(&true).unwrap();
+        alarm.add_state_placeholders(&mut placeholders);
+        assert_eq!(placeholders.get("state").unwrap(), "present");
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{measurement, Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+type Item = measurement::ValuePair;
+
+// Compares the numerator of the data relative to its denominator.
+pub enum ValuePair {
+    // Bad if numerator / denominator >= ratio.
+    Ratio(f64),
+    // Bad if numerator - denominator >= difference.
+    Difference(f64),
+}
+
+impl TryFrom<&config::Alarm> for ValuePair {
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match &alarm.type_ {
+            config::AlarmType::Ratio(ratio) => {
+                if ratio.ratio.is_nan() {
+                    Err(Error::invalid_config("ratio", "cannot be NaN."))
+                } else {
+                    Ok(Self::Ratio(ratio.ratio))
+                }
+            }
+            config::AlarmType::Difference(difference) => {
+                if difference.difference.is_nan() {
+                    Err(Error::invalid_config("difference", "cannot be NaN."))
+                } else {
+                    Ok(Self::Difference(difference.difference))
+                }
+            }
+            _ => Err(Error::Message(String::from(
+                "Expected ratio or difference alarm config.",
+            ))),
+        }
+    }
+}
+
+impl DataSink for ValuePair {
+    type Item = Item;
+
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        let (value, threshold) = match self {
+            Self::Ratio(ratio) => {
+                if data.denominator() == 0. {
+                    return Err(Error::Message(format!(
+                        "Cannot divide {:.2} by zero.",
+                        data.numerator()
+                    )));
+                }
+                (data.numerator() / data.denominator(), *ratio)
+            }
+            Self::Difference(difference) => (data.numerator() - data.denominator(), *difference),
+        };
+        Ok(if value >= threshold {
+            SinkDecision::Bad
+        } else {
+            SinkDecision::Good
+        })
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::Ratio(_) => String::from("ratio"),
+            Self::Difference(_) => String::from("difference"),
+        }
+    }
+
+    fn describe_threshold(&self) -> String {
+        match self {
+            Self::Ratio(ratio) => format!("Bad when numerator / denominator >= {ratio:.2}"),
+            Self::Difference(difference) => {
+                format!("Bad when numerator - denominator >= {difference:.2}")
+            }
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        placeholders.insert(
+            String::from("numerator"),
+            format!("{:.2}", data.numerator()),
+        );
+        placeholders.insert(
+            String::from("denominator"),
+            format!("{:.2}", data.denominator()),
+        );
+        if data.denominator() != 0. {
+            placeholders.insert(
+                String::from("ratio"),
+                format!("{:.2}", data.numerator() / data.denominator()),
+            );
+        }
+        placeholders.insert(
+            String::from("difference"),
+            format!("{:.2}", data.numerator() - data.denominator()),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::measurement::Measurement;
+
+    fn pair(numerator: f64, denominator: f64) -> Item {
+        Item::new((numerator, denominator)).unwrap()
+    }
+
+    fn value_pair(config: &str) -> Result<ValuePair> {
+        let config = format!("name = \"Alarm\"\naction = \"Action\"\n{config}");
+        ValuePair::try_from(&toml::from_str::<config::Alarm>(&config).unwrap())
+    }
+
+    fn decision(sink: &mut ValuePair, numerator: f64, denominator: f64) -> SinkDecision {
+        sink.put_data(&pair(numerator, denominator)).unwrap()
+    }
+
+    #[test]
+    fn test_ratio() {
+        let mut sink = value_pair("ratio = 2.0").unwrap();
+        assert!(matches!(decision(&mut sink, 3., 2.), SinkDecision::Good));
+        assert!(matches!(decision(&mut sink, 4., 2.), SinkDecision::Bad));
+        assert!(matches!(decision(&mut sink, 5., 2.), SinkDecision::Bad));
+        // A negative denominator flips the sign of the ratio.
+        assert!(matches!(decision(&mut sink, 4., -2.), SinkDecision::Good));
+        assert!(matches!(decision(&mut sink, -4., -2.), SinkDecision::Bad));
+        assert_eq!(
+            sink.put_data(&pair(1., 0.)).unwrap_err().to_string(),
+            "Cannot divide 1.00 by zero."
+        );
+        assert!(matches!(
+            decision(&mut sink, 1., -0.001),
+            SinkDecision::Good
+        ));
+        assert_eq!(
+            sink.describe_threshold(),
+            "Bad when numerator / denominator >= 2.00"
+        );
+    }
+
+    #[test]
+    fn test_difference() {
+        let mut sink = value_pair("difference = 10").unwrap();
+        assert!(matches!(decision(&mut sink, 19., 10.), SinkDecision::Good));
+        assert!(matches!(decision(&mut sink, 20., 10.), SinkDecision::Bad));
+        assert!(matches!(decision(&mut sink, 5., -5.), SinkDecision::Bad));
+        assert!(matches!(decision(&mut sink, -5., 5.), SinkDecision::Good));
+        // Unlike the ratio, a zero denominator is fine.
+        assert!(matches!(decision(&mut sink, 10., 0.), SinkDecision::Bad));
+        let mut sink = value_pair("difference = -1").unwrap();
+        assert!(matches!(decision(&mut sink, -2., 0.), SinkDecision::Good));
+        assert!(matches!(decision(&mut sink, -1., 0.), SinkDecision::Bad));
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(value_pair("ratio = nan").is_err());
+        assert!(value_pair("difference = nan").is_err());
+        assert!(value_pair("level = 10").is_err());
+        assert!(Item::new((f64::NAN, 1.)).is_err());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let sink = value_pair("ratio = 2.0").unwrap();
+        let mut placeholders = PlaceholderMap::new();
+        sink.add_placeholders(&pair(3., 2.), &mut placeholders);
+        assert_eq!(placeholders["numerator"], "3.00");
+        assert_eq!(placeholders["denominator"], "2.00");
+        assert_eq!(placeholders["ratio"], "1.50");
+        assert_eq!(placeholders["difference"], "1.00");
+        let mut placeholders = PlaceholderMap::new();
+        sink.add_placeholders(&pair(3., 0.), &mut placeholders);
+        assert!(!placeholders.contains_key("ratio"));
+    }
+}
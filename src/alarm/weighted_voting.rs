@@ -0,0 +1,296 @@
+use crate::{Error, PlaceholderMap, Result};
+
+use super::{DataSink, SinkDecision};
+use crate::config;
+
+// Combines several data sinks on the same data, e.g. a level and a delta that are each too noisy
+// on their own. The data is bad if the summed weight of the data sinks deciding bad exceeds the
+// threshold. Without a threshold, there is a single data sink whose decision is passed through.
+#[derive(Clone)]
+pub struct WeightedVoting<S>
+where
+    S: DataSink,
+{
+    votes: Vec<(f64, S)>,
+    threshold_weight: Option<f64>,
+    bad_weight: f64,
+}
+
+impl<S> WeightedVoting<S>
+where
+    S: DataSink,
+{
+    fn new(votes: Vec<(f64, S)>, threshold_weight: Option<f64>) -> Result<Self> {
+        if votes.is_empty() {
+            Err(Error::invalid_config("votes", "cannot be empty."))
+        } else if votes
+            .iter()
+            .any(|(weight, _)| !weight.is_finite() || *weight <= 0.0)
+        {
+            Err(Error::invalid_config("weight", "must be greater than 0."))
+        } else if matches!(threshold_weight, Some(x) if !x.is_finite() || x < 0.0) {
+            Err(Error::invalid_config(
+                "threshold_weight",
+                "cannot be negative.",
+            ))
+        } else {
+            Ok(Self {
+                votes,
+                threshold_weight,
+                bad_weight: 0.0,
+            })
+        }
+    }
+
+    fn single(inner: S) -> Self {
+        Self {
+            votes: vec![(1.0, inner)],
+            threshold_weight: None,
+            bad_weight: 0.0,
+        }
+    }
+}
+
+impl<'a, S> TryFrom<&'a config::Alarm> for WeightedVoting<S>
+where
+    S: DataSink + for<'b> TryFrom<&'b config::Alarm, Error = Error>,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &'a config::Alarm) -> std::result::Result<Self, Self::Error> {
+        match (alarm.votes.is_empty(), alarm.threshold_weight) {
+            (true, None) => Ok(Self::single(S::try_from(alarm)?)),
+            (true, Some(_)) => Err(Error::invalid_config(
+                "threshold_weight",
+                "requires 'votes'.",
+            )),
+            (false, None) => Err(Error::invalid_config(
+                "votes",
+                "requires 'threshold_weight'.",
+            )),
+            (false, Some(threshold_weight)) => {
+                if !matches!(alarm.type_, config::AlarmType::Default(_)) {
+                    return Err(Error::Message(String::from(
+                        "The alarm type options have to be set per vote if 'votes' is used.",
+                    )));
+                }
+                // Each vote is configured like an alarm of its own with only the type options
+                // changed.
+                let votes = alarm
+                    .votes
+                    .iter()
+                    .map(|vote| {
+                        let vote_alarm = config::Alarm {
+                            votes: Vec::new(),
+                            threshold_weight: None,
+                            type_: vote.type_.clone(),
+                            ..alarm.clone()
+                        };
+                        Ok((vote.weight, S::try_from(&vote_alarm)?))
+                    })
+                    .collect::<Result<_>>()?;
+                Self::new(votes, Some(threshold_weight))
+            }
+        }
+    }
+}
+
+impl<S> DataSink for WeightedVoting<S>
+where
+    S: DataSink,
+{
+    type Item = S::Item;
+
+    // Every data sink gets the data, even if the decision is already clear, to keep the state of
+    // e.g. a delta up to date.
+    fn put_data(&mut self, data: &Self::Item) -> Result<SinkDecision> {
+        let Some(threshold_weight) = self.threshold_weight else {
+            return self.votes[0].1.put_data(data);
+        };
+        let mut bad_weight = 0.0;
+        let mut result = Ok(());
+        for (weight, sink) in self.votes.iter_mut() {
+            match sink.put_data(data) {
+                Ok(SinkDecision::Bad) => bad_weight += *weight,
+                Ok(SinkDecision::Good) => {}
+                Err(err) => result = result.and(Err(err)),
+            }
+        }
+        self.bad_weight = bad_weight;
+        result?;
+        Ok(if bad_weight > threshold_weight {
+            SinkDecision::Bad
+        } else {
+            SinkDecision::Good
+        })
+    }
+
+    fn name(&self) -> String {
+        match self.threshold_weight {
+            Some(_) => {
+                let names: Vec<String> = self.votes.iter().map(|(_, x)| x.name()).collect();
+                format!("weighted_voting({})", names.join(", "))
+            }
+            None => self.votes[0].1.name(),
+        }
+    }
+
+    fn unit(&self) -> &str {
+        self.votes[0].1.unit()
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        let formatted = self.votes[0].1.format_data(data);
+        match self.threshold_weight {
+            Some(threshold_weight) => format!(
+                "{formatted} (bad weight {}, threshold {threshold_weight})",
+                self.bad_weight
+            ),
+            None => formatted,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.votes.iter().try_for_each(|(_, x)| x.validate())
+    }
+
+    fn reset(&mut self) {
+        self.bad_weight = 0.0;
+        for (_, sink) in self.votes.iter_mut() {
+            sink.reset();
+        }
+    }
+
+    fn describe_threshold(&self) -> String {
+        match self.threshold_weight {
+            Some(threshold_weight) => {
+                let votes: Vec<String> = self
+                    .votes
+                    .iter()
+                    .map(|(weight, x)| format!("{} (weight {weight})", x.describe_threshold()))
+                    .collect();
+                format!(
+                    "Bad when the weight of the bad votes exceeds {threshold_weight}: {}",
+                    votes.join(", ")
+                )
+            }
+            None => self.votes[0].1.describe_threshold(),
+        }
+    }
+
+    fn add_placeholders(&self, data: &Self::Item, placeholders: &mut PlaceholderMap) {
+        self.votes[0].1.add_placeholders(data, placeholders);
+    }
+
+    fn add_state_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        for (_, sink) in self.votes.iter() {
+            sink.add_state_placeholders(placeholders);
+        }
+        if self.threshold_weight.is_some() {
+            placeholders.insert(String::from("bad_weight"), self.bad_weight.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::MockDataSink;
+    use super::*;
+
+    // Bad if the data is at least `level`.
+    fn vote(weight: f64, level: u8) -> (f64, MockDataSink) {
+        let mut sink = MockDataSink::new();
+        sink.expect_put_data().times(1).returning(move |x| {
+            Ok(if *x >= level {
+                SinkDecision::Bad
+            } else {
+                SinkDecision::Good
+            })
+        });
+        (weight, sink)
+    }
+
+    fn decision(data: u8) -> SinkDecision {
+        let mut voting =
+            WeightedVoting::new(vec![vote(1.0, 10), vote(1.0, 20), vote(3.0, 30)], Some(2.5))
+                .unwrap();
+        voting.put_data(&data).unwrap()
+    }
+
+    #[test]
+    fn test_voting() {
+        assert!(matches!(decision(5), SinkDecision::Good));
+        // The two low weight votes are bad.
+        assert!(matches!(decision(25), SinkDecision::Good));
+        // All three votes are bad.
+        assert!(matches!(decision(35), SinkDecision::Bad));
+
+        // Only the high weight vote is bad.
+        let mut voting =
+            WeightedVoting::new(vec![vote(1.0, 50), vote(1.0, 50), vote(3.0, 30)], Some(2.5))
+                .unwrap();
+        assert!(matches!(voting.put_data(&35).unwrap(), SinkDecision::Bad));
+        voting.votes[0]
+            .1
+            .expect_format_data()
+            .returning(|x| x.to_string());
+        assert_eq!(voting.format_data(&35), "35 (bad weight 3, threshold 2.5)");
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut failing = MockDataSink::new();
+        failing
+            .expect_put_data()
+            .times(1)
+            .returning(|_| Err(Error::Message(String::from("Error"))));
+        // The remaining votes still get the data.
+        let mut voting =
+            WeightedVoting::new(vec![(1.0, failing), vote(1.0, 10)], Some(0.5)).unwrap();
+        assert!(voting.put_data(&10).is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(WeightedVoting::<MockDataSink>::new(Vec::new(), Some(1.0)).is_err());
+        for weight in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(WeightedVoting::new(vec![(weight, MockDataSink::new())], Some(1.0)).is_err());
+        }
+        for threshold_weight in [-1.0, f64::NAN] {
+            assert!(
+                WeightedVoting::new(vec![(1.0, MockDataSink::new())], Some(threshold_weight))
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let voting = |text: &str| {
+            let alarm: config::Alarm = toml::from_str(text).unwrap();
+            WeightedVoting::<super::super::Level>::try_from(&alarm)
+        };
+        let alarm = voting(
+            r#"
+            name = "Alarm"
+            action = "Action"
+            threshold_weight = 1.5
+            votes = [{ weight = 1, level = 80 }, { weight = 2, level = 90 }]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(alarm.name(), "weighted_voting(level, level)");
+        assert!(voting("name = \"Alarm\"\naction = \"Action\"\nlevel = 80")
+            .unwrap()
+            .threshold_weight
+            .is_none());
+        assert!(voting(
+            "name = \"Alarm\"\naction = \"Action\"\nthreshold_weight = 1\nvotes = [{ weight = 1, level = 80 }]\nlevel = 80"
+        )
+        .is_err());
+        assert!(
+            voting("name = \"Alarm\"\naction = \"Action\"\nlevel = 80\nthreshold_weight = 1")
+                .is_err()
+        );
+    }
+}
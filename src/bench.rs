@@ -0,0 +1,43 @@
+// Internals needed by the benchmarks in `benches/`. This is not part of the public API.
+use crate::alarm::{Alarm, AlarmBase, Level, RepeatSchedule, StateMachine};
+use crate::{action, config, PlaceholderMap, Result};
+use async_trait::async_trait;
+
+struct NoopAction;
+
+#[async_trait]
+impl action::Action for NoopAction {
+    async fn trigger(&self, _placeholders: PlaceholderMap) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Alarm with the real state machine and a level data sink with a level of 15. Bad data triggers
+// the action on every cycle once the alarm is bad. The actions return immediately and logging is
+// off, so only the alarm itself is measured.
+pub struct LevelAlarm(AlarmBase<Level<u64>>);
+
+impl LevelAlarm {
+    pub fn new(cycles: u32) -> Self {
+        let config: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nlevel = 15").unwrap();
+        let off = Some(log::LevelFilter::Off);
+        let mut alarm = AlarmBase::new(
+            String::from("Alarm"),
+            String::from("ID"),
+            std::sync::Arc::new(NoopAction),
+            PlaceholderMap::new(),
+            StateMachine::new(cycles, RepeatSchedule::Fixed(1), 1, 0, String::new(), off).unwrap(),
+            Level::<u64>::try_from(&config).unwrap(),
+            String::new(),
+        )
+        .unwrap();
+        alarm.set_recover_action(Some(std::sync::Arc::new(NoopAction)), PlaceholderMap::new());
+        alarm.set_log_level(off);
+        Self(alarm)
+    }
+
+    pub async fn put_data(&mut self, data: u64) {
+        self.0.put_data(&data, PlaceholderMap::new()).await.unwrap();
+    }
+}
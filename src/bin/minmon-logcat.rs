@@ -0,0 +1,83 @@
+#![deny(warnings)]
+
+use minmon::{Error, Result};
+
+#[derive(Default)]
+struct Filter {
+    alarm_name: Option<String>,
+    event_type: Option<String>,
+}
+
+impl Filter {
+    fn matches(&self, event: &serde_json::Value) -> bool {
+        let matches = |filter: &Option<String>, key: &str| match filter {
+            Some(filter) => event[key].as_str() == Some(filter.as_str()),
+            None => true,
+        };
+        matches(&self.alarm_name, "alarm_name") && matches(&self.event_type, "event_type")
+    }
+}
+
+fn parse_args() -> Result<(std::path::PathBuf, Filter)> {
+    let mut path = None;
+    let mut filter = Filter::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| Error::Message(format!("Missing value for '{arg}'.")))
+        };
+        match arg.as_str() {
+            "--alarm" => filter.alarm_name = Some(value()?),
+            "--event" => filter.event_type = Some(value()?),
+            _ if path.is_none() => path = Some(std::path::PathBuf::from(arg)),
+            _ => return Err(Error::Message(format!("Unexpected argument '{arg}'."))),
+        }
+    }
+    match path {
+        Some(path) => Ok((path, filter)),
+        None => Err(Error::Message(String::from(
+            "Usage: minmon-logcat <file> [--alarm <alarm_name>] [--event <event_type>]",
+        ))),
+    }
+}
+
+fn format_event(event: &serde_json::Value) -> String {
+    let get = |key: &str| event[key].as_str().unwrap_or("-");
+    match get("event_type") {
+        "report" => format!("{} [report] {}", get("event_timestamp"), get("event_name")),
+        event_type => format!(
+            "{} [{}] {} / {} ({}): {}",
+            get("event_timestamp"),
+            event_type,
+            get("check_name"),
+            get("alarm_name"),
+            get("alarm_id"),
+            get("alarm_state")
+        ),
+    }
+}
+
+fn main_wrapper() -> Result<()> {
+    use std::io::BufRead;
+    let (path, filter) = parse_args()?;
+    let file = std::fs::File::open(&path)
+        .map_err(|x| Error::Message(format!("Could not open {}: {x}", path.display())))?;
+    for (number, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line =
+            line.map_err(|x| Error::Message(format!("Could not read {}: {x}", path.display())))?;
+        match serde_json::from_str::<serde_json::Value>(&line) {
+            Ok(event) if filter.matches(&event) => println!("{}", format_event(&event)),
+            Ok(_) => {}
+            Err(err) => eprintln!("Skipping invalid line {}: {err}", number + 1),
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(error) = main_wrapper() {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
+}
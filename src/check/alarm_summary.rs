@@ -0,0 +1,128 @@
+use super::DataSource;
+use crate::{alarm, config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+// States of other alarms, e.g. of the components of a service. There is no data of its own, the
+// registry is read on every interval of the check.
+pub struct AlarmSummary {
+    id: Vec<String>,
+    alarms: Vec<alarm::AlarmChain>,
+}
+
+impl AlarmSummary {
+    pub fn new(check: &config::Check, registry: &alarm::AlarmRegistry) -> Result<Self> {
+        if let config::CheckType::AlarmSummary(alarm_summary) = &check.type_ {
+            if alarm_summary.alarm_ids.is_empty() {
+                Err(Error::invalid_config("alarm_ids", "cannot be empty."))
+            } else {
+                Ok(Self {
+                    id: vec![check.name.clone()],
+                    // An alarm of the summary is bad like a dependency of an alarm, i.e. if it is
+                    // bad for any ID.
+                    alarms: alarm_summary
+                        .alarm_ids
+                        .iter()
+                        .map(|x| alarm::AlarmChain::new(x.clone(), registry.clone()))
+                        .collect(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for AlarmSummary {
+    type Item = measurement::AlarmSummary;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let bad_alarms = self
+            .alarms
+            .iter()
+            .filter(|x| x.dependency_bad())
+            .map(|x| String::from(x.depends_on_alarm_id()))
+            .collect();
+        Ok(vec![
+            Self::Item::new((self.alarms.len(), bad_alarms)).map(Some)
+        ])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        data.to_string()
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alarm::{DataSink, SinkDecision};
+
+    fn alarm_summary(alarm_ids: &str, registry: &alarm::AlarmRegistry) -> Result<AlarmSummary> {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Service"
+            type = "AlarmSummary"
+            alarm_ids = [{alarm_ids}]
+            "#
+        ))
+        .unwrap();
+        AlarmSummary::new(&config, registry)
+    }
+
+    async fn decide(
+        check: &mut AlarmSummary,
+        sink: &mut alarm::AlarmSummary,
+    ) -> (SinkDecision, String) {
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        (sink.put_data(&data).unwrap(), check.format_data(&data))
+    }
+
+    #[tokio::test]
+    async fn test_alarm_summary() {
+        let registry = alarm::AlarmRegistry::default();
+        let set_state = |check: &str, state: alarm::StateKind| {
+            registry
+                .write()
+                .unwrap()
+                .insert(alarm::registry_key(check, "Alarm", "ID"), state);
+        };
+        for check in ["A", "B", "C"] {
+            set_state(check, alarm::StateKind::Good);
+        }
+        let mut check = alarm_summary(r#""A/Alarm", "B/Alarm", "C/Alarm""#, &registry).unwrap();
+        assert_eq!(check.ids(), ["Service"]);
+        let alarm_config: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"\nmin_bad_count = 2").unwrap();
+        let mut sink = alarm::AlarmSummary::try_from(&alarm_config).unwrap();
+        set_state("B", alarm::StateKind::Bad);
+        let (decision, text) = decide(&mut check, &mut sink).await;
+        assert!(matches!(decision, SinkDecision::Good));
+        assert_eq!(text, "1 of 3 alarms bad");
+
+        // Errors do not count as bad.
+        set_state("C", alarm::StateKind::Error);
+        assert!(matches!(
+            decide(&mut check, &mut sink).await.0,
+            SinkDecision::Good
+        ));
+
+        set_state("A", alarm::StateKind::Bad);
+        let (decision, text) = decide(&mut check, &mut sink).await;
+        assert!(matches!(decision, SinkDecision::Bad));
+        assert_eq!(text, "2 of 3 alarms bad");
+    }
+
+    #[test]
+    fn test_validation() {
+        let registry = alarm::AlarmRegistry::default();
+        assert!(alarm_summary("", &registry).is_err());
+        assert!(alarm_summary(r#""A/Alarm""#, &registry).is_ok());
+    }
+}
@@ -0,0 +1,229 @@
+use super::DataSource;
+use crate::{config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait Resolver: Send + Sync {
+    async fn resolve(&self, hostname: &str) -> std::io::Result<Vec<std::net::IpAddr>>;
+}
+
+pub struct SystemResolver;
+
+#[async_trait]
+impl Resolver for SystemResolver {
+    async fn resolve(&self, hostname: &str) -> std::io::Result<Vec<std::net::IpAddr>> {
+        // The port is required by the lookup but not used.
+        Ok(tokio::net::lookup_host((hostname, 0))
+            .await?
+            .map(|x| x.ip())
+            .collect())
+    }
+}
+
+struct CacheEntry {
+    resolution: measurement::DnsResolution,
+    resolved_at: tokio::time::Instant,
+}
+
+pub struct DnsResolution<T = SystemResolver>
+where
+    T: Resolver,
+{
+    hostnames: Vec<String>,
+    // The system resolver does not expose the TTL of the records, so results are kept for this
+    // fixed duration instead.
+    min_cache_ttl: std::time::Duration,
+    cache: Vec<Option<CacheEntry>>,
+    resolver: T,
+}
+
+impl TryFrom<&config::Check> for DnsResolution {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::DnsResolution(dns_resolution) = &check.type_ {
+            if dns_resolution.hostnames.is_empty() {
+                Err(Error::invalid_config("hostnames", "cannot be empty."))
+            } else if dns_resolution.hostnames.iter().any(|x| x.is_empty()) {
+                Err(Error::invalid_config(
+                    "hostnames",
+                    "cannot contain empty hostnames.",
+                ))
+            } else {
+                Ok(Self::new(
+                    dns_resolution.hostnames.clone(),
+                    std::time::Duration::from_secs(dns_resolution.min_cache_ttl.into()),
+                    SystemResolver,
+                ))
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+impl<T> DnsResolution<T>
+where
+    T: Resolver,
+{
+    fn new(hostnames: Vec<String>, min_cache_ttl: std::time::Duration, resolver: T) -> Self {
+        Self {
+            cache: hostnames.iter().map(|_| None).collect(),
+            hostnames,
+            min_cache_ttl,
+            resolver,
+        }
+    }
+
+    // Failed lookups are reported as an empty list of addresses, so that a missing record can be
+    // told apart from a failing check. They are not cached.
+    async fn resolve(&self, hostname: &str) -> Result<measurement::DnsResolution> {
+        let start = tokio::time::Instant::now();
+        let addresses = self.resolver.resolve(hostname).await.unwrap_or_default();
+        let resolve_time = start.elapsed().as_secs_f64() * 1000.0;
+        measurement::DnsResolution::new((addresses, resolve_time))
+    }
+}
+
+#[async_trait]
+impl<T> DataSource for DnsResolution<T>
+where
+    T: Resolver,
+{
+    type Item = measurement::DnsResolution;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let mut res = Vec::new();
+        for i in 0..self.hostnames.len() {
+            if let Some(entry) = &self.cache[i] {
+                if entry.resolved_at.elapsed() < self.min_cache_ttl {
+                    res.push(Ok(Some(entry.resolution.clone())));
+                    continue;
+                }
+            }
+            let resolution = self.resolve(&self.hostnames[i]).await;
+            self.cache[i] = match &resolution {
+                Ok(resolution) if !resolution.addresses().is_empty() => Some(CacheEntry {
+                    resolution: resolution.clone(),
+                    resolved_at: tokio::time::Instant::now(),
+                }),
+                _ => None,
+            };
+            res.push(resolution.map(Some));
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        data.to_string()
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.hostnames[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alarm::{DataSink, SinkDecision};
+    use mockall::predicate::eq;
+
+    fn address(address: &str) -> std::net::IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn expected_ips_alarm() -> crate::alarm::DnsResolution {
+        let config: config::Alarm = toml::from_str(
+            "name = \"Alarm\"\naction = \"Action\"\nexpected_ips = [\"10.0.0.1\", \"10.0.0.2\"]",
+        )
+        .unwrap();
+        crate::alarm::DnsResolution::try_from(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_expected_ips() {
+        let mut resolver = MockResolver::new();
+        resolver
+            .expect_resolve()
+            .with(eq("example.com"))
+            .returning(|_| Ok(vec![address("10.0.0.2"), address("10.0.0.1")]));
+        resolver
+            .expect_resolve()
+            .with(eq("changed.example.com"))
+            .returning(|_| Ok(vec![address("10.0.0.1"), address("10.0.0.3")]));
+        resolver
+            .expect_resolve()
+            .with(eq("missing.example.com"))
+            .returning(|_| Err(std::io::Error::other("no record")));
+        let mut check = DnsResolution::new(
+            vec![
+                String::from("example.com"),
+                String::from("changed.example.com"),
+                String::from("missing.example.com"),
+            ],
+            std::time::Duration::ZERO,
+            resolver,
+        );
+        let data = check.get_data().await.unwrap();
+        let data: Vec<_> = data.into_iter().map(|x| x.unwrap().unwrap()).collect();
+        assert_eq!(
+            data[0].addresses(),
+            [address("10.0.0.1"), address("10.0.0.2")]
+        );
+        assert!(data[2].addresses().is_empty());
+
+        let mut alarm = expected_ips_alarm();
+        assert!(matches!(
+            alarm.put_data(&data[0]).unwrap(),
+            SinkDecision::Good
+        ));
+        assert!(matches!(
+            alarm.put_data(&data[1]).unwrap(),
+            SinkDecision::Bad
+        ));
+        assert!(matches!(
+            alarm.put_data(&data[2]).unwrap(),
+            SinkDecision::Bad
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cache() {
+        let mut resolver = MockResolver::new();
+        resolver
+            .expect_resolve()
+            .times(2)
+            .returning(|_| Ok(vec![address("10.0.0.1")]));
+        let mut check = DnsResolution::new(
+            vec![String::from("example.com")],
+            std::time::Duration::from_secs(60),
+            resolver,
+        );
+        check.get_data().await.unwrap();
+        tokio::time::advance(std::time::Duration::from_secs(30)).await;
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        assert_eq!(data.addresses(), [address("10.0.0.1")]);
+        tokio::time::advance(std::time::Duration::from_secs(30)).await;
+        check.get_data().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failures_not_cached() {
+        let mut resolver = MockResolver::new();
+        resolver
+            .expect_resolve()
+            .times(2)
+            .returning(|_| Ok(Vec::new()));
+        let mut check = DnsResolution::new(
+            vec![String::from("example.com")],
+            std::time::Duration::from_secs(60),
+            resolver,
+        );
+        check.get_data().await.unwrap();
+        check.get_data().await.unwrap();
+    }
+}
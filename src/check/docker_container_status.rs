@@ -20,10 +20,10 @@ impl DockerContainerStatus {
                 Some(bollard::container::InspectContainerOptions { size: false }),
             )
             .await
-            .map_err(|x| Error(format!("Docker error: {x}")))
+            .map_err(|x| Error::Message(format!("Docker error: {x}")))
             .and_then(|x| {
                 x.state
-                    .ok_or_else(|| Error(String::from("Could not read container state.")))
+                    .ok_or_else(|| Error::Message(String::from("Could not read container state.")))
             })
     }
 
@@ -34,7 +34,7 @@ impl DockerContainerStatus {
         let state = Self::container_state(docker, container).await?;
         let status = state
             .status
-            .ok_or_else(|| Error(String::from("Could not read container status.")))
+            .ok_or_else(|| Error::Message(String::from("Could not read container status.")))
             .map(|x| x == bollard::models::ContainerStateStatusEnum::RUNNING)?;
         let health = state
             .health
@@ -73,7 +73,7 @@ impl DataSource for DockerContainerStatus {
             u64::MAX,
             bollard::API_DEFAULT_VERSION,
         )
-        .map_err(|x| Error(format!("Could not create docker client: {x}")))?;
+        .map_err(|x| Error::Message(format!("Could not create docker client: {x}")))?;
         let mut res = Vec::new();
         for container in self.containers.iter() {
             res.push(
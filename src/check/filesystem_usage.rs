@@ -5,7 +5,10 @@ use async_trait::async_trait;
 use measurement::Measurement;
 
 pub struct FilesystemUsage {
+    id: Vec<String>,
     mountpoints: Vec<String>,
+    space: bool,
+    inodes: bool,
 }
 
 impl TryFrom<&config::Check> for FilesystemUsage {
@@ -14,12 +17,29 @@ impl TryFrom<&config::Check> for FilesystemUsage {
     fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
         if let config::CheckType::FilesystemUsage(filesystem_usage) = &check.type_ {
             if filesystem_usage.mountpoints.iter().any(|x| x.is_empty()) {
-                Err(Error(String::from(
-                    "'mountpoints' cannot contain empty paths.",
+                Err(Error::invalid_config(
+                    "mountpoints",
+                    "cannot contain empty paths.",
+                ))
+            } else if !filesystem_usage.space && !filesystem_usage.inodes {
+                Err(Error::Message(String::from(
+                    "Either 'space' or 'inodes' or both need to be enabled.",
                 )))
             } else {
+                let mut id = Vec::new();
+                for mountpoint in filesystem_usage.mountpoints.iter() {
+                    if filesystem_usage.space {
+                        id.push(mountpoint.clone());
+                    }
+                    if filesystem_usage.inodes {
+                        id.push(format!("{mountpoint}[inodes]"));
+                    }
+                }
                 Ok(Self {
+                    id,
                     mountpoints: filesystem_usage.mountpoints.clone(),
+                    space: filesystem_usage.space,
+                    inodes: filesystem_usage.inodes,
                 })
             }
         } else {
@@ -28,6 +48,16 @@ impl TryFrom<&config::Check> for FilesystemUsage {
     }
 }
 
+// Percentage of the blocks (inodes) that are not available. Filesystems without any blocks (e.g.
+// pseudo filesystems like `/proc`) or inodes (e.g. some tmpfs variants) cannot have a usage.
+fn usage(total: u64, available: u64, unit: &str) -> Result<measurement::Level> {
+    if total == 0 {
+        return Err(Error::Message(format!("Filesystem has no {unit}.")));
+    }
+    let used = total.saturating_sub(available);
+    measurement::Level::new((used * 100 / total) as u8)
+}
+
 #[async_trait]
 impl DataSource for FilesystemUsage {
     type Item = measurement::Level;
@@ -35,13 +65,20 @@ impl DataSource for FilesystemUsage {
     async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
         let mut res = Vec::new();
         for mountpoint in self.mountpoints.iter() {
-            res.push(match nix::sys::statvfs::statvfs(mountpoint.as_str()) {
-                Err(err) => Err(Error(format!("Call to 'statvfs' failed: {err}"))),
-                Ok(stat) => {
-                    let usage = (stat.blocks() - stat.blocks_available()) * 100 / stat.blocks();
-                    Self::Item::new(usage as u8).map(Some)
-                }
-            })
+            let stat = nix::sys::statvfs::statvfs(mountpoint.as_str())
+                .map_err(|err| Error::Message(format!("Call to 'statvfs' failed: {err}")));
+            if self.space {
+                res.push(stat.clone().and_then(|stat| {
+                    usage(stat.blocks(), stat.blocks_available(), "blocks").map(Some)
+                }));
+            }
+            if self.inodes {
+                res.push(
+                    stat.clone().and_then(|stat| {
+                        usage(stat.files(), stat.files_free(), "inodes").map(Some)
+                    }),
+                );
+            }
         }
         Ok(res)
     }
@@ -51,6 +88,44 @@ impl DataSource for FilesystemUsage {
     }
 
     fn ids(&self) -> &[String] {
-        &self.mountpoints[..]
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_usage() {
+        assert_eq!(usage(200, 50, "blocks").unwrap().data(), 75);
+        assert_eq!(usage(200, 0, "blocks").unwrap().data(), 100);
+        assert_eq!(usage(200, 200, "blocks").unwrap().data(), 0);
+        assert_eq!(usage(3, 2, "blocks").unwrap().data(), 33);
+        assert!(usage(0, 0, "blocks").is_err());
+        assert!(matches!(
+            usage(0, 0, "inodes"),
+            Err(Error::Message(x)) if x == "Filesystem has no inodes."
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_ids() {
+        let check = |options: &str| {
+            let config: config::Check = toml::from_str(&format!(
+                "name = \"Check\"\ntype = \"FilesystemUsage\"\nmountpoints = [\"/\", \"/tmp\"]\n{options}"
+            ))
+            .unwrap();
+            FilesystemUsage::try_from(&config)
+        };
+        assert_eq!(check("").unwrap().ids(), ["/", "/tmp"]);
+        let mut both = check("inodes = true").unwrap();
+        assert_eq!(both.ids(), ["/", "/[inodes]", "/tmp", "/tmp[inodes]"]);
+        assert_eq!(both.get_data().await.unwrap().len(), 4);
+        assert_eq!(
+            check("space = false\ninodes = true").unwrap().ids(),
+            ["/[inodes]", "/tmp[inodes]"]
+        );
+        assert!(check("space = false").is_err());
     }
 }
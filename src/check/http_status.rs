@@ -0,0 +1,265 @@
+use super::DataSource;
+use crate::{config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+const BODY_SNIPPET_LENGTH: usize = 256;
+
+pub struct HttpStatus {
+    id: Vec<String>,
+    method: reqwest::Method,
+    headers: reqwest::header::HeaderMap,
+    body: String,
+    client: reqwest::Client,
+}
+
+impl TryFrom<&config::Check> for HttpStatus {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::HttpStatus(http_status) = &check.type_ {
+            if http_status.url.is_empty() {
+                return Err(Error::invalid_config("url", "cannot be empty."));
+            }
+            let mut headers = http_status.headers.clone();
+            if !headers.contains_key("User-Agent") {
+                headers.insert(String::from("User-Agent"), crate::user_agent());
+            }
+            let headers = headers
+                .iter()
+                .map(|(k, v)| {
+                    let name = reqwest::header::HeaderName::try_from(k)
+                        .map_err(|x| Error::Message(format!("Could not parse header name: {x}")))?;
+                    let value = reqwest::header::HeaderValue::try_from(v).map_err(|x| {
+                        Error::Message(format!("Could not parse header value: {x}"))
+                    })?;
+                    Ok((name, value))
+                })
+                .collect::<Result<_>>()?;
+            let redirect = match http_status.max_redirects {
+                0 => reqwest::redirect::Policy::none(),
+                max_redirects => reqwest::redirect::Policy::limited(max_redirects as usize),
+            };
+            let client = reqwest::Client::builder()
+                .redirect(redirect)
+                .build()
+                .map_err(|x| Error::Message(format!("Could not create HTTP client: {x}")))?;
+            Ok(Self {
+                id: vec![http_status.url.clone()],
+                method: reqwest::Method::from(http_status.method),
+                headers,
+                body: http_status.body.clone(),
+                client,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+// The first bytes of the body. Invalid UTF-8 (e.g. a character cut in half) is replaced.
+fn body_snippet(body: &[u8]) -> String {
+    String::from_utf8_lossy(&body[..body.len().min(BODY_SNIPPET_LENGTH)]).into_owned()
+}
+
+#[async_trait]
+impl DataSource for HttpStatus {
+    type Item = measurement::HttpResponse;
+
+    // The response time is measured until the headers of the response are received.
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let start = tokio::time::Instant::now();
+        let mut response = self
+            .client
+            .request(self.method.clone(), &self.id[0])
+            .headers(self.headers.clone())
+            .body(self.body.clone())
+            .send()
+            .await
+            .map_err(|x| Error::Message(format!("HTTP request failed: {x}")))?;
+        let response_time = start.elapsed().as_secs_f64() * 1000.0;
+        let status_code = response.status().as_u16();
+        let mut body = Vec::new();
+        while body.len() < BODY_SNIPPET_LENGTH {
+            match response
+                .chunk()
+                .await
+                .map_err(|x| Error::Message(format!("Could not read HTTP response: {x}")))?
+            {
+                Some(chunk) => body.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+        Ok(vec![Self::Item::new((
+            status_code,
+            response_time,
+            body_snippet(&body),
+        ))
+        .map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        data.to_string()
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alarm::{DataSink, SinkDecision};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Answers every request with the given status line and body.
+    async fn serve(status: &'static str, body: &'static str) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request).await.unwrap();
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{address}/")
+    }
+
+    fn http_status(url: String) -> HttpStatus {
+        let config: config::Check = toml::from_str(&format!(
+            "name = \"Check\"\ntype = \"HttpStatus\"\nurl = \"{url}\""
+        ))
+        .unwrap();
+        HttpStatus::try_from(&config).unwrap()
+    }
+
+    fn status_alarm() -> crate::alarm::HttpStatus {
+        let config: config::Alarm =
+            toml::from_str("name = \"Alarm\"\naction = \"Action\"").unwrap();
+        crate::alarm::HttpStatus::try_from(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let mut check = http_status(serve("200 OK", "Hello").await);
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        assert_eq!(data.status_code(), 200);
+        assert_eq!(data.body_snippet(), "Hello");
+        assert!(data.response_time() >= 0.0);
+        assert!(matches!(
+            status_alarm().put_data(&data).unwrap(),
+            SinkDecision::Good
+        ));
+
+        let mut check = http_status(serve("500 Internal Server Error", "").await);
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        assert_eq!(data.status_code(), 500);
+        assert!(matches!(
+            status_alarm().put_data(&data).unwrap(),
+            SinkDecision::Bad
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        drop(listener);
+        assert!(http_status(url).get_data().await.is_err());
+    }
+
+    // One request per cycle feeds the alarms on the status code, the response time and the body.
+    #[tokio::test]
+    async fn test_alarm_fan_out() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = requests.clone();
+        tokio::spawn(async move {
+            // Only the first request gets an answer, the next one is refused.
+            let (mut stream, _) = listener.accept().await.unwrap();
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).await.unwrap();
+            let body = "Down for maintenance";
+            let response = format!(
+                "HTTP/1.1 503 Service Unavailable\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+        let check_config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Check"
+            type = "HttpStatus"
+            url = "http://{address}/"
+            [[alarms]]
+            name = "Status"
+            action = "Status"
+            error_action = "StatusError"
+            [[alarms]]
+            name = "Latency"
+            action = "Latency"
+            error_action = "LatencyError"
+            level = 60000
+            [[alarms]]
+            name = "Body"
+            action = "Body"
+            error_action = "BodyError"
+            pattern = "maintenance"
+            "#
+        ))
+        .unwrap();
+        let names = ["Status", "Latency", "Body"];
+        let actions: Vec<_> = names
+            .iter()
+            .flat_map(|name| [String::from(*name), format!("{name}Error")])
+            .map(|name| (name, crate::action::TestAction::new()))
+            .collect();
+        let action_map = crate::ActionMap::from_iter(actions.iter().map(|(name, action)| {
+            (
+                name.clone(),
+                std::sync::Arc::new(action.clone()) as std::sync::Arc<dyn crate::action::Action>,
+            )
+        }));
+        let mut check = crate::check::from_check_config(
+            &check_config,
+            &action_map,
+            &crate::AlarmGroupMap::new(),
+            &crate::alarm::AlarmRegistry::default(),
+        )
+        .unwrap();
+        crate::uptime::init().unwrap();
+        let action = |name: &str| &actions.iter().find(|x| x.0 == name).unwrap().1;
+
+        check.trigger().await;
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+        action("Status").assert_triggered_once();
+        action("Body").assert_triggered_once();
+        action("Latency").assert_not_triggered();
+        assert_eq!(
+            action("Body").last_placeholders().unwrap()["body_snippet"],
+            "Down for maintenance"
+        );
+
+        // An error of the request reaches all alarms.
+        check.trigger().await;
+        for name in names {
+            action(&format!("{name}Error")).assert_triggered_once();
+        }
+    }
+
+    #[test]
+    fn test_body_snippet() {
+        assert_eq!(body_snippet(b"short"), "short");
+        assert_eq!(body_snippet(&[b'a'; 300]).len(), BODY_SNIPPET_LENGTH);
+    }
+}
@@ -0,0 +1,233 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Numeric MBean attributes read with the Jolokia JMX-HTTP bridge.
+pub struct Jmx {
+    url: String,
+    attributes: Vec<String>,
+    username: Option<String>,
+    password: Option<String>,
+    client: reqwest::Client,
+}
+
+impl TryFrom<&config::Check> for Jmx {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::Jmx(jmx) = &check.type_ {
+            if jmx.url.is_empty() {
+                return Err(Error::invalid_config("url", "cannot be empty."));
+            }
+            if jmx.attributes.is_empty() {
+                return Err(Error::invalid_config("attributes", "cannot be empty."));
+            }
+            if let Some(attribute) = jmx.attributes.iter().find(|x| !x.contains(':')) {
+                return Err(Error::Message(format!(
+                    "'{attribute}' is not of the form '<domain>:<key>=<value>/<attribute>'."
+                )));
+            }
+            if jmx.password.is_some() && jmx.username.is_none() {
+                return Err(Error::invalid_config(
+                    "password",
+                    "cannot be set without 'username'.",
+                ));
+            }
+            let client = reqwest::Client::builder()
+                .user_agent(crate::user_agent())
+                .build()
+                .map_err(|x| Error::Message(format!("Could not create HTTP client: {x}")))?;
+            Ok(Self {
+                url: String::from(jmx.url.trim_end_matches('/')),
+                attributes: jmx.attributes.clone(),
+                username: jmx.username.clone(),
+                password: jmx.password.clone(),
+                client,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+// Jolokia reports errors (e.g. unknown MBeans or attributes) with a status in the JSON body.
+fn parse_response(response: &serde_json::Value) -> Result<f64> {
+    match response["status"].as_u64() {
+        Some(200) => {}
+        Some(status) => {
+            return Err(Error::Message(format!(
+                "Jolokia error (status {status}): {}",
+                response["error"].as_str().unwrap_or("unknown error")
+            )))
+        }
+        None => {
+            return Err(Error::Message(String::from(
+                "Jolokia response has no status.",
+            )))
+        }
+    }
+    match &response["value"] {
+        serde_json::Value::Number(number) => number.as_f64().ok_or_else(|| {
+            Error::Message(format!("Value {number} cannot be represented as a float."))
+        }),
+        serde_json::Value::Null => Err(Error::Message(String::from(
+            "Jolokia response has no value.",
+        ))),
+        value => Err(Error::Message(format!(
+            "Value {value} is not a number. Use a path to select a numeric value."
+        ))),
+    }
+}
+
+impl Jmx {
+    async fn read(&self, attribute: &str) -> Result<f64> {
+        let mut request = self.client.get(format!("{}/read/{attribute}", self.url));
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_ref());
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|x| Error::Message(format!("Jolokia request failed: {x}")))?
+            .error_for_status()
+            .map_err(|x| Error::Message(format!("Jolokia request failed: {x}")))?
+            .text()
+            .await
+            .map_err(|x| Error::Message(format!("Could not read Jolokia response: {x}")))?;
+        let response = serde_json::from_str(&response)
+            .map_err(|x| Error::Message(format!("Could not parse Jolokia response: {x}")))?;
+        parse_response(&response)
+    }
+}
+
+#[async_trait]
+impl DataSource for Jmx {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let mut res = Vec::new();
+        for attribute in self.attributes.iter() {
+            res.push(self.read(attribute).await.map(Some));
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("value {data:.2}")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.attributes[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Answers like Jolokia for the heap memory usage. Unknown attributes are errors and requests
+    // without the expected basic auth header are rejected.
+    async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let length = stream.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..length]).to_string();
+                // Basic auth with "user:secret".
+                let (status, body) = if !request
+                    .lines()
+                    .any(|x| x.eq_ignore_ascii_case("Authorization: Basic dXNlcjpzZWNyZXQ="))
+                {
+                    ("401 Unauthorized", String::new())
+                } else if request
+                    .starts_with("GET /jolokia/read/java.lang:type=Memory/HeapMemoryUsage/used ")
+                {
+                    (
+                        "200 OK",
+                        String::from(
+                            r#"{"request":{"mbean":"java.lang:type=Memory","path":"used","attribute":"HeapMemoryUsage","type":"read"},"value":47948568,"timestamp":1700000000,"status":200}"#,
+                        ),
+                    )
+                } else if request
+                    .starts_with("GET /jolokia/read/java.lang:type=Memory/HeapMemoryUsage ")
+                {
+                    (
+                        "200 OK",
+                        String::from(
+                            r#"{"request":{"mbean":"java.lang:type=Memory","attribute":"HeapMemoryUsage","type":"read"},"value":{"init":264241152,"committed":251658240,"max":4164943872,"used":47948568},"timestamp":1700000000,"status":200}"#,
+                        ),
+                    )
+                } else {
+                    (
+                        "200 OK",
+                        String::from(
+                            r#"{"request":{"mbean":"java.lang:type=Memory","attribute":"Missing","type":"read"},"error_type":"javax.management.AttributeNotFoundException","error":"javax.management.AttributeNotFoundException : No such attribute: Missing","status":404}"#,
+                        ),
+                    )
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{address}/jolokia/")
+    }
+
+    fn jmx(url: &str, credentials: &str) -> Jmx {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Check"
+            type = "Jmx"
+            url = "{url}"
+            attributes = [
+                "java.lang:type=Memory/HeapMemoryUsage/used",
+                "java.lang:type=Memory/HeapMemoryUsage",
+                "java.lang:type=Memory/Missing",
+            ]
+            {credentials}
+            "#
+        ))
+        .unwrap();
+        Jmx::try_from(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_jmx() {
+        let url = serve().await;
+        let mut check = jmx(&url, "username = \"user\"\npassword = \"secret\"");
+        let data = check.get_data().await.unwrap();
+        assert_eq!(*data[0].as_ref().unwrap(), Some(47948568.0));
+        // Objects need a path to select a numeric value.
+        assert!(data[1].is_err());
+        assert!(data[2]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("No such attribute: Missing"));
+
+        let mut check = jmx(&url, "");
+        let data = check.get_data().await.unwrap();
+        assert!(data[0].as_ref().unwrap_err().to_string().contains("401"));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response = serde_json::json!({"value": 1.5, "status": 200});
+        assert_eq!(parse_response(&response).unwrap(), 1.5);
+        let response = serde_json::json!({"value": "foo", "status": 200});
+        assert!(parse_response(&response).is_err());
+        let response = serde_json::json!({"error": "Not found", "status": 404});
+        assert_eq!(
+            parse_response(&response).unwrap_err().to_string(),
+            "Jolokia error (status 404): Not found"
+        );
+        assert!(parse_response(&serde_json::json!({})).is_err());
+    }
+}
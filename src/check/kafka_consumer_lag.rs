@@ -0,0 +1,378 @@
+use super::DataSource;
+use crate::{config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+use rdkafka::consumer::Consumer;
+
+// Committed offset of partitions the group has not committed to yet.
+const NO_OFFSET: i64 = -1;
+
+// The requests of the check to the cluster. They block until the brokers answer or the timeout
+// expires.
+#[cfg_attr(test, mockall::automock)]
+trait Cluster: Send + Sync {
+    fn partitions(&self, topic: &str) -> Result<Vec<i32>>;
+
+    // Committed offset of the group by partition, `NO_OFFSET` if there is none.
+    fn committed_offsets(&self, topic: &str, partitions: &[i32]) -> Result<Vec<(i32, i64)>>;
+
+    // Offset of the next message of the partition.
+    fn high_watermark(&self, topic: &str, partition: i32) -> Result<i64>;
+}
+
+fn kafka_error(error: rdkafka::error::KafkaError) -> Error {
+    Error::Message(format!("Kafka request failed: {error}"))
+}
+
+// A consumer with the group ID only to read the committed offsets of the group. It never
+// subscribes to the topic, so it does not join the group and cause a rebalance.
+struct Client {
+    consumer: rdkafka::consumer::BaseConsumer,
+    timeout: std::time::Duration,
+}
+
+impl Client {
+    fn new(kafka: &KafkaConfig) -> Result<Self> {
+        let mut config = rdkafka::ClientConfig::new();
+        config
+            .set("bootstrap.servers", kafka.brokers.join(","))
+            .set("group.id", &kafka.group)
+            .set("client.id", "minmon")
+            .set("enable.auto.commit", "false");
+        if let Some((username, password)) = &kafka.credentials {
+            config
+                .set("security.protocol", "SASL_PLAINTEXT")
+                .set("sasl.mechanism", "PLAIN")
+                .set("sasl.username", username)
+                .set("sasl.password", password);
+        }
+        Ok(Self {
+            consumer: config.create().map_err(kafka_error)?,
+            timeout: kafka.timeout,
+        })
+    }
+}
+
+impl Cluster for Client {
+    fn partitions(&self, topic: &str) -> Result<Vec<i32>> {
+        let metadata = self
+            .consumer
+            .fetch_metadata(Some(topic), self.timeout)
+            .map_err(kafka_error)?;
+        let topic_metadata = metadata
+            .topics()
+            .iter()
+            .find(|x| x.name() == topic)
+            .ok_or_else(|| {
+                Error::Message(format!("Kafka returned no metadata for topic '{topic}'."))
+            })?;
+        if let Some(error) = topic_metadata.error() {
+            return Err(Error::Message(format!(
+                "Kafka error for topic '{topic}': {}",
+                rdkafka::error::RDKafkaErrorCode::from(error)
+            )));
+        }
+        Ok(topic_metadata.partitions().iter().map(|x| x.id()).collect())
+    }
+
+    fn committed_offsets(&self, topic: &str, partitions: &[i32]) -> Result<Vec<(i32, i64)>> {
+        let mut list = rdkafka::TopicPartitionList::new();
+        for partition in partitions {
+            list.add_partition(topic, *partition);
+        }
+        let list = self
+            .consumer
+            .committed_offsets(list, self.timeout)
+            .map_err(kafka_error)?;
+        list.elements_for_topic(topic)
+            .iter()
+            .map(|x| {
+                x.error().map_err(kafka_error)?;
+                let committed = match x.offset() {
+                    rdkafka::Offset::Offset(offset) => offset,
+                    _ => NO_OFFSET,
+                };
+                Ok((x.partition(), committed))
+            })
+            .collect()
+    }
+
+    fn high_watermark(&self, topic: &str, partition: i32) -> Result<i64> {
+        self.consumer
+            .fetch_watermarks(topic, partition, self.timeout)
+            .map(|(_low, high)| high)
+            .map_err(kafka_error)
+    }
+}
+
+struct PartitionOffsets {
+    partition: i32,
+    committed: i64,
+    high_watermark: i64,
+}
+
+fn partition_offsets(cluster: &dyn Cluster, topic: &str) -> Result<Vec<PartitionOffsets>> {
+    let partitions = cluster.partitions(topic)?;
+    if partitions.is_empty() {
+        return Err(Error::Message(format!(
+            "Topic '{topic}' has no partitions."
+        )));
+    }
+    let committed: std::collections::HashMap<i32, i64> = cluster
+        .committed_offsets(topic, &partitions)?
+        .into_iter()
+        .collect();
+    partitions
+        .into_iter()
+        .map(|partition| {
+            Ok(PartitionOffsets {
+                partition,
+                committed: committed.get(&partition).copied().unwrap_or(NO_OFFSET),
+                high_watermark: cluster.high_watermark(topic, partition)?,
+            })
+        })
+        .collect()
+}
+
+// Partitions without a committed offset are left out, like the consumer group tool of Kafka does.
+// An offset committed after the high watermark was read counts as no lag.
+fn consumer_lag(offsets: &[PartitionOffsets]) -> Result<measurement::ConsumerLag> {
+    let partitions: Vec<(i32, u64)> = offsets
+        .iter()
+        .filter(|x| x.committed != NO_OFFSET)
+        .map(|x| {
+            let lag = x.high_watermark.saturating_sub(x.committed).max(0);
+            (x.partition, lag as u64)
+        })
+        .collect();
+    if partitions.is_empty() {
+        Err(Error::Message(String::from(
+            "The group has no committed offsets for the topic.",
+        )))
+    } else {
+        measurement::ConsumerLag::new(partitions)
+    }
+}
+
+// The options of the check needed to create the client on the first cycle, so that parsing the
+// config doesn't start the threads of librdkafka.
+struct KafkaConfig {
+    brokers: Vec<String>,
+    group: String,
+    // Username and password for SASL/PLAIN.
+    credentials: Option<(String, String)>,
+    // Of each request to the brokers.
+    timeout: std::time::Duration,
+}
+
+// Sum of the lag of a consumer group over all partitions of a topic.
+pub struct KafkaConsumerLag {
+    id: Vec<String>,
+    topic: String,
+    config: KafkaConfig,
+    client: Option<std::sync::Arc<Client>>,
+}
+
+impl TryFrom<&config::Check> for KafkaConsumerLag {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::KafkaConsumerLag(kafka) = &check.type_ {
+            if kafka.brokers.is_empty() {
+                Err(Error::invalid_config("brokers", "cannot be empty."))
+            } else if kafka.brokers.iter().any(|x| x.is_empty()) {
+                Err(Error::invalid_config(
+                    "brokers",
+                    "cannot contain empty addresses.",
+                ))
+            } else if kafka.group.is_empty() {
+                Err(Error::invalid_config("group", "cannot be empty."))
+            } else if kafka.topic.is_empty() {
+                Err(Error::invalid_config("topic", "cannot be empty."))
+            } else {
+                let credentials = match (&kafka.username, &kafka.password) {
+                    (Some(username), Some(password)) => Some((username.clone(), password.clone())),
+                    (None, None) => None,
+                    (Some(_), None) => {
+                        return Err(Error::invalid_config(
+                            "username",
+                            "cannot be set without 'password'.",
+                        ))
+                    }
+                    (None, Some(_)) => {
+                        return Err(Error::invalid_config(
+                            "password",
+                            "cannot be set without 'username'.",
+                        ))
+                    }
+                };
+                let timeout = check.timeout.unwrap_or_else(config::default::check_timeout);
+                Ok(Self {
+                    id: vec![format!("{}[{}]", kafka.topic, kafka.group)],
+                    topic: kafka.topic.clone(),
+                    config: KafkaConfig {
+                        brokers: kafka.brokers.clone(),
+                        group: kafka.group.clone(),
+                        credentials,
+                        timeout: std::time::Duration::from_secs(timeout.into()),
+                    },
+                    client: None,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for KafkaConsumerLag {
+    type Item = measurement::ConsumerLag;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let client = match &self.client {
+            Some(client) => client.clone(),
+            None => match Client::new(&self.config) {
+                Ok(client) => self.client.insert(std::sync::Arc::new(client)).clone(),
+                Err(err) => return Ok(vec![Err(err)]),
+            },
+        };
+        let topic = self.topic.clone();
+        // librdkafka blocks the thread while it waits for the brokers.
+        let lag = tokio::task::spawn_blocking(move || {
+            consumer_lag(&partition_offsets(client.as_ref(), &topic)?)
+        })
+        .await
+        .map_err(|x| Error::Message(format!("Kafka request failed: {x}")))?;
+        Ok(vec![lag.map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("consumer {data}")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use mockall::predicate::eq;
+
+    // Committed offset and high watermark of the partitions of the topic "orders".
+    const OFFSETS: [(i64, i64); 3] = [(90, 100), (NO_OFFSET, 40), (250, 300)];
+
+    #[test]
+    fn test_partition_offsets() {
+        let mut cluster = MockCluster::new();
+        cluster
+            .expect_partitions()
+            .with(eq("orders"))
+            .returning(|_| Ok(vec![0, 1, 2]));
+        // Partitions without a committed offset can be missing in the response.
+        cluster
+            .expect_committed_offsets()
+            .withf(|topic, partitions| topic == "orders" && partitions == [0, 1, 2])
+            .returning(|_, _| Ok(vec![(2, OFFSETS[2].0), (0, OFFSETS[0].0)]));
+        cluster
+            .expect_high_watermark()
+            .returning(|_, partition| Ok(OFFSETS[partition as usize].1));
+        let lag = consumer_lag(&partition_offsets(&cluster, "orders").unwrap()).unwrap();
+        assert_eq!(lag.data(), [(0, 10), (2, 50)]);
+        assert_eq!(lag.total(), 60);
+
+        let mut cluster = MockCluster::new();
+        cluster.expect_partitions().returning(|_| Ok(Vec::new()));
+        assert_eq!(
+            partition_offsets(&cluster, "orders")
+                .err()
+                .unwrap()
+                .to_string(),
+            "Topic 'orders' has no partitions."
+        );
+
+        let mut cluster = MockCluster::new();
+        cluster.expect_partitions().returning(|_| Ok(vec![0]));
+        cluster
+            .expect_committed_offsets()
+            .returning(|_, _| Ok(vec![(0, 5)]));
+        cluster
+            .expect_high_watermark()
+            .returning(|_, _| Err(Error::Message(String::from("Leader not available"))));
+        assert!(partition_offsets(&cluster, "orders").is_err());
+    }
+
+    #[test]
+    fn test_consumer_lag() {
+        let lag = |offsets: &[(i64, i64)]| {
+            let offsets: Vec<PartitionOffsets> = offsets
+                .iter()
+                .enumerate()
+                .map(
+                    |(partition, (committed, high_watermark))| PartitionOffsets {
+                        partition: partition as i32,
+                        committed: *committed,
+                        high_watermark: *high_watermark,
+                    },
+                )
+                .collect();
+            consumer_lag(&offsets).map(|x| (x.total(), x.data()))
+        };
+        assert_eq!(lag(&OFFSETS).unwrap(), (60, vec![(0, 10), (2, 50)]));
+        // Committed after the high watermark was read.
+        assert_eq!(
+            lag(&[(110, 100), (5, 5)]).unwrap(),
+            (0, vec![(0, 0), (1, 0)])
+        );
+        assert!(lag(&[(NO_OFFSET, 100)]).is_err());
+    }
+
+    fn kafka(options: &str) -> Result<KafkaConsumerLag> {
+        let config: config::Check = toml::from_str(&format!(
+            "name = \"Check\"\ntype = \"KafkaConsumerLag\"\n{options}"
+        ))
+        .unwrap();
+        KafkaConsumerLag::try_from(&config)
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_broker() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        let mut check = kafka(&format!(
+            "brokers = [\"{closed}\"]\ngroup = \"billing\"\ntopic = \"orders\"\ntimeout = 1"
+        ))
+        .unwrap();
+        assert!(check.get_data().await.unwrap().remove(0).is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        let valid = "group = \"billing\"\ntopic = \"orders\"";
+        assert!(kafka(&format!("brokers = [\"localhost:9092\"]\n{valid}")).is_ok());
+        assert!(kafka(&format!("brokers = []\n{valid}")).is_err());
+        assert!(kafka(&format!("brokers = [\"\"]\n{valid}")).is_err());
+        assert!(kafka("brokers = [\"localhost:9092\"]\ngroup = \"\"\ntopic = \"orders\"").is_err());
+        assert!(
+            kafka("brokers = [\"localhost:9092\"]\ngroup = \"billing\"\ntopic = \"\"").is_err()
+        );
+        assert!(kafka(&format!(
+            "brokers = [\"localhost:9092\"]\n{valid}\nusername = \"minmon\""
+        ))
+        .is_err());
+        assert!(kafka(&format!(
+            "brokers = [\"localhost:9092\"]\n{valid}\npassword = \"secret\""
+        ))
+        .is_err());
+        let check = kafka(&format!(
+            "brokers = [\"localhost:9092\"]\n{valid}\ntimeout = 3"
+        ))
+        .unwrap();
+        assert_eq!(check.ids(), ["orders[billing]"]);
+        assert_eq!(check.config.timeout, std::time::Duration::from_secs(3));
+    }
+}
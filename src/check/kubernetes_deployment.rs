@@ -0,0 +1,137 @@
+use super::kubernetes_pod::Client;
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Ratio of the available to the desired replicas of deployments.
+pub struct KubernetesDeployment {
+    deployments: Vec<String>,
+    client: Client,
+}
+
+impl TryFrom<&config::Check> for KubernetesDeployment {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::KubernetesDeployment(kubernetes_deployment) = &check.type_ {
+            if kubernetes_deployment.deployments.is_empty() {
+                Err(Error::invalid_config("deployments", "cannot be empty."))
+            } else if kubernetes_deployment
+                .deployments
+                .iter()
+                .any(|x| x.is_empty())
+            {
+                Err(Error::Message(String::from(
+                    "Deployment names cannot be empty.",
+                )))
+            } else {
+                Ok(Self {
+                    deployments: kubernetes_deployment.deployments.clone(),
+                    client: Client::new(
+                        &kubernetes_deployment.kubeconfig,
+                        &kubernetes_deployment.namespace,
+                    )?,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+// A deployment scaled to 0 has nothing that could be unavailable.
+fn available_ratio(deployment: &serde_json::Value) -> f64 {
+    // Kubernetes defaults to one replica and leaves out the available replicas if there are none.
+    let desired = deployment["spec"]["replicas"].as_u64().unwrap_or(1);
+    let available = deployment["status"]["availableReplicas"]
+        .as_u64()
+        .unwrap_or(0);
+    if desired == 0 {
+        1.0
+    } else {
+        available as f64 / desired as f64
+    }
+}
+
+#[async_trait]
+impl DataSource for KubernetesDeployment {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let mut res = Vec::new();
+        for deployment in self.deployments.iter() {
+            res.push(
+                self.client
+                    .get("apis/apps/v1", &format!("deployments/{deployment}"), &[])
+                    .await
+                    .map(|x| Some(available_ratio(&x))),
+            );
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("available ratio {data:.2}")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.deployments[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::kubernetes_pod::test::{serve, write_kubeconfig};
+    use super::*;
+
+    fn kubernetes_deployment(kubeconfig: &str, deployments: &str) -> Result<KubernetesDeployment> {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Check"
+            type = "KubernetesDeployment"
+            kubeconfig = "{kubeconfig}"
+            namespace = "apps"
+            deployments = [{deployments}]
+            "#
+        ))
+        .unwrap();
+        KubernetesDeployment::try_from(&config)
+    }
+
+    #[tokio::test]
+    async fn test_kubernetes_deployment() {
+        let kubeconfig = write_kubeconfig("deployment", &serve().await, None);
+        let mut check =
+            kubernetes_deployment(&kubeconfig, r#""web", "down", "idle", "missing""#).unwrap();
+        assert_eq!(check.ids(), ["web", "down", "idle", "missing"]);
+        let data = check.get_data().await.unwrap();
+        assert_eq!(data[0].as_ref().unwrap().unwrap(), 0.75);
+        assert_eq!(data[1].as_ref().unwrap().unwrap(), 0.0);
+        assert_eq!(data[2].as_ref().unwrap().unwrap(), 1.0);
+        assert_eq!(
+            data[3].as_ref().unwrap_err().to_string(),
+            "Kubernetes API request failed: not found"
+        );
+        assert_eq!(check.format_data(&0.75), "available ratio 0.75");
+        std::fs::remove_file(kubeconfig).unwrap();
+    }
+
+    #[test]
+    fn test_available_ratio() {
+        assert_eq!(available_ratio(&serde_json::json!({})), 0.0);
+        assert_eq!(
+            available_ratio(&serde_json::json!({"status": {"availableReplicas": 1}})),
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let kubeconfig = write_kubeconfig("deployment-validation", "http://127.0.0.1:1", None);
+        assert!(kubernetes_deployment(&kubeconfig, "").is_err());
+        assert!(kubernetes_deployment(&kubeconfig, r#""""#).is_err());
+        assert!(kubernetes_deployment(&kubeconfig, r#""web""#).is_ok());
+        std::fs::remove_file(kubeconfig).unwrap();
+    }
+}
@@ -0,0 +1,602 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use base64::Engine;
+
+// Credentials that Kubernetes mounts into every pod.
+const SERVICE_ACCOUNT_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount";
+
+enum Token {
+    None,
+    Static(String),
+    // Service account tokens are rotated by the kubelet, so the file is read for every request.
+    File(std::path::PathBuf),
+}
+
+struct Credentials {
+    server: String,
+    namespace: Option<String>,
+    token: Token,
+    certificate_authority: Option<Vec<u8>>,
+}
+
+fn read(path: &std::path::Path) -> Result<String> {
+    std::fs::read_to_string(path)
+        .map_err(|x| Error::Message(format!("Could not read '{}': {x}", path.display())))
+}
+
+fn in_cluster_credentials(host: &str) -> Result<Credentials> {
+    let path = std::path::Path::new(SERVICE_ACCOUNT_PATH);
+    let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| String::from("443"));
+    let host = if host.contains(':') {
+        format!("[{host}]")
+    } else {
+        String::from(host)
+    };
+    Ok(Credentials {
+        server: format!("https://{host}:{port}"),
+        namespace: Some(String::from(read(&path.join("namespace"))?.trim())),
+        token: Token::File(path.join("token")),
+        certificate_authority: Some(read(&path.join("ca.crt"))?.into_bytes()),
+    })
+}
+
+// Like kubectl, the KUBECONFIG environment variable takes precedence over ~/.kube/config. Only the
+// first file of the variable is used, they are not merged.
+fn default_kubeconfig() -> Result<std::path::PathBuf> {
+    if let Some(kubeconfig) = std::env::var_os("KUBECONFIG") {
+        if let Some(path) = std::env::split_paths(&kubeconfig).next() {
+            return Ok(path);
+        }
+    }
+    std::env::var_os("HOME")
+        .map(|x| std::path::Path::new(&x).join(".kube").join("config"))
+        .ok_or_else(|| Error::Message(String::from("Could not find a kubeconfig.")))
+}
+
+// Uses the current context of the kubeconfig, which is YAML (or JSON) like in kubectl.
+fn kubeconfig_credentials(path: &std::path::Path) -> Result<Credentials> {
+    let kubeconfig: serde_json::Value = serde_yaml::from_str(&read(path)?).map_err(|x| {
+        Error::Message(format!(
+            "Could not parse kubeconfig '{}': {x}",
+            path.display()
+        ))
+    })?;
+    let named = |list: &str, name: &str| {
+        kubeconfig[list]
+            .as_array()
+            .and_then(|x| x.iter().find(|x| x["name"] == name))
+            .ok_or_else(|| {
+                Error::Message(format!("'{name}' not found in '{list}' of the kubeconfig."))
+            })
+    };
+    let context_name = kubeconfig["current-context"]
+        .as_str()
+        .ok_or_else(|| Error::Message(String::from("The kubeconfig has no current context.")))?;
+    let context = &named("contexts", context_name)?["context"];
+    let cluster = &named("clusters", context["cluster"].as_str().unwrap_or_default())?["cluster"];
+    let server = cluster["server"]
+        .as_str()
+        .ok_or_else(|| Error::Message(String::from("The kubeconfig cluster has no server.")))?;
+    let certificate_authority = match (
+        cluster["certificate-authority-data"].as_str(),
+        cluster["certificate-authority"].as_str(),
+    ) {
+        (Some(data), _) => Some(
+            base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|x| {
+                    Error::Message(format!("Could not decode certificate authority: {x}"))
+                })?,
+        ),
+        // Relative paths are relative to the kubeconfig, like in kubectl.
+        (None, Some(file)) => {
+            Some(read(&path.parent().unwrap_or(std::path::Path::new("")).join(file))?.into_bytes())
+        }
+        (None, None) => None,
+    };
+    let user = match context["user"].as_str() {
+        Some(user) => &named("users", user)?["user"],
+        None => &serde_json::Value::Null,
+    };
+    let token = match (user["token"].as_str(), user["tokenFile"].as_str()) {
+        (Some(token), _) => Token::Static(String::from(token)),
+        (None, Some(file)) => Token::File(std::path::PathBuf::from(file)),
+        (None, None) if user["client-certificate-data"].is_string() => {
+            return Err(Error::Message(String::from(
+                "Client certificates are not supported, use a token.",
+            )))
+        }
+        (None, None) => Token::None,
+    };
+    Ok(Credentials {
+        server: String::from(server.trim_end_matches('/')),
+        namespace: context["namespace"].as_str().map(String::from),
+        token,
+        certificate_authority,
+    })
+}
+
+#[cfg(any(feature = "__tls", feature = "__rustls"))]
+fn add_root_certificate(
+    builder: reqwest::ClientBuilder,
+    pem: &[u8],
+) -> Result<reqwest::ClientBuilder> {
+    let certificate = reqwest::Certificate::from_pem(pem)
+        .map_err(|x| Error::Message(format!("Could not parse certificate authority: {x}")))?;
+    Ok(builder.add_root_certificate(certificate))
+}
+
+#[cfg(not(any(feature = "__tls", feature = "__rustls")))]
+fn add_root_certificate(
+    _builder: reqwest::ClientBuilder,
+    _pem: &[u8],
+) -> Result<reqwest::ClientBuilder> {
+    Err(Error::Message(String::from(
+        "A certificate authority requires MinMon to be built with TLS support.",
+    )))
+}
+
+// Sends requests to the Kubernetes API server in one namespace. Also used by the
+// KubernetesDeployment check.
+pub(super) struct Client {
+    server: String,
+    namespace: String,
+    token: Token,
+    client: reqwest::Client,
+}
+
+impl Client {
+    // The credentials of the service account are used when running in a pod, unless a kubeconfig
+    // is set explicitly.
+    pub(super) fn new(kubeconfig: &Option<String>, namespace: &Option<String>) -> Result<Self> {
+        let credentials = match (kubeconfig, std::env::var("KUBERNETES_SERVICE_HOST")) {
+            (Some(kubeconfig), _) => kubeconfig_credentials(std::path::Path::new(kubeconfig))?,
+            (None, Ok(host)) => in_cluster_credentials(&host)?,
+            (None, Err(_)) => kubeconfig_credentials(&default_kubeconfig()?)?,
+        };
+        let namespace = match namespace {
+            Some(namespace) if namespace.is_empty() => {
+                return Err(Error::invalid_config("namespace", "cannot be empty."))
+            }
+            Some(namespace) => namespace.clone(),
+            None => credentials
+                .namespace
+                .unwrap_or_else(|| String::from("default")),
+        };
+        let mut builder = reqwest::Client::builder().user_agent(crate::user_agent());
+        if let Some(certificate_authority) = &credentials.certificate_authority {
+            builder = add_root_certificate(builder, certificate_authority)?;
+        }
+        let client = builder
+            .build()
+            .map_err(|x| Error::Message(format!("Could not create HTTP client: {x}")))?;
+        Ok(Self {
+            server: credentials.server,
+            namespace,
+            token: credentials.token,
+            client,
+        })
+    }
+
+    // Gets a resource of the namespace, e.g. `get("api/v1", "pods/foo", &[])`.
+    pub(super) async fn get(
+        &self,
+        api: &str,
+        resource: &str,
+        query: &[(&str, &str)],
+    ) -> Result<serde_json::Value> {
+        let url = format!(
+            "{}/{api}/namespaces/{}/{resource}",
+            self.server, self.namespace
+        );
+        let mut request = self.client.get(url).query(query);
+        request = match &self.token {
+            Token::None => request,
+            Token::Static(token) => request.bearer_auth(token),
+            Token::File(path) => request.bearer_auth(read(path)?.trim()),
+        };
+        let response = request
+            .send()
+            .await
+            .map_err(|x| Error::Message(format!("Kubernetes API request failed: {x}")))?;
+        let status = response.status();
+        let response = response
+            .text()
+            .await
+            .map_err(|x| Error::Message(format!("Could not read Kubernetes API response: {x}")))?;
+        let response: Option<serde_json::Value> = serde_json::from_str(&response).ok();
+        // Errors come with a status object describing the error.
+        if !status.is_success() {
+            return Err(Error::Message(
+                match response.as_ref().and_then(|x| x["message"].as_str()) {
+                    Some(message) => format!("Kubernetes API request failed: {message}"),
+                    None => format!("Kubernetes API request failed: HTTP status {status}"),
+                },
+            ));
+        }
+        response
+            .ok_or_else(|| Error::Message(String::from("Could not parse Kubernetes API response.")))
+    }
+}
+
+enum Selector {
+    Name(String),
+    Labels(String),
+}
+
+// Ratio of the ready pods, selected by name or by label selector.
+pub struct KubernetesPod {
+    id: Vec<String>,
+    selector: Selector,
+    client: Client,
+}
+
+impl TryFrom<&config::Check> for KubernetesPod {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::KubernetesPod(kubernetes_pod) = &check.type_ {
+            let selector = match (&kubernetes_pod.pod, &kubernetes_pod.label_selector) {
+                (Some(pod), None) if !pod.is_empty() => Selector::Name(pod.clone()),
+                (None, Some(labels)) if !labels.is_empty() => Selector::Labels(labels.clone()),
+                (Some(_), Some(_)) => {
+                    return Err(Error::invalid_config(
+                        "pod",
+                        "cannot be combined with 'label_selector'.",
+                    ))
+                }
+                _ => {
+                    return Err(Error::Message(String::from(
+                        "Either 'pod' or 'label_selector' must be set.",
+                    )))
+                }
+            };
+            Ok(Self {
+                id: vec![match &selector {
+                    Selector::Name(name) | Selector::Labels(name) => name.clone(),
+                }],
+                selector,
+                client: Client::new(&kubernetes_pod.kubeconfig, &kubernetes_pod.namespace)?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+fn ready(pod: &serde_json::Value) -> bool {
+    pod["status"]["conditions"].as_array().is_some_and(|x| {
+        x.iter()
+            .any(|x| x["type"] == "Ready" && x["status"] == "True")
+    })
+}
+
+impl KubernetesPod {
+    async fn ready_ratio(&self) -> Result<f64> {
+        match &self.selector {
+            Selector::Name(name) => {
+                let pod = self
+                    .client
+                    .get("api/v1", &format!("pods/{name}"), &[])
+                    .await?;
+                Ok(if ready(&pod) { 1.0 } else { 0.0 })
+            }
+            Selector::Labels(labels) => {
+                let pods = self
+                    .client
+                    .get("api/v1", "pods", &[("labelSelector", labels)])
+                    .await?;
+                let pods = pods["items"].as_array().ok_or_else(|| {
+                    Error::Message(String::from("Kubernetes API response has no items."))
+                })?;
+                if pods.is_empty() {
+                    return Err(Error::Message(format!(
+                        "No pods match the label selector '{labels}'."
+                    )));
+                }
+                Ok(pods.iter().filter(|x| ready(x)).count() as f64 / pods.len() as f64)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for KubernetesPod {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        Ok(vec![self.ready_ratio().await.map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("ready ratio {data:.2}")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn pod(name: &str, ready: bool) -> String {
+        format!(
+            r#"{{"metadata":{{"name":"{name}"}},"status":{{"phase":"Running","conditions":[{{"type":"Initialized","status":"True"}},{{"type":"Ready","status":"{}"}}]}}}}"#,
+            if ready { "True" } else { "False" }
+        )
+    }
+
+    // Answers like the Kubernetes API server for the namespace "apps". Requests without the
+    // expected bearer token are rejected.
+    pub(crate) async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = String::new();
+                while !request.contains("\r\n\r\n") {
+                    let mut buffer = [0; 4096];
+                    let length = stream.read(&mut buffer).await.unwrap();
+                    if length == 0 {
+                        break;
+                    }
+                    request.push_str(&String::from_utf8_lossy(&buffer[..length]));
+                }
+                let path = request.split(' ').nth(1).unwrap_or_default();
+                let (status, body) = if !request
+                    .lines()
+                    .any(|x| x.eq_ignore_ascii_case("Authorization: Bearer secret"))
+                {
+                    ("401 Unauthorized", String::from("Unauthorized"))
+                } else {
+                    match path {
+                        "/api/v1/namespaces/apps/pods/web-0" => ("200 OK", pod("web-0", true)),
+                        "/api/v1/namespaces/apps/pods/web-1" => ("200 OK", pod("web-1", false)),
+                        "/api/v1/namespaces/apps/pods?labelSelector=app%3Dweb" => (
+                            "200 OK",
+                            format!(
+                                r#"{{"kind":"PodList","items":[{},{},{}]}}"#,
+                                pod("web-0", true),
+                                pod("web-1", false),
+                                pod("web-2", true),
+                            ),
+                        ),
+                        "/api/v1/namespaces/apps/pods?labelSelector=app%3Dnone" => {
+                            ("200 OK", String::from(r#"{"kind":"PodList","items":[]}"#))
+                        }
+                        "/apis/apps/v1/namespaces/apps/deployments/web" => (
+                            "200 OK",
+                            String::from(
+                                r#"{"spec":{"replicas":4},"status":{"replicas":4,"availableReplicas":3}}"#,
+                            ),
+                        ),
+                        "/apis/apps/v1/namespaces/apps/deployments/down" => (
+                            "200 OK",
+                            String::from(r#"{"spec":{"replicas":2},"status":{}}"#),
+                        ),
+                        "/apis/apps/v1/namespaces/apps/deployments/idle" => (
+                            "200 OK",
+                            String::from(r#"{"spec":{"replicas":0},"status":{}}"#),
+                        ),
+                        _ => (
+                            "404 Not Found",
+                            String::from(
+                                r#"{"kind":"Status","status":"Failure","message":"not found","reason":"NotFound","code":404}"#,
+                            ),
+                        ),
+                    }
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{address}/")
+    }
+
+    // Writes a kubeconfig for the server and returns its path.
+    pub(crate) fn write_kubeconfig(name: &str, server: &str, namespace: Option<&str>) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "minmon-test-kubeconfig-{name}-{}",
+            std::process::id()
+        ));
+        let mut context = serde_json::json!({"cluster": "test", "user": "monitoring"});
+        if let Some(namespace) = namespace {
+            context["namespace"] = serde_json::Value::from(namespace);
+        }
+        let kubeconfig = serde_json::json!({
+            "apiVersion": "v1",
+            "kind": "Config",
+            "current-context": "test",
+            "contexts": [{"name": "test", "context": context}],
+            "clusters": [{"name": "test", "cluster": {"server": server}}],
+            "users": [{"name": "monitoring", "user": {"token": "secret"}}],
+        });
+        std::fs::write(&path, serde_yaml::to_string(&kubeconfig).unwrap()).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn kubernetes_pod(kubeconfig: &str, selector: &str) -> Result<KubernetesPod> {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Check"
+            type = "KubernetesPod"
+            kubeconfig = "{kubeconfig}"
+            {selector}
+            "#
+        ))
+        .unwrap();
+        KubernetesPod::try_from(&config)
+    }
+
+    async fn ready_ratio(kubeconfig: &str, selector: &str) -> Result<f64> {
+        kubernetes_pod(kubeconfig, selector)
+            .unwrap()
+            .get_data()
+            .await
+            .unwrap()
+            .remove(0)
+            .map(|x| x.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_kubernetes_pod() {
+        let kubeconfig = write_kubeconfig("pod", &serve().await, Some("apps"));
+        let check = kubernetes_pod(&kubeconfig, r#"label_selector = "app=web""#).unwrap();
+        assert_eq!(check.ids(), ["app=web"]);
+        assert_eq!(check.format_data(&0.5), "ready ratio 0.50");
+        assert_eq!(
+            ready_ratio(&kubeconfig, r#"label_selector = "app=web""#)
+                .await
+                .unwrap(),
+            2.0 / 3.0
+        );
+        assert_eq!(
+            ready_ratio(&kubeconfig, r#"pod = "web-0""#).await.unwrap(),
+            1.0
+        );
+        assert_eq!(
+            ready_ratio(&kubeconfig, r#"pod = "web-1""#).await.unwrap(),
+            0.0
+        );
+        assert_eq!(
+            ready_ratio(&kubeconfig, r#"label_selector = "app=none""#)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "No pods match the label selector 'app=none'."
+        );
+        assert_eq!(
+            ready_ratio(&kubeconfig, r#"pod = "web-9""#)
+                .await
+                .unwrap_err()
+                .to_string(),
+            "Kubernetes API request failed: not found"
+        );
+        // The namespace option takes precedence over the one of the context.
+        assert!(
+            ready_ratio(&kubeconfig, "pod = \"web-0\"\nnamespace = \"other\"")
+                .await
+                .is_err()
+        );
+        std::fs::remove_file(kubeconfig).unwrap();
+
+        // Connection errors are errors of the measurement, not of the check.
+        let kubeconfig = write_kubeconfig("pod-refused", "http://127.0.0.1:1", None);
+        assert!(ready_ratio(&kubeconfig, r#"pod = "web-0""#)
+            .await
+            .unwrap_err()
+            .to_string()
+            .starts_with("Kubernetes API request failed"));
+        std::fs::remove_file(kubeconfig).unwrap();
+    }
+
+    #[test]
+    fn test_validation() {
+        let kubeconfig = write_kubeconfig("pod-validation", "http://127.0.0.1:1", None);
+        assert!(kubernetes_pod(&kubeconfig, "").is_err());
+        assert!(kubernetes_pod(&kubeconfig, r#"pod = """#).is_err());
+        assert!(kubernetes_pod(&kubeconfig, "pod = \"a\"\nlabel_selector = \"app=a\"").is_err());
+        assert!(kubernetes_pod(&kubeconfig, "pod = \"a\"\nnamespace = \"\"").is_err());
+        assert!(kubernetes_pod(&kubeconfig, r#"pod = "a""#).is_ok());
+        std::fs::remove_file(&kubeconfig).unwrap();
+        assert!(kubernetes_pod(&kubeconfig, r#"pod = "a""#).is_err());
+    }
+
+    #[test]
+    fn test_kubeconfig_credentials() {
+        let path = std::env::temp_dir().join(format!(
+            "minmon-test-kubeconfig-credentials-{}",
+            std::process::id()
+        ));
+        let credentials = |kubeconfig: serde_json::Value| {
+            std::fs::write(&path, serde_yaml::to_string(&kubeconfig).unwrap()).unwrap();
+            kubeconfig_credentials(&path)
+        };
+        let prod = credentials(serde_json::json!({
+            "current-context": "prod",
+            "contexts": [
+                {"name": "dev", "context": {"cluster": "dev", "user": "dev"}},
+                {"name": "prod", "context": {"cluster": "prod", "user": "prod"}},
+            ],
+            "clusters": [{"name": "prod", "cluster": {
+                "server": "https://k8s.example.com:6443/",
+                "certificate-authority-data": "LS0tLS1CRUdJTg==",
+            }}],
+            "users": [{"name": "prod", "user": {"tokenFile": "/run/token"}}],
+        }))
+        .unwrap();
+        assert_eq!(prod.server, "https://k8s.example.com:6443");
+        assert_eq!(prod.namespace, None);
+        assert_eq!(prod.certificate_authority.unwrap(), b"-----BEGIN");
+        assert!(
+            matches!(prod.token, Token::File(path) if path == std::path::Path::new("/run/token"))
+        );
+        assert_eq!(
+            credentials(serde_json::json!({
+                "current-context": "dev",
+                "contexts": [{"name": "dev", "context": {"cluster": "dev", "user": "dev"}}],
+                "clusters": [{"name": "prod", "cluster": {"server": "https://k8s"}}],
+            }))
+            .err()
+            .unwrap()
+            .to_string(),
+            "'dev' not found in 'clusters' of the kubeconfig."
+        );
+        assert!(credentials(serde_json::json!({
+            "current-context": "dev",
+            "contexts": [{"name": "dev", "context": {"cluster": "dev", "user": "dev"}}],
+            "clusters": [{"name": "dev", "cluster": {"server": "https://k8s"}}],
+            "users": [{"name": "dev", "user": {"client-certificate-data": "Zm9v"}}],
+        }))
+        .is_err());
+        // As written by kubectl.
+        std::fs::write(
+            &path,
+            r#"apiVersion: v1
+clusters:
+- cluster:
+    server: https://127.0.0.1:6443
+  name: kind
+contexts:
+- context:
+    cluster: kind
+    namespace: monitoring
+    user: kind
+  name: kind
+current-context: kind
+kind: Config
+preferences: {}
+users:
+- name: kind
+  user:
+    token: secret
+"#,
+        )
+        .unwrap();
+        let kind = kubeconfig_credentials(&path).unwrap();
+        assert_eq!(kind.server, "https://127.0.0.1:6443");
+        assert_eq!(kind.namespace.unwrap(), "monitoring");
+        assert!(matches!(kind.token, Token::Static(token) if token == "secret"));
+        std::fs::write(&path, "apiVersion: v1\nkind: Config\n").unwrap();
+        assert_eq!(
+            kubeconfig_credentials(&path).err().unwrap().to_string(),
+            "The kubeconfig has no current context."
+        );
+        std::fs::write(&path, "clusters: [").unwrap();
+        assert!(kubeconfig_credentials(&path)
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Could not parse kubeconfig"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use std::os::unix::fs::MetadataExt;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt};
+
+// Number of new lines matching the pattern since the last check.
+pub struct LogFilePattern {
+    id: Vec<String>,
+    regex: regex::Regex,
+    max_lines_per_check: usize,
+    tail: Option<Tail>,
+    // Only at startup the file is read from the end. Files that appear later (e.g. after a
+    // rotation) are read from the start.
+    started: bool,
+}
+
+struct Tail {
+    reader: tokio::io::BufReader<tokio::fs::File>,
+    inode: u64,
+    position: u64,
+    // Incomplete last line that is still being written.
+    partial: String,
+}
+
+impl TryFrom<&config::Check> for LogFilePattern {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::LogFilePattern(log_file_pattern) = &check.type_ {
+            if log_file_pattern.path.is_empty() {
+                Err(Error::invalid_config("path", "cannot be empty."))
+            } else if log_file_pattern.max_lines_per_check == 0 {
+                Err(Error::invalid_config("max_lines_per_check", "cannot be 0."))
+            } else {
+                Ok(Self {
+                    id: vec![log_file_pattern.path.clone()],
+                    regex: regex::Regex::new(&log_file_pattern.pattern)
+                        .map_err(|x| Error::Message(format!("Could not parse pattern: {x}")))?,
+                    max_lines_per_check: log_file_pattern.max_lines_per_check,
+                    tail: None,
+                    started: false,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+impl Tail {
+    async fn open(path: &str, from_end: bool) -> Result<Self> {
+        let mut file = tokio::fs::File::open(path)
+            .await
+            .map_err(|x| Error::Message(format!("Could not open {path} for reading: {x}")))?;
+        let metadata = file
+            .metadata()
+            .await
+            .map_err(|x| Error::Message(format!("Could not read metadata of {path}: {x}")))?;
+        let position = if from_end {
+            file.seek(std::io::SeekFrom::End(0))
+                .await
+                .map_err(|x| Error::Message(format!("Could not seek in {path}: {x}")))?
+        } else {
+            0
+        };
+        Ok(Self {
+            reader: tokio::io::BufReader::new(file),
+            inode: metadata.ino(),
+            position,
+            partial: String::new(),
+        })
+    }
+
+    // Returns the number of complete lines read and how many of them match.
+    async fn read_lines(&mut self, regex: &regex::Regex, max_lines: usize) -> Result<(usize, u64)> {
+        let mut lines = 0;
+        let mut matches = 0;
+        while lines < max_lines {
+            let read = self
+                .reader
+                .read_line(&mut self.partial)
+                .await
+                .map_err(|x| Error::Message(format!("Could not read log file: {x}")))?;
+            self.position += read as u64;
+            if !self.partial.ends_with('\n') {
+                break;
+            }
+            if regex.is_match(self.partial.trim_end_matches(['\n', '\r'])) {
+                matches += 1;
+            }
+            lines += 1;
+            self.partial.clear();
+        }
+        Ok((lines, matches))
+    }
+}
+
+impl LogFilePattern {
+    // The file was replaced (rotated) if the inode changed and truncated if it became smaller.
+    async fn rotated(&self, tail: &Tail) -> Result<bool> {
+        match tokio::fs::metadata(&self.id[0]).await {
+            Ok(metadata) => Ok(metadata.ino() != tail.inode || metadata.len() < tail.position),
+            // Rotated but the new file was not created yet.
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(Error::Message(format!(
+                "Could not read metadata of {}: {err}",
+                self.id[0]
+            ))),
+        }
+    }
+
+    async fn count_matches(&mut self) -> Result<u64> {
+        let mut tail = match self.tail.take() {
+            Some(tail) => tail,
+            None => {
+                let from_end = !self.started;
+                self.started = true;
+                Tail::open(&self.id[0], from_end).await?
+            }
+        };
+        // The rest of the old file is read before switching to the new one.
+        let (mut lines, mut matches) = tail
+            .read_lines(&self.regex, self.max_lines_per_check)
+            .await?;
+        if lines < self.max_lines_per_check && self.rotated(&tail).await? {
+            tail = Tail::open(&self.id[0], false).await?;
+            let (new_lines, new_matches) = tail
+                .read_lines(&self.regex, self.max_lines_per_check - lines)
+                .await?;
+            lines += new_lines;
+            matches += new_matches;
+        }
+        log::debug!(
+            "Read {lines} lines from {}, {matches} of them match.",
+            self.id[0]
+        );
+        self.tail = Some(tail);
+        Ok(matches)
+    }
+}
+
+#[async_trait]
+impl DataSource for LogFilePattern {
+    type Item = u64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        Ok(vec![self.count_matches().await.map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("{data} matching lines")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Write;
+
+    fn temp_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "minmon-test-log-file-pattern-{}.log",
+            rand::random::<u64>()
+        ))
+    }
+
+    fn append(path: &std::path::Path, text: &str) {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+    }
+
+    fn log_file_pattern(path: &std::path::Path, max_lines_per_check: usize) -> LogFilePattern {
+        let config: config::Check = toml::from_str(&format!(
+            "name = \"Check\"\ntype = \"LogFilePattern\"\npath = \"{}\"\npattern = \"ERROR\"\nmax_lines_per_check = {max_lines_per_check}",
+            path.display()
+        ))
+        .unwrap();
+        LogFilePattern::try_from(&config).unwrap()
+    }
+
+    async fn count(check: &mut LogFilePattern) -> u64 {
+        check.get_data().await.unwrap().remove(0).unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_count() {
+        let path = temp_path();
+        append(&path, "ERROR before start\n");
+        let mut check = log_file_pattern(&path, 100);
+        assert_eq!(count(&mut check).await, 0);
+        append(&path, "INFO a\nERROR b\nERROR c\n");
+        assert_eq!(count(&mut check).await, 2);
+        assert_eq!(count(&mut check).await, 0);
+        // Incomplete lines are counted once they are complete.
+        append(&path, "ERROR incompl");
+        assert_eq!(count(&mut check).await, 0);
+        append(&path, "ete\nINFO d\n");
+        assert_eq!(count(&mut check).await, 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rotation() {
+        let path = temp_path();
+        let rotated_path = path.with_extension("log.1");
+        append(&path, "INFO start\n");
+        let mut check = log_file_pattern(&path, 100);
+        assert_eq!(count(&mut check).await, 0);
+        append(&path, "ERROR old\n");
+        std::fs::rename(&path, &rotated_path).unwrap();
+        assert_eq!(count(&mut check).await, 1);
+        append(&path, "ERROR new\nERROR new\n");
+        assert_eq!(count(&mut check).await, 2);
+        // Truncation in place.
+        std::fs::write(&path, "ERROR truncated\n").unwrap();
+        assert_eq!(count(&mut check).await, 1);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&rotated_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_max_lines_per_check() {
+        let path = temp_path();
+        append(&path, "");
+        let mut check = log_file_pattern(&path, 2);
+        assert_eq!(count(&mut check).await, 0);
+        append(&path, "ERROR\nERROR\nERROR\nERROR\nERROR\n");
+        assert_eq!(count(&mut check).await, 2);
+        assert_eq!(count(&mut check).await, 2);
+        assert_eq!(count(&mut check).await, 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_file() {
+        let path = temp_path();
+        let mut check = log_file_pattern(&path, 100);
+        assert!(check.get_data().await.unwrap()[0].is_err());
+        append(&path, "ERROR created\n");
+        assert_eq!(count(&mut check).await, 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
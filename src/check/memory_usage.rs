@@ -20,7 +20,7 @@ impl TryFrom<&config::Check> for MemoryUsage {
     fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
         if let config::CheckType::MemoryUsage(memory_usage) = &check.type_ {
             if !memory_usage.memory && !memory_usage.swap {
-                Err(Error(String::from(
+                Err(Error::Message(String::from(
                     "Either 'memory' or 'swap' or both need to be enabled.",
                 )))
             } else {
@@ -51,22 +51,22 @@ impl DataSource for MemoryUsage {
         let meminfo = MeminfoFileContent::try_from_file(MEMINFO_PATH).await?;
         let mut res = Vec::new();
         if self.memory {
-            res.push(if meminfo.mem_total != 0 {
-                Ok(((meminfo.mem_total - meminfo.mem_available) * 100 / meminfo.mem_total) as u8)
-                    .and_then(Self::Item::new)
-                    .map(Some)
-            } else {
-                Err(Error(String::from("Could not read memory usage.")))
-            });
+            res.push(
+                ((meminfo.mem_total - meminfo.mem_available) * 100)
+                    .checked_div(meminfo.mem_total)
+                    .ok_or_else(|| Error::Message(String::from("Could not read memory usage.")))
+                    .and_then(|x| Self::Item::new(x as u8))
+                    .map(Some),
+            );
         }
         if self.swap {
-            res.push(if meminfo.swap_total != 0 {
-                Ok(((meminfo.swap_total - meminfo.swap_free) * 100 / meminfo.swap_total) as u8)
-                    .and_then(Self::Item::new)
-                    .map(Some)
-            } else {
-                Err(Error(String::from("Could not read swap usage.")))
-            });
+            res.push(
+                ((meminfo.swap_total - meminfo.swap_free) * 100)
+                    .checked_div(meminfo.swap_total)
+                    .ok_or_else(|| Error::Message(String::from("Could not read swap usage.")))
+                    .and_then(|x| Self::Item::new(x as u8))
+                    .map(Some),
+            );
         }
         Ok(res)
     }
@@ -91,7 +91,7 @@ impl MeminfoFileContent {
     async fn try_from_file(path: &str) -> Result<Self> {
         let buffer = tokio::fs::read_to_string(path)
             .await
-            .map_err(|x| Error(format!("Could not open {path} for reading: {x}")))?;
+            .map_err(|x| Error::Message(format!("Could not open {path} for reading: {x}")))?;
         Self::try_from(&*buffer)
     }
 
@@ -120,10 +120,10 @@ impl TryFrom<&str> for MeminfoFileContent {
             }
         }
         Ok(Self {
-            mem_total: mem_total.ok_or_else(|| Error(PARSE_ERROR.to_string()))?,
-            mem_available: mem_available.ok_or_else(|| Error(PARSE_ERROR.to_string()))?,
-            swap_total: swap_total.ok_or_else(|| Error(PARSE_ERROR.to_string()))?,
-            swap_free: swap_free.ok_or_else(|| Error(PARSE_ERROR.to_string()))?,
+            mem_total: mem_total.ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?,
+            mem_available: mem_available.ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?,
+            swap_total: swap_total.ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?,
+            swap_free: swap_free.ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?,
         })
     }
 }
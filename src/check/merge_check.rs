@@ -0,0 +1,224 @@
+use super::{alarms, check_base, Check, DataSource};
+use crate::alarm::{self, BoxedDataSink, DataSink};
+use crate::{config, measurement, ActionMap, AlarmGroupMap, Error, Result};
+
+// Data of a check whose parts are watched by alarms with data sinks of different types, e.g. the
+// status code, the response time and the body of one HTTP request. The data sink of each alarm is
+// chosen by its config and gets the part of the result it watches, see `extract`.
+pub trait CheckResult: measurement::Measurement + Send + Sync + 'static {
+    fn data_sink(alarm: &config::Alarm) -> Result<BoxedDataSink<Self>>;
+}
+
+// Data sink that gets the part of the check result returned by `extract`.
+// Not used by any check yet.
+#[allow(dead_code)]
+pub fn extract<R, S, F>(data_sink: S, extract: F) -> BoxedDataSink<R>
+where
+    R: measurement::Measurement + Send + Sync + 'static,
+    S: DataSink + 'static,
+    F: Fn(&R) -> S::Item + Send + Sync + 'static,
+{
+    Box::new(alarm::map_sink(data_sink, extract))
+}
+
+impl<R> TryFrom<&config::Alarm> for BoxedDataSink<R>
+where
+    R: CheckResult,
+{
+    type Error = Error;
+
+    fn try_from(alarm: &config::Alarm) -> std::result::Result<Self, Self::Error> {
+        R::data_sink(alarm)
+    }
+}
+
+// Check that gets the data of the data source once per cycle and fans it out to alarms with data
+// sinks of different types. An error of the data source is passed to all of them, like for the
+// other checks.
+// Not used by any check yet.
+#[allow(dead_code)]
+pub fn merge_check<T>(
+    check_config: &config::Check,
+    data_source: T,
+    actions: &ActionMap,
+    alarm_groups: &AlarmGroupMap,
+    registry: &alarm::AlarmRegistry,
+) -> Result<Box<dyn Check>>
+where
+    T: DataSource + 'static,
+    T::Item: CheckResult,
+{
+    let all_alarms = alarms::<BoxedDataSink<T::Item>>(
+        check_config,
+        data_source.ids(),
+        actions,
+        alarm_groups,
+        registry,
+    )?;
+    check_base(check_config, data_source, all_alarms, actions)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action;
+    use crate::measurement::Measurement;
+    use async_trait::async_trait;
+
+    #[derive(Clone, Default)]
+    struct ProcessResult {
+        exit_status: u8,
+        // In milliseconds.
+        duration: f64,
+        output: String,
+    }
+
+    impl std::fmt::Display for ProcessResult {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(
+                f,
+                "exit status {} after {:.2} ms",
+                self.exit_status, self.duration
+            )
+        }
+    }
+
+    impl Measurement for ProcessResult {
+        type Data = (u8, f64, String);
+        const UNIT: &'static str = "";
+
+        fn new((exit_status, duration, output): Self::Data) -> Result<Self> {
+            Ok(Self {
+                exit_status,
+                duration,
+                output,
+            })
+        }
+
+        fn data(&self) -> Self::Data {
+            (self.exit_status, self.duration, self.output.clone())
+        }
+    }
+
+    impl CheckResult for ProcessResult {
+        fn data_sink(alarm: &config::Alarm) -> Result<BoxedDataSink<Self>> {
+            match &alarm.type_ {
+                config::AlarmType::StatusCode(_) => {
+                    Ok(extract(alarm::StatusCode::try_from(alarm)?, |x: &Self| {
+                        measurement::StatusCode::new(x.exit_status).unwrap()
+                    }))
+                }
+                config::AlarmType::Level(_) => Ok(extract(
+                    alarm::Level::<f64>::try_from(alarm)?,
+                    |x: &Self| x.duration,
+                )),
+                config::AlarmType::Regex(_) => {
+                    Ok(extract(alarm::Regex::try_from(alarm)?, |x: &Self| {
+                        measurement::Text::new(x.output.clone()).unwrap()
+                    }))
+                }
+                _ => Err(Error::Message(String::from(
+                    "Expected status code, level or regex alarm config.",
+                ))),
+            }
+        }
+    }
+
+    struct CountingDataSource {
+        calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        results: Vec<Result<ProcessResult>>,
+        ids: Vec<String>,
+    }
+
+    #[async_trait]
+    impl DataSource for CountingDataSource {
+        type Item = ProcessResult;
+
+        async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(vec![self.results.remove(0).map(Some)])
+        }
+
+        fn format_data(&self, data: &Self::Item) -> String {
+            data.to_string()
+        }
+
+        fn ids(&self) -> &[String] {
+            &self.ids[..]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_merge_check() {
+        let check_config: config::Check = toml::from_str(
+            r#"
+            name = "Backup"
+            type = "MemoryUsage"
+            [[alarms]]
+            name = "Exit status"
+            action = "Bad"
+            error_action = "Error"
+            status_codes = [0]
+            [[alarms]]
+            name = "Duration"
+            action = "Bad"
+            error_action = "Error"
+            level = 100
+            [[alarms]]
+            name = "Output"
+            action = "Bad"
+            error_action = "Error"
+            pattern = "error"
+            "#,
+        )
+        .unwrap();
+        let bad = action::TestAction::new();
+        let error = action::TestAction::new();
+        let actions = ActionMap::from([
+            (
+                String::from("Bad"),
+                std::sync::Arc::new(bad.clone()) as std::sync::Arc<dyn action::Action>,
+            ),
+            (
+                String::from("Error"),
+                std::sync::Arc::new(error.clone()) as std::sync::Arc<dyn action::Action>,
+            ),
+        ]);
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let data_source = CountingDataSource {
+            calls: calls.clone(),
+            results: vec![
+                ProcessResult::new((1, 150., String::from("error: disk full"))),
+                Err(Error::Message(String::from("Process not found."))),
+            ],
+            ids: vec![String::from("backup.sh")],
+        };
+        let mut check = merge_check(
+            &check_config,
+            data_source,
+            &actions,
+            &AlarmGroupMap::new(),
+            &alarm::AlarmRegistry::default(),
+        )
+        .unwrap();
+        crate::uptime::init().unwrap();
+        // One run of the data source is bad for all three alarms.
+        check.trigger().await;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(bad.trigger_count(), 3);
+        let values: Vec<_> = bad
+            .records()
+            .iter()
+            .map(|x| x.placeholders["alarm_value"].clone())
+            .collect();
+        assert_eq!(values, ["1", "150.00", "error: disk full"]);
+        // The error of the data source reaches all three alarms.
+        check.trigger().await;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(error.trigger_count(), 3);
+        assert!(error
+            .records()
+            .iter()
+            .all(|x| x.placeholders["check_error"] == "Process not found."));
+    }
+}
@@ -3,26 +3,97 @@ use crate::alarm;
 use crate::alarm::{Alarm, AlarmBase, DataSink};
 use crate::config;
 use crate::measurement;
-use crate::ActionMap;
+use crate::{ActionMap, AlarmGroupMap};
 use crate::{Error, PlaceholderMap, Result};
 use async_trait::async_trait;
 
+mod alarm_summary;
+mod dns_resolution;
 #[cfg(feature = "docker")]
 mod docker_container_status;
+#[cfg(feature = "http")]
+mod elasticsearch_query;
 mod filesystem_usage;
+#[cfg(feature = "http")]
+mod http_status;
+#[cfg(feature = "http")]
+mod jmx;
+#[cfg(feature = "kafka")]
+mod kafka_consumer_lag;
+#[cfg(feature = "kubernetes")]
+mod kubernetes_deployment;
+#[cfg(feature = "kubernetes")]
+mod kubernetes_pod;
+mod log_file_pattern;
 mod memory_usage;
+mod merge_check;
+mod network_interface;
 mod network_throughput;
+mod path_exists;
+mod port_scan;
+#[cfg(feature = "postgres")]
+mod postgres;
 mod pressure_average;
 mod process_exit_status;
+mod process_memory;
+mod process_output;
+mod process_uptime;
+mod process_value;
+#[cfg(feature = "http")]
+mod prometheus_comparison;
+#[cfg(feature = "http")]
+mod prometheus_query;
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "redis")]
+mod redis_latency;
+#[cfg(feature = "s3")]
+mod s3_bucket;
+#[cfg(feature = "snmp")]
+mod snmp_get;
+#[cfg(feature = "ssl-cert")]
+mod ssl_cert_expiry;
+mod system_load;
 mod systemd_unit_status;
+mod tcp_connect;
 #[cfg(feature = "sensors")]
 mod temperature;
+mod topology;
 
+#[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait Check: Send + Sync {
     async fn trigger(&mut self);
     fn interval(&self) -> std::time::Duration;
+    // Maximum random delay before the first cycle. None if it was not configured.
+    fn jitter(&self) -> Option<std::time::Duration>;
+    // If set, the check is triggered on this schedule instead of every interval.
+    fn cron(&self) -> Option<cron::Schedule>;
     fn name(&self) -> &str;
+    fn backpressure(&self) -> Backpressure;
+    fn dot_graphs(&self) -> Vec<String>;
+    fn describe(&self) -> Vec<alarm::AlarmDescriptor>;
+    // Simulated cycles of each alarm for the given decisions, formatted as text.
+    fn simulate(&self, decisions: &[alarm::SinkDecision]) -> Vec<String>;
+    // Replays the recorded data (one RFC 3339 timestamp and value per line) through each alarm,
+    // formatted as text. Changes the state of the alarms, see `Alarm::replay`.
+    async fn replay(&mut self, text: &str) -> Result<Vec<String>>;
+    fn snapshots(&self) -> Result<Vec<alarm::AlarmSnapshot>>;
+    // Restores the alarms from the snapshots with the same log ID. Snapshots are only used if the
+    // configuration of the alarm did not change. Used snapshots are removed from the map.
+    fn restore(
+        &mut self,
+        snapshots: &mut std::collections::HashMap<String, alarm::AlarmSnapshot>,
+        actions: &ActionMap,
+    ) -> Result<()>;
+    fn reload_states(&self) -> Result<Vec<alarm::ReloadState>>;
+    // Continues the alarms from the states with the same log ID from before a config reload, see
+    // `ReloadState::applies_to`. Used states are removed from the map.
+    fn take_over(
+        &mut self,
+        states: &mut std::collections::HashMap<String, alarm::ReloadState>,
+        actions: &ActionMap,
+    ) -> Result<()>;
 }
 
 #[async_trait]
@@ -34,17 +105,80 @@ pub trait DataSource: Send + Sync {
     fn ids(&self) -> &[String];
 }
 
+// What `crate::run_check` does when a cycle is due while the check is still held elsewhere, e.g.
+// by a slow cycle or a config reload. The check cannot be locked then, so this is a copy of what
+// is needed of it.
+#[derive(Clone)]
+pub struct Backpressure {
+    pub policy: config::Backpressure,
+    name: String,
+    action: Option<std::sync::Arc<dyn action::Action>>,
+    placeholders: PlaceholderMap,
+}
+
+impl Backpressure {
+    pub fn new(
+        policy: config::Backpressure,
+        name: String,
+        action: Option<std::sync::Arc<dyn action::Action>>,
+        placeholders: PlaceholderMap,
+    ) -> Self {
+        Self {
+            policy,
+            name,
+            action,
+            placeholders,
+        }
+    }
+
+    // Called for every cycle that found the check busy. `misses` is the number of these cycles in
+    // a row, it is passed to the action as the `alarm_queue_depth` placeholder.
+    pub async fn missed(&self, misses: u64) {
+        match self.policy {
+            config::Backpressure::Skip => log::warn!(
+                "Check '{}' is still busy, skipping the cycle ({} in a row).",
+                self.name,
+                misses
+            ),
+            config::Backpressure::Wait => log::warn!(
+                "Check '{}' is still busy, waiting for it ({} in a row).",
+                self.name,
+                misses
+            ),
+        }
+        if let Some(action) = &self.action {
+            let mut placeholders = crate::global_placeholders();
+            crate::merge_placeholders(&mut placeholders, &self.placeholders);
+            placeholders.insert(String::from("check_name"), self.name.clone());
+            placeholders.insert(String::from("alarm_queue_depth"), misses.to_string());
+            if let Err(err) = action.trigger(placeholders).await {
+                log::error!(
+                    "Backpressure action of check '{}' had an error: {}",
+                    self.name,
+                    err
+                );
+            }
+        }
+    }
+}
+
 pub struct CheckBase<T, U>
 where
     T: DataSource,
     U: Alarm,
 {
     interval: std::time::Duration,
+    jitter: Option<std::time::Duration>,
+    cron: Option<cron::Schedule>,
     name: String,
     timeout: std::time::Duration,
     placeholders: PlaceholderMap,
     data_source: T,
     alarms: Vec<Vec<U>>,
+    // Limits the number of alarms that are evaluated at the same time.
+    alarm_permits: tokio::sync::Semaphore,
+    backpressure: config::Backpressure,
+    backpressure_action: Option<std::sync::Arc<dyn action::Action>>,
 }
 
 impl<T, U> CheckBase<T, U>
@@ -52,24 +186,39 @@ where
     T: DataSource,
     U: Alarm<Item = T::Item>,
 {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         interval: std::time::Duration,
+        jitter: Option<std::time::Duration>,
+        cron: Option<cron::Schedule>,
         name: String,
         timeout: Option<std::time::Duration>,
         placeholders: PlaceholderMap,
         data_source: T,
         alarms: Vec<Vec<U>>,
+        max_concurrent_alarms: usize,
     ) -> Result<Self> {
         if interval.is_zero() {
-            Err(Error(String::from("'interval' cannot be 0.")))
+            Err(Error::invalid_config("interval", "cannot be 0."))
+        } else if cron.is_some() && jitter.is_some() {
+            Err(Error::invalid_config(
+                "jitter",
+                "cannot be used together with 'cron'.",
+            ))
         } else if name.is_empty() {
-            Err(Error(String::from("'name' cannot be empty.")))
+            Err(Error::invalid_config("name", "cannot be empty."))
         } else if matches!(timeout, Some(timeout) if timeout.is_zero()) {
-            Err(Error(String::from("'timeout' cannot be 0.")))
+            Err(Error::invalid_config("timeout", "cannot be 0."))
         } else if matches!(timeout, Some(timeout) if timeout > interval) {
-            Err(Error(String::from(
-                "'timeout' cannot be greater than 'interval'.",
-            )))
+            Err(Error::invalid_config(
+                "timeout",
+                "cannot be greater than 'interval'.",
+            ))
+        } else if max_concurrent_alarms == 0 {
+            Err(Error::invalid_config(
+                "max_concurrent_alarms",
+                "cannot be 0.",
+            ))
         } else {
             let timeout = timeout.unwrap_or_else(|| {
                 interval.min(std::time::Duration::from_secs(
@@ -78,14 +227,54 @@ where
             });
             Ok(Self {
                 interval,
+                jitter,
+                cron,
                 name,
                 timeout,
                 placeholders,
                 data_source,
                 alarms,
+                alarm_permits: tokio::sync::Semaphore::new(max_concurrent_alarms),
+                backpressure: config::Backpressure::default(),
+                backpressure_action: None,
             })
         }
     }
+
+    fn set_backpressure(
+        &mut self,
+        backpressure: config::Backpressure,
+        action: Option<std::sync::Arc<dyn action::Action>>,
+    ) {
+        self.backpressure = backpressure;
+        self.backpressure_action = action;
+    }
+
+    // Placeholders of the check are added to the placeholder map before it is passed to the
+    // alarms. The alarms add their own ones on top (see `Alarm::put_data`).
+    fn add_placeholders(&self, placeholders: &mut PlaceholderMap) {
+        crate::merge_placeholders(placeholders, &self.placeholders);
+        placeholders.insert(String::from("check_name"), self.name.clone());
+    }
+
+    // If the data source fails or does not finish within the timeout, every ID gets the error.
+    async fn get_data(&mut self) -> Vec<Result<Option<T::Item>>> {
+        let res = tokio::time::timeout(self.timeout, self.data_source.get_data()).await;
+        let data_vec = match res {
+            Ok(inner) => inner,
+            Err(_) => Err(Error::Timeout {
+                check_id: self.name.clone(),
+                duration: self.timeout,
+            }),
+        };
+        data_vec.unwrap_or_else(|x| {
+            let mut res = Vec::new();
+            for _ in 0..self.data_source.ids().len() {
+                res.push(Err(x.clone()))
+            }
+            res
+        })
+    }
 }
 
 #[async_trait]
@@ -96,25 +285,10 @@ where
 {
     async fn trigger(&mut self) {
         let mut placeholders = crate::global_placeholders();
-        crate::merge_placeholders(&mut placeholders, &self.placeholders);
-        placeholders.insert(String::from("check_name"), self.name.clone());
-        let res = tokio::time::timeout(self.timeout, self.data_source.get_data()).await;
+        self.add_placeholders(&mut placeholders);
+        let data_vec = self.get_data().await;
         let ids = self.data_source.ids();
-        let data_vec = match res {
-            Ok(inner) => inner,
-            Err(_) => Err(Error(format!(
-                "Timed out after {} seconds.",
-                self.timeout.as_secs()
-            ))),
-        };
-        let data_vec = data_vec.unwrap_or_else(|x| {
-            let mut res = Vec::new();
-            for _ in 0..ids.len() {
-                res.push(Err(x.clone()))
-            }
-            res
-        });
-        for ((i, data), alarms) in data_vec.iter().enumerate().zip(self.alarms.iter_mut()) {
+        for (i, data) in data_vec.iter().enumerate() {
             match data {
                 Ok(data) => match data {
                     Some(data) => log::debug!(
@@ -132,47 +306,305 @@ where
                     err
                 ),
             }
-            for alarm in alarms.iter_mut() {
+        }
+        // The alarms of all IDs are evaluated concurrently, so that a slow action of one alarm
+        // does not delay the others. The semaphore is never closed.
+        let alarm_permits = &self.alarm_permits;
+        let evaluations = data_vec
+            .iter()
+            .zip(self.alarms.iter_mut())
+            .flat_map(|(data, alarms)| alarms.iter_mut().map(move |alarm| (data, alarm)))
+            .map(|(data, alarm)| {
                 let mut placeholders = placeholders.clone();
-                let result = match data {
-                    Ok(data) => match data {
-                        Some(data) => alarm.put_data(data, placeholders).await,
-                        None => Ok(()),
-                    },
-                    Err(err) => {
-                        placeholders.insert(String::from("check_error"), err.to_string());
-                        alarm.put_error(err, placeholders).await
+                async move {
+                    let _permit = alarm_permits.acquire().await;
+                    let result = match data {
+                        Ok(data) => match data {
+                            Some(data) => alarm.put_data(data, placeholders).await,
+                            None => alarm.tick(placeholders).await,
+                        },
+                        Err(err) => {
+                            placeholders.insert(String::from("check_error"), err.to_string());
+                            alarm.put_error(err, placeholders).await
+                        }
+                    };
+                    if let Err(err) = result {
+                        log::error!("{} had an error: {}", alarm.log_id(), err);
                     }
-                };
-                if let Err(err) = result {
-                    log::error!("{} had an error: {}", alarm.log_id(), err);
                 }
-            }
-        }
+            });
+        futures_util::future::join_all(evaluations).await;
     }
 
     fn interval(&self) -> std::time::Duration {
         self.interval
     }
 
+    fn jitter(&self) -> Option<std::time::Duration> {
+        self.jitter
+    }
+
+    fn cron(&self) -> Option<cron::Schedule> {
+        self.cron.clone()
+    }
+
     fn name(&self) -> &str {
         self.name.as_str()
     }
+
+    fn backpressure(&self) -> Backpressure {
+        Backpressure::new(
+            self.backpressure,
+            self.name.clone(),
+            self.backpressure_action.clone(),
+            self.placeholders.clone(),
+        )
+    }
+
+    fn dot_graphs(&self) -> Vec<String> {
+        self.alarms
+            .iter()
+            .flatten()
+            .map(|alarm| alarm.to_dot_graph())
+            .collect()
+    }
+
+    fn describe(&self) -> Vec<alarm::AlarmDescriptor> {
+        self.alarms
+            .iter()
+            .flatten()
+            .map(|alarm| alarm.describe())
+            .collect()
+    }
+
+    fn simulate(&self, decisions: &[alarm::SinkDecision]) -> Vec<String> {
+        self.alarms
+            .iter()
+            .flatten()
+            .map(|alarm| {
+                let mut text = format!("{}:\n", alarm.log_id());
+                for event in alarm.simulate(decisions) {
+                    text.push_str(&format!("    {event}\n"));
+                }
+                text
+            })
+            .collect()
+    }
+
+    async fn replay(&mut self, text: &str) -> Result<Vec<String>> {
+        let data = parse_recorded_data(text)?;
+        let mut replays = Vec::new();
+        for alarm in self.alarms.iter_mut().flatten() {
+            let mut text = format!("{}:\n", alarm.log_id());
+            for event in alarm.replay(&data).await? {
+                text.push_str(&format!("    {event}\n"));
+            }
+            replays.push(text);
+        }
+        Ok(replays)
+    }
+
+    fn snapshots(&self) -> Result<Vec<alarm::AlarmSnapshot>> {
+        self.alarms
+            .iter()
+            .flatten()
+            .map(|alarm| alarm.to_snapshot())
+            .collect()
+    }
+
+    fn restore(
+        &mut self,
+        snapshots: &mut std::collections::HashMap<String, alarm::AlarmSnapshot>,
+        actions: &ActionMap,
+    ) -> Result<()> {
+        for alarms in self.alarms.iter_mut() {
+            let mut restored = Vec::with_capacity(alarms.len());
+            for alarm in alarms.drain(..) {
+                match snapshots.remove(alarm.log_id()) {
+                    Some(snapshot) if snapshot.same_config(&alarm.to_snapshot()?) => {
+                        log::info!("{} restored from saved state.", alarm.log_id());
+                        restored.push(alarm.restore(snapshot, actions)?);
+                    }
+                    Some(_) => {
+                        log::info!(
+                            "{} changed its configuration, discarding saved state.",
+                            alarm.log_id()
+                        );
+                        restored.push(alarm);
+                    }
+                    None => restored.push(alarm),
+                }
+            }
+            *alarms = restored;
+        }
+        Ok(())
+    }
+
+    fn reload_states(&self) -> Result<Vec<alarm::ReloadState>> {
+        self.alarms
+            .iter()
+            .flatten()
+            .map(|alarm| {
+                Ok(alarm::ReloadState {
+                    snapshot: alarm.to_snapshot()?,
+                    threshold_summary: alarm.describe().threshold_summary,
+                })
+            })
+            .collect()
+    }
+
+    // The alarm keeps its new configuration and only takes the state of the state machine.
+    fn take_over(
+        &mut self,
+        states: &mut std::collections::HashMap<String, alarm::ReloadState>,
+        actions: &ActionMap,
+    ) -> Result<()> {
+        for alarms in self.alarms.iter_mut() {
+            let mut reloaded = Vec::with_capacity(alarms.len());
+            for alarm in alarms.drain(..) {
+                let snapshot = alarm.to_snapshot()?;
+                match states.remove(alarm.log_id()) {
+                    Some(state)
+                        if state.applies_to(&snapshot, &alarm.describe().threshold_summary) =>
+                    {
+                        let snapshot = alarm::AlarmSnapshot {
+                            state_machine: state.snapshot.state_machine,
+                            ..snapshot
+                        };
+                        reloaded.push(alarm.restore(snapshot, actions)?);
+                    }
+                    Some(_) => {
+                        log::info!(
+                            "{} changed its threshold or cycles, resetting its state.",
+                            alarm.log_id()
+                        );
+                        reloaded.push(alarm);
+                    }
+                    None => {
+                        log::info!("{} was added.", alarm.log_id());
+                        reloaded.push(alarm);
+                    }
+                }
+            }
+            *alarms = reloaded;
+        }
+        Ok(())
+    }
 }
 
-fn factory<'a, T, U>(check_config: &'a config::Check, actions: &ActionMap) -> Result<Box<dyn Check>>
+// One RFC 3339 timestamp and value per line, separated by whitespace. Empty lines are skipped.
+fn parse_recorded_data<T>(text: &str) -> Result<Vec<alarm::HistoricalDataPoint<T>>>
+where
+    T: measurement::Measurement,
+{
+    let parse_line = |line: &str| {
+        let (timestamp, data) = line
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| Error::Message(String::from("Expected a timestamp and a value.")))?;
+        let timestamp = chrono::DateTime::parse_from_rfc3339(timestamp)
+            .map_err(|x| Error::Message(format!("Invalid timestamp '{timestamp}': {x}")))?;
+        Ok(alarm::HistoricalDataPoint {
+            timestamp: timestamp.into(),
+            data: T::parse(data.trim())?,
+        })
+    };
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            parse_line(line.trim())
+                .map_err(|x: Error| Error::Message(format!("Line {}: {}", i + 1, x)))
+        })
+        .collect()
+}
+
+fn factory<'a, T, U>(
+    check_config: &'a config::Check,
+    actions: &ActionMap,
+    alarm_groups: &AlarmGroupMap,
+    registry: &alarm::AlarmRegistry,
+) -> Result<Box<dyn Check>>
+where
+    T: DataSource + TryFrom<&'a config::Check, Error = Error> + 'static,
+    U: DataSink<Item = T::Item> + for<'b> TryFrom<&'b config::Alarm, Error = Error> + 'static,
+{
+    let data_source = T::try_from(check_config)?;
+    let all_alarms = alarms::<U>(
+        check_config,
+        data_source.ids(),
+        actions,
+        alarm_groups,
+        registry,
+    )?;
+    check_base(check_config, data_source, all_alarms, actions)
+}
+
+// Like `factory`, but the alarms are instantiated per routing key of the data instead of per ID
+// (see `alarm::AlarmRouter`).
+fn routed_factory<'a, T, U>(
+    check_config: &'a config::Check,
+    actions: &ActionMap,
+    alarm_groups: &AlarmGroupMap,
+    registry: &alarm::AlarmRegistry,
+    max_routes: Option<usize>,
+    routing_key: impl Fn(&T::Item) -> Result<String> + Clone + Send + Sync + 'static,
+) -> Result<Box<dyn Check>>
 where
     T: DataSource + TryFrom<&'a config::Check, Error = Error> + 'static,
-    U: DataSink<Item = T::Item> + TryFrom<&'a config::Alarm, Error = Error> + 'static,
+    U: DataSink<Item = T::Item>
+        + for<'b> TryFrom<&'b config::Alarm, Error = Error>
+        + Clone
+        + 'static,
 {
     let data_source = T::try_from(check_config)?;
-    let mut all_alarms: Vec<Vec<AlarmBase<U>>> = Vec::new();
-    for (i, id) in data_source.ids().iter().enumerate() {
-        let mut alarms: Vec<AlarmBase<U>> = Vec::new();
+    let all_alarms = alarms::<U>(
+        check_config,
+        data_source.ids(),
+        actions,
+        alarm_groups,
+        registry,
+    )?
+    .into_iter()
+    .map(|alarms| {
+        alarms
+            .into_iter()
+            .map(|alarm| {
+                let mut router = alarm::AlarmRouter::new(
+                    alarm,
+                    check_config.name.clone(),
+                    Box::new(routing_key.clone()),
+                    max_routes,
+                )?;
+                router.set_registry(registry.clone());
+                Ok(router)
+            })
+            .collect::<Result<Vec<_>>>()
+    })
+    .collect::<Result<_>>()?;
+    check_base(check_config, data_source, all_alarms, actions)
+}
+
+// The data sink of the alarms around the one of the alarm type.
+type AlarmSink<U> = alarm::Sampling<alarm::WeightedVoting<U>>;
+
+// The alarms of every ID, in the order of the IDs of the data source.
+fn alarms<U>(
+    check_config: &config::Check,
+    ids: &[String],
+    actions: &ActionMap,
+    alarm_groups: &AlarmGroupMap,
+    registry: &alarm::AlarmRegistry,
+) -> Result<Vec<Vec<AlarmBase<AlarmSink<U>>>>>
+where
+    U: DataSink + for<'b> TryFrom<&'b config::Alarm, Error = Error> + 'static,
+{
+    let mut all_alarms: Vec<Vec<AlarmBase<AlarmSink<U>>>> = Vec::new();
+    for (i, id) in ids.iter().enumerate() {
+        let mut alarms: Vec<AlarmBase<AlarmSink<U>>> = Vec::new();
         let mut used_names = std::collections::HashSet::new();
-        for alarm_config in check_config.alarms.iter() {
+        for (j, alarm_config) in check_config.alarms.iter().enumerate() {
             if !used_names.insert(alarm_config.name.clone()) {
-                return Err(Error(format!(
+                return Err(Error::Message(format!(
                     "Found duplicate alarm name '{}' for check '{}'.",
                     alarm_config.name, check_config.name
                 )));
@@ -183,9 +615,7 @@ where
             );
             if alarm_config.disable {
                 log::info!("{} is disabled.", alarm_log_id);
-                continue;
-            }
-            if i == 0 {
+            } else if i == 0 {
                 log::info!(
                     "Alarm '{}' from check '{}' will be triggered after {} bad cycles and recover after {} good cycles.",
                     alarm_config.name,
@@ -194,45 +624,135 @@ where
                     alarm_config.recover_cycles
                 );
             }
-            let data_sink = U::try_from(alarm_config)?;
-            let alarm_state_machine = alarm::StateMachine::new(
+            let data_sink = alarm::Sampling::try_from(alarm_config)?;
+            let registry_key = alarm::registry_key(&check_config.name, &alarm_config.name, id);
+            // The alarms of all other IDs are copies of the ones of the first ID.
+            if let Some(first_alarms) = all_alarms.first() {
+                let mut alarm = first_alarms[j].clone_reset(
+                    alarm_config.name.clone(),
+                    id.clone(),
+                    alarm_log_id,
+                    data_sink,
+                );
+                alarm.set_registry(registry.clone(), registry_key);
+                alarms.push(alarm);
+                continue;
+            }
+            let mut alarm_state_machine = alarm::StateMachine::new(
                 alarm_config.cycles,
-                alarm_config.repeat_cycles,
+                alarm::RepeatSchedule::from(&alarm_config.repeat_cycles),
                 alarm_config.recover_cycles,
                 alarm_config.error_repeat_cycles,
                 alarm_log_id.clone(),
+                alarm_config.log_level.map(log::LevelFilter::from),
             )?;
-            let alarm = alarm::AlarmBase::new(
+            alarm_state_machine.set_stabilize_cycles(alarm_config.stabilize_cycles);
+            let mut alarm = alarm::AlarmBase::new(
                 alarm_config.name.clone(),
                 id.clone(),
                 action::get_action(&alarm_config.action, actions)?,
                 alarm_config.placeholders.clone(),
+                alarm_state_machine,
+                data_sink,
+                alarm_log_id,
+            )?;
+            if let Some(group) = &alarm_config.group {
+                alarm.set_group_placeholders(
+                    alarm_groups
+                        .get(group)
+                        .ok_or_else(|| Error::Message(format!("Alarm group '{group}' not found.")))?
+                        .clone(),
+                );
+            }
+            alarm.set_escalation(
+                alarm_config
+                    .escalation
+                    .iter()
+                    .map(|x| Ok((x.cycles, action::get_action(&x.action, actions)?)))
+                    .collect::<Result<_>>()?,
+            )?;
+            alarm.set_recover_action(
                 match &alarm_config.recover_action {
                     Some(action) => Some(action::get_action(action, actions)?),
                     None => None,
                 },
                 alarm_config.recover_placeholders.clone(),
+            );
+            alarm.set_error_action(
                 match &alarm_config.error_action {
                     Some(action) => Some(action::get_action(action, actions)?),
                     None => None,
                 },
                 alarm_config.error_placeholders.clone(),
+            );
+            alarm.set_error_recover_action(
                 match &alarm_config.error_recover_action {
                     Some(action) => Some(action::get_action(action, actions)?),
                     None => None,
                 },
                 alarm_config.error_recover_placeholders.clone(),
-                alarm_config.invert,
-                alarm_state_machine,
-                data_sink,
-                alarm_log_id,
+            );
+            alarm.set_invert(alarm_config.invert);
+            alarm.set_cooldown(
+                alarm_config
+                    .cooldown
+                    .map(|x| std::time::Duration::from_secs(x.into())),
+            )?;
+            alarm.set_auto_recovery(
+                alarm_config
+                    .auto_recovery
+                    .map(|x| std::time::Duration::from_secs(x.into())),
+            )?;
+            alarm.set_log_level(alarm_config.log_level.map(log::LevelFilter::from));
+            alarm.set_disabled(alarm_config.disable);
+            alarm.set_metadata(alarm::AlarmMetadata::try_from(alarm_config)?);
+            alarm.set_never_bad_warning(
+                alarm_config.warn_if_never_bad_cycles,
+                match &alarm_config.warn_action {
+                    Some(action) => Some(action::get_action(action, actions)?),
+                    None => None,
+                },
             )?;
+            alarm.set_max_error_cycles(alarm_config.max_error_cycles)?;
+            alarm.set_fast_recovery_warning(
+                alarm_config
+                    .warn_fast_recovery
+                    .then_some(alarm_config.fast_recovery_warn_count),
+            );
+            alarm.set_warm_up_cycles(alarm_config.warm_up_cycles);
+            for source in alarm_config.placeholder_sources.iter() {
+                alarm.add_placeholder_source(alarm::from_placeholder_source_config(source)?);
+            }
+            alarm.set_chain(
+                alarm_config
+                    .depends_on_alarm_id
+                    .as_ref()
+                    .map(|x| alarm::AlarmChain::new(x.clone(), registry.clone())),
+            );
+            alarm.set_registry(registry.clone(), registry_key);
             alarms.push(alarm);
         }
         all_alarms.push(alarms);
     }
-    Ok(Box::new(CheckBase::new(
+    Ok(all_alarms)
+}
+
+fn check_base<T, U>(
+    check_config: &config::Check,
+    data_source: T,
+    all_alarms: Vec<Vec<U>>,
+    actions: &ActionMap,
+) -> Result<Box<dyn Check>>
+where
+    T: DataSource + 'static,
+    U: Alarm<Item = T::Item> + 'static,
+{
+    let mut check = CheckBase::new(
         std::time::Duration::from_secs(check_config.interval.into()),
+        check_config
+            .jitter
+            .map(|x| std::time::Duration::from_secs(x.into())),
+        check_config.cron.as_deref().map(parse_cron).transpose()?,
         check_config.name.clone(),
         check_config
             .timeout
@@ -240,49 +760,572 @@ where
         check_config.placeholders.clone(),
         data_source,
         all_alarms,
-    )?))
+        check_config.max_concurrent_alarms as usize,
+    )?;
+    let backpressure_action = match &check_config.backpressure_action {
+        Some(action) => Some(action::get_action(action, actions)?),
+        None => None,
+    };
+    check.set_backpressure(check_config.backpressure, backpressure_action);
+    Ok(Box::new(check))
+}
+
+// Schedules that never match (before the year 2100), e.g. February 30, are rejected.
+fn parse_cron(text: &str) -> Result<cron::Schedule> {
+    let schedule: cron::Schedule = text
+        .parse()
+        .map_err(|x| Error::Message(format!("Invalid cron schedule '{text}': {x}")))?;
+    if schedule.upcoming(chrono::Utc).next().is_none() {
+        return Err(Error::Message(format!(
+            "Cron schedule '{text}' never matches."
+        )));
+    }
+    Ok(schedule)
 }
 
 pub fn from_check_config(
     check_config: &config::Check,
     actions: &ActionMap,
+    alarm_groups: &AlarmGroupMap,
+    registry: &alarm::AlarmRegistry,
 ) -> Result<Box<dyn Check>> {
     match &check_config.type_ {
         // NOTE Add mapping here when implementing new data source / alarms.
+        // The data source needs the registry, so it cannot be created from the config alone.
+        config::CheckType::AlarmSummary(_) => {
+            let data_source = alarm_summary::AlarmSummary::new(check_config, registry)?;
+            let all_alarms = alarms::<alarm::AlarmSummary>(
+                check_config,
+                data_source.ids(),
+                actions,
+                alarm_groups,
+                registry,
+            )?;
+            check_base(check_config, data_source, all_alarms, actions)
+        }
+        config::CheckType::DnsResolution(_) => factory::<
+            dns_resolution::DnsResolution,
+            alarm::DnsResolution,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
         #[cfg(feature = "docker")]
-        config::CheckType::DockerContainerStatus(_) => factory::<
-            docker_container_status::DockerContainerStatus,
-            alarm::BinaryState,
-        >(check_config, actions),
-        config::CheckType::FilesystemUsage(_) => {
-            factory::<filesystem_usage::FilesystemUsage, alarm::Level>(check_config, actions)
+        config::CheckType::DockerContainerStatus(_) => {
+            factory::<docker_container_status::DockerContainerStatus, alarm::BinaryState>(
+                check_config,
+                actions,
+                alarm_groups,
+                registry,
+            )
+        }
+        #[cfg(feature = "http")]
+        config::CheckType::ElasticsearchQuery(_) => {
+            factory::<
+                elasticsearch_query::ElasticsearchQuery,
+                alarm::MinMaxTracking<alarm::NumericSink<u64>>,
+            >(check_config, actions, alarm_groups, registry)
         }
-        config::CheckType::MemoryUsage(_) => {
-            factory::<memory_usage::MemoryUsage, alarm::Level>(check_config, actions)
+        config::CheckType::FilesystemUsage(_) => factory::<
+            filesystem_usage::FilesystemUsage,
+            alarm::MinMaxTracking<alarm::NumericSink>,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        #[cfg(feature = "http")]
+        config::CheckType::HttpStatus(_) => factory::<http_status::HttpStatus, alarm::HttpStatus>(
+            check_config,
+            actions,
+            alarm_groups,
+            registry,
+        ),
+        #[cfg(feature = "http")]
+        config::CheckType::Jmx(_) => factory::<
+            jmx::Jmx,
+            alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+        >(check_config, actions, alarm_groups, registry),
+        #[cfg(feature = "kafka")]
+        config::CheckType::KafkaConsumerLag(_) => factory::<
+            kafka_consumer_lag::KafkaConsumerLag,
+            alarm::ConsumerLag,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        #[cfg(feature = "kubernetes")]
+        config::CheckType::KubernetesDeployment(_) => {
+            factory::<kubernetes_deployment::KubernetesDeployment, alarm::Level<f64>>(
+                check_config,
+                actions,
+                alarm_groups,
+                registry,
+            )
         }
-        config::CheckType::NetworkThroughput(_) => {
-            factory::<network_throughput::NetworkThroughput, alarm::DataSize>(check_config, actions)
+        #[cfg(feature = "kubernetes")]
+        config::CheckType::KubernetesPod(_) => factory::<
+            kubernetes_pod::KubernetesPod,
+            alarm::Level<f64>,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        config::CheckType::LogFilePattern(_) => {
+            factory::<
+                log_file_pattern::LogFilePattern,
+                alarm::MinMaxTracking<alarm::NumericSink<u64>>,
+            >(check_config, actions, alarm_groups, registry)
         }
-        config::CheckType::PressureAverage(_) => {
-            factory::<pressure_average::PressureAverage, alarm::Level>(check_config, actions)
+        config::CheckType::MemoryUsage(_) => factory::<
+            memory_usage::MemoryUsage,
+            alarm::MinMaxTracking<alarm::NumericSink>,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        config::CheckType::NetworkInterface(_) => {
+            factory::<
+                network_interface::NetworkInterface,
+                alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+            >(check_config, actions, alarm_groups, registry)
         }
+        config::CheckType::NetworkThroughput(_) => factory::<
+            network_throughput::NetworkThroughput,
+            alarm::DataSize,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        config::CheckType::PathExists(_) => factory::<path_exists::PathExists, alarm::TwoState>(
+            check_config,
+            actions,
+            alarm_groups,
+            registry,
+        ),
+        config::CheckType::PortScan(_) => factory::<port_scan::PortScan, alarm::PortScan>(
+            check_config,
+            actions,
+            alarm_groups,
+            registry,
+        ),
+        #[cfg(feature = "postgres")]
+        config::CheckType::Postgres(_) => factory::<
+            postgres::Postgres,
+            alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+        >(check_config, actions, alarm_groups, registry),
+        config::CheckType::PressureAverage(_) => factory::<
+            pressure_average::PressureAverage,
+            alarm::MinMaxTracking<alarm::NumericSink>,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
         config::CheckType::ProcessExitStatus(_) => factory::<
             process_exit_status::ProcessExitStatus,
             alarm::StatusCode,
-        >(check_config, actions),
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        config::CheckType::ProcessMemory(_) => factory::<
+            process_memory::ProcessMemory,
+            alarm::DataSize,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        config::CheckType::ProcessOutput(_) => {
+            factory::<process_output::ProcessOutput, alarm::Regex>(
+                check_config,
+                actions,
+                alarm_groups,
+                registry,
+            )
+        }
+        config::CheckType::ProcessUptime(_) => factory::<
+            process_uptime::ProcessUptime,
+            alarm::TwoState,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        config::CheckType::ProcessValue(process_value) => match &process_value.route_by {
+            Some(route_by) => {
+                let route_by = route_by.clone();
+                routed_factory::<process_value::ProcessValue, alarm::ProcessValue>(
+                    check_config,
+                    actions,
+                    alarm_groups,
+                    registry,
+                    process_value.max_routes.map(|x| x as usize),
+                    move |data: &measurement::ProcessValue| {
+                        data.placeholders().get(&route_by).cloned().ok_or_else(|| {
+                            Error::Message(format!(
+                                "Process did not print placeholder '{route_by}'."
+                            ))
+                        })
+                    },
+                )
+            }
+            None => factory::<process_value::ProcessValue, alarm::ProcessValue>(
+                check_config,
+                actions,
+                alarm_groups,
+                registry,
+            ),
+        },
+        #[cfg(feature = "http")]
+        config::CheckType::PrometheusComparison(_) => {
+            factory::<prometheus_comparison::PrometheusComparison, alarm::ValuePair>(
+                check_config,
+                actions,
+                alarm_groups,
+                registry,
+            )
+        }
+        #[cfg(feature = "http")]
+        config::CheckType::PrometheusQuery(_) => {
+            factory::<
+                prometheus_query::PrometheusQuery,
+                alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+            >(check_config, actions, alarm_groups, registry)
+        }
+        #[cfg(feature = "redis")]
+        config::CheckType::Redis(_) => factory::<
+            redis::Redis,
+            alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+        >(check_config, actions, alarm_groups, registry),
+        #[cfg(feature = "redis")]
+        config::CheckType::RedisLatency(_) => factory::<
+            redis_latency::RedisLatency,
+            alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        #[cfg(feature = "s3")]
+        config::CheckType::S3Bucket(_) => factory::<
+            s3_bucket::S3Bucket,
+            alarm::MinMaxTracking<alarm::NumericSink<u64>>,
+        >(check_config, actions, alarm_groups, registry),
+        #[cfg(feature = "snmp")]
+        config::CheckType::SnmpGet(_) => factory::<
+            snmp_get::SnmpGet,
+            alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+        >(check_config, actions, alarm_groups, registry),
+        #[cfg(feature = "ssl-cert")]
+        config::CheckType::SslCertExpiry(_) => factory::<
+            ssl_cert_expiry::SslCertExpiry,
+            alarm::Level<f64>,
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        config::CheckType::SystemLoad(_) => factory::<
+            system_load::SystemLoad,
+            alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+        >(check_config, actions, alarm_groups, registry),
         config::CheckType::SystemdUnitStatus(_) => factory::<
             systemd_unit_status::SystemdUnitStatus,
             alarm::BinaryState,
-        >(check_config, actions),
+        >(
+            check_config, actions, alarm_groups, registry
+        ),
+        config::CheckType::TcpConnect(_) => factory::<
+            tcp_connect::TcpConnect,
+            alarm::MinMaxTracking<alarm::NumericSink<f64>>,
+        >(check_config, actions, alarm_groups, registry),
         #[cfg(feature = "sensors")]
         config::CheckType::Temperature(_) => {
-            factory::<temperature::Temperature, alarm::Temperature>(check_config, actions)
+            factory::<temperature::Temperature, alarm::Temperature>(
+                check_config,
+                actions,
+                alarm_groups,
+                registry,
+            )
         }
+        config::CheckType::Topology(_) => factory::<topology::Topology, alarm::Topology>(
+            check_config,
+            actions,
+            alarm_groups,
+            registry,
+        ),
     }
-    .map_err(|x| {
-        Error(format!(
-            "Failed to create check '{}' from config: {}",
-            check_config.name, x
-        ))
+    .map_err(|x| Error::CheckFailed {
+        check_id: check_config.name.clone(),
+        source: std::sync::Arc::new(x),
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Takes a while to trigger, like a webhook to a slow server.
+    struct SlowAction(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait]
+    impl action::Action for SlowAction {
+        async fn trigger(&self, _placeholders: PlaceholderMap) -> Result<()> {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    // Virtual time of a cycle in which 4 alarms of each of the 2 IDs trigger the slow action.
+    async fn cycle_time(max_concurrent_alarms: Option<u32>) -> std::time::Duration {
+        let mut text = String::from("name = \"Slow\"\ntype = \"MemoryUsage\"\nmemory = true\n");
+        if let Some(max_concurrent_alarms) = max_concurrent_alarms {
+            text.push_str(&format!(
+                "max_concurrent_alarms = {max_concurrent_alarms}\n"
+            ));
+        }
+        for i in 0..4 {
+            text.push_str(&format!(
+                "[[alarms]]\nname = \"Alarm{i}\"\naction = \"Slow\"\ncycles = 1\nlevel = 1\n"
+            ));
+        }
+        let check_config: config::Check = toml::from_str(&text).unwrap();
+        let triggers = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let actions = ActionMap::from([(
+            String::from("Slow"),
+            std::sync::Arc::new(SlowAction(triggers.clone())) as std::sync::Arc<dyn action::Action>,
+        )]);
+        let data_source = SlowDataSource {
+            delay: std::time::Duration::ZERO,
+            ids: vec![String::from("a"), String::from("b")],
+        };
+        let all_alarms = alarms::<alarm::NumericSink<f64>>(
+            &check_config,
+            data_source.ids(),
+            &actions,
+            &AlarmGroupMap::new(),
+            &alarm::AlarmRegistry::default(),
+        )
+        .unwrap();
+        let mut check = check_base(&check_config, data_source, all_alarms, &actions).unwrap();
+        crate::uptime::init().unwrap();
+        let start = tokio::time::Instant::now();
+        check.trigger().await;
+        let elapsed = start.elapsed();
+        assert_eq!(triggers.load(std::sync::atomic::Ordering::SeqCst), 8);
+        elapsed
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_max_concurrent_alarms() {
+        let millis = std::time::Duration::from_millis;
+        assert_eq!(cycle_time(None).await, millis(800));
+        assert_eq!(cycle_time(Some(2)).await, millis(400));
+        // Bounded by the slowest alarm instead of the sum of all alarms.
+        assert_eq!(cycle_time(Some(8)).await, millis(100));
+        assert_eq!(cycle_time(Some(16)).await, millis(100));
+    }
+
+    // Takes a while to return its data, like an HTTP request to an unresponsive host.
+    struct SlowDataSource {
+        delay: std::time::Duration,
+        ids: Vec<String>,
+    }
+
+    #[async_trait]
+    impl DataSource for SlowDataSource {
+        type Item = f64;
+
+        async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+            tokio::time::sleep(self.delay).await;
+            Ok(vec![Ok(Some(1.5)), Ok(Some(2.5))])
+        }
+
+        fn format_data(&self, data: &Self::Item) -> String {
+            data.to_string()
+        }
+
+        fn ids(&self) -> &[String] {
+            &self.ids[..]
+        }
+    }
+
+    fn slow_check(delay: u64) -> CheckBase<SlowDataSource, AlarmBase<alarm::NumericSink<f64>>> {
+        CheckBase::new(
+            std::time::Duration::from_secs(60),
+            None,
+            None,
+            String::from("Slow"),
+            Some(std::time::Duration::from_secs(5)),
+            PlaceholderMap::new(),
+            SlowDataSource {
+                delay: std::time::Duration::from_secs(delay),
+                ids: vec![String::from("a"), String::from("b")],
+            },
+            Vec::new(),
+            1,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout() {
+        let data = slow_check(4).get_data().await;
+        assert_eq!(data.len(), 2);
+        assert_eq!(*data[1].as_ref().unwrap(), Some(2.5));
+        let start = tokio::time::Instant::now();
+        let data = slow_check(3600).get_data().await;
+        assert_eq!(start.elapsed(), std::time::Duration::from_secs(5));
+        assert_eq!(data.len(), 2);
+        for data in data {
+            assert_eq!(
+                data.unwrap_err().to_string(),
+                "Check 'Slow' timed out after 5 seconds."
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_recorded_data() {
+        let data = parse_recorded_data::<f64>(
+            "2024-01-01T00:00:00Z 1.5\n\n2024-01-01T01:00:00+01:00\t-2\n",
+        )
+        .unwrap();
+        assert_eq!(data.len(), 2);
+        assert_eq!(data[0].timestamp, data[1].timestamp);
+        assert_eq!(data[1].data, -2.);
+        assert_eq!(
+            parse_recorded_data::<f64>("2024-01-01T00:00:00Z\n")
+                .err()
+                .unwrap()
+                .to_string(),
+            "Line 1: Expected a timestamp and a value."
+        );
+        assert!(parse_recorded_data::<f64>("yesterday 1").is_err());
+        assert!(parse_recorded_data::<measurement::Text>("2024-01-01T00:00:00Z foo").is_err());
+    }
+
+    #[test]
+    fn test_max_concurrent_alarms_zero() {
+        let check_config: config::Check = toml::from_str(
+            r#"
+            name = "Ports"
+            type = "PortScan"
+            host = "127.0.0.1"
+            ports = [22]
+            max_concurrent_alarms = 0
+            "#,
+        )
+        .unwrap();
+        let result = from_check_config(
+            &check_config,
+            &ActionMap::new(),
+            &AlarmGroupMap::new(),
+            &alarm::AlarmRegistry::default(),
+        );
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .ends_with("'max_concurrent_alarms' cannot be 0."));
+    }
+
+    #[tokio::test]
+    async fn test_routed_alarms() {
+        let check_config: config::Check = toml::from_str(
+            r#"
+            name = "Devices"
+            type = "ProcessValue"
+            path = "/bin/sh"
+            arguments = ["-c", "echo 20; echo device=sda"]
+            route_by = "device"
+            [[alarms]]
+            name = "Alarm"
+            action = "Action"
+            level = 10
+            "#,
+        )
+        .unwrap();
+        let action = action::TestAction::new();
+        let actions = ActionMap::from([(
+            String::from("Action"),
+            std::sync::Arc::new(action.clone()) as std::sync::Arc<dyn action::Action>,
+        )]);
+        let registry = alarm::AlarmRegistry::default();
+        let mut check =
+            from_check_config(&check_config, &actions, &AlarmGroupMap::new(), &registry).unwrap();
+        crate::uptime::init().unwrap();
+        check.trigger().await;
+        action.assert_triggered_once();
+        assert_eq!(
+            action.last_placeholders().unwrap()["alarm_name"],
+            "Alarm (sda)"
+        );
+        assert_eq!(
+            registry.read().unwrap().get("Devices/Alarm/sh-sda"),
+            Some(&alarm::StateKind::Bad)
+        );
+    }
+
+    #[test]
+    fn test_cron() {
+        let check = |options: &str| {
+            let check_config: config::Check = toml::from_str(&format!(
+                "name = \"Ports\"\ntype = \"PortScan\"\nhost = \"127.0.0.1\"\nports = [22]\n{options}"
+            ))
+            .unwrap();
+            from_check_config(
+                &check_config,
+                &ActionMap::new(),
+                &AlarmGroupMap::new(),
+                &alarm::AlarmRegistry::default(),
+            )
+        };
+        assert!(check("").unwrap().cron().is_none());
+        assert_eq!(
+            check("cron = \"0 0 3 * * *\"")
+                .unwrap()
+                .cron()
+                .unwrap()
+                .to_string(),
+            "0 0 3 * * *"
+        );
+        assert!(check("cron = \"@daily\"").is_ok());
+        assert!(check("cron = \"0 3 * * *\"").is_err());
+        assert!(check("cron = \"0 0 0 30 Feb *\"").is_err());
+        assert!(check("cron = \"0 0 3 * * *\"\njitter = 10").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_backpressure() {
+        let check_config: config::Check = toml::from_str(
+            r#"
+            name = "Ports"
+            type = "PortScan"
+            host = "127.0.0.1"
+            ports = [22]
+            backpressure = "Wait"
+            backpressure_action = "Action"
+            placeholders = {"team" = "ops"}
+            "#,
+        )
+        .unwrap();
+        let action = action::TestAction::new();
+        let actions = ActionMap::from([(
+            String::from("Action"),
+            std::sync::Arc::new(action.clone()) as std::sync::Arc<dyn action::Action>,
+        )]);
+        let check = from_check_config(
+            &check_config,
+            &actions,
+            &AlarmGroupMap::new(),
+            &alarm::AlarmRegistry::default(),
+        )
+        .unwrap();
+        let backpressure = check.backpressure();
+        assert_eq!(backpressure.policy, config::Backpressure::Wait);
+        crate::uptime::init().unwrap();
+        backpressure.missed(2).await;
+        action.assert_triggered_once();
+        let placeholders = action.last_placeholders().unwrap();
+        assert_eq!(placeholders["check_name"], "Ports");
+        assert_eq!(placeholders["team"], "ops");
+        assert_eq!(placeholders["alarm_queue_depth"], "2");
+        let result = from_check_config(
+            &check_config,
+            &ActionMap::new(),
+            &AlarmGroupMap::new(),
+            &alarm::AlarmRegistry::default(),
+        );
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .ends_with("Action 'Action' not found."));
+    }
+}
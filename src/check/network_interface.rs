@@ -0,0 +1,256 @@
+use super::DataSource;
+use crate::{config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+const NET_DEV_PATH: &str = "/proc/net/dev";
+
+static PARSE_ERROR: &str = "Could not parse net/dev file.";
+
+// Bytes or packets per second, computed from the counters of two consecutive readings.
+pub struct NetworkInterface {
+    id: Vec<String>,
+    interfaces: Vec<String>,
+    received: bool,
+    sent: bool,
+    packets: bool,
+    log_format: config::DataSizeFormat,
+    last_received: Vec<RateCounter>,
+    last_sent: Vec<RateCounter>,
+}
+
+impl TryFrom<&config::Check> for NetworkInterface {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::NetworkInterface(network_interface) = &check.type_ {
+            if !network_interface.received && !network_interface.sent {
+                Err(Error::Message(String::from(
+                    "At least one of 'received' or 'sent' needs to be enabled.",
+                )))
+            } else if network_interface.interfaces.is_empty() {
+                Err(Error::invalid_config("interfaces", "cannot be empty."))
+            } else {
+                let mut id = Vec::new();
+                for interface in network_interface.interfaces.iter() {
+                    if network_interface.received {
+                        id.push(format!("{interface}[rx]"));
+                    }
+                    if network_interface.sent {
+                        id.push(format!("{interface}[tx]"));
+                    }
+                }
+                let count = network_interface.interfaces.len();
+                Ok(Self {
+                    id,
+                    interfaces: network_interface.interfaces.clone(),
+                    received: network_interface.received,
+                    sent: network_interface.sent,
+                    packets: network_interface.packets,
+                    log_format: network_interface.log_format,
+                    last_received: vec![RateCounter::default(); count],
+                    last_sent: vec![RateCounter::default(); count],
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct RateCounter {
+    last: Option<(u64, std::time::Instant)>,
+}
+
+impl RateCounter {
+    // Counters that wrap around are either 64 bit or 32 bit (older drivers). A 32 bit counter
+    // can be told apart by its last value that fits into 32 bits.
+    fn update(&mut self, value: u64, now: std::time::Instant) -> Option<f64> {
+        let res = self.last.map(|(last, last_time)| {
+            let delta = if value >= last {
+                value - last
+            } else if last <= u32::MAX.into() {
+                u64::from(u32::MAX) - last + 1 + value
+            } else {
+                u64::MAX - last + 1 + value
+            };
+            let seconds = now.duration_since(last_time).as_secs_f64();
+            if seconds > 0.0 {
+                delta as f64 / seconds
+            } else {
+                0.0
+            }
+        });
+        self.last = Some((value, now));
+        res
+    }
+}
+
+#[cfg_attr(test, derive(PartialEq, Debug))]
+struct InterfaceCounters {
+    rx_bytes: u64,
+    rx_packets: u64,
+    tx_bytes: u64,
+    tx_packets: u64,
+}
+
+fn parse_net_dev(text: &str, interface: &str) -> Result<InterfaceCounters> {
+    // The first two lines are the table header.
+    for line in text.lines().skip(2) {
+        let (name, fields) = line
+            .split_once(':')
+            .ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?;
+        if name.trim() != interface {
+            continue;
+        }
+        let fields = fields
+            .split_whitespace()
+            .map(|x| x.parse::<u64>())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| Error::Message(PARSE_ERROR.to_string()))?;
+        if fields.len() < 10 {
+            return Err(Error::Message(PARSE_ERROR.to_string()));
+        }
+        return Ok(InterfaceCounters {
+            rx_bytes: fields[0],
+            rx_packets: fields[1],
+            tx_bytes: fields[8],
+            tx_packets: fields[9],
+        });
+    }
+    Err(Error::Message(format!(
+        "Interface '{interface}' not found."
+    )))
+}
+
+#[async_trait]
+impl DataSource for NetworkInterface {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let net_dev = tokio::fs::read_to_string(NET_DEV_PATH).await.map_err(|x| {
+            Error::Message(format!("Could not open {NET_DEV_PATH} for reading: {x}"))
+        })?;
+        let now = std::time::Instant::now();
+        let mut res = Vec::new();
+        for (last_sent, (last_received, interface)) in self
+            .last_sent
+            .iter_mut()
+            .zip(self.last_received.iter_mut().zip(self.interfaces.iter()))
+        {
+            let counters = parse_net_dev(&net_dev, interface);
+            if self.received {
+                res.push(counters.as_ref().map_err(|x| x.clone()).map(|x| {
+                    last_received.update(
+                        if self.packets {
+                            x.rx_packets
+                        } else {
+                            x.rx_bytes
+                        },
+                        now,
+                    )
+                }));
+            }
+            if self.sent {
+                res.push(counters.as_ref().map_err(|x| x.clone()).map(|x| {
+                    last_sent.update(
+                        if self.packets {
+                            x.tx_packets
+                        } else {
+                            x.tx_bytes
+                        },
+                        now,
+                    )
+                }));
+            }
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        if self.packets {
+            return format!("throughput {data:.2} packets/s");
+        }
+        // Fractions of bytes are not shown.
+        let data_size = measurement::DataSize::new(data.round() as u64).unwrap_or_default();
+        let throughput = match self.log_format {
+            config::DataSizeFormat::Binary => data_size.as_string_binary(),
+            config::DataSizeFormat::Decimal => data_size.as_string_decimal(),
+            config::DataSizeFormat::Bytes => format!("{data_size}"),
+        };
+        format!("throughput {throughput}/s")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NET_DEV: &str = "\
+Inter-|   Receive                                                |  Transmit
+ face |bytes    packets errs drop fifo frame compressed multicast|bytes    packets errs drop fifo colls carrier compressed
+    lo:  156509     266    0    0    0     0          0         0   156509     266    0    0    0     0       0          0
+  eth0: 1000000    2000    0    0    0     0          0         0   500000    1000    0    0    0     0       0          0
+";
+
+    #[test]
+    fn test_parse_net_dev() {
+        assert_eq!(
+            parse_net_dev(NET_DEV, "eth0").unwrap(),
+            InterfaceCounters {
+                rx_bytes: 1000000,
+                rx_packets: 2000,
+                tx_bytes: 500000,
+                tx_packets: 1000,
+            }
+        );
+        assert!(parse_net_dev(NET_DEV, "eth1").is_err());
+        assert!(parse_net_dev(NET_DEV, "eth").is_err());
+    }
+
+    #[test]
+    fn test_rate_counter() {
+        let start = std::time::Instant::now();
+        let mut counter = RateCounter::default();
+        assert_eq!(counter.update(1000000, start), None);
+        let second = start + std::time::Duration::from_secs(2);
+        assert_eq!(counter.update(3000000, second), Some(1000000.0));
+        // 32 bit counter wrap-around.
+        let mut counter = RateCounter::default();
+        counter.update(u64::from(u32::MAX) - 99, start);
+        assert_eq!(counter.update(100, second), Some(100.0));
+        // 64 bit counter wrap-around.
+        let mut counter = RateCounter::default();
+        counter.update(u64::MAX - 99, start);
+        assert_eq!(counter.update(100, second), Some(100.0));
+    }
+
+    #[test]
+    fn test_format_data() {
+        let mut network_interface = NetworkInterface {
+            id: vec![String::from("eth0[rx]")],
+            interfaces: vec![String::from("eth0")],
+            received: true,
+            sent: false,
+            packets: false,
+            log_format: config::DataSizeFormat::Decimal,
+            last_received: vec![RateCounter::default()],
+            last_sent: vec![RateCounter::default()],
+        };
+        assert_eq!(
+            network_interface.format_data(&1234567.8),
+            "throughput 1.235MB/s"
+        );
+        network_interface.packets = true;
+        assert_eq!(
+            network_interface.format_data(&12.0),
+            "throughput 12.00 packets/s"
+        );
+    }
+}
@@ -20,13 +20,10 @@ impl NetworkThroughput {
     pub async fn bytes_from_file(path: &str) -> Result<Item> {
         let buffer = tokio::fs::read_to_string(path)
             .await
-            .map_err(|x| Error(format!("Could not open {path} for reading: {x}")))?;
-        Item::new(
-            buffer
-                .trim()
-                .parse::<u64>()
-                .map_err(|x| Error(format!("Could not parse interface statistics file: {x}")))?,
-        )
+            .map_err(|x| Error::Message(format!("Could not open {path} for reading: {x}")))?;
+        Item::new(buffer.trim().parse::<u64>().map_err(|x| {
+            Error::Message(format!("Could not parse interface statistics file: {x}"))
+        })?)
     }
 }
 
@@ -36,7 +33,7 @@ impl TryFrom<&config::Check> for NetworkThroughput {
     fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
         if let config::CheckType::NetworkThroughput(network_throughput) = &check.type_ {
             if !network_throughput.received && !network_throughput.sent {
-                Err(Error(String::from(
+                Err(Error::Message(String::from(
                     "At least one of 'received' or 'sent' needs to be enabled.",
                 )))
             } else {
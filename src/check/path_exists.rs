@@ -0,0 +1,57 @@
+use super::DataSource;
+use crate::config;
+use crate::measurement::Measurement;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+pub struct PathExists {
+    paths: Vec<String>,
+}
+
+impl TryFrom<&config::Check> for PathExists {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::PathExists(path_exists) = &check.type_ {
+            if path_exists.paths.iter().any(|x| x.is_empty()) {
+                Err(Error::invalid_config(
+                    "paths",
+                    "cannot contain empty paths.",
+                ))
+            } else {
+                Ok(Self {
+                    paths: path_exists.paths.clone(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for PathExists {
+    type Item = bool;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let mut res = Vec::new();
+        for path in self.paths.iter() {
+            // Errors other than "not found" (e.g. missing permissions) are reported as errors.
+            res.push(
+                tokio::fs::try_exists(path)
+                    .await
+                    .map(Some)
+                    .map_err(|x| Error::Message(format!("Could not check path '{path}': {x}"))),
+            );
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("state {}", data.format())
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.paths[..]
+    }
+}
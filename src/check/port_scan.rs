@@ -0,0 +1,163 @@
+use super::DataSource;
+use crate::{config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+// Ports of a host that are not in the expected state (open or closed).
+pub struct PortScan {
+    id: Vec<String>,
+    ports: Vec<u16>,
+    mode: config::PortMode,
+    connect_timeout: std::time::Duration,
+}
+
+impl TryFrom<&config::Check> for PortScan {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::PortScan(port_scan) = &check.type_ {
+            if port_scan.host.is_empty() {
+                Err(Error::invalid_config("host", "cannot be empty."))
+            } else if port_scan.ports.is_empty() {
+                Err(Error::invalid_config("ports", "cannot be empty."))
+            } else if port_scan.connect_timeout == 0 {
+                Err(Error::invalid_config("connect_timeout", "cannot be 0."))
+            } else {
+                Ok(Self {
+                    id: vec![port_scan.host.clone()],
+                    ports: port_scan.ports.clone(),
+                    mode: port_scan.mode,
+                    connect_timeout: std::time::Duration::from_secs(
+                        port_scan.connect_timeout.into(),
+                    ),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+// Refused and timed out connections both count as closed.
+async fn is_open(
+    address: std::net::IpAddr,
+    port: u16,
+    connect_timeout: std::time::Duration,
+) -> bool {
+    matches!(
+        tokio::time::timeout(
+            connect_timeout,
+            tokio::net::TcpStream::connect((address, port))
+        )
+        .await,
+        Ok(Ok(_))
+    )
+}
+
+impl PortScan {
+    async fn scan(&self) -> Result<measurement::PortScan> {
+        let host = &self.id[0];
+        let address = tokio::net::lookup_host((host.as_str(), 0))
+            .await
+            .map_err(|x| Error::Message(format!("Could not resolve '{host}': {x}")))?
+            .next()
+            .ok_or_else(|| Error::Message(format!("Could not resolve '{host}'.")))?
+            .ip();
+        let expect_open = self.mode == config::PortMode::MustBeOpen;
+        // All ports are probed at the same time so that the timeouts don't add up.
+        let probes: Vec<_> = self
+            .ports
+            .iter()
+            .map(|port| tokio::spawn(is_open(address, *port, self.connect_timeout)))
+            .collect();
+        let mut offending_ports = Vec::new();
+        for (port, probe) in self.ports.iter().zip(probes) {
+            let open = probe
+                .await
+                .map_err(|x| Error::Message(format!("Could not probe port {port}: {x}")))?;
+            if open != expect_open {
+                offending_ports.push(*port);
+            }
+        }
+        measurement::PortScan::new(offending_ports)
+    }
+}
+
+#[async_trait]
+impl DataSource for PortScan {
+    type Item = measurement::PortScan;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        Ok(vec![self.scan().await.map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        data.to_string()
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alarm::{DataSink, SinkDecision};
+
+    fn port_scan(host: &str, ports: Vec<u16>, mode: config::PortMode) -> PortScan {
+        PortScan {
+            id: vec![String::from(host)],
+            ports,
+            mode,
+            connect_timeout: std::time::Duration::from_secs(1),
+        }
+    }
+
+    async fn offending_ports(check: &mut PortScan) -> Vec<u16> {
+        check
+            .get_data()
+            .await
+            .unwrap()
+            .remove(0)
+            .unwrap()
+            .unwrap()
+            .data()
+    }
+
+    #[tokio::test]
+    async fn test_scan() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = listener.local_addr().unwrap().port();
+        // Bound but not listening, so the connection is refused.
+        let refused = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = refused.local_addr().unwrap().port();
+        drop(refused);
+
+        let mut check = port_scan(
+            "127.0.0.1",
+            vec![open, closed],
+            config::PortMode::MustBeOpen,
+        );
+        assert_eq!(offending_ports(&mut check).await, [closed]);
+        let mut check = port_scan(
+            "127.0.0.1",
+            vec![open, closed],
+            config::PortMode::MustBeClosed,
+        );
+        assert_eq!(offending_ports(&mut check).await, [open]);
+        let mut check = port_scan("127.0.0.1", vec![open], config::PortMode::MustBeOpen);
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        assert!(matches!(
+            crate::alarm::PortScan.put_data(&data).unwrap(),
+            SinkDecision::Good
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_error() {
+        let mut check = port_scan("invalid..host", vec![22], config::PortMode::MustBeOpen);
+        assert!(check.get_data().await.unwrap()[0].is_err());
+    }
+}
@@ -66,7 +66,8 @@ impl PressureAverage {
                     {
                         match pressure.full {
                             Some(full) => self.add_data_from_line(&full, res),
-                            _ => self.add_data_from_error(&Error(PARSE_ERROR.to_string()), res),
+                            _ => self
+                                .add_data_from_error(&Error::Message(PARSE_ERROR.to_string()), res),
                         }
                     }
                 }
@@ -90,11 +91,11 @@ impl TryFrom<&config::Check> for PressureAverage {
                 && pressure.io == config::PressureChoice::None
                 && pressure.memory == config::PressureChoice::None
             {
-                Err(Error(String::from(
+                Err(Error::Message(String::from(
                     "At least one of 'cpu', 'io', or 'memory' needs to be enabled.",
                 )))
             } else if !pressure.avg10 && !pressure.avg60 && !pressure.avg300 {
-                Err(Error(String::from(
+                Err(Error::Message(String::from(
                     "At least one of 'avg10', 'avg60', or 'avg300' needs to be enabled.",
                 )))
             } else {
@@ -206,7 +207,7 @@ impl PressureFileContent {
     async fn try_from_file(path: &str) -> Result<Self> {
         let buffer = tokio::fs::read_to_string(path)
             .await
-            .map_err(|x| Error(format!("Could not open {path} for reading: {x}")))?;
+            .map_err(|x| Error::Message(format!("Could not open {path} for reading: {x}")))?;
         Self::try_from(&*buffer)
     }
 }
@@ -225,7 +226,7 @@ impl TryFrom<&str> for PressureFileContent {
             }
         }
         Ok(Self {
-            some: some.ok_or_else(|| Error(PARSE_ERROR.to_string()))?,
+            some: some.ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?,
             full,
         })
     }
@@ -241,15 +242,19 @@ struct PressureFileLine {
 impl PressureFileLine {
     fn parse_avg_str(avg_str: &str, label: &str) -> Result<u8> {
         let mut parts = avg_str.split('=');
-        if parts.next().ok_or_else(|| Error(PARSE_ERROR.to_string()))? != label {
-            return Err(Error(PARSE_ERROR.to_string()));
+        if parts
+            .next()
+            .ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?
+            != label
+        {
+            return Err(Error::Message(PARSE_ERROR.to_string()));
         }
         match parts.next() {
             Some(avg_str) => avg_str
                 .parse::<f32>()
                 .map(|x| x as u8)
-                .map_err(|x| Error(x.to_string())),
-            _ => Err(Error(PARSE_ERROR.into())),
+                .map_err(|x| Error::Message(x.to_string())),
+            _ => Err(Error::Message(PARSE_ERROR.into())),
         }
     }
 }
@@ -262,18 +267,24 @@ impl TryFrom<&str> for PressureFileLine {
         let label = match parts.next() {
             Some("some") => Ok(PressureLabel::Some),
             Some("full") => Ok(PressureLabel::Full),
-            _ => Err(Error(PARSE_ERROR.into())),
+            _ => Err(Error::Message(PARSE_ERROR.into())),
         }?;
         let avg10 = Self::parse_avg_str(
-            parts.next().ok_or_else(|| Error(PARSE_ERROR.to_string()))?,
+            parts
+                .next()
+                .ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?,
             "avg10",
         )?;
         let avg60 = Self::parse_avg_str(
-            parts.next().ok_or_else(|| Error(PARSE_ERROR.to_string()))?,
+            parts
+                .next()
+                .ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?,
             "avg60",
         )?;
         let avg300 = Self::parse_avg_str(
-            parts.next().ok_or_else(|| Error(PARSE_ERROR.to_string()))?,
+            parts
+                .next()
+                .ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?,
             "avg300",
         )?;
         Ok(PressureFileLine {
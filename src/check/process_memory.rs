@@ -0,0 +1,235 @@
+use super::DataSource;
+use crate::{config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+type Item = measurement::DataSize;
+
+// Memory usage of processes, either identified by their PID or by a glob pattern of their name.
+pub struct ProcessMemory {
+    id: Vec<String>,
+    pids: Vec<u32>,
+    names: Vec<String>,
+    rss: bool,
+    swap: bool,
+    peak: bool,
+    log_format: config::DataSizeFormat,
+}
+
+impl TryFrom<&config::Check> for ProcessMemory {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::ProcessMemory(process_memory) = &check.type_ {
+            if process_memory.pids.is_empty() && process_memory.names.is_empty() {
+                Err(Error::Message(String::from(
+                    "At least one of 'pids' or 'names' must be set.",
+                )))
+            } else if process_memory.pids.contains(&0) {
+                Err(Error::invalid_config("pids", "cannot contain 0."))
+            } else if process_memory.names.iter().any(|x| x.is_empty()) {
+                Err(Error::invalid_config(
+                    "names",
+                    "cannot contain empty names.",
+                ))
+            } else if !process_memory.rss && !process_memory.swap && !process_memory.peak {
+                Err(Error::Message(String::from(
+                    "At least one of 'rss', 'swap' or 'peak' needs to be enabled.",
+                )))
+            } else {
+                let mut id = Vec::new();
+                for process in process_memory
+                    .pids
+                    .iter()
+                    .map(|x| x.to_string())
+                    .chain(process_memory.names.iter().cloned())
+                {
+                    if process_memory.rss {
+                        id.push(format!("{process}[rss]"));
+                    }
+                    if process_memory.swap {
+                        id.push(format!("{process}[swap]"));
+                    }
+                    if process_memory.peak {
+                        id.push(format!("{process}[peak]"));
+                    }
+                }
+                Ok(Self {
+                    id,
+                    pids: process_memory.pids.clone(),
+                    names: process_memory.names.clone(),
+                    rss: process_memory.rss,
+                    swap: process_memory.swap,
+                    peak: process_memory.peak,
+                    log_format: process_memory.log_format,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+// Memory fields of `/proc/<pid>/status` in bytes.
+#[derive(Debug, PartialEq)]
+struct Status {
+    rss: u64,
+    swap: u64,
+    peak: u64,
+}
+
+impl Status {
+    fn parse(status: &str) -> Result<Self> {
+        let field = |name: &str| -> Result<u64> {
+            // The values are always given in kB (which are actually KiB).
+            let line = status
+                .lines()
+                .find_map(|x| x.strip_prefix(name)?.strip_prefix(':'))
+                .ok_or_else(|| {
+                    Error::Message(format!("Could not find {name} in process status."))
+                })?;
+            line.trim()
+                .strip_suffix("kB")
+                .and_then(|x| x.trim().parse::<u64>().ok())
+                .map(|x| x * 1024)
+                .ok_or_else(|| Error::Message(format!("Could not parse {name} in process status.")))
+        };
+        Ok(Self {
+            rss: field("VmRSS")?,
+            swap: field("VmSwap")?,
+            peak: field("VmPeak")?,
+        })
+    }
+}
+
+#[cfg(target_os = "linux")]
+async fn read_status(pid: u32) -> Result<Status> {
+    match tokio::fs::read_to_string(format!("/proc/{pid}/status")).await {
+        Ok(status) => Status::parse(&status),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(Error::Message(format!(
+            "Process with PID {pid} does not exist."
+        ))),
+        Err(err) => Err(Error::Message(format!(
+            "Could not read status of PID {pid}: {err}"
+        ))),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn read_status(_pid: u32) -> Result<Status> {
+    Err(Error::Message(String::from("Only supported on Linux.")))
+}
+
+// The process is looked up again in every cycle, so restarted processes are found as well.
+async fn read_status_by_name(pattern: &str) -> Result<Status> {
+    match super::process_uptime::find_pid(pattern).await? {
+        Some(pid) => read_status(pid).await,
+        None => Err(Error::Message(format!("No process matches '{pattern}'."))),
+    }
+}
+
+#[async_trait]
+impl DataSource for ProcessMemory {
+    type Item = Item;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let mut statuses = Vec::new();
+        for pid in self.pids.iter() {
+            statuses.push(read_status(*pid).await);
+        }
+        for name in self.names.iter() {
+            statuses.push(read_status_by_name(name).await);
+        }
+        let mut res = Vec::new();
+        for status in statuses.iter() {
+            let value = |field: fn(&Status) -> u64| {
+                status
+                    .as_ref()
+                    .map_err(|x| x.clone())
+                    .and_then(|x| Item::new(field(x)))
+                    .map(Some)
+            };
+            if self.rss {
+                res.push(value(|x| x.rss));
+            }
+            if self.swap {
+                res.push(value(|x| x.swap));
+            }
+            if self.peak {
+                res.push(value(|x| x.peak));
+            }
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        let memory = match self.log_format {
+            config::DataSizeFormat::Binary => data.as_string_binary(),
+            config::DataSizeFormat::Decimal => data.as_string_decimal(),
+            config::DataSizeFormat::Bytes => format!("{data}"),
+        };
+        format!("memory {memory}")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let status = "Name:\tminmon\nVmPeak:\t  12345 kB\nVmSize:\t  12000 kB\nVmRSS:\t    100 kB\nVmSwap:\t      0 kB\n";
+        assert_eq!(
+            Status::parse(status).unwrap(),
+            Status {
+                rss: 100 * 1024,
+                swap: 0,
+                peak: 12345 * 1024,
+            }
+        );
+        assert!(Status::parse("Name:\tkthreadd\n").is_err());
+        assert!(Status::parse("VmPeak:\tfoo kB\nVmRSS:\t1 kB\nVmSwap:\t1 kB\n").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_memory() {
+        let mut check = ProcessMemory {
+            id: vec![String::from("self[rss]"), String::from("self[peak]")],
+            pids: vec![std::process::id()],
+            names: Vec::new(),
+            rss: true,
+            swap: false,
+            peak: true,
+            log_format: config::DataSizeFormat::Binary,
+        };
+        let data = check.get_data().await.unwrap();
+        let rss = data[0].as_ref().unwrap().unwrap().data();
+        let peak = data[1].as_ref().unwrap().unwrap().data();
+        // Between 1 MiB and 16 GiB is plausible for the test binary.
+        assert!((1 << 20..16 << 30).contains(&rss));
+        assert!(peak >= rss);
+
+        let mut check = ProcessMemory {
+            names: vec![String::from("no-such-process-*")],
+            pids: Vec::new(),
+            id: vec![String::from("no-such-process-*[rss]")],
+            ..check
+        };
+        assert!(check.get_data().await.unwrap()[0].is_err());
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap();
+        assert!(read_status(child.id()).await.is_ok());
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(read_status(child.id()).await.is_err());
+    }
+}
@@ -0,0 +1,47 @@
+use super::DataSource;
+use crate::process::ProcessConfig;
+use crate::{config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+pub struct ProcessOutput {
+    id: Vec<String>,
+    process_config: ProcessConfig,
+}
+
+impl TryFrom<&config::Check> for ProcessOutput {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::ProcessOutput(process_output) = &check.type_ {
+            let process_config = ProcessConfig::try_from(&process_output.process_config)?;
+            Ok(Self {
+                id: vec![process_config.file_name().map(|x| x.into())?],
+                process_config,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for ProcessOutput {
+    type Item = measurement::Text;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let output = self.process_config.run_stdout().await?;
+        // Strip the trailing newline so patterns can be anchored with '$'.
+        let output = String::from(output.trim_end());
+        Ok(vec![Self::Item::new(output).map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("output '{}'", data.format())
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
@@ -0,0 +1,187 @@
+use super::DataSource;
+use crate::config;
+use crate::measurement::Measurement;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Whether a process is alive, either identified by its PID or by a glob pattern of its name.
+pub struct ProcessUptime {
+    pids: Vec<u32>,
+    names: Vec<String>,
+    ids: Vec<String>,
+}
+
+impl TryFrom<&config::Check> for ProcessUptime {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::ProcessUptime(process_uptime) = &check.type_ {
+            if process_uptime.pids.is_empty() && process_uptime.names.is_empty() {
+                Err(Error::Message(String::from(
+                    "At least one of 'pids' or 'names' must be set.",
+                )))
+            } else if process_uptime.pids.contains(&0) {
+                Err(Error::invalid_config("pids", "cannot contain 0."))
+            } else if process_uptime.names.iter().any(|x| x.is_empty()) {
+                Err(Error::invalid_config(
+                    "names",
+                    "cannot contain empty names.",
+                ))
+            } else {
+                Ok(Self {
+                    pids: process_uptime.pids.clone(),
+                    names: process_uptime.names.clone(),
+                    ids: process_uptime
+                        .pids
+                        .iter()
+                        .map(|x| x.to_string())
+                        .chain(process_uptime.names.iter().cloned())
+                        .collect(),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+// Matches `*` (any number of characters) and `?` (exactly one character).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` in the pattern and the text position it is matched up to.
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    t = matched + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// Zombies are dead processes that have not been reaped by their parent yet.
+#[cfg(target_os = "linux")]
+async fn pid_alive(pid: u32) -> Result<bool> {
+    match tokio::fs::read_to_string(format!("/proc/{pid}/stat")).await {
+        // The state follows the command name in parentheses, which can contain any characters.
+        Ok(stat) => Ok(!stat
+            .rsplit_once(')')
+            .is_some_and(|(_, rest)| rest.trim_start().starts_with(['Z', 'X']))),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(Error::Message(format!(
+            "Could not read stat of PID {pid}: {err}"
+        ))),
+    }
+}
+
+// The first living process whose name matches the pattern. Also used by the ProcessMemory check.
+#[cfg(target_os = "linux")]
+pub(super) async fn find_pid(pattern: &str) -> Result<Option<u32>> {
+    let mut entries = tokio::fs::read_dir("/proc")
+        .await
+        .map_err(|x| Error::Message(format!("Could not list processes: {x}")))?;
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|x| Error::Message(format!("Could not list processes: {x}")))?
+    {
+        let Some(pid) = entry.file_name().to_str().and_then(|x| x.parse().ok()) else {
+            continue;
+        };
+        // The process can exit while scanning, so read errors are ignored.
+        let Ok(comm) = tokio::fs::read_to_string(entry.path().join("comm")).await else {
+            continue;
+        };
+        if glob_match(pattern, comm.trim_end_matches('\n')) && pid_alive(pid).await? {
+            return Ok(Some(pid));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn pid_alive(_pid: u32) -> Result<bool> {
+    Err(Error::Message(String::from("Only supported on Linux.")))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) async fn find_pid(_pattern: &str) -> Result<Option<u32>> {
+    Err(Error::Message(String::from("Only supported on Linux.")))
+}
+
+#[async_trait]
+impl DataSource for ProcessUptime {
+    type Item = bool;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let mut res = Vec::new();
+        for pid in self.pids.iter() {
+            res.push(pid_alive(*pid).await.map(Some));
+        }
+        for name in self.names.iter() {
+            res.push(find_pid(name).await.map(|x| Some(x.is_some())));
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("state {}", data.format())
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.ids[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("nginx", "nginx"));
+        assert!(!glob_match("nginx", "nginx2"));
+        assert!(glob_match("nginx*", "nginx: worker"));
+        assert!(glob_match("*worker", "nginx: worker"));
+        assert!(glob_match("ngi?x", "nginx"));
+        assert!(glob_match("*g*n*", "nginx"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+        assert!(!glob_match("n*z", "nginx"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_process_uptime() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("60")
+            .spawn()
+            .unwrap();
+        let mut check = ProcessUptime {
+            pids: vec![child.id()],
+            names: vec![String::from("sle?p")],
+            ids: vec![child.id().to_string(), String::from("sle?p")],
+        };
+        let data = check.get_data().await.unwrap();
+        assert!(matches!(data[..], [Ok(Some(true)), Ok(Some(true))]));
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(!pid_alive(child.id()).await.unwrap());
+    }
+}
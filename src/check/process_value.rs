@@ -0,0 +1,166 @@
+use super::DataSource;
+use crate::process::ProcessConfig;
+use crate::{config, measurement, PlaceholderMap};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+pub struct ProcessValue {
+    id: Vec<String>,
+    value_type: config::ValueType,
+    process_config: ProcessConfig,
+}
+
+impl TryFrom<&config::Check> for ProcessValue {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::ProcessValue(process_value) = &check.type_ {
+            if process_value.route_by.as_deref() == Some("") {
+                return Err(Error::invalid_config("route_by", "cannot be empty."));
+            }
+            let process_config = ProcessConfig::try_from(&process_value.process_config)?;
+            Ok(Self {
+                id: vec![process_config.file_name().map(|x| x.into())?],
+                value_type: process_value.value_type,
+                process_config,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+// The first line is the value. The optional second line contains placeholders separated by
+// whitespace, e.g. `load=0.5 users=3`. Further lines are ignored, like the long output of Nagios
+// plugins.
+fn parse_output(output: &str, value_type: config::ValueType) -> Result<measurement::ProcessValue> {
+    let mut lines = output.lines();
+    let value = lines.next().unwrap_or_default().trim();
+    let value = match value_type {
+        config::ValueType::Float => value.parse::<f64>().ok(),
+        config::ValueType::Integer => value.parse::<i64>().ok().map(|x| x as f64),
+    }
+    .ok_or_else(|| Error::Message(format!("Could not parse '{value}' as {value_type:?}.")))?;
+    let mut placeholders = PlaceholderMap::new();
+    for placeholder in lines.next().unwrap_or_default().split_whitespace() {
+        match placeholder.split_once('=') {
+            Some((name, value)) if !name.is_empty() => {
+                placeholders.insert(String::from(name), String::from(value));
+            }
+            _ => {
+                return Err(Error::Message(format!(
+                    "Expected 'name=value' for placeholder, got '{placeholder}'."
+                )))
+            }
+        }
+    }
+    measurement::ProcessValue::new((value, placeholders))
+}
+
+#[async_trait]
+impl DataSource for ProcessValue {
+    type Item = measurement::ProcessValue;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let (code, stdout, stderr) = self.process_config.run_output().await?;
+        if !stderr.is_empty() {
+            log::debug!(
+                "Process '{}' wrote to stderr: {}",
+                self.id[0],
+                stderr.trim_end()
+            );
+        }
+        if code != 0 {
+            return Err(Error::Message(format!(
+                "Process exited with status code {code}."
+            )));
+        }
+        Ok(vec![parse_output(&stdout, self.value_type).map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("value {}", data.format())
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn process_value(script: &str, value_type: &str) -> ProcessValue {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Check"
+            type = "ProcessValue"
+            path = "/bin/sh"
+            arguments = ["-c", '{script}']
+            value_type = "{value_type}"
+            "#
+        ))
+        .unwrap();
+        ProcessValue::try_from(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_process_value() {
+        let mut check = process_value("echo 42.5; echo load=0.5 users=3; echo ignored", "Float");
+        assert_eq!(check.ids(), ["sh"]);
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        assert_eq!(data.value(), 42.5);
+        assert_eq!(data.placeholders()["load"], "0.5");
+        assert_eq!(data.placeholders()["users"], "3");
+
+        let mut check = process_value("echo 42; echo warning >&2; exit 2", "Integer");
+        assert_eq!(
+            check.get_data().await.err().unwrap().to_string(),
+            "Process exited with status code 2."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout() {
+        let mut check =
+            super::super::CheckBase::<_, crate::alarm::AlarmBase<crate::alarm::ProcessValue>>::new(
+                std::time::Duration::from_secs(60),
+                None,
+                None,
+                String::from("Check"),
+                Some(std::time::Duration::from_secs(1)),
+                PlaceholderMap::new(),
+                process_value("sleep 10; echo 1", "Float"),
+                Vec::new(),
+                1,
+            )
+            .unwrap();
+        let start = std::time::Instant::now();
+        let data = check.get_data().await;
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        assert_eq!(
+            data[0].as_ref().unwrap_err().to_string(),
+            "Check 'Check' timed out after 1 seconds."
+        );
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let data = parse_output("-3\n", config::ValueType::Integer).unwrap();
+        assert_eq!(data.value(), -3.);
+        assert!(data.placeholders().is_empty());
+        assert!(parse_output("1.5\n", config::ValueType::Integer).is_err());
+        assert_eq!(
+            parse_output("1.5\n", config::ValueType::Float)
+                .unwrap()
+                .value(),
+            1.5
+        );
+        assert!(parse_output("", config::ValueType::Float).is_err());
+        assert!(parse_output("NaN", config::ValueType::Float).is_err());
+        assert!(parse_output("1\nload", config::ValueType::Float).is_err());
+        assert!(parse_output("1\n=0.5", config::ValueType::Float).is_err());
+    }
+}
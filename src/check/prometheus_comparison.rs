@@ -0,0 +1,196 @@
+use super::prometheus_query::Client;
+use super::DataSource;
+use crate::config;
+use crate::measurement::{self, Measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Results of two instant PromQL queries, compared by the alarms.
+pub struct PrometheusComparison {
+    // With the placeholders of the check filled in.
+    numerator_query: String,
+    denominator_query: String,
+    id: Vec<String>,
+    client: Client,
+}
+
+impl TryFrom<&config::Check> for PrometheusComparison {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::PrometheusComparison(prometheus_comparison) = &check.type_ {
+            if prometheus_comparison.numerator_query.is_empty() {
+                return Err(Error::invalid_config("numerator_query", "cannot be empty."));
+            }
+            if prometheus_comparison.denominator_query.is_empty() {
+                return Err(Error::invalid_config(
+                    "denominator_query",
+                    "cannot be empty.",
+                ));
+            }
+            let numerator_query = crate::fill_placeholders(
+                &prometheus_comparison.numerator_query,
+                &check.placeholders,
+            );
+            let denominator_query = crate::fill_placeholders(
+                &prometheus_comparison.denominator_query,
+                &check.placeholders,
+            );
+            Ok(Self {
+                id: vec![format!("{numerator_query} vs. {denominator_query}")],
+                numerator_query,
+                denominator_query,
+                client: Client::new(
+                    &prometheus_comparison.url,
+                    prometheus_comparison.expect_single_series,
+                    &prometheus_comparison.username,
+                    &prometheus_comparison.password,
+                    &prometheus_comparison.bearer_token,
+                )?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+impl PrometheusComparison {
+    async fn execute(&self) -> Result<measurement::ValuePair> {
+        let (numerator, denominator) = tokio::join!(
+            self.client.query(&self.numerator_query),
+            self.client.query(&self.denominator_query)
+        );
+        let numerator = numerator.map_err(|x| Error::Message(format!("Numerator query: {x}")))?;
+        let denominator =
+            denominator.map_err(|x| Error::Message(format!("Denominator query: {x}")))?;
+        measurement::ValuePair::new((numerator, denominator))
+    }
+}
+
+#[async_trait]
+impl DataSource for PrometheusComparison {
+    type Item = measurement::ValuePair;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        Ok(vec![self.execute().await.map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!(
+            "numerator {:.2}, denominator {:.2}",
+            data.numerator(),
+            data.denominator()
+        )
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Answers the query for 'lag{role="primary"}' with 30 and 'lag{role="secondary"}' with 10.
+    // Other queries return no time series.
+    async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = String::new();
+                while !request.ends_with("%22%7D") {
+                    let mut buffer = [0; 4096];
+                    let length = stream.read(&mut buffer).await.unwrap();
+                    if length == 0 {
+                        break;
+                    }
+                    request.push_str(&String::from_utf8_lossy(&buffer[..length]));
+                }
+                let value = if request.contains("role%3D%22primary%22") {
+                    Some("30")
+                } else if request.contains("role%3D%22secondary%22") {
+                    Some("10")
+                } else {
+                    None
+                };
+                let body = match value {
+                    Some(value) => format!(
+                        r#"{{"status":"success","data":{{"resultType":"vector","result":[{{"metric":{{}},"value":[1700000000.123,"{value}"]}}]}}}}"#
+                    ),
+                    None => String::from(
+                        r#"{"status":"success","data":{"resultType":"vector","result":[]}}"#,
+                    ),
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{address}")
+    }
+
+    fn prometheus_comparison(url: &str, denominator_role: &str) -> PrometheusComparison {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Check"
+            type = "PrometheusComparison"
+            url = "{url}"
+            numerator_query = 'lag{{role="{{{{role}}}}"}}'
+            denominator_query = 'lag{{role="{denominator_role}"}}'
+            placeholders = {{"role" = "primary"}}
+            "#
+        ))
+        .unwrap();
+        PrometheusComparison::try_from(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_comparison() {
+        let url = serve().await;
+        let mut check = prometheus_comparison(&url, "secondary");
+        assert_eq!(
+            check.ids(),
+            ["lag{role=\"primary\"} vs. lag{role=\"secondary\"}"]
+        );
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        assert_eq!(data.numerator(), 30.);
+        assert_eq!(data.denominator(), 10.);
+        assert_eq!(
+            check.format_data(&data),
+            "numerator 30.00, denominator 10.00"
+        );
+
+        let mut check = prometheus_comparison(&url, "missing");
+        assert_eq!(
+            check
+                .get_data()
+                .await
+                .unwrap()
+                .remove(0)
+                .unwrap_err()
+                .to_string(),
+            "Denominator query: Query returned no time series."
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        let config: config::Check = toml::from_str(
+            r#"
+            name = "Check"
+            type = "PrometheusComparison"
+            url = "http://localhost:9090"
+            numerator_query = "up"
+            denominator_query = ""
+            "#,
+        )
+        .unwrap();
+        assert!(PrometheusComparison::try_from(&config).is_err());
+    }
+}
@@ -0,0 +1,347 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Result of an instant PromQL query.
+pub struct PrometheusQuery {
+    // With the placeholders of the check filled in.
+    query: Vec<String>,
+    client: Client,
+}
+
+enum Authentication {
+    None,
+    Basic(String, Option<String>),
+    Bearer(String),
+}
+
+// Sends instant queries to a Prometheus server. Also used by the PrometheusComparison check.
+pub(super) struct Client {
+    url: String,
+    expect_single_series: bool,
+    authentication: Authentication,
+    client: reqwest::Client,
+}
+
+impl Client {
+    pub(super) fn new(
+        url: &str,
+        expect_single_series: bool,
+        username: &Option<String>,
+        password: &Option<String>,
+        bearer_token: &Option<String>,
+    ) -> Result<Self> {
+        if url.is_empty() {
+            return Err(Error::invalid_config("url", "cannot be empty."));
+        }
+        let authentication = match (username, password, bearer_token) {
+            (None, None, None) => Authentication::None,
+            (Some(username), password, None) => {
+                Authentication::Basic(username.clone(), password.clone())
+            }
+            (None, None, Some(token)) => Authentication::Bearer(token.clone()),
+            (None, Some(_), None) => {
+                return Err(Error::invalid_config(
+                    "password",
+                    "cannot be set without 'username'.",
+                ))
+            }
+            _ => {
+                return Err(Error::invalid_config(
+                    "bearer_token",
+                    "cannot be combined with 'username' or 'password'.",
+                ))
+            }
+        };
+        let client = reqwest::Client::builder()
+            .user_agent(crate::user_agent())
+            .build()
+            .map_err(|x| Error::Message(format!("Could not create HTTP client: {x}")))?;
+        Ok(Self {
+            url: format!("{}/api/v1/query", url.trim_end_matches('/')),
+            expect_single_series,
+            authentication,
+            client,
+        })
+    }
+
+    pub(super) async fn query(&self, query: &str) -> Result<f64> {
+        let mut request = self.client.post(&self.url).form(&[("query", query)]);
+        request = match &self.authentication {
+            Authentication::None => request,
+            Authentication::Basic(username, password) => {
+                request.basic_auth(username, password.as_ref())
+            }
+            Authentication::Bearer(token) => request.bearer_auth(token),
+        };
+        let response = request
+            .send()
+            .await
+            .map_err(|x| Error::Message(format!("Prometheus request failed: {x}")))?;
+        let status = response.status();
+        let response = response
+            .text()
+            .await
+            .map_err(|x| Error::Message(format!("Could not read Prometheus response: {x}")))?;
+        // Query errors come with an error status and a JSON body describing the error.
+        match serde_json::from_str(&response) {
+            Ok(response) => parse_response(&response, self.expect_single_series),
+            Err(_) if !status.is_success() => Err(Error::Message(format!(
+                "Prometheus request failed: HTTP status {status}"
+            ))),
+            Err(err) => Err(Error::Message(format!(
+                "Could not parse Prometheus response: {err}"
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&config::Check> for PrometheusQuery {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::PrometheusQuery(prometheus_query) = &check.type_ {
+            if prometheus_query.query.is_empty() {
+                return Err(Error::invalid_config("query", "cannot be empty."));
+            }
+            Ok(Self {
+                query: vec![crate::fill_placeholders(
+                    &prometheus_query.query,
+                    &check.placeholders,
+                )],
+                client: Client::new(
+                    &prometheus_query.url,
+                    prometheus_query.expect_single_series,
+                    &prometheus_query.username,
+                    &prometheus_query.password,
+                    &prometheus_query.bearer_token,
+                )?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+// Samples are pairs of the timestamp and the value as a string.
+fn parse_sample(sample: &serde_json::Value) -> Result<f64> {
+    let value = sample[1]
+        .as_str()
+        .ok_or_else(|| Error::Message(String::from("Prometheus response has no sample value.")))?;
+    match value.parse::<f64>() {
+        Ok(value) if value.is_nan() => Err(Error::Message(String::from("Query result is NaN."))),
+        Ok(value) => Ok(value),
+        Err(_) => Err(Error::Message(format!(
+            "Sample value '{value}' is not a number."
+        ))),
+    }
+}
+
+fn parse_response(response: &serde_json::Value, expect_single_series: bool) -> Result<f64> {
+    match response["status"].as_str() {
+        Some("success") => {}
+        Some(_) => {
+            return Err(Error::Message(format!(
+                "Prometheus error ({}): {}",
+                response["errorType"].as_str().unwrap_or("unknown"),
+                response["error"].as_str().unwrap_or("unknown error")
+            )))
+        }
+        None => {
+            return Err(Error::Message(String::from(
+                "Prometheus response has no status.",
+            )))
+        }
+    }
+    let data = &response["data"];
+    match data["resultType"].as_str() {
+        Some("scalar") => parse_sample(&data["result"]),
+        Some("vector") => match data["result"].as_array().map(|x| &x[..]) {
+            None | Some([]) => Err(Error::Message(String::from(
+                "Query returned no time series.",
+            ))),
+            Some([series, ..]) if !expect_single_series => parse_sample(&series["value"]),
+            Some([series]) => parse_sample(&series["value"]),
+            Some(result) => Err(Error::Message(format!(
+                "Query returned {} time series, expected one.",
+                result.len()
+            ))),
+        },
+        Some(result_type) => Err(Error::Message(format!(
+            "Unsupported result type '{result_type}'. Use an instant vector or a scalar."
+        ))),
+        None => Err(Error::Message(String::from(
+            "Prometheus response has no result type.",
+        ))),
+    }
+}
+
+#[async_trait]
+impl DataSource for PrometheusQuery {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        Ok(vec![self.client.query(&self.query[0]).await.map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("value {data:.2}")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.query[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Answers like Prometheus, depending on the job in the form encoded query. Requests without
+    // the expected bearer token are rejected.
+    async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                // The form body may arrive separately from the headers. It ends with the closing
+                // parentheses of the query.
+                let mut request = String::new();
+                while !request.ends_with("%29%29") {
+                    let mut buffer = [0; 4096];
+                    let length = stream.read(&mut buffer).await.unwrap();
+                    if length == 0 {
+                        break;
+                    }
+                    request.push_str(&String::from_utf8_lossy(&buffer[..length]));
+                }
+                let (status, body) = if !request.starts_with("POST /prometheus/api/v1/query ") {
+                    ("404 Not Found", "404 page not found")
+                } else if !request
+                    .lines()
+                    .any(|x| x.eq_ignore_ascii_case("Authorization: Bearer secret"))
+                {
+                    ("401 Unauthorized", "Unauthorized")
+                } else if request.contains("job%3D%22api%22") {
+                    (
+                        "200 OK",
+                        r#"{"status":"success","data":{"resultType":"vector","result":[{"metric":{"job":"api"},"value":[1700000000.123,"12.5"]}]}}"#,
+                    )
+                } else if request.contains("job%3D%22all%22") {
+                    (
+                        "200 OK",
+                        r#"{"status":"success","data":{"resultType":"vector","result":[{"metric":{"instance":"a"},"value":[1700000000.123,"1"]},{"metric":{"instance":"b"},"value":[1700000000.123,"2"]}]}}"#,
+                    )
+                } else if request.contains("job%3D%22nan%22") {
+                    (
+                        "200 OK",
+                        r#"{"status":"success","data":{"resultType":"vector","result":[{"metric":{},"value":[1700000000.123,"NaN"]}]}}"#,
+                    )
+                } else if request.contains("job%3D%22invalid%22") {
+                    (
+                        "400 Bad Request",
+                        r#"{"status":"error","errorType":"bad_data","error":"invalid parameter \"query\": 1:1: parse error"}"#,
+                    )
+                } else {
+                    (
+                        "200 OK",
+                        r#"{"status":"success","data":{"resultType":"vector","result":[]}}"#,
+                    )
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{address}/prometheus/")
+    }
+
+    fn prometheus_query(
+        url: &str,
+        job: &str,
+        expect_single_series: bool,
+        token: &str,
+    ) -> PrometheusQuery {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Check"
+            type = "PrometheusQuery"
+            url = "{url}"
+            query = 'sum(rate(http_requests_total{{job="{{{{job_name}}}}"}}[5m]))'
+            expect_single_series = {expect_single_series}
+            bearer_token = "{token}"
+            placeholders = {{"job_name" = "{job}"}}
+            "#
+        ))
+        .unwrap();
+        PrometheusQuery::try_from(&config).unwrap()
+    }
+
+    async fn query(url: &str, job: &str, expect_single_series: bool) -> Result<f64> {
+        let mut check = prometheus_query(url, job, expect_single_series, "secret");
+        check
+            .get_data()
+            .await
+            .unwrap()
+            .remove(0)
+            .map(|x| x.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_query() {
+        let url = serve().await;
+        let check = prometheus_query(&url, "api", false, "secret");
+        assert_eq!(
+            check.ids(),
+            ["sum(rate(http_requests_total{job=\"api\"}[5m]))"]
+        );
+        assert_eq!(query(&url, "api", true).await.unwrap(), 12.5);
+        assert_eq!(query(&url, "all", false).await.unwrap(), 1.0);
+        assert_eq!(
+            query(&url, "all", true).await.unwrap_err().to_string(),
+            "Query returned 2 time series, expected one."
+        );
+        assert_eq!(
+            query(&url, "missing", false).await.unwrap_err().to_string(),
+            "Query returned no time series."
+        );
+        assert_eq!(
+            query(&url, "nan", false).await.unwrap_err().to_string(),
+            "Query result is NaN."
+        );
+        assert!(query(&url, "invalid", false)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("parse error"));
+
+        let mut check = prometheus_query(&url, "api", false, "wrong");
+        let data = check.get_data().await.unwrap();
+        assert!(data[0].as_ref().unwrap_err().to_string().contains("401"));
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let response = serde_json::json!({
+            "status": "success",
+            "data": {"resultType": "scalar", "result": [1700000000.123, "-3.5"]}
+        });
+        assert_eq!(parse_response(&response, true).unwrap(), -3.5);
+        let response = serde_json::json!({
+            "status": "success",
+            "data": {"resultType": "matrix", "result": []}
+        });
+        assert!(parse_response(&response, false).is_err());
+        let response = serde_json::json!({
+            "status": "success",
+            "data": {"resultType": "scalar", "result": [1700000000.123, "foo"]}
+        });
+        assert!(parse_response(&response, false).is_err());
+        assert!(parse_response(&serde_json::json!({}), false).is_err());
+    }
+}
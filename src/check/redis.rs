@@ -0,0 +1,361 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Numeric result of a Redis command, e.g. `DBSIZE` or a field of `INFO`.
+pub struct Redis {
+    id: Vec<String>,
+    command: Vec<String>,
+    field_name: Option<String>,
+    client: Client,
+}
+
+impl TryFrom<&config::Check> for Redis {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::Redis(redis) = &check.type_ {
+            let command: Vec<String> = redis.command.split_whitespace().map(String::from).collect();
+            if command.is_empty() {
+                return Err(Error::invalid_config("command", "cannot be empty."));
+            }
+            let id = match &redis.field_name {
+                Some(field_name) if field_name.is_empty() => {
+                    return Err(Error::invalid_config("field_name", "cannot be empty."))
+                }
+                Some(field_name) => format!("{}[{field_name}]", command.join(" ")),
+                None => command.join(" "),
+            };
+            Ok(Self {
+                id: vec![id],
+                command,
+                field_name: redis.field_name.clone(),
+                client: Client::new(
+                    &redis.address,
+                    &redis.username,
+                    &redis.password,
+                    redis.database,
+                )?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+// `INFO` replies consist of `field:value` lines and `# Section` headers.
+fn parse_reply(reply: redis::Value, field_name: Option<&str>) -> Result<f64> {
+    let text = match reply {
+        redis::Value::Int(value) if field_name.is_none() => return Ok(value as f64),
+        redis::Value::Int(_) => return Err(Error::Message(String::from("Expected a text reply."))),
+        redis::Value::Status(text) => text,
+        redis::Value::Okay => String::from("OK"),
+        redis::Value::Data(data) => String::from_utf8_lossy(&data).into_owned(),
+        redis::Value::Nil => return Err(Error::Message(String::from("Redis returned nil."))),
+        redis::Value::Bulk(_) => {
+            return Err(Error::Message(String::from(
+                "Expected a number, got an array.",
+            )))
+        }
+    };
+    let value = match field_name {
+        Some(field_name) => text
+            .lines()
+            .find_map(|x| x.strip_prefix(field_name)?.strip_prefix(':'))
+            .ok_or_else(|| Error::Message(format!("Field '{field_name}' not found.")))?,
+        None => text.trim(),
+    };
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Error::Message(format!("Value '{value}' is not a number.")))
+}
+
+// Keeps the connection open between the checks. It is reopened after I/O errors.
+pub(super) struct Client {
+    address: String,
+    client: redis::Client,
+    connection: Option<redis::aio::Connection>,
+}
+
+impl Client {
+    pub(super) fn new(
+        address: &str,
+        username: &Option<String>,
+        password: &Option<String>,
+        database: u32,
+    ) -> Result<Self> {
+        if address.is_empty() {
+            return Err(Error::invalid_config("address", "cannot be empty."));
+        } else if username.is_some() && password.is_none() {
+            return Err(Error::invalid_config(
+                "username",
+                "cannot be set without 'password'.",
+            ));
+        }
+        let (host, port) = address
+            .rsplit_once(':')
+            .and_then(|(host, port)| Some((host, port.parse().ok()?)))
+            .ok_or_else(|| {
+                Error::invalid_config(
+                    "address",
+                    format!("{address} is not of the form host:port."),
+                )
+            })?;
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let client = redis::Client::open(redis::ConnectionInfo {
+            addr: redis::ConnectionAddr::Tcp(String::from(host), port),
+            redis: redis::RedisConnectionInfo {
+                db: database.into(),
+                username: username.clone(),
+                password: password.clone(),
+            },
+        })
+        .map_err(|x| Error::Message(format!("Invalid Redis connection: {x}")))?;
+        Ok(Self {
+            address: String::from(address),
+            client,
+            connection: None,
+        })
+    }
+
+    async fn open(&self) -> Result<redis::aio::Connection> {
+        self.client
+            .get_async_connection()
+            .await
+            .map_err(|x| Error::Message(format!("Could not connect to '{}': {x}", self.address)))
+    }
+
+    // Returns the reply and how long the command took, without the time to connect. A reused
+    // connection may have been closed by the server in the meantime, so the command is retried
+    // once with a new connection.
+    pub(super) async fn command(
+        &mut self,
+        args: &[&str],
+    ) -> Result<(redis::Value, std::time::Duration)> {
+        let command = redis::cmd(args[0]).arg(&args[1..]).clone();
+        let (mut connection, reused) = match self.connection.take() {
+            Some(connection) => (connection, true),
+            None => (self.open().await?, false),
+        };
+        let mut start = std::time::Instant::now();
+        let mut result = command.query_async(&mut connection).await;
+        if reused && matches!(&result, Err(err) if err.is_io_error()) {
+            log::debug!("Reconnecting to Redis: {}", result.unwrap_err());
+            connection = self.open().await?;
+            start = std::time::Instant::now();
+            result = command.query_async(&mut connection).await;
+        }
+        let elapsed = start.elapsed();
+        match result {
+            // The connection stays usable after error replies of the server.
+            Err(ref err) if err.is_io_error() => {}
+            _ => self.connection = Some(connection),
+        }
+        result
+            .map(|x| (x, elapsed))
+            .map_err(|x| Error::Message(format!("Redis error: {x}")))
+    }
+}
+
+#[async_trait]
+impl DataSource for Redis {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let args: Vec<&str> = self.command.iter().map(|x| x.as_str()).collect();
+        let value = self
+            .client
+            .command(&args)
+            .await
+            .and_then(|(reply, _)| parse_reply(reply, self.field_name.as_deref()));
+        Ok(vec![value.map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("value {data}")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+pub(super) mod test {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+
+    async fn read_line(stream: &mut tokio::io::BufStream<tokio::net::TcpStream>) -> String {
+        let mut line = String::new();
+        stream.read_line(&mut line).await.unwrap();
+        String::from(line.trim_end())
+    }
+
+    // Answers a few commands like Redis. Only the password "secret" (for the user "default") is
+    // accepted.
+    pub(in crate::check) async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut stream = tokio::io::BufStream::new(stream);
+                    let mut authenticated = false;
+                    loop {
+                        let header = read_line(&mut stream).await;
+                        let Some(count) = header.strip_prefix('*') else {
+                            return;
+                        };
+                        let mut args = Vec::new();
+                        for _ in 0..count.parse().unwrap() {
+                            read_line(&mut stream).await;
+                            args.push(read_line(&mut stream).await);
+                        }
+                        let args: Vec<&str> = args.iter().map(|x| x.as_str()).collect();
+                        let reply = match args[..] {
+                            ["AUTH", "secret"] | ["AUTH", "default", "secret"] => {
+                                authenticated = true;
+                                String::from("+OK\r\n")
+                            }
+                            ["AUTH", ..] => String::from(
+                                "-WRONGPASS invalid username-password pair or user is disabled.\r\n",
+                            ),
+                            _ if !authenticated => {
+                                String::from("-NOAUTH Authentication required.\r\n")
+                            }
+                            ["SELECT", "2"] => String::from("+OK\r\n"),
+                            ["SELECT", _] => String::from("-ERR DB index is out of range\r\n"),
+                            ["PING"] => String::from("+PONG\r\n"),
+                            ["DBSIZE"] => String::from(":42\r\n"),
+                            ["GET", "missing"] => String::from("$-1\r\n"),
+                            ["GET", _] => String::from("$4\r\n12.5\r\n"),
+                            ["CONFIG", "GET", "maxmemory"] => {
+                                String::from("*2\r\n$9\r\nmaxmemory\r\n$1\r\n0\r\n")
+                            }
+                            ["INFO", "server"] => {
+                                let info = "# Server\r\nredis_version:7.2.4\r\nuptime_in_seconds:3600\r\n";
+                                format!("${}\r\n{info}\r\n", info.len())
+                            }
+                            ["QUIT"] => return,
+                            _ => String::from("-ERR unknown command\r\n"),
+                        };
+                        stream.write_all(reply.as_bytes()).await.unwrap();
+                        stream.flush().await.unwrap();
+                    }
+                });
+            }
+        });
+        address
+    }
+
+    fn redis(config: &str) -> Redis {
+        let config: config::Check =
+            toml::from_str(&format!("name = \"Check\"\ntype = \"Redis\"\n{config}")).unwrap();
+        Redis::try_from(&config).unwrap()
+    }
+
+    async fn value(check: &mut Redis) -> Result<f64> {
+        check
+            .get_data()
+            .await
+            .unwrap()
+            .remove(0)
+            .map(|x| x.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_redis() {
+        let address = serve().await;
+        let connection = format!("address = \"{address}\"\npassword = \"secret\"");
+        let mut check = redis(&format!("{connection}\ncommand = \"DBSIZE\""));
+        assert_eq!(value(&mut check).await.unwrap(), 42.0);
+        // The connection is reused.
+        assert_eq!(value(&mut check).await.unwrap(), 42.0);
+
+        let mut check = redis(&format!(
+            "{connection}\ndatabase = 2\ncommand = \"INFO server\"\nfield_name = \"uptime_in_seconds\""
+        ));
+        assert_eq!(check.ids(), ["INFO server[uptime_in_seconds]"]);
+        assert_eq!(value(&mut check).await.unwrap(), 3600.0);
+
+        let mut check = redis(&format!(
+            "{connection}\ncommand = \"INFO server\"\nfield_name = \"redis_version\""
+        ));
+        assert!(value(&mut check).await.is_err());
+
+        let mut check = redis(&format!("{connection}\ncommand = \"GET foo\""));
+        assert_eq!(value(&mut check).await.unwrap(), 12.5);
+        let mut check = redis(&format!("{connection}\ncommand = \"GET missing\""));
+        assert_eq!(
+            value(&mut check).await.unwrap_err().to_string(),
+            "Redis returned nil."
+        );
+        // The whole array is read so that the next command still works.
+        let mut check = redis(&format!("{connection}\ncommand = \"CONFIG GET maxmemory\""));
+        assert!(value(&mut check).await.is_err());
+        check.command = vec![String::from("DBSIZE")];
+        assert_eq!(value(&mut check).await.unwrap(), 42.0);
+
+        let mut check = redis(&format!("{connection}\ncommand = \"FOO\""));
+        assert!(value(&mut check)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("unknown command"));
+        let mut check = redis(&format!("{connection}\ndatabase = 3\ncommand = \"DBSIZE\""));
+        assert!(value(&mut check).await.is_err());
+        let mut check = redis(&format!(
+            "address = \"{address}\"\npassword = \"wrong\"\ncommand = \"DBSIZE\""
+        ));
+        assert!(value(&mut check)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("authentication failed"));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        let mut check = redis(&format!("address = \"{address}\"\ncommand = \"DBSIZE\""));
+        assert!(value(&mut check).await.is_err());
+
+        let address = serve().await;
+        check.client = Client::new(
+            &address,
+            &Some(String::from("default")),
+            &Some(String::from("secret")),
+            0,
+        )
+        .unwrap();
+        assert_eq!(value(&mut check).await.unwrap(), 42.0);
+        // Closed by the server.
+        redis::cmd("QUIT")
+            .query_async::<_, ()>(check.client.connection.as_mut().unwrap())
+            .await
+            .ok();
+        assert_eq!(value(&mut check).await.unwrap(), 42.0);
+    }
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(parse_reply(redis::Value::Int(-3), None).unwrap(), -3.0);
+        assert!(parse_reply(redis::Value::Int(3), Some("foo")).is_err());
+        assert_eq!(
+            parse_reply(redis::Value::Status(String::from("1.5")), None).unwrap(),
+            1.5
+        );
+        let info = redis::Value::Data(
+            b"# Memory\r\nused_memory:1024\r\nused_memory_human:1.00K\r\n".to_vec(),
+        );
+        assert_eq!(parse_reply(info, Some("used_memory")).unwrap(), 1024.0);
+        assert!(parse_reply(redis::Value::Data(b"foo".to_vec()), None).is_err());
+        assert!(parse_reply(redis::Value::Bulk(vec![]), None).is_err());
+        assert!(parse_reply(redis::Value::Okay, None).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+use super::redis::Client;
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Round-trip time of a Redis `PING` in milliseconds, without the time to connect.
+pub struct RedisLatency {
+    id: Vec<String>,
+    client: Client,
+}
+
+impl TryFrom<&config::Check> for RedisLatency {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::RedisLatency(redis_latency) = &check.type_ {
+            Ok(Self {
+                id: vec![redis_latency.address.clone()],
+                client: Client::new(
+                    &redis_latency.address,
+                    &redis_latency.username,
+                    &redis_latency.password,
+                    redis_latency.database,
+                )?,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for RedisLatency {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let latency = self
+            .client
+            .command(&["PING"])
+            .await
+            .map(|(_, elapsed)| Some(elapsed.as_secs_f64() * 1000.0));
+        Ok(vec![latency])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("latency {data:.2} ms")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn redis_latency(address: &str, password: &str) -> RedisLatency {
+        let config: config::Check = toml::from_str(&format!(
+            "name = \"Check\"\ntype = \"RedisLatency\"\naddress = \"{address}\"\npassword = \"{password}\""
+        ))
+        .unwrap();
+        RedisLatency::try_from(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_latency() {
+        let address = super::super::redis::test::serve().await;
+        let mut check = redis_latency(&address, "secret");
+        let data = check.get_data().await.unwrap();
+        assert!(data[0].as_ref().unwrap().unwrap() >= 0.0);
+
+        let mut check = redis_latency(&address, "wrong");
+        assert!(check.get_data().await.unwrap()[0].is_err());
+    }
+}
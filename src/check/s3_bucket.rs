@@ -0,0 +1,399 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+use aws_sdk_s3::error::ProvideErrorMetadata;
+
+// Total size in bytes and number of the objects in a bucket (below the prefix).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct S3BucketStats {
+    pub size: u64,
+    pub object_count: u64,
+}
+
+// Objects of one response of ListObjectsV2 and the token for the next one if there are more.
+#[derive(Debug, Default, PartialEq)]
+struct Page {
+    stats: S3BucketStats,
+    next_continuation_token: Option<String>,
+}
+
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+trait ListObjects: Send + Sync {
+    async fn list_objects_v2(&self, continuation_token: Option<String>) -> Result<Page>;
+}
+
+// Adds up all pages of the listing. S3 returns at most 1000 objects per page.
+async fn bucket_stats(client: &dyn ListObjects) -> Result<S3BucketStats> {
+    let mut stats = S3BucketStats::default();
+    let mut continuation_token = None;
+    loop {
+        let page = client.list_objects_v2(continuation_token).await?;
+        stats.size = stats.size.saturating_add(page.stats.size);
+        stats.object_count += page.stats.object_count;
+        match page.next_continuation_token {
+            Some(token) => continuation_token = Some(token),
+            None => return Ok(stats),
+        }
+    }
+}
+
+struct Client {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    prefix: Option<String>,
+}
+
+impl Client {
+    // The credentials and, unless the region is configured, the region are taken from the default
+    // chains of the AWS SDK. Custom endpoints, e.g. of S3 compatible storage, get path-style URLs.
+    async fn new(s3_bucket: &S3BucketConfig) -> Self {
+        let region = aws_config::meta::region::RegionProviderChain::first_try(
+            s3_bucket
+                .region
+                .clone()
+                .map(aws_sdk_s3::config::Region::new),
+        )
+        .or_default_provider()
+        .or_else("us-east-1");
+        let sdk_config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+            .region(region)
+            .load()
+            .await;
+        let mut config = aws_sdk_s3::config::Builder::from(&sdk_config);
+        if let Some(endpoint) = &s3_bucket.endpoint {
+            config = config.endpoint_url(endpoint).force_path_style(true);
+        }
+        Self {
+            client: aws_sdk_s3::Client::from_conf(config.build()),
+            bucket: s3_bucket.bucket.clone(),
+            prefix: s3_bucket.prefix.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl ListObjects for Client {
+    async fn list_objects_v2(&self, continuation_token: Option<String>) -> Result<Page> {
+        let output = self
+            .client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .set_prefix(self.prefix.clone())
+            .set_continuation_token(continuation_token)
+            .send()
+            .await
+            .map_err(|x| match (x.code(), x.message()) {
+                (Some(code), Some(message)) => {
+                    Error::Message(format!("S3 request failed: {code}: {message}"))
+                }
+                _ => Error::Message(format!(
+                    "S3 request failed: {}",
+                    aws_sdk_s3::error::DisplayErrorContext(&x)
+                )),
+            })?;
+        let mut stats = S3BucketStats::default();
+        for object in output.contents() {
+            let size = object
+                .size()
+                .and_then(|x| u64::try_from(x).ok())
+                .ok_or_else(|| {
+                    Error::Message(String::from("S3 response has an object without size."))
+                })?;
+            stats.size = stats.size.saturating_add(size);
+            stats.object_count += 1;
+        }
+        let next_continuation_token = if output.is_truncated() == Some(true) {
+            Some(
+                output
+                    .next_continuation_token()
+                    .map(String::from)
+                    .ok_or_else(|| {
+                        Error::Message(String::from(
+                            "S3 response is truncated without continuation token.",
+                        ))
+                    })?,
+            )
+        } else {
+            None
+        };
+        Ok(Page {
+            stats,
+            next_continuation_token,
+        })
+    }
+}
+
+// The options of the check needed to create the client on the first cycle, because loading the
+// AWS config is async.
+struct S3BucketConfig {
+    bucket: String,
+    prefix: Option<String>,
+    region: Option<String>,
+    endpoint: Option<String>,
+}
+
+// Total size or number of the objects in a bucket, optionally only the ones below a prefix.
+pub struct S3Bucket {
+    id: Vec<String>,
+    measure: config::S3Measure,
+    config: S3BucketConfig,
+    client: Option<Client>,
+}
+
+impl TryFrom<&config::Check> for S3Bucket {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::S3Bucket(s3_bucket) = &check.type_ {
+            if s3_bucket.bucket.is_empty() {
+                Err(Error::invalid_config("bucket", "cannot be empty."))
+            } else if s3_bucket.bucket.contains('/') {
+                Err(Error::invalid_config("bucket", "cannot contain '/'."))
+            } else if s3_bucket.region.as_ref().is_some_and(|x| x.is_empty()) {
+                Err(Error::invalid_config("region", "cannot be empty."))
+            } else if s3_bucket
+                .endpoint
+                .as_ref()
+                .is_some_and(|x| !x.starts_with("http://") && !x.starts_with("https://"))
+            {
+                Err(Error::invalid_config(
+                    "endpoint",
+                    "needs to be an http:// or https:// URL.",
+                ))
+            } else {
+                Ok(Self {
+                    id: vec![match &s3_bucket.prefix {
+                        Some(prefix) => format!("{}/{prefix}", s3_bucket.bucket),
+                        None => s3_bucket.bucket.clone(),
+                    }],
+                    measure: s3_bucket.measure,
+                    config: S3BucketConfig {
+                        bucket: s3_bucket.bucket.clone(),
+                        prefix: s3_bucket.prefix.clone(),
+                        region: s3_bucket.region.clone(),
+                        endpoint: s3_bucket.endpoint.clone(),
+                    },
+                    client: None,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for S3Bucket {
+    type Item = u64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let client = match &self.client {
+            Some(client) => client,
+            None => self.client.insert(Client::new(&self.config).await),
+        };
+        let stats = bucket_stats(client).await;
+        Ok(vec![stats.map(|x| {
+            Some(match self.measure {
+                config::S3Measure::Size => x.size,
+                config::S3Measure::ObjectCount => x.object_count,
+            })
+        })])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        match self.measure {
+            config::S3Measure::Size => format!("size {data} bytes"),
+            config::S3Measure::ObjectCount => format!("{data} objects"),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn page(sizes: &[u64], next_continuation_token: Option<&str>) -> Page {
+        Page {
+            stats: S3BucketStats {
+                size: sizes.iter().sum(),
+                object_count: sizes.len() as u64,
+            },
+            next_continuation_token: next_continuation_token.map(String::from),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bucket_stats() {
+        let mut client = MockListObjects::new();
+        let mut sequence = mockall::Sequence::new();
+        for (token, page) in [
+            (None, page(&[1000; 1000], Some("2"))),
+            (Some("2"), page(&[10; 1000], Some("3"))),
+            (Some("3"), page(&[5, 7], None)),
+        ] {
+            client
+                .expect_list_objects_v2()
+                .once()
+                .in_sequence(&mut sequence)
+                .withf(move |x| x.as_deref() == token)
+                .return_once(|_| Ok(page));
+        }
+        assert_eq!(
+            bucket_stats(&client).await.unwrap(),
+            S3BucketStats {
+                size: 1_010_012,
+                object_count: 2002
+            }
+        );
+
+        let mut client = MockListObjects::new();
+        client
+            .expect_list_objects_v2()
+            .once()
+            .returning(|_| Ok(page(&[], None)));
+        assert_eq!(
+            bucket_stats(&client).await.unwrap(),
+            S3BucketStats::default()
+        );
+
+        let mut client = MockListObjects::new();
+        client
+            .expect_list_objects_v2()
+            .times(2)
+            .returning(|token| match token {
+                None => Ok(page(&[1], Some("2"))),
+                Some(_) => Err(Error::Message(String::from("Timeout"))),
+            });
+        assert_eq!(
+            bucket_stats(&client).await.unwrap_err().to_string(),
+            "Timeout"
+        );
+    }
+
+    // Answers like S3 with two pages for the bucket "bucket" and records the request lines and
+    // the authorization headers.
+    async fn serve(requests: std::sync::Arc<std::sync::Mutex<Vec<(String, String)>>>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = String::new();
+                while !request.contains("\r\n\r\n") {
+                    let mut buffer = [0; 4096];
+                    let length = stream.read(&mut buffer).await.unwrap();
+                    if length == 0 {
+                        break;
+                    }
+                    request.push_str(&String::from_utf8_lossy(&buffer[..length]));
+                }
+                let path = String::from(request.split(' ').nth(1).unwrap_or_default());
+                let authorization = request
+                    .lines()
+                    .find_map(|x| {
+                        x.split_once(": ")
+                            .filter(|(name, _)| name.eq_ignore_ascii_case("authorization"))
+                    })
+                    .map(|(_, value)| value)
+                    .unwrap_or_default();
+                requests
+                    .lock()
+                    .unwrap()
+                    .push((path.clone(), String::from(authorization)));
+                let (path, query) = path.split_once('?').unwrap_or((&path, ""));
+                let parameters: Vec<&str> = query.split('&').collect();
+                let page = |parameter| parameters.contains(&parameter);
+                let (status, body) = match (
+                    path.trim_end_matches('/'),
+                    page("list-type=2") && page("prefix=logs%2F"),
+                    page("continuation-token=page%2F2"),
+                ) {
+                    ("/bucket", true, false) => (
+                        "200 OK",
+                        "<ListBucketResult><IsTruncated>true</IsTruncated><NextContinuationToken>page/2</NextContinuationToken><Contents><Size>3</Size></Contents></ListBucketResult>",
+                    ),
+                    ("/bucket", true, true) => (
+                        "200 OK",
+                        "<ListBucketResult><IsTruncated>false</IsTruncated><Contents><Size>4</Size></Contents><Contents><Size>5</Size></Contents></ListBucketResult>",
+                    ),
+                    _ => (
+                        "404 Not Found",
+                        "<Error><Code>NoSuchBucket</Code><Message>The specified bucket does not exist</Message></Error>",
+                    ),
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/xml\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{address}")
+    }
+
+    fn s3_bucket(options: &str) -> Result<S3Bucket> {
+        let config: config::Check = toml::from_str(&format!(
+            "name = \"Check\"\ntype = \"S3Bucket\"\nregion = \"eu-west-1\"\n{options}"
+        ))
+        .unwrap();
+        S3Bucket::try_from(&config)
+    }
+
+    #[tokio::test]
+    async fn test_s3_bucket() {
+        std::env::set_var("AWS_ACCESS_KEY_ID", "MINMONTEST");
+        std::env::set_var("AWS_SECRET_ACCESS_KEY", "secret");
+        let requests = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let endpoint = serve(requests.clone()).await;
+        let mut check = s3_bucket(&format!(
+            "bucket = \"bucket\"\nprefix = \"logs/\"\nendpoint = \"{endpoint}\"\nmeasure = \"ObjectCount\""
+        ))
+        .unwrap();
+        assert_eq!(check.ids(), ["bucket/logs/"]);
+        assert_eq!(
+            check.get_data().await.unwrap()[0].as_ref().unwrap(),
+            &Some(3)
+        );
+        assert_eq!(check.format_data(&3), "3 objects");
+        let requests = requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|(_, authorization)| authorization
+            .starts_with("AWS4-HMAC-SHA256 Credential=MINMONTEST/")
+            && authorization.contains("/eu-west-1/s3/aws4_request")));
+
+        let mut check = s3_bucket(&format!(
+            "bucket = \"bucket\"\nprefix = \"logs/\"\nendpoint = \"{endpoint}\""
+        ))
+        .unwrap();
+        assert_eq!(
+            check.get_data().await.unwrap()[0].as_ref().unwrap(),
+            &Some(12)
+        );
+        assert_eq!(check.format_data(&12), "size 12 bytes");
+
+        let mut check =
+            s3_bucket(&format!("bucket = \"missing\"\nendpoint = \"{endpoint}\"")).unwrap();
+        assert_eq!(
+            check.get_data().await.unwrap()[0]
+                .as_ref()
+                .unwrap_err()
+                .to_string(),
+            "S3 request failed: NoSuchBucket: The specified bucket does not exist"
+        );
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(s3_bucket("bucket = \"\"").is_err());
+        assert!(s3_bucket("bucket = \"a/b\"").is_err());
+        assert!(s3_bucket("bucket = \"bucket\"\nendpoint = \"not a url\"").is_err());
+        assert!(s3_bucket("bucket = \"bucket\"\nendpoint = \"http://minio:9000/\"").is_ok());
+    }
+}
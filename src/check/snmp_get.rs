@@ -0,0 +1,328 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Numeric values read with SNMP v2c GET requests. Counters are reported as rate per second.
+pub struct SnmpGet {
+    address: String,
+    community: String,
+    oids: Vec<String>,
+    parsed_oids: Vec<snmp2::Oid<'static>>,
+    counters: Vec<CounterRate>,
+}
+
+impl TryFrom<&config::Check> for SnmpGet {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::SnmpGet(snmp_get) = &check.type_ {
+            if snmp_get.address.is_empty() {
+                return Err(Error::invalid_config("address", "cannot be empty."));
+            }
+            if snmp_get.oids.is_empty() {
+                return Err(Error::invalid_config("oids", "cannot be empty."));
+            }
+            let parsed_oids = snmp_get
+                .oids
+                .iter()
+                .map(|x| parse_oid(x))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(Self {
+                address: snmp_get.address.clone(),
+                community: snmp_get.community.clone(),
+                oids: snmp_get.oids.clone(),
+                counters: vec![CounterRate::default(); parsed_oids.len()],
+                parsed_oids,
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+fn parse_oid(oid: &str) -> Result<snmp2::Oid<'static>> {
+    oid.trim_start_matches('.')
+        .parse()
+        .map_err(|_| Error::Message(format!("'{oid}' is not a valid OID.")))
+}
+
+#[cfg_attr(test, derive(PartialEq, Debug))]
+enum Value {
+    Gauge(u64),
+    // Value and maximum before it wraps around.
+    Counter(u64, u64),
+}
+
+fn decode_value(value: &snmp2::Value) -> Result<Value> {
+    match *value {
+        snmp2::Value::Integer(value) => u64::try_from(value)
+            .map(Value::Gauge)
+            .map_err(|_| Error::Message(String::from("Negative integers are not supported."))),
+        snmp2::Value::Unsigned32(value) => Ok(Value::Gauge(value.into())),
+        snmp2::Value::Counter32(value) => Ok(Value::Counter(value.into(), u32::MAX.into())),
+        snmp2::Value::Counter64(value) => Ok(Value::Counter(value, u64::MAX)),
+        snmp2::Value::NoSuchObject => Err(Error::Message(String::from("No such object."))),
+        snmp2::Value::NoSuchInstance => Err(Error::Message(String::from("No such instance."))),
+        snmp2::Value::EndOfMibView => Err(Error::Message(String::from("End of MIB view."))),
+        ref value => Err(Error::Message(format!("Unsupported SNMP value {value:?}."))),
+    }
+}
+
+#[derive(Clone, Default)]
+struct CounterRate {
+    last: Option<(u64, std::time::Instant)>,
+}
+
+impl CounterRate {
+    fn update(&mut self, value: u64, max: u64, now: std::time::Instant) -> Option<f64> {
+        let res = self.last.map(|(last, last_time)| {
+            let delta = if value >= last {
+                value - last
+            } else {
+                // wrap-around detected
+                max - last + 1 + value
+            };
+            let seconds = now.duration_since(last_time).as_secs_f64();
+            if seconds > 0.0 {
+                delta as f64 / seconds
+            } else {
+                0.0
+            }
+        });
+        self.last = Some((value, now));
+        res
+    }
+}
+
+impl SnmpGet {
+    // A new session (and socket) is used for every request, so late responses to earlier requests
+    // cannot be mistaken for the current one. Without a response the check times out.
+    async fn request(&self) -> Result<Vec<Result<Value>>> {
+        let mut session = snmp2::AsyncSession::new_v2c(
+            self.address.as_str(),
+            self.community.as_bytes(),
+            (rand::random::<u32>() >> 1) as i32,
+        )
+        .await
+        .map_err(|x| Error::Message(format!("Could not connect to '{}': {x}", self.address)))?;
+        let oids: Vec<&snmp2::Oid> = self.parsed_oids.iter().collect();
+        let response = session
+            .get_many(&oids)
+            .await
+            .map_err(|x| Error::Message(format!("SNMP request failed: {x}")))?;
+        if response.error_status != 0 {
+            return Err(Error::Message(format!(
+                "SNMP agent returned error status {} for the OID at index {}.",
+                response.error_status, response.error_index
+            )));
+        }
+        let varbinds: Vec<_> = response.varbinds.collect();
+        if varbinds.len() != self.parsed_oids.len() {
+            return Err(Error::Message(String::from(
+                "SNMP response does not contain all requested OIDs.",
+            )));
+        }
+        Ok(varbinds
+            .iter()
+            .zip(self.parsed_oids.iter())
+            .map(|((oid, value), expected)| {
+                if oid == expected {
+                    decode_value(value)
+                } else {
+                    Err(Error::Message(String::from(
+                        "SNMP response contains a different OID.",
+                    )))
+                }
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl DataSource for SnmpGet {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let values = self.request().await?;
+        let now = std::time::Instant::now();
+        Ok(values
+            .into_iter()
+            .zip(self.counters.iter_mut())
+            .map(|(value, counter)| {
+                value.map(|value| match value {
+                    Value::Gauge(value) => Some(value as f64),
+                    Value::Counter(value, max) => counter.update(value, max, now),
+                })
+            })
+            .collect())
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("value {data:.2}")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.oids[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const OID_INTEGER: &str = "1.3.6.1.2.1.1.7.0";
+    const OID_GAUGE32: &str = "1.3.6.1.2.1.2.2.1.5.1";
+    const OID_COUNTER32: &str = "1.3.6.1.2.1.2.2.1.10.1";
+    const OID_COUNTER64: &str = "1.3.6.1.2.1.31.1.1.1.6.1";
+    const OID_STRING: &str = "1.3.6.1.2.1.1.1.0";
+    const OID_MISSING: &str = "1.3.6.1.4.1.8072.9999.0";
+
+    // Short form BER encoding, enough for the varbinds of the test agent.
+    fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut res = vec![tag, content.len() as u8];
+        res.extend_from_slice(content);
+        res
+    }
+
+    fn unsigned(tag: u8, value: u64) -> Vec<u8> {
+        let bytes = value.to_be_bytes();
+        let start = bytes.iter().position(|x| *x != 0).unwrap_or(7);
+        let mut content = bytes[start..].to_vec();
+        if content[0] & 0x80 != 0 {
+            content.insert(0, 0);
+        }
+        tlv(tag, &content)
+    }
+
+    // Answers GET requests with the community "secret" like an SNMP agent. The counters increase
+    // with every request.
+    async fn serve() -> String {
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = socket.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            let mut buffer = vec![0; 65535];
+            for i in 0u64.. {
+                let (length, peer) = socket.recv_from(&mut buffer).await.unwrap();
+                let request = snmp2::Pdu::from_bytes(&buffer[..length]).unwrap();
+                if request.community != b"secret" {
+                    continue;
+                }
+                let varbinds: Vec<u8> = request
+                    .varbinds
+                    .clone()
+                    .flat_map(|(oid, _)| {
+                        let value = match oid.to_id_string().as_str() {
+                            OID_INTEGER => unsigned(0x02, 72),
+                            OID_GAUGE32 => unsigned(0x42, 4000000000),
+                            OID_COUNTER32 => {
+                                unsigned(0x41, (u64::from(u32::MAX) - 500 + i * 1000) % (1 << 32))
+                            }
+                            OID_COUNTER64 => unsigned(0x46, i * 1000),
+                            OID_STRING => tlv(0x04, b"Linux"),
+                            _ => tlv(0x80, &[]),
+                        };
+                        tlv(0x30, &[tlv(0x06, oid.as_bytes()), value].concat())
+                    })
+                    .collect();
+                let mut response = request.clone();
+                response.message_type = snmp2::MessageType::Response;
+                response.varbinds = snmp2::Varbinds::from_bytes(&varbinds);
+                socket
+                    .send_to(&response.to_bytes().unwrap(), peer)
+                    .await
+                    .unwrap();
+            }
+        });
+        address
+    }
+
+    fn snmp_get(address: &str, community: &str) -> SnmpGet {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Check"
+            type = "SnmpGet"
+            address = "{address}"
+            community = "{community}"
+            oids = ["{OID_INTEGER}", "{OID_GAUGE32}", "{OID_COUNTER32}", "{OID_COUNTER64}", "{OID_STRING}", "{OID_MISSING}"]
+            "#
+        ))
+        .unwrap();
+        SnmpGet::try_from(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_snmp_get() {
+        let address = serve().await;
+        let mut check = snmp_get(&address, "secret");
+        let data = check.get_data().await.unwrap();
+        assert_eq!(*data[0].as_ref().unwrap(), Some(72.0));
+        assert_eq!(*data[1].as_ref().unwrap(), Some(4000000000.0));
+        // Counters report a rate, starting with the second measurement.
+        assert_eq!(*data[2].as_ref().unwrap(), None);
+        assert_eq!(*data[3].as_ref().unwrap(), None);
+        assert!(data[4].is_err());
+        assert_eq!(data[5].as_ref().unwrap_err().to_string(), "No such object.");
+        let data = check.get_data().await.unwrap();
+        // The Counter32 wrapped around.
+        assert!(data[2].as_ref().unwrap().unwrap() > 0.0);
+        assert!(data[3].as_ref().unwrap().unwrap() > 0.0);
+
+        // An agent does not answer requests with a wrong community.
+        let mut check = snmp_get(&address, "public");
+        assert!(
+            tokio::time::timeout(std::time::Duration::from_millis(100), check.get_data())
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_oid() {
+        assert_eq!(
+            parse_oid(".1.3.6.1.4.1.8072.3.2.10").unwrap(),
+            parse_oid("1.3.6.1.4.1.8072.3.2.10").unwrap()
+        );
+        assert!(parse_oid("1").is_err());
+        assert!(parse_oid("1.40").is_err());
+        assert!(parse_oid("1.3.foo").is_err());
+    }
+
+    #[test]
+    fn test_decode_value() {
+        assert_eq!(
+            decode_value(&snmp2::Value::Integer(256)).unwrap(),
+            Value::Gauge(256)
+        );
+        assert!(decode_value(&snmp2::Value::Integer(-1)).is_err());
+        assert_eq!(
+            decode_value(&snmp2::Value::Unsigned32(u32::MAX)).unwrap(),
+            Value::Gauge(u32::MAX.into())
+        );
+        assert_eq!(
+            decode_value(&snmp2::Value::Counter32(100)).unwrap(),
+            Value::Counter(100, u32::MAX.into())
+        );
+        assert_eq!(
+            decode_value(&snmp2::Value::Counter64(100)).unwrap(),
+            Value::Counter(100, u64::MAX)
+        );
+        assert!(decode_value(&snmp2::Value::OctetString(b"foo")).is_err());
+        assert!(decode_value(&snmp2::Value::NoSuchInstance).is_err());
+    }
+
+    #[test]
+    fn test_counter_rate() {
+        let start = std::time::Instant::now();
+        let second = start + std::time::Duration::from_secs(10);
+        let mut counter = CounterRate::default();
+        assert_eq!(counter.update(1000, u64::MAX, start), None);
+        assert_eq!(counter.update(6000, u64::MAX, second), Some(500.0));
+        let mut counter = CounterRate::default();
+        counter.update(u64::from(u32::MAX) - 499, u32::MAX.into(), start);
+        assert_eq!(counter.update(500, u32::MAX.into(), second), Some(100.0));
+        let mut counter = CounterRate::default();
+        counter.update(u64::MAX - 499, u64::MAX, start);
+        assert_eq!(counter.update(500, u64::MAX, second), Some(100.0));
+    }
+}
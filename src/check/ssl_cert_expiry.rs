@@ -0,0 +1,216 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Days until the certificate of the server expires, 0 if it has already expired.
+pub struct SslCertExpiry {
+    addresses: Vec<String>,
+    sni_override: Option<String>,
+    connector: tokio_native_tls::TlsConnector,
+}
+
+impl TryFrom<&config::Check> for SslCertExpiry {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::SslCertExpiry(ssl_cert_expiry) = &check.type_ {
+            if ssl_cert_expiry.addresses.is_empty() {
+                return Err(Error::invalid_config("addresses", "cannot be empty."));
+            }
+            if ssl_cert_expiry.addresses.iter().any(|x| x.is_empty()) {
+                return Err(Error::invalid_config(
+                    "addresses",
+                    "cannot contain empty addresses.",
+                ));
+            }
+            if ssl_cert_expiry
+                .sni_override
+                .as_ref()
+                .is_some_and(|x| x.is_empty())
+            {
+                return Err(Error::invalid_config("sni_override", "cannot be empty."));
+            }
+            // Only the expiry is checked, so certificates that are invalid otherwise (e.g.
+            // self-signed or already expired ones) must not fail the handshake.
+            let connector = tokio_native_tls::native_tls::TlsConnector::builder()
+                .danger_accept_invalid_certs(true)
+                .danger_accept_invalid_hostnames(true)
+                .build()
+                .map_err(|x| Error::Message(format!("Could not create TLS connector: {x}")))?;
+            Ok(Self {
+                addresses: ssl_cert_expiry.addresses.clone(),
+                sni_override: ssl_cert_expiry.sni_override.clone(),
+                connector: connector.into(),
+            })
+        } else {
+            panic!();
+        }
+    }
+}
+
+// The host part of `host:port`, without the brackets of IPv6 addresses.
+fn host(address: &str) -> &str {
+    let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+fn days_remaining(certificate: &openssl::x509::X509Ref) -> Result<f64> {
+    let now = openssl::asn1::Asn1Time::days_from_now(0)
+        .map_err(|x| Error::Message(format!("Could not get the current time: {x}")))?;
+    let diff = now
+        .diff(certificate.not_after())
+        .map_err(|x| Error::Message(format!("Could not compare the expiry time: {x}")))?;
+    let days = f64::from(diff.days) + f64::from(diff.secs) / 86400.0;
+    Ok(days.max(0.0))
+}
+
+impl SslCertExpiry {
+    async fn connect(&self, address: &str) -> Result<f64> {
+        let stream = tokio::net::TcpStream::connect(address)
+            .await
+            .map_err(|x| Error::Message(format!("Could not connect to '{address}': {x}")))?;
+        let domain = self
+            .sni_override
+            .as_deref()
+            .unwrap_or_else(|| host(address));
+        let stream =
+            self.connector.connect(domain, stream).await.map_err(|x| {
+                Error::Message(format!("TLS handshake with '{address}' failed: {x}"))
+            })?;
+        let certificate = stream
+            .get_ref()
+            .peer_certificate()
+            .map_err(|x| Error::Message(format!("Could not get certificate of '{address}': {x}")))?
+            .ok_or_else(|| Error::Message(format!("'{address}' did not send a certificate.")))?
+            .to_der()
+            .map_err(|x| {
+                Error::Message(format!("Could not encode certificate of '{address}': {x}"))
+            })?;
+        let certificate = openssl::x509::X509::from_der(&certificate).map_err(|x| {
+            Error::Message(format!("Could not parse certificate of '{address}': {x}"))
+        })?;
+        days_remaining(&certificate)
+    }
+}
+
+#[async_trait]
+impl DataSource for SslCertExpiry {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let mut res = Vec::new();
+        for address in self.addresses.iter() {
+            res.push(self.connect(address).await.map(Some));
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("certificate expires in {data:.2} days")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.addresses[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use openssl::{asn1, hash, pkey, rsa, x509};
+
+    const DAY: i64 = 86400;
+
+    // Self-signed certificate that is valid until `not_after` (seconds since the epoch).
+    fn certificate(not_after: i64) -> (x509::X509, pkey::PKey<pkey::Private>) {
+        let key = pkey::PKey::from_rsa(rsa::Rsa::generate(2048).unwrap()).unwrap();
+        let mut name = x509::X509NameBuilder::new().unwrap();
+        name.append_entry_by_text("CN", "localhost").unwrap();
+        let name = name.build();
+        let mut builder = x509::X509::builder().unwrap();
+        builder.set_version(2).unwrap();
+        builder.set_subject_name(&name).unwrap();
+        builder.set_issuer_name(&name).unwrap();
+        builder.set_pubkey(&key).unwrap();
+        builder
+            .set_not_before(&asn1::Asn1Time::from_unix(not_after - 30 * DAY).unwrap())
+            .unwrap();
+        builder
+            .set_not_after(&asn1::Asn1Time::from_unix(not_after).unwrap())
+            .unwrap();
+        builder.sign(&key, hash::MessageDigest::sha256()).unwrap();
+        (builder.build(), key)
+    }
+
+    fn now() -> i64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    }
+
+    // Completes the TLS handshake of every connection with the given certificate.
+    async fn serve(certificate: x509::X509, key: pkey::PKey<pkey::Private>) -> String {
+        let identity = tokio_native_tls::native_tls::Identity::from_pkcs8(
+            &certificate.to_pem().unwrap(),
+            &key.private_key_to_pem_pkcs8().unwrap(),
+        )
+        .unwrap();
+        let acceptor = tokio_native_tls::TlsAcceptor::from(
+            tokio_native_tls::native_tls::TlsAcceptor::new(identity).unwrap(),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let _ = acceptor.accept(stream).await;
+            }
+        });
+        address
+    }
+
+    fn ssl_cert_expiry(addresses: Vec<String>) -> SslCertExpiry {
+        let addresses: Vec<String> = addresses.iter().map(|x| format!("\"{x}\"")).collect();
+        let config: config::Check = toml::from_str(&format!(
+            "name = \"Check\"\ntype = \"SslCertExpiry\"\naddresses = [{}]",
+            addresses.join(", ")
+        ))
+        .unwrap();
+        SslCertExpiry::try_from(&config).unwrap()
+    }
+
+    #[test]
+    fn test_days_remaining() {
+        let (certificate_valid, _) = certificate(now() + 10 * DAY);
+        let days = days_remaining(&certificate_valid).unwrap();
+        assert!((9.99..=10.0).contains(&days));
+        let (certificate_expired, _) = certificate(now() - DAY);
+        assert_eq!(days_remaining(&certificate_expired).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_host() {
+        assert_eq!(host("example.com:443"), "example.com");
+        assert_eq!(host("[::1]:443"), "::1");
+        assert_eq!(host("example.com"), "example.com");
+    }
+
+    #[tokio::test]
+    async fn test_ssl_cert_expiry() {
+        let (certificate_valid, key) = certificate(now() + 10 * DAY);
+        let address_valid = serve(certificate_valid, key).await;
+        let (certificate_expired, key) = certificate(now() - DAY);
+        let address_expired = serve(certificate_expired, key).await;
+        let refused = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address_refused = refused.local_addr().unwrap().to_string();
+        drop(refused);
+        let mut check = ssl_cert_expiry(vec![address_valid, address_expired, address_refused]);
+        let data = check.get_data().await.unwrap();
+        let days = *data[0].as_ref().unwrap().as_ref().unwrap();
+        assert!((9.99..=10.0).contains(&days));
+        assert_eq!(*data[1].as_ref().unwrap().as_ref().unwrap(), 0.0);
+        assert!(data[2].is_err());
+    }
+}
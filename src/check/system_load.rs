@@ -0,0 +1,206 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+const LOADAVG_PATH: &str = "/proc/loadavg";
+
+static PARSE_ERROR: &str = "Could not parse loadavg file.";
+
+pub struct SystemLoad {
+    id: Vec<String>,
+    load1: bool,
+    load5: bool,
+    load15: bool,
+    // Number of CPU cores the load averages are divided by, if enabled.
+    per_core: Option<f64>,
+}
+
+impl TryFrom<&config::Check> for SystemLoad {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::SystemLoad(system_load) = &check.type_ {
+            if !system_load.load1 && !system_load.load5 && !system_load.load15 {
+                Err(Error::Message(String::from(
+                    "At least one of 'load1', 'load5', or 'load15' needs to be enabled.",
+                )))
+            } else {
+                let mut id = Vec::new();
+                if system_load.load1 {
+                    id.push(String::from("load1"));
+                }
+                if system_load.load5 {
+                    id.push(String::from("load5"));
+                }
+                if system_load.load15 {
+                    id.push(String::from("load15"));
+                }
+                let per_core = if system_load.per_core {
+                    let cores = std::thread::available_parallelism().map_err(|x| {
+                        Error::Message(format!("Could not get number of CPU cores: {x}"))
+                    })?;
+                    Some(cores.get() as f64)
+                } else {
+                    None
+                };
+                Ok(Self {
+                    id,
+                    load1: system_load.load1,
+                    load5: system_load.load5,
+                    load15: system_load.load15,
+                    per_core,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+impl SystemLoad {
+    fn add_data(&self, load_averages: &Result<LoadAverages>, res: &mut Vec<Result<Option<f64>>>) {
+        let cores = self.per_core.unwrap_or(1.0);
+        let value = |field: fn(&LoadAverages) -> f64| {
+            load_averages
+                .as_ref()
+                .map(|x| Some(field(x) / cores))
+                .map_err(|x| x.clone())
+        };
+        if self.load1 {
+            res.push(value(|x| x.load1));
+        }
+        if self.load5 {
+            res.push(value(|x| x.load5));
+        }
+        if self.load15 {
+            res.push(value(|x| x.load15));
+        }
+    }
+}
+
+#[async_trait]
+impl DataSource for SystemLoad {
+    type Item = f64;
+
+    // The file is read once for all load averages.
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let load_averages = LoadAverages::try_from_file(LOADAVG_PATH).await;
+        let mut res = Vec::new();
+        self.add_data(&load_averages, &mut res);
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        match self.per_core {
+            Some(_) => format!("load average {data:.2} per core"),
+            None => format!("load average {data:.2}"),
+        }
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg_attr(test, derive(PartialEq, Debug))]
+struct LoadAverages {
+    load1: f64,
+    load5: f64,
+    load15: f64,
+}
+
+impl LoadAverages {
+    async fn try_from_file(path: &str) -> Result<Self> {
+        let buffer = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|x| Error::Message(format!("Could not open {path} for reading: {x}")))?;
+        Self::try_from(&*buffer)
+    }
+}
+
+impl TryFrom<&str> for LoadAverages {
+    type Error = Error;
+
+    // The file also contains the number of runnable and total tasks and the last PID, which are
+    // ignored.
+    fn try_from(text: &str) -> std::result::Result<Self, Self::Error> {
+        let mut parts = text.split_whitespace().map(|x| {
+            x.parse::<f64>()
+                .ok()
+                .filter(|x| x.is_finite())
+                .ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))
+        });
+        let mut next = || {
+            parts
+                .next()
+                .ok_or_else(|| Error::Message(PARSE_ERROR.to_string()))?
+        };
+        Ok(Self {
+            load1: next()?,
+            load5: next()?,
+            load15: next()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn system_load(per_core: Option<f64>) -> SystemLoad {
+        SystemLoad {
+            id: vec![
+                String::from("load1"),
+                String::from("load5"),
+                String::from("load15"),
+            ],
+            load1: true,
+            load5: true,
+            load15: true,
+            per_core,
+        }
+    }
+
+    #[test]
+    fn test_load_averages_from_str() {
+        let load_averages = LoadAverages::try_from("0.52 1.25 2.00 3/456 7890\n").unwrap();
+        assert_eq!(
+            load_averages,
+            LoadAverages {
+                load1: 0.52,
+                load5: 1.25,
+                load15: 2.0,
+            }
+        );
+        assert!(LoadAverages::try_from("0.52 1.25").is_err());
+        assert!(LoadAverages::try_from("0.52 foo 2.00 3/456 7890").is_err());
+        assert!(LoadAverages::try_from("").is_err());
+    }
+
+    #[test]
+    fn test_add_data() {
+        let load_averages = LoadAverages::try_from("3.00 1.50 0.75 1/100 1234").unwrap();
+        let mut res = Vec::new();
+        system_load(None).add_data(&Ok(load_averages), &mut res);
+        let res: Vec<f64> = res.into_iter().map(|x| x.unwrap().unwrap()).collect();
+        assert_eq!(res, [3.0, 1.5, 0.75]);
+
+        let load_averages = LoadAverages::try_from("3.00 1.50 0.75 1/100 1234").unwrap();
+        let mut res = Vec::new();
+        system_load(Some(4.0)).add_data(&Ok(load_averages), &mut res);
+        let res: Vec<f64> = res.into_iter().map(|x| x.unwrap().unwrap()).collect();
+        assert_eq!(res, [0.75, 0.375, 0.1875]);
+
+        // A load above 1 is normal with a single core and is not scaled down.
+        let load_averages = LoadAverages::try_from("1.20 1.10 1.00 2/100 1234").unwrap();
+        let mut res = Vec::new();
+        system_load(Some(1.0)).add_data(&Ok(load_averages), &mut res);
+        assert_eq!(*res[0].as_ref().unwrap(), Some(1.2));
+
+        let mut res = Vec::new();
+        system_load(None).add_data(&Err(Error::Message(PARSE_ERROR.to_string())), &mut res);
+        assert_eq!(res.len(), 3);
+        assert!(res.iter().all(|x| x.is_err()));
+    }
+}
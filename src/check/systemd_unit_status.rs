@@ -92,8 +92,8 @@ impl DataSource for SystemdUnitStatus {
             res.push(match code {
                 0 => Self::Item::new(true).map(Some),
                 SYSTEMCTL_STATUS_NOT_ACTIVE => Self::Item::new(false).map(Some),
-                SYSTEMCTL_STATUS_NO_SUCH_UNIT => Err(Error(String::from("No such unit."))),
-                code => Err(Error(format!("Unknown error code {code}."))),
+                SYSTEMCTL_STATUS_NO_SUCH_UNIT => Err(Error::Message(String::from("No such unit."))),
+                code => Err(Error::Message(format!("Unknown error code {code}."))),
             });
         }
         Ok(res)
@@ -0,0 +1,133 @@
+use super::DataSource;
+use crate::config;
+use crate::{Error, Result};
+use async_trait::async_trait;
+
+// Connection time in milliseconds. Failed connections are errors.
+pub struct TcpConnect {
+    addresses: Vec<String>,
+    connect_timeout: std::time::Duration,
+}
+
+impl TryFrom<&config::Check> for TcpConnect {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::TcpConnect(tcp_connect) = &check.type_ {
+            if tcp_connect.addresses.is_empty() {
+                Err(Error::invalid_config("addresses", "cannot be empty."))
+            } else if tcp_connect.addresses.iter().any(|x| x.is_empty()) {
+                Err(Error::invalid_config(
+                    "addresses",
+                    "cannot contain empty addresses.",
+                ))
+            } else if tcp_connect.connect_timeout == 0 {
+                Err(Error::invalid_config("connect_timeout", "cannot be 0."))
+            } else {
+                Ok(Self {
+                    addresses: tcp_connect.addresses.clone(),
+                    connect_timeout: std::time::Duration::from_secs(
+                        tcp_connect.connect_timeout.into(),
+                    ),
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+// The name is resolved before the timer starts so that only the connection itself is measured.
+async fn connect(address: &str, connect_timeout: std::time::Duration) -> Result<f64> {
+    let socket_address = tokio::net::lookup_host(address)
+        .await
+        .map_err(|x| Error::Message(format!("Could not resolve '{address}': {x}")))?
+        .next()
+        .ok_or_else(|| Error::Message(format!("Could not resolve '{address}'.")))?;
+    let start = tokio::time::Instant::now();
+    match tokio::time::timeout(
+        connect_timeout,
+        tokio::net::TcpStream::connect(socket_address),
+    )
+    .await
+    {
+        Ok(Ok(_)) => Ok(start.elapsed().as_secs_f64() * 1000.0),
+        Ok(Err(err)) => Err(Error::Message(format!(
+            "Could not connect to '{address}': {err}"
+        ))),
+        Err(_) => Err(Error::Message(format!(
+            "Connection to '{address}' timed out after {} seconds.",
+            connect_timeout.as_secs()
+        ))),
+    }
+}
+
+#[async_trait]
+impl DataSource for TcpConnect {
+    type Item = f64;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        let mut res = Vec::new();
+        for address in self.addresses.iter() {
+            res.push(connect(address, self.connect_timeout).await.map(Some));
+        }
+        Ok(res)
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        format!("connection time {data:.2} ms")
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.addresses[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alarm::{DataSink, SinkDecision};
+
+    fn tcp_connect(addresses: Vec<String>) -> TcpConnect {
+        TcpConnect {
+            addresses,
+            connect_timeout: std::time::Duration::from_secs(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connect() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        // Bound but not listening, so the connection is refused.
+        let refused = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let refused_address = refused.local_addr().unwrap().to_string();
+        drop(refused);
+        let mut check = tcp_connect(vec![address, refused_address]);
+        let data = check.get_data().await.unwrap();
+        let latency = *data[0].as_ref().unwrap().as_ref().unwrap();
+        assert!(latency >= 0.0);
+        assert!(data[1].is_err());
+
+        // Any real connection takes longer than 0 ms.
+        let config: config::Alarm = toml::from_str(
+            r#"
+            name = "Latency"
+            action = "Action"
+            level = 0
+            "#,
+        )
+        .unwrap();
+        let mut alarm = crate::alarm::Level::<f64>::try_from(&config).unwrap();
+        assert!(matches!(
+            alarm.put_data(&latency).unwrap(),
+            SinkDecision::Bad
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_error() {
+        let mut check = tcp_connect(vec![String::from("missing-port")]);
+        assert!(check.get_data().await.unwrap()[0].is_err());
+    }
+}
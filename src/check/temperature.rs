@@ -38,13 +38,15 @@ impl Temperature {
                         subfeature
                             .get_value()
                             .map(|x| x as <Item as Measurement>::Data)
-                            .map_err(|x| Error(format!("Could not read temperature: {x}")))?,
+                            .map_err(|x| {
+                                Error::Message(format!("Could not read temperature: {x}"))
+                            })?,
                     )
                     .map(Some);
                 };
             }
         }
-        Err(Error(String::from("Could not read temperature.")))
+        Err(Error::Message(String::from("Could not read temperature.")))
     }
 }
 
@@ -55,7 +57,7 @@ impl TryFrom<&config::SensorsId> for SensorsId {
         let mut res = None;
         let sensor = sensors::Sensors::new();
         for chip in sensor.detected_chips(sensors_id.sensor()).map_err(|x| {
-            Error(format!(
+            Error::Message(format!(
                 "Failed to parse sensor name '{}': {x}",
                 sensors_id.sensor()
             ))
@@ -76,7 +78,9 @@ impl TryFrom<&config::SensorsId> for SensorsId {
                     *x.subfeature_type() == sensors::SubfeatureType::SENSORS_SUBFEATURE_TEMP_INPUT
                 }) {
                     if res.is_some() {
-                        return Err(Error(format!("Sensor '{sensors_id}' is not unique.")));
+                        return Err(Error::Message(format!(
+                            "Sensor '{sensors_id}' is not unique."
+                        )));
                     }
                     res = Some(Self {
                         sensor: chip_name.clone(),
@@ -85,7 +89,7 @@ impl TryFrom<&config::SensorsId> for SensorsId {
                 }
             }
         }
-        res.ok_or_else(|| Error(format!("Sensor '{sensors_id}' not found.")))
+        res.ok_or_else(|| Error::Message(format!("Sensor '{sensors_id}' not found.")))
     }
 }
 
@@ -0,0 +1,209 @@
+use super::DataSource;
+use crate::{config, measurement};
+use crate::{Error, Result};
+use async_trait::async_trait;
+use measurement::Measurement;
+
+// Connections between services that are expected to work, tried from the host MinMon runs on.
+pub struct Topology {
+    id: Vec<String>,
+    connections: Vec<config::TopologyConnection>,
+    connect_timeout: std::time::Duration,
+    use_traceroute: bool,
+}
+
+impl TryFrom<&config::Check> for Topology {
+    type Error = Error;
+
+    fn try_from(check: &config::Check) -> std::result::Result<Self, Self::Error> {
+        if let config::CheckType::Topology(topology) = &check.type_ {
+            if topology.connections.is_empty() {
+                Err(Error::invalid_config("connections", "cannot be empty."))
+            } else if topology.connections.iter().any(|x| x.target.is_empty()) {
+                Err(Error::invalid_config("target", "cannot be empty."))
+            } else if topology.connect_timeout == 0 {
+                Err(Error::invalid_config("connect_timeout", "cannot be 0."))
+            } else {
+                Ok(Self {
+                    id: vec![check.name.clone()],
+                    connections: topology.connections.clone(),
+                    connect_timeout: std::time::Duration::from_secs(
+                        topology.connect_timeout.into(),
+                    ),
+                    use_traceroute: topology.use_traceroute,
+                })
+            }
+        } else {
+            panic!();
+        }
+    }
+}
+
+fn describe(connection: &config::TopologyConnection) -> String {
+    match connection.source {
+        Some(source) => format!("{source} -> {}:{}", connection.target, connection.port),
+        None => format!("{}:{}", connection.target, connection.port),
+    }
+}
+
+// Connects from the source address if there is one, so that e.g. the route of a specific network
+// is used.
+async fn connect(
+    connection: &config::TopologyConnection,
+    connect_timeout: std::time::Duration,
+) -> Result<()> {
+    let target = tokio::net::lookup_host((connection.target.as_str(), connection.port))
+        .await
+        .map_err(|x| Error::Message(format!("Could not resolve: {x}")))?
+        .find(|x| match connection.source {
+            Some(source) => source.is_ipv4() == x.is_ipv4(),
+            None => true,
+        })
+        .ok_or_else(|| Error::Message(String::from("Could not resolve.")))?;
+    let socket = if target.is_ipv4() {
+        tokio::net::TcpSocket::new_v4()
+    } else {
+        tokio::net::TcpSocket::new_v6()
+    }?;
+    if let Some(source) = connection.source {
+        socket
+            .bind((source, 0).into())
+            .map_err(|x| Error::Message(format!("Could not bind to {source}: {x}")))?;
+    }
+    match tokio::time::timeout(connect_timeout, socket.connect(target)).await {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(err)) => Err(Error::Message(err.to_string())),
+        Err(_) => Err(Error::Message(format!(
+            "Timed out after {} seconds",
+            connect_timeout.as_secs()
+        ))),
+    }
+}
+
+// The output is kept even if traceroute fails, e.g. because the target cannot be reached.
+async fn traceroute(connection: &config::TopologyConnection) -> String {
+    let mut command = tokio::process::Command::new("traceroute");
+    command.args(["-n", "-w", "1", "-q", "1"]);
+    if let Some(source) = connection.source {
+        command.arg("-s").arg(source.to_string());
+    }
+    command.arg(&connection.target).kill_on_drop(true);
+    match command.output().await {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+        Err(err) => format!("Could not run traceroute: {err}"),
+    }
+}
+
+impl Topology {
+    async fn check(&self) -> Result<measurement::Topology> {
+        let results = futures_util::future::join_all(
+            self.connections
+                .iter()
+                .map(|x| connect(x, self.connect_timeout)),
+        )
+        .await;
+        let mut failures = Vec::new();
+        for (connection, result) in self.connections.iter().zip(results) {
+            if let Err(err) = result {
+                failures.push(measurement::ConnectionFailure {
+                    connection: describe(connection),
+                    error: err.to_string(),
+                    traceroute: if self.use_traceroute {
+                        Some(traceroute(connection).await)
+                    } else {
+                        None
+                    },
+                });
+            }
+        }
+        measurement::Topology::new((self.connections.len(), failures))
+    }
+}
+
+#[async_trait]
+impl DataSource for Topology {
+    type Item = measurement::Topology;
+
+    async fn get_data(&mut self) -> Result<Vec<Result<Option<Self::Item>>>> {
+        Ok(vec![self.check().await.map(Some)])
+    }
+
+    fn format_data(&self, data: &Self::Item) -> String {
+        data.to_string()
+    }
+
+    fn ids(&self) -> &[String] {
+        &self.id[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn topology(connections: &str, use_traceroute: bool) -> Result<Topology> {
+        let config: config::Check = toml::from_str(&format!(
+            r#"
+            name = "Mesh"
+            type = "Topology"
+            connections = [{connections}]
+            connect_timeout = 1
+            use_traceroute = {use_traceroute}
+            "#
+        ))
+        .unwrap();
+        Topology::try_from(&config)
+    }
+
+    #[tokio::test]
+    async fn test_topology() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = listener.local_addr().unwrap().port();
+        // Bound but not listening, so the connection is refused.
+        let refused = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = refused.local_addr().unwrap().port();
+        drop(refused);
+        let mut check = topology(
+            &format!(
+                r#"
+                {{ target = "127.0.0.1", port = {open} }},
+                {{ source = "127.0.0.1", target = "localhost", port = {open} }},
+                {{ source = "127.0.0.1", target = "127.0.0.1", port = {closed} }},
+                "#
+            ),
+            false,
+        )
+        .unwrap();
+        assert_eq!(check.ids(), ["Mesh"]);
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        assert_eq!(data.connection_count(), 3);
+        assert_eq!(data.failures().len(), 1);
+        assert_eq!(
+            data.failures()[0].connection,
+            format!("127.0.0.1 -> 127.0.0.1:{closed}")
+        );
+        assert!(data.failures()[0].traceroute.is_none());
+        assert_eq!(check.format_data(&data), "1 of 3 connections failed");
+    }
+
+    #[tokio::test]
+    async fn test_bind_error() {
+        // 192.0.2.1 is reserved for documentation, so it is not a local address.
+        let mut check = topology(
+            r#"{ source = "192.0.2.1", target = "127.0.0.1", port = 1 }"#,
+            false,
+        )
+        .unwrap();
+        let data = check.get_data().await.unwrap().remove(0).unwrap().unwrap();
+        assert!(data.failures()[0]
+            .error
+            .starts_with("Could not bind to 192.0.2.1"));
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(topology("", false).is_err());
+        assert!(topology(r#"{ target = "", port = 1 }"#, false).is_err());
+        assert!(topology(r#"{ target = "localhost", port = 1 }"#, true).is_ok());
+    }
+}
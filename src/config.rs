@@ -2,10 +2,6 @@ use crate::{Error, PlaceholderMap};
 
 use serde::Deserialize;
 
-trait Validate {
-    fn validate(&self) -> bool;
-}
-
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
@@ -14,8 +10,18 @@ pub struct Config {
     #[serde(default)]
     pub report: Report,
     #[serde(default)]
+    pub state: State,
+    #[serde(default)]
+    pub defaults: Defaults,
+    #[serde(default)]
+    pub dead_letter_queue: DeadLetterQueue,
+    #[serde(default)]
+    pub rate_limit: RateLimit,
+    #[serde(default)]
     pub actions: Vec<Action>,
     #[serde(default)]
+    pub alarm_groups: Vec<AlarmGroup>,
+    #[serde(default)]
     pub checks: Vec<Check>,
 }
 
@@ -54,6 +60,27 @@ impl From<LogLevel> for log::Level {
     }
 }
 
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum AlarmLogLevel {
+    Off,
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl From<AlarmLogLevel> for log::LevelFilter {
+    fn from(level: AlarmLogLevel) -> Self {
+        match level {
+            AlarmLogLevel::Off => log::LevelFilter::Off,
+            AlarmLogLevel::Debug => log::LevelFilter::Debug,
+            AlarmLogLevel::Info => log::LevelFilter::Info,
+            AlarmLogLevel::Warning => log::LevelFilter::Warn,
+            AlarmLogLevel::Error => log::LevelFilter::Error,
+        }
+    }
+}
+
 #[derive(Default, Deserialize, PartialEq, Debug)]
 pub enum LogTarget {
     #[default]
@@ -87,6 +114,97 @@ impl Default for Report {
     }
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct State {
+    #[serde(default)]
+    pub path: Option<std::path::PathBuf>,
+    #[serde(default = "default::state_shutdown_timeout")]
+    pub shutdown_timeout: u32,
+    #[serde(default)]
+    pub max_age: Option<u32>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            path: None,
+            shutdown_timeout: default::STATE_SHUTDOWN_TIMEOUT,
+            max_age: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Defaults {
+    #[serde(default = "default::check_timeout")]
+    pub check_timeout: u32,
+}
+
+impl Default for Defaults {
+    fn default() -> Self {
+        Self {
+            check_timeout: default::CHECK_TIMEOUT,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeadLetterQueue {
+    #[serde(default)]
+    pub path: Option<std::path::PathBuf>,
+    #[serde(default = "default::dead_letter_queue_max_entries")]
+    pub max_entries: u32,
+    #[serde(default = "default::dead_letter_queue_retry_interval")]
+    pub retry_interval: u32,
+    #[serde(default = "default::dead_letter_queue_max_retry_interval")]
+    pub max_retry_interval: u32,
+}
+
+impl Default for DeadLetterQueue {
+    fn default() -> Self {
+        Self {
+            path: None,
+            max_entries: default::DEAD_LETTER_QUEUE_MAX_ENTRIES,
+            retry_interval: default::DEAD_LETTER_QUEUE_RETRY_INTERVAL,
+            max_retry_interval: default::DEAD_LETTER_QUEUE_MAX_RETRY_INTERVAL,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimit {
+    #[serde(default)]
+    pub max_notifications_per_window: Option<u32>,
+    #[serde(default = "default::rate_limit_window")]
+    pub window: u32,
+    #[serde(default)]
+    pub overflow: RateLimitOverflow,
+    #[serde(default = "default::rate_limit_max_queued")]
+    pub max_queued: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max_notifications_per_window: None,
+            window: default::RATE_LIMIT_WINDOW,
+            overflow: RateLimitOverflow::default(),
+            max_queued: default::RATE_LIMIT_MAX_QUEUED,
+        }
+    }
+}
+
+#[derive(Default, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum RateLimitOverflow {
+    #[default]
+    Drop,
+    Queue,
+}
+
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct ReportEvent {
@@ -98,7 +216,7 @@ pub struct ReportEvent {
     pub placeholders: PlaceholderMap,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, PartialEq, Clone)]
 pub struct Action {
     #[serde(default)]
     pub disable: bool,
@@ -111,19 +229,39 @@ pub struct Action {
     pub type_: ActionType,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(tag = "type")]
 pub enum ActionType {
+    Digest(ActionDigest),
     #[cfg(feature = "smtp")]
     Email(ActionEmail),
+    #[cfg(feature = "grpc")]
+    GrpcHealth(ActionGrpcHealth),
+    #[cfg(feature = "http-health")]
+    HttpHealth(ActionHttpHealth),
+    JsonLines(ActionJsonLines),
     Log(ActionLog),
     Process(ActionProcess),
+    #[cfg(feature = "syslog")]
+    Syslog(ActionSyslog),
     #[cfg(feature = "http")]
     Webhook(ActionWebhook),
+    #[cfg(feature = "websocket")]
+    WebSocket(ActionWebSocket),
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionDigest {
+    pub action: String,
+    #[serde(default = "default::action_digest_window")]
+    pub window: u32,
+    #[serde(default = "default::action_digest_item_template")]
+    pub item_template: String,
 }
 
 #[cfg(feature = "smtp")]
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ActionEmail {
     pub from: String,
@@ -151,7 +289,31 @@ pub enum SmtpSecurity {
     Plain,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[cfg(feature = "grpc")]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionGrpcHealth {
+    pub bind_address: std::net::SocketAddr,
+}
+
+#[cfg(feature = "http-health")]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionHttpHealth {
+    pub bind_address: std::net::SocketAddr,
+    #[serde(default)]
+    pub readiness_alarms: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionJsonLines {
+    pub path: std::path::PathBuf,
+    #[serde(default)]
+    pub max_file_size_bytes: Option<u64>,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ActionLog {
     #[serde(default)]
@@ -159,15 +321,52 @@ pub struct ActionLog {
     pub template: String,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ActionProcess {
     #[serde(flatten)]
     pub process_config: ProcessConfig,
 }
 
+#[cfg(feature = "syslog")]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionSyslog {
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default)]
+    pub facility: SyslogFacility,
+    pub template: String,
+}
+
+#[derive(Deserialize, serde::Serialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum Severity {
+    Critical,
+    High,
+    Medium,
+    Low,
+    #[default]
+    Info,
+}
+
+#[cfg(feature = "syslog")]
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum SyslogFacility {
+    #[default]
+    User,
+    Daemon,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
 #[cfg(feature = "http")]
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct ActionWebhook {
     pub url: String,
@@ -179,6 +378,15 @@ pub struct ActionWebhook {
     pub body: String,
 }
 
+#[cfg(feature = "websocket")]
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ActionWebSocket {
+    pub bind_address: std::net::SocketAddr,
+    #[serde(default = "default::websocket_channel_capacity")]
+    pub channel_capacity: usize,
+}
+
 #[cfg(feature = "http")]
 #[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
 #[allow(clippy::upper_case_acronyms)]
@@ -201,6 +409,16 @@ pub struct Check {
     #[serde(default)]
     pub timeout: Option<u32>,
     #[serde(default)]
+    pub jitter: Option<u32>,
+    #[serde(default)]
+    pub cron: Option<String>,
+    #[serde(default = "default::check_max_concurrent_alarms")]
+    pub max_concurrent_alarms: u32,
+    #[serde(default)]
+    pub backpressure: Backpressure,
+    #[serde(default)]
+    pub backpressure_action: Option<String>,
+    #[serde(default)]
     pub placeholders: PlaceholderMap,
     #[serde(flatten)]
     pub type_: CheckType,
@@ -208,19 +426,82 @@ pub struct Check {
     pub alarms: Vec<Alarm>,
 }
 
+// What a check does when a cycle is due while the check is still busy.
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum Backpressure {
+    #[default]
+    Skip,
+    Wait,
+}
+
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(tag = "type")]
 pub enum CheckType {
+    AlarmSummary(CheckAlarmSummary),
+    DnsResolution(CheckDnsResolution),
     #[cfg(feature = "docker")]
     DockerContainerStatus(CheckDockerContainerStatus),
+    #[cfg(feature = "http")]
+    ElasticsearchQuery(CheckElasticsearchQuery),
     FilesystemUsage(CheckFilesystemUsage),
+    #[cfg(feature = "http")]
+    HttpStatus(CheckHttpStatus),
+    #[cfg(feature = "http")]
+    Jmx(CheckJmx),
+    #[cfg(feature = "kafka")]
+    KafkaConsumerLag(CheckKafkaConsumerLag),
+    #[cfg(feature = "kubernetes")]
+    KubernetesDeployment(CheckKubernetesDeployment),
+    #[cfg(feature = "kubernetes")]
+    KubernetesPod(CheckKubernetesPod),
+    LogFilePattern(CheckLogFilePattern),
     MemoryUsage(CheckMemoryUsage),
+    NetworkInterface(CheckNetworkInterface),
     NetworkThroughput(CheckNetworkThroughput),
+    PathExists(CheckPathExists),
+    PortScan(CheckPortScan),
+    #[cfg(feature = "postgres")]
+    Postgres(CheckPostgres),
     PressureAverage(CheckPressureAverage),
     ProcessExitStatus(CheckProcessExitStatus),
+    ProcessMemory(CheckProcessMemory),
+    ProcessOutput(CheckProcessOutput),
+    ProcessUptime(CheckProcessUptime),
+    ProcessValue(CheckProcessValue),
+    #[cfg(feature = "http")]
+    PrometheusComparison(CheckPrometheusComparison),
+    #[cfg(feature = "http")]
+    PrometheusQuery(CheckPrometheusQuery),
+    #[cfg(feature = "redis")]
+    Redis(CheckRedis),
+    #[cfg(feature = "redis")]
+    RedisLatency(CheckRedisLatency),
+    #[cfg(feature = "s3")]
+    S3Bucket(CheckS3Bucket),
+    #[cfg(feature = "snmp")]
+    SnmpGet(CheckSnmpGet),
+    #[cfg(feature = "ssl-cert")]
+    SslCertExpiry(CheckSslCertExpiry),
+    SystemLoad(CheckSystemLoad),
     SystemdUnitStatus(CheckSystemdUnitStatus),
+    TcpConnect(CheckTcpConnect),
     #[cfg(feature = "sensors")]
     Temperature(CheckTemperature),
+    Topology(CheckTopology),
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckAlarmSummary {
+    pub alarm_ids: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckDnsResolution {
+    pub hostnames: Vec<String>,
+    #[serde(default)]
+    pub min_cache_ttl: u32,
 }
 
 #[cfg(feature = "docker")]
@@ -232,10 +513,91 @@ pub struct CheckDockerContainerStatus {
     pub containers: Vec<String>,
 }
 
+#[cfg(feature = "http")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckElasticsearchQuery {
+    pub url: String,
+    pub index: String,
+    #[serde(default)]
+    pub query: Option<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    #[serde(default = "default::check_elasticsearch_query_verify_certificate")]
+    pub verify_certificate: bool,
+}
+
+#[cfg(feature = "kubernetes")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckKubernetesDeployment {
+    #[serde(default)]
+    pub kubeconfig: Option<String>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    pub deployments: Vec<String>,
+}
+
+#[cfg(feature = "kubernetes")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckKubernetesPod {
+    #[serde(default)]
+    pub kubeconfig: Option<String>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub pod: Option<String>,
+    #[serde(default)]
+    pub label_selector: Option<String>,
+}
+
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct CheckFilesystemUsage {
     pub mountpoints: Vec<String>,
+    #[serde(default = "default::check_filesystem_usage_space")]
+    pub space: bool,
+    #[serde(default)]
+    pub inodes: bool,
+}
+
+#[cfg(feature = "http")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckJmx {
+    pub url: String,
+    pub attributes: Vec<String>,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+#[cfg(feature = "kafka")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckKafkaConsumerLag {
+    pub brokers: Vec<String>,
+    pub group: String,
+    pub topic: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckLogFilePattern {
+    pub path: String,
+    pub pattern: String,
+    #[serde(default = "default::check_log_file_pattern_max_lines_per_check")]
+    pub max_lines_per_check: usize,
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
@@ -247,6 +609,20 @@ pub struct CheckMemoryUsage {
     pub swap: bool,
 }
 
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckNetworkInterface {
+    pub interfaces: Vec<String>,
+    #[serde(default)]
+    pub received: bool,
+    #[serde(default)]
+    pub sent: bool,
+    #[serde(default)]
+    pub packets: bool,
+    #[serde(default)]
+    pub log_format: DataSizeFormat,
+}
+
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct CheckNetworkThroughput {
@@ -269,59 +645,277 @@ pub enum DataSizeFormat {
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
-pub struct CheckPressureAverage {
-    #[serde(default)]
-    pub cpu: bool,
-    #[serde(default)]
-    pub io: PressureChoice,
-    #[serde(default)]
-    pub memory: PressureChoice,
-    #[serde(default)]
-    pub avg10: bool,
-    #[serde(default)]
-    pub avg60: bool,
+pub struct CheckPathExists {
+    pub paths: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckPortScan {
+    pub host: String,
+    pub ports: Vec<u16>,
     #[serde(default)]
-    pub avg300: bool,
+    pub mode: PortMode,
+    #[serde(default = "default::check_port_scan_connect_timeout")]
+    pub connect_timeout: u32,
 }
 
 #[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
-pub enum PressureChoice {
+pub enum PortMode {
     #[default]
-    None,
-    Some,
-    Full,
-    Both,
+    MustBeOpen,
+    MustBeClosed,
 }
 
+#[cfg(feature = "http")]
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
-pub struct CheckSystemdUnitStatus {
-    pub units: Vec<SystemdUnitConfig>,
+pub struct CheckHttpStatus {
+    pub url: String,
+    #[serde(default = "default::check_http_status_method")]
+    pub method: HttpMethod,
+    #[serde(default)]
+    pub headers: std::collections::HashMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default = "default::check_http_status_max_redirects")]
+    pub max_redirects: u32,
 }
 
+#[cfg(feature = "http")]
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
-#[serde(untagged)]
-pub enum SystemdUnitConfig {
-    System(String),
-    User(SystemdUnitConfigUser),
+pub struct CheckPrometheusComparison {
+    pub url: String,
+    pub numerator_query: String,
+    pub denominator_query: String,
+    #[serde(default)]
+    pub expect_single_series: bool,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub bearer_token: Option<String>,
 }
 
+#[cfg(feature = "http")]
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
-pub struct SystemdUnitConfigUser {
-    pub unit: String,
+pub struct CheckPrometheusQuery {
+    pub url: String,
+    pub query: String,
     #[serde(default)]
-    pub uid: u32,
+    pub expect_single_series: bool,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub bearer_token: Option<String>,
 }
 
-impl SystemdUnitConfig {
-    pub fn unit(&self) -> &str {
-        match self {
-            SystemdUnitConfig::System(unit) => unit,
-            SystemdUnitConfig::User(config) => &config.unit,
-        }
-    }
+#[cfg(feature = "redis")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckRedis {
+    pub address: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub database: u32,
+    pub command: String,
+    #[serde(default)]
+    pub field_name: Option<String>,
+}
+
+#[cfg(feature = "redis")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckRedisLatency {
+    pub address: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub database: u32,
+}
+
+#[cfg(feature = "s3")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckS3Bucket {
+    pub bucket: String,
+    #[serde(default)]
+    pub prefix: Option<String>,
+    #[serde(default)]
+    pub region: Option<String>,
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub measure: S3Measure,
+}
+
+#[cfg(feature = "s3")]
+#[derive(Default, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum S3Measure {
+    #[default]
+    Size,
+    ObjectCount,
+}
+
+#[cfg(feature = "snmp")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSnmpGet {
+    pub address: String,
+    #[serde(default = "default::check_snmp_get_community")]
+    pub community: String,
+    pub oids: Vec<String>,
+}
+
+#[cfg(feature = "ssl-cert")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSslCertExpiry {
+    pub addresses: Vec<String>,
+    #[serde(default)]
+    pub sni_override: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckTcpConnect {
+    pub addresses: Vec<String>,
+    #[serde(default = "default::check_tcp_connect_timeout")]
+    pub connect_timeout: u32,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckTopology {
+    pub connections: Vec<TopologyConnection>,
+    #[serde(default = "default::check_tcp_connect_timeout")]
+    pub connect_timeout: u32,
+    #[serde(default)]
+    pub use_traceroute: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct TopologyConnection {
+    #[serde(default)]
+    pub source: Option<std::net::IpAddr>,
+    pub target: String,
+    pub port: u16,
+}
+
+#[cfg(feature = "postgres")]
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckPostgres {
+    pub connection_string: String,
+    #[serde(default = "default::check_postgres_query")]
+    pub query: String,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckProcessUptime {
+    #[serde(default)]
+    pub pids: Vec<u32>,
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckProcessMemory {
+    #[serde(default)]
+    pub pids: Vec<u32>,
+    #[serde(default)]
+    pub names: Vec<String>,
+    #[serde(default)]
+    pub rss: bool,
+    #[serde(default)]
+    pub swap: bool,
+    #[serde(default)]
+    pub peak: bool,
+    #[serde(default)]
+    pub log_format: DataSizeFormat,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckPressureAverage {
+    #[serde(default)]
+    pub cpu: bool,
+    #[serde(default)]
+    pub io: PressureChoice,
+    #[serde(default)]
+    pub memory: PressureChoice,
+    #[serde(default)]
+    pub avg10: bool,
+    #[serde(default)]
+    pub avg60: bool,
+    #[serde(default)]
+    pub avg300: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSystemLoad {
+    #[serde(default)]
+    pub load1: bool,
+    #[serde(default)]
+    pub load5: bool,
+    #[serde(default)]
+    pub load15: bool,
+    #[serde(default)]
+    pub per_core: bool,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone, Copy, Default)]
+pub enum PressureChoice {
+    #[default]
+    None,
+    Some,
+    Full,
+    Both,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckSystemdUnitStatus {
+    pub units: Vec<SystemdUnitConfig>,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+#[serde(untagged)]
+pub enum SystemdUnitConfig {
+    System(String),
+    User(SystemdUnitConfigUser),
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SystemdUnitConfigUser {
+    pub unit: String,
+    #[serde(default)]
+    pub uid: u32,
+}
+
+impl SystemdUnitConfig {
+    pub fn unit(&self) -> &str {
+        match self {
+            SystemdUnitConfig::System(unit) => unit,
+            SystemdUnitConfig::User(config) => &config.unit,
+        }
+    }
 
     pub fn uid(&self) -> u32 {
         match self {
@@ -386,6 +980,34 @@ pub struct CheckProcessExitStatus {
 
 #[derive(Deserialize, PartialEq, Debug)]
 #[serde(deny_unknown_fields)]
+pub struct CheckProcessOutput {
+    #[serde(flatten)]
+    pub process_config: ProcessConfig,
+}
+
+#[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct CheckProcessValue {
+    #[serde(default)]
+    pub value_type: ValueType,
+    #[serde(default)]
+    pub route_by: Option<String>,
+    #[serde(default)]
+    pub max_routes: Option<u32>,
+    #[serde(flatten)]
+    pub process_config: ProcessConfig,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub enum ValueType {
+    #[default]
+    Float,
+    Integer,
+}
+
+#[derive(Deserialize, PartialEq, Debug, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct ProcessConfig {
     pub path: std::path::PathBuf,
     #[serde(default)]
@@ -401,6 +1023,14 @@ pub struct ProcessConfig {
 }
 
 #[derive(Deserialize, PartialEq, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmGroup {
+    pub name: String,
+    #[serde(default)]
+    pub placeholders: PlaceholderMap,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
 pub struct Alarm {
     #[serde(default)]
     pub disable: bool,
@@ -408,10 +1038,24 @@ pub struct Alarm {
     pub action: String,
     #[serde(default)]
     pub placeholders: PlaceholderMap,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub severity: Severity,
+    #[serde(default)]
+    pub runbook_url: Option<String>,
+    #[serde(default)]
+    pub owner: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
     #[serde(default = "default::check_alarm_cycles")]
     pub cycles: u32,
     #[serde(default)]
-    pub repeat_cycles: u32,
+    pub repeat_cycles: RepeatCycles,
+    #[serde(default)]
+    pub escalation: Vec<AlarmEscalation>,
     #[serde(default)]
     pub recover_action: Option<String>,
     #[serde(default)]
@@ -430,22 +1074,121 @@ pub struct Alarm {
     pub error_recover_placeholders: PlaceholderMap,
     #[serde(default)]
     pub invert: bool,
+    #[serde(default)]
+    pub cooldown: Option<u32>,
+    #[serde(default)]
+    pub auto_recovery: Option<u32>,
+    #[serde(default)]
+    pub sample_rate: Option<f64>,
+    #[serde(default)]
+    pub warn_if_never_bad_cycles: Option<u32>,
+    #[serde(default)]
+    pub warn_action: Option<String>,
+    #[serde(default)]
+    pub max_error_cycles: Option<u32>,
+    #[serde(default)]
+    pub warn_fast_recovery: bool,
+    #[serde(default = "default::alarm_fast_recovery_warn_count")]
+    pub fast_recovery_warn_count: u32,
+    #[serde(default)]
+    pub warm_up_cycles: u32,
+    #[serde(default)]
+    pub stabilize_cycles: u32,
+    #[serde(default)]
+    pub placeholder_sources: Vec<PlaceholderSource>,
+    #[serde(default)]
+    pub depends_on_alarm_id: Option<String>,
+    #[serde(default)]
+    pub log_level: Option<AlarmLogLevel>,
+    #[serde(default)]
+    pub votes: Vec<AlarmVote>,
+    #[serde(default)]
+    pub threshold_weight: Option<f64>,
     #[serde(flatten)]
     pub type_: AlarmType,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+// One of the data sinks of a weighted voting alarm, configured like the alarm type options of the
+// alarm itself.
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+pub struct AlarmVote {
+    pub weight: f64,
+    #[serde(flatten)]
+    pub type_: AlarmType,
+}
+
+// `repeat_cycles` is either a fixed number of cycles, a list of cycles or an exponential backoff.
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(untagged)]
+pub enum RepeatCycles {
+    Fixed(u32),
+    Sequence(Vec<u32>),
+    Exponential(RepeatCyclesExponential),
+}
+
+impl Default for RepeatCycles {
+    fn default() -> Self {
+        Self::Fixed(0)
+    }
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct RepeatCyclesExponential {
+    pub initial: u32,
+    pub factor: f64,
+    pub max: u32,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum PlaceholderSource {
+    Hostname,
+    EnvVar(PlaceholderSourceEnvVar),
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct PlaceholderSourceEnvVar {
+    pub variable: String,
+    #[serde(default)]
+    pub placeholder: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmEscalation {
+    pub cycles: u32,
+    pub action: String,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
 #[serde(untagged)]
 pub enum AlarmType {
     DataSize(AlarmDataSize),
     Default(AlarmDefault),
+    TwoState(AlarmTwoState),
     StatusCode(AlarmStatusCode),
     Level(AlarmLevel),
+    Delta(AlarmDelta),
+    Trend(AlarmTrend),
+    Ema(AlarmEma),
+    MultiThreshold(AlarmMultiThreshold),
+    Regex(AlarmRegex),
+    #[cfg(feature = "http")]
+    HttpStatus(AlarmHttpStatus),
+    DnsResolution(AlarmDnsResolution),
+    Topology(AlarmTopology),
+    AlarmSummary(AlarmAlarmSummary),
+    #[cfg(feature = "http")]
+    Ratio(AlarmRatio),
+    #[cfg(feature = "http")]
+    Difference(AlarmDifference),
     #[cfg(feature = "sensors")]
     Temperature(AlarmTemperature),
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct AlarmDataSize {
     #[serde(default)]
@@ -453,7 +1196,7 @@ pub struct AlarmDataSize {
     data_size: u64,
 }
 
-#[derive(Deserialize, PartialEq, Default, Debug)]
+#[derive(Deserialize, PartialEq, Default, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub enum DataSizeUnit {
     #[default]
@@ -482,24 +1225,131 @@ impl AlarmDataSize {
 
 // This is a dummy that is used if no alarm specific fields are found.
 // Works only for alarms with only optional/defaulted fields.
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct AlarmDefault {}
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct AlarmLevel {
-    pub level: u8,
+    pub level: f64,
+    #[serde(default)]
+    pub alert_on_new_max: bool,
+    #[serde(default)]
+    pub unit: String,
 }
 
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmDelta {
+    pub delta: f64,
+    #[serde(default)]
+    pub alert_on_new_max: bool,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmTrend {
+    pub trend: TrendDirection,
+    pub window: u32,
+    #[serde(default)]
+    pub slope_threshold: Option<f64>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmEma {
+    pub ema_level: f64,
+    pub alpha: f64,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmMultiThreshold {
+    pub thresholds: Vec<AlarmThreshold>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmThreshold {
+    pub level: f64,
+    pub severity: Severity,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Copy, Debug)]
+pub enum TrendDirection {
+    Increasing,
+    Decreasing,
+    SteepIncrease,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmRegex {
+    pub pattern: String,
+    #[serde(default = "default::alarm_regex_match_is_bad")]
+    pub match_is_bad: bool,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmTwoState {
+    #[serde(default = "default::alarm_two_state_bad_on_false")]
+    pub bad_on_false: bool,
+    #[serde(default = "default::alarm_two_state_true_state")]
+    pub true_state: String,
+    #[serde(default = "default::alarm_two_state_false_state")]
+    pub false_state: String,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct AlarmStatusCode {
     pub status_codes: Vec<u8>,
 }
 
+#[cfg(feature = "http")]
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmRatio {
+    pub ratio: f64,
+}
+
+#[cfg(feature = "http")]
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmDifference {
+    pub difference: f64,
+}
+
+// Inclusive range of the good status codes.
+#[cfg(feature = "http")]
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmHttpStatus {
+    pub status_range: [u16; 2],
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmDnsResolution {
+    pub expected_ips: Vec<std::net::IpAddr>,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmTopology {
+    pub max_failures: u32,
+}
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlarmAlarmSummary {
+    pub min_bad_count: u32,
+}
+
 #[cfg(feature = "sensors")]
-#[derive(Deserialize, PartialEq, Debug)]
+#[derive(Deserialize, PartialEq, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct AlarmTemperature {
     pub temperature: i16,
@@ -511,11 +1361,54 @@ pub mod default {
         REPORT_INTERVAL
     }
 
+    pub const STATE_SHUTDOWN_TIMEOUT: u32 = 10;
+    pub fn state_shutdown_timeout() -> u32 {
+        STATE_SHUTDOWN_TIMEOUT
+    }
+
+    pub const DEAD_LETTER_QUEUE_MAX_ENTRIES: u32 = 1000;
+    pub fn dead_letter_queue_max_entries() -> u32 {
+        DEAD_LETTER_QUEUE_MAX_ENTRIES
+    }
+
+    pub const DEAD_LETTER_QUEUE_RETRY_INTERVAL: u32 = 60;
+    pub fn dead_letter_queue_retry_interval() -> u32 {
+        DEAD_LETTER_QUEUE_RETRY_INTERVAL
+    }
+
+    pub const DEAD_LETTER_QUEUE_MAX_RETRY_INTERVAL: u32 = 3600;
+    pub fn dead_letter_queue_max_retry_interval() -> u32 {
+        DEAD_LETTER_QUEUE_MAX_RETRY_INTERVAL
+    }
+
+    pub const RATE_LIMIT_WINDOW: u32 = 60;
+    pub fn rate_limit_window() -> u32 {
+        RATE_LIMIT_WINDOW
+    }
+
+    pub const RATE_LIMIT_MAX_QUEUED: u32 = 100;
+    pub fn rate_limit_max_queued() -> u32 {
+        RATE_LIMIT_MAX_QUEUED
+    }
+
+    pub const ALARM_NAME_MAX_LENGTH: usize = 64;
+
     pub const ACTION_TIMEOUT: u32 = 10;
     pub fn action_timeout() -> u32 {
         ACTION_TIMEOUT
     }
 
+    pub const ACTION_DIGEST_WINDOW: u32 = 60;
+    pub fn action_digest_window() -> u32 {
+        ACTION_DIGEST_WINDOW
+    }
+
+    pub const ACTION_DIGEST_ITEM_TEMPLATE: &str =
+        "{{check_name}} / {{alarm_name}} ({{check_id}}): {{alarm_event}}";
+    pub fn action_digest_item_template() -> String {
+        ACTION_DIGEST_ITEM_TEMPLATE.into()
+    }
+
     pub const CHECK_INTERVAL: u32 = 300;
     pub fn check_interval() -> u32 {
         CHECK_INTERVAL
@@ -536,17 +1429,318 @@ pub mod default {
         CHECK_TIMEOUT
     }
 
+    pub const CHECK_MAX_CONCURRENT_ALARMS: u32 = 1;
+    pub fn check_max_concurrent_alarms() -> u32 {
+        CHECK_MAX_CONCURRENT_ALARMS
+    }
+
+    pub const CHECK_LOG_FILE_PATTERN_MAX_LINES_PER_CHECK: usize = 100000;
+    pub fn check_log_file_pattern_max_lines_per_check() -> usize {
+        CHECK_LOG_FILE_PATTERN_MAX_LINES_PER_CHECK
+    }
+
+    pub const CHECK_PORT_SCAN_CONNECT_TIMEOUT: u32 = 1;
+    pub fn check_port_scan_connect_timeout() -> u32 {
+        CHECK_PORT_SCAN_CONNECT_TIMEOUT
+    }
+
+    #[cfg(feature = "postgres")]
+    pub const CHECK_POSTGRES_QUERY: &str = "SELECT 1";
+    #[cfg(feature = "postgres")]
+    pub fn check_postgres_query() -> String {
+        CHECK_POSTGRES_QUERY.into()
+    }
+
+    pub const CHECK_FILESYSTEM_USAGE_SPACE: bool = true;
+    pub fn check_filesystem_usage_space() -> bool {
+        CHECK_FILESYSTEM_USAGE_SPACE
+    }
+
+    #[cfg(feature = "snmp")]
+    pub const CHECK_SNMP_GET_COMMUNITY: &str = "public";
+    #[cfg(feature = "snmp")]
+    pub fn check_snmp_get_community() -> String {
+        CHECK_SNMP_GET_COMMUNITY.into()
+    }
+
+    pub const CHECK_TCP_CONNECT_TIMEOUT: u32 = 3;
+    pub fn check_tcp_connect_timeout() -> u32 {
+        CHECK_TCP_CONNECT_TIMEOUT
+    }
+
+    #[cfg(feature = "http")]
+    pub const CHECK_ELASTICSEARCH_QUERY_VERIFY_CERTIFICATE: bool = true;
+    #[cfg(feature = "http")]
+    pub fn check_elasticsearch_query_verify_certificate() -> bool {
+        CHECK_ELASTICSEARCH_QUERY_VERIFY_CERTIFICATE
+    }
+
+    #[cfg(feature = "http")]
+    pub const CHECK_HTTP_STATUS_METHOD: super::HttpMethod = super::HttpMethod::GET;
+    #[cfg(feature = "http")]
+    pub fn check_http_status_method() -> super::HttpMethod {
+        CHECK_HTTP_STATUS_METHOD
+    }
+
+    #[cfg(feature = "http")]
+    pub const CHECK_HTTP_STATUS_MAX_REDIRECTS: u32 = 5;
+    #[cfg(feature = "http")]
+    pub fn check_http_status_max_redirects() -> u32 {
+        CHECK_HTTP_STATUS_MAX_REDIRECTS
+    }
+
+    #[cfg(feature = "http")]
+    pub const ALARM_HTTP_STATUS_STATUS_RANGE: [u16; 2] = [200, 299];
+
+    pub const ALARM_FAST_RECOVERY_WARN_COUNT: u32 = 3;
+    pub fn alarm_fast_recovery_warn_count() -> u32 {
+        ALARM_FAST_RECOVERY_WARN_COUNT
+    }
+
+    pub const ALARM_REGEX_MATCH_IS_BAD: bool = true;
+    pub fn alarm_regex_match_is_bad() -> bool {
+        ALARM_REGEX_MATCH_IS_BAD
+    }
+
+    pub const ALARM_TWO_STATE_BAD_ON_FALSE: bool = true;
+    pub fn alarm_two_state_bad_on_false() -> bool {
+        ALARM_TWO_STATE_BAD_ON_FALSE
+    }
+
+    pub const ALARM_TWO_STATE_TRUE_STATE: &str = "up";
+    pub fn alarm_two_state_true_state() -> String {
+        ALARM_TWO_STATE_TRUE_STATE.into()
+    }
+
+    pub const ALARM_TWO_STATE_FALSE_STATE: &str = "down";
+    pub fn alarm_two_state_false_state() -> String {
+        ALARM_TWO_STATE_FALSE_STATE.into()
+    }
+
     pub const DOCKER_SOCKET_PATH: &str = "/var/run/docker.sock";
     pub fn docker_socket_path() -> String {
         DOCKER_SOCKET_PATH.into()
     }
+
+    #[cfg(feature = "websocket")]
+    pub const WEBSOCKET_CHANNEL_CAPACITY: usize = 16;
+    #[cfg(feature = "websocket")]
+    pub fn websocket_channel_capacity() -> usize {
+        WEBSOCKET_CHANNEL_CAPACITY
+    }
+}
+
+// Problem of the config found by `Config::validate`. Alarms are identified by the check name and
+// the alarm name joined with '/'.
+#[derive(Debug, PartialEq)]
+pub enum ValidationError {
+    UndefinedAction {
+        alarm_id: String,
+        action_name: String,
+    },
+    DuplicateAlarm {
+        alarm_id: String,
+    },
+    AlarmNameTooLong {
+        alarm_id: String,
+    },
+    InvalidTag {
+        alarm_id: String,
+        tag: String,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ValidationError::UndefinedAction {
+                alarm_id,
+                action_name,
+            } => write!(
+                f,
+                "Alarm '{alarm_id}' references the undefined action '{action_name}'."
+            ),
+            ValidationError::DuplicateAlarm { alarm_id } => {
+                write!(f, "Alarm '{alarm_id}' is defined more than once.")
+            }
+            ValidationError::AlarmNameTooLong { alarm_id } => write!(
+                f,
+                "Name of alarm '{alarm_id}' is longer than {} characters.",
+                default::ALARM_NAME_MAX_LENGTH
+            ),
+            ValidationError::InvalidTag { alarm_id, tag } => write!(
+                f,
+                "Tag '{tag}' of alarm '{alarm_id}' may only contain letters, digits and hyphens."
+            ),
+        }
+    }
+}
+
+// Tags are used in URLs like `/alarms/config?tag=database`, so they are restricted to characters
+// that need no escaping.
+pub fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty() && tag.chars().all(|x| x.is_ascii_alphanumeric() || x == '-')
+}
+
+impl Config {
+    // Finds all problems with the alarms at once, while MinMon stops at the first one on start-up.
+    // Disabled checks are skipped because their alarms are never instantiated.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let action_names: std::collections::HashSet<_> =
+            self.actions.iter().map(|x| x.name.as_str()).collect();
+        let mut alarm_ids = std::collections::HashSet::new();
+        let mut errors = Vec::new();
+        for check in self.checks.iter().filter(|x| !x.disable) {
+            for alarm in check.alarms.iter() {
+                let alarm_id = format!("{}/{}", check.name, alarm.name);
+                if !alarm_ids.insert(alarm_id.clone()) {
+                    errors.push(ValidationError::DuplicateAlarm {
+                        alarm_id: alarm_id.clone(),
+                    });
+                }
+                if alarm.name.chars().count() > default::ALARM_NAME_MAX_LENGTH {
+                    errors.push(ValidationError::AlarmNameTooLong {
+                        alarm_id: alarm_id.clone(),
+                    });
+                }
+                for tag in alarm.tags.iter().filter(|x| !is_valid_tag(x)) {
+                    errors.push(ValidationError::InvalidTag {
+                        alarm_id: alarm_id.clone(),
+                        tag: tag.clone(),
+                    });
+                }
+                let references = std::iter::once(&alarm.action)
+                    .chain(alarm.escalation.iter().map(|x| &x.action))
+                    .chain(alarm.recover_action.iter())
+                    .chain(alarm.error_action.iter())
+                    .chain(alarm.error_recover_action.iter())
+                    .chain(alarm.warn_action.iter());
+                for action_name in references {
+                    if !action_names.contains(action_name.as_str()) {
+                        errors.push(ValidationError::UndefinedAction {
+                            alarm_id: alarm_id.clone(),
+                            action_name: action_name.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    // Compares the SHA-256 checksum of the raw file content to the expected one (hex encoded).
+    pub fn verify_checksum(path: &std::path::Path, expected_sha256: &str) -> Result<(), Error> {
+        use sha2::Digest;
+        let content = std::fs::read(path)?;
+        let checksum: String = sha2::Sha256::digest(content)
+            .iter()
+            .map(|x| format!("{x:02x}"))
+            .collect();
+        if checksum.eq_ignore_ascii_case(expected_sha256.trim()) {
+            Ok(())
+        } else {
+            Err(Error::Message(format!(
+                "Checksum of '{}' is {checksum}, expected {expected_sha256}.",
+                path.display()
+            )))
+        }
+    }
+}
+
+// Replaces `${VAR}` by the value of the environment variable `VAR` and `${VAR:-default}` by the
+// default if `VAR` is not set. `$${` is a literal `${`. The values are not expanded again, so values
+// containing `${` are rejected as they most likely refer to other variables.
+fn expand_env_vars(text: &str) -> Result<String, Error> {
+    let mut res = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            res.push_str(&rest[..start]);
+            res.push('{');
+            rest = &rest[start + 2..];
+            continue;
+        }
+        res.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| Error::Message(format!("Unterminated variable in '{text}'.")))?;
+        let reference = &rest[start + 2..start + end];
+        let (name, default) = match reference.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (reference, None),
+        };
+        if name.is_empty() {
+            return Err(Error::Message(format!("Empty variable name in '{text}'.")));
+        }
+        let value = match (std::env::var(name), default) {
+            (Ok(value), _) => value,
+            (Err(_), Some(default)) => String::from(default),
+            (Err(_), None) => {
+                return Err(Error::Message(format!(
+                    "Environment variable '{name}' is not set."
+                )))
+            }
+        };
+        if value.contains("${") {
+            return Err(Error::Message(format!(
+                "Value of variable '{name}' contains another variable reference."
+            )));
+        }
+        res.push_str(&value);
+        rest = &rest[start + end + 1..];
+    }
+    res.push_str(rest);
+    Ok(res)
+}
+
+fn expand_env_vars_in_value(value: &mut toml::Value) -> Result<(), Error> {
+    match value {
+        toml::Value::String(text) => *text = expand_env_vars(text)?,
+        toml::Value::Array(array) => {
+            for value in array.iter_mut() {
+                expand_env_vars_in_value(value)?;
+            }
+        }
+        toml::Value::Table(table) => {
+            for (_, value) in table.iter_mut() {
+                expand_env_vars_in_value(value)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
 }
 
 impl TryFrom<&str> for Config {
     type Error = Error;
 
+    // Environment variables are expanded in all string values (not in the keys). Without any
+    // variables the text is parsed directly because the errors point to the exact location then.
     fn try_from(text: &str) -> Result<Self, Self::Error> {
-        toml::from_str(text).map_err(|x| Error(x.to_string()))
+        let config: Config = if !text.contains("${") {
+            toml::from_str(text).map_err(|x| Error::Message(x.to_string()))?
+        } else {
+            let mut value: toml::Value =
+                toml::from_str(text).map_err(|x| Error::Message(x.to_string()))?;
+            expand_env_vars_in_value(&mut value)?;
+            Config::deserialize(value).map_err(|x| Error::Message(x.to_string()))?
+        };
+        config.apply_defaults()
+    }
+}
+
+impl Config {
+    // Checks without a timeout get the default one, but not more than their interval.
+    fn apply_defaults(mut self) -> Result<Self, Error> {
+        if self.defaults.check_timeout == 0 {
+            return Err(Error::invalid_config("check_timeout", "cannot be 0."));
+        }
+        for check in self.checks.iter_mut() {
+            if check.timeout.is_none() {
+                check.timeout = Some(self.defaults.check_timeout.min(check.interval));
+            }
+        }
+        Ok(self)
     }
 }
 
@@ -555,10 +1749,9 @@ impl TryFrom<&std::path::Path> for Config {
 
     fn try_from(path: &std::path::Path) -> Result<Self, Self::Error> {
         use std::io::Read;
-        let mut file = std::fs::File::open(path).map_err(|x| Error(x.to_string()))?;
+        let mut file = std::fs::File::open(path)?;
         let mut content = String::new();
-        file.read_to_string(&mut content)
-            .map_err(|x| Error(x.to_string()))?;
+        file.read_to_string(&mut content)?;
         Config::try_from(content.as_str())
     }
 }
@@ -567,6 +1760,91 @@ impl TryFrom<&std::path::Path> for Config {
 mod test {
     use super::*;
 
+    #[test]
+    fn test_expand_env_vars() {
+        std::env::set_var("MINMON_TEST_EXPAND", "value");
+        std::env::set_var("MINMON_TEST_EXPAND_CIRCULAR", "${MINMON_TEST_EXPAND}");
+        std::env::remove_var("MINMON_TEST_EXPAND_MISSING");
+        assert_eq!(
+            expand_env_vars("a${MINMON_TEST_EXPAND}b${MINMON_TEST_EXPAND}").unwrap(),
+            "avaluebvalue"
+        );
+        assert_eq!(
+            expand_env_vars("${MINMON_TEST_EXPAND:-default}").unwrap(),
+            "value"
+        );
+        assert_eq!(
+            expand_env_vars("${MINMON_TEST_EXPAND_MISSING:-default}").unwrap(),
+            "default"
+        );
+        assert_eq!(
+            expand_env_vars("${MINMON_TEST_EXPAND_MISSING:-}").unwrap(),
+            ""
+        );
+        assert_eq!(
+            expand_env_vars("echo $${HOME} {{foo}}").unwrap(),
+            "echo ${HOME} {{foo}}"
+        );
+        assert!(expand_env_vars("${MINMON_TEST_EXPAND_MISSING}").is_err());
+        assert!(expand_env_vars("${MINMON_TEST_EXPAND_CIRCULAR}").is_err());
+        assert!(expand_env_vars("${MINMON_TEST_EXPAND_MISSING:-${MINMON_TEST_EXPAND}}").is_err());
+        assert!(expand_env_vars("${}").is_err());
+        assert!(expand_env_vars("${UNTERMINATED").is_err());
+    }
+
+    #[test]
+    fn test_from_str_env_vars() {
+        std::env::set_var("MINMON_TEST_CHECK_NAME", "Expanded");
+        let text = r#"
+            [[checks]]
+            name = "${MINMON_TEST_CHECK_NAME}"
+            type = "FilesystemUsage"
+            mountpoints = ["${MINMON_TEST_CHECK_NAME_MISSING:-/}"]
+        "#;
+        let config = Config::try_from(text).unwrap();
+        assert_eq!(config.checks[0].name, "Expanded");
+        assert!(matches!(
+            &config.checks[0].type_,
+            CheckType::FilesystemUsage(x) if x.mountpoints == ["/"]
+        ));
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let path = std::env::temp_dir().join(format!("minmon-checksum-{}", std::process::id()));
+        std::fs::write(&path, "abc").unwrap();
+        let checksum = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+        assert!(Config::verify_checksum(&path, checksum).is_ok());
+        assert!(Config::verify_checksum(&path, &checksum.to_uppercase()).is_ok());
+        std::fs::write(&path, "abd").unwrap();
+        assert!(Config::verify_checksum(&path, checksum).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(Config::verify_checksum(&path, checksum).is_err());
+    }
+
+    #[test]
+    fn test_repeat_cycles() {
+        #[derive(Deserialize)]
+        struct Test {
+            repeat_cycles: RepeatCycles,
+        }
+        let parse = |text: &str| toml::from_str::<Test>(text).map(|x| x.repeat_cycles);
+        assert_eq!(parse("repeat_cycles = 5").unwrap(), RepeatCycles::Fixed(5));
+        assert_eq!(
+            parse("repeat_cycles = [1, 5, 15]").unwrap(),
+            RepeatCycles::Sequence(vec![1, 5, 15])
+        );
+        assert_eq!(
+            parse("repeat_cycles = {initial = 1, factor = 2.0, max = 60}").unwrap(),
+            RepeatCycles::Exponential(RepeatCyclesExponential {
+                initial: 1,
+                factor: 2.0,
+                max: 60
+            })
+        );
+        assert!(parse("repeat_cycles = {initial = 1}").is_err());
+    }
+
     #[test]
     fn test_from_str_defaults() {
         let text = r#"
@@ -577,10 +1855,144 @@ mod test {
         assert!(config.report.disable);
         assert_eq!(config.report.interval, default::REPORT_INTERVAL);
         assert_eq!(config.report.events.len(), 0);
+        assert_eq!(config.state.path, None);
+        assert_eq!(
+            config.state.shutdown_timeout,
+            default::STATE_SHUTDOWN_TIMEOUT
+        );
+        assert_eq!(config.state.max_age, None);
+        assert_eq!(config.defaults.check_timeout, default::CHECK_TIMEOUT);
+        assert_eq!(config.dead_letter_queue.path, None);
+        assert_eq!(
+            config.dead_letter_queue.max_entries,
+            default::DEAD_LETTER_QUEUE_MAX_ENTRIES
+        );
+        assert_eq!(config.rate_limit.max_notifications_per_window, None);
+        assert_eq!(config.rate_limit.overflow, RateLimitOverflow::Drop);
         assert_eq!(config.actions.len(), 0);
         assert_eq!(config.checks.len(), 0);
     }
 
+    #[test]
+    fn test_validate() {
+        let text = format!(
+            r#"
+            [[actions]]
+            name = "Log"
+            type = "Log"
+            template = "{{{{alarm_name}}}}"
+
+            [[checks]]
+            name = "Filesystem"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "Full"
+            action = "Log"
+            recover_action = "pagerduty-oncall"
+            level = 90
+
+            [[checks.alarms]]
+            name = "Full"
+            action = "Log"
+            level = 95
+
+            [[checks.alarms]]
+            name = "{}"
+            action = "Log"
+            level = 50
+
+            [[checks.alarms]]
+            name = "Tagged"
+            action = "Log"
+            tags = ["database", "non-critical", "two words"]
+            level = 50
+
+            [[checks]]
+            name = "Disabled"
+            disable = true
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks.alarms]]
+            name = "Full"
+            action = "Missing"
+            level = 90
+            "#,
+            "x".repeat(65)
+        );
+        let config = Config::try_from(text.as_str()).unwrap();
+        assert_eq!(
+            config.validate(),
+            [
+                ValidationError::UndefinedAction {
+                    alarm_id: String::from("Filesystem/Full"),
+                    action_name: String::from("pagerduty-oncall")
+                },
+                ValidationError::DuplicateAlarm {
+                    alarm_id: String::from("Filesystem/Full")
+                },
+                ValidationError::AlarmNameTooLong {
+                    alarm_id: format!("Filesystem/{}", "x".repeat(65))
+                },
+                ValidationError::InvalidTag {
+                    alarm_id: String::from("Filesystem/Tagged"),
+                    tag: String::from("two words")
+                },
+            ]
+        );
+        assert_eq!(
+            config.validate()[3].to_string(),
+            "Tag 'two words' of alarm 'Filesystem/Tagged' may only contain letters, digits and hyphens."
+        );
+        assert_eq!(
+            config.validate()[0].to_string(),
+            "Alarm 'Filesystem/Full' references the undefined action 'pagerduty-oncall'."
+        );
+        let config =
+            Config::try_from(text.replace(r#""pagerduty-oncall""#, r#""Log""#).as_str()).unwrap();
+        assert_eq!(config.validate().len(), 3);
+        assert!(is_valid_tag("db-2"));
+        assert!(!is_valid_tag(""));
+        assert!(!is_valid_tag("db_2"));
+        assert!(!is_valid_tag("dätabase"));
+    }
+
+    #[test]
+    fn test_default_check_timeout() {
+        let text = r#"
+            [defaults]
+            check_timeout = 20
+
+            [[checks]]
+            name = "Default"
+            type = "FilesystemUsage"
+            mountpoints = ["/"]
+
+            [[checks]]
+            name = "Short interval"
+            type = "FilesystemUsage"
+            interval = 10
+            mountpoints = ["/"]
+
+            [[checks]]
+            name = "Own timeout"
+            type = "FilesystemUsage"
+            timeout = 1
+            mountpoints = ["/"]
+        "#;
+        let config = Config::try_from(text).unwrap();
+        let timeouts: Vec<_> = config.checks.iter().map(|x| x.timeout).collect();
+        assert_eq!(timeouts, [Some(20), Some(10), Some(1)]);
+        let config = Config::try_from(
+            "[[checks]]\nname = \"Default\"\ntype = \"FilesystemUsage\"\nmountpoints = [\"/\"]",
+        )
+        .unwrap();
+        assert_eq!(config.checks[0].timeout, Some(default::CHECK_TIMEOUT));
+        assert!(Config::try_from("[defaults]\ncheck_timeout = 0").is_err());
+    }
+
     #[test]
     #[cfg(feature = "systemd")]
     fn test_from_str_non_defaults() {
@@ -593,6 +2005,11 @@ mod test {
             disable = true
             interval = 12345
 
+            [state]
+            path = "/var/lib/minmon/state.json"
+            shutdown_timeout = 30
+            max_age = 3600
+
             [[report.events]]
             disable = true
             name = "report-event"
@@ -629,6 +2046,12 @@ mod test {
         assert_eq!(config.log.level, LogLevel::Error);
         assert!(config.report.disable);
         assert_eq!(config.report.interval, 12345);
+        assert_eq!(
+            config.state.path,
+            Some(std::path::PathBuf::from("/var/lib/minmon/state.json"))
+        );
+        assert_eq!(config.state.shutdown_timeout, 30);
+        assert_eq!(config.state.max_age, Some(3600));
 
         assert_eq!(config.report.events.len(), 1);
         let event = config.report.events.first().unwrap();
@@ -660,7 +2083,9 @@ mod test {
         assert_eq!(
             check.type_,
             CheckType::FilesystemUsage(CheckFilesystemUsage {
-                mountpoints: vec![String::from("/home"), String::from("/srv")]
+                mountpoints: vec![String::from("/home"), String::from("/srv")],
+                space: true,
+                inodes: false,
             })
         );
 
@@ -668,9 +2093,16 @@ mod test {
         let alarm = check.alarms.first().unwrap();
         assert!(alarm.disable);
         assert_eq!(alarm.name, "test-alarm");
-        assert_eq!(alarm.type_, AlarmType::Level(AlarmLevel { level: 75 }));
+        assert_eq!(
+            alarm.type_,
+            AlarmType::Level(AlarmLevel {
+                level: 75.,
+                alert_on_new_max: false,
+                unit: String::new(),
+            })
+        );
         assert_eq!(alarm.cycles, 3);
-        assert_eq!(alarm.repeat_cycles, 600);
+        assert_eq!(alarm.repeat_cycles, RepeatCycles::Fixed(600));
         assert_eq!(alarm.action, "test-action");
         assert_eq!(alarm.recover_cycles, 4);
         assert_eq!(alarm.recover_action, Some(String::from("test-action")));
@@ -0,0 +1,458 @@
+use crate::action::Action;
+use crate::{config, ActionMap, Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+// Failed trigger of an action. It is saved as a JSON file so that it survives restarts.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct DeadLetterEntry {
+    pub action_name: String,
+    // The check name, the alarm name and the ID joined with '/' or the name of the report event.
+    pub alarm_id: String,
+    // The `alarm_event` placeholder or "report" for report events.
+    pub event_type: String,
+    pub placeholders: PlaceholderMap,
+    pub failed_at: String,
+    pub failure_reason: String,
+}
+
+impl DeadLetterEntry {
+    fn new(action_name: String, placeholders: PlaceholderMap, error: &Error) -> Self {
+        let get = |key| {
+            placeholders
+                .get(key)
+                .map(String::as_str)
+                .unwrap_or_default()
+        };
+        let (alarm_id, event_type) = match placeholders.get("event_name") {
+            Some(event_name) => (event_name.clone(), String::from("report")),
+            None => (
+                crate::alarm::registry_key(get("check_name"), get("alarm_name"), get("check_id")),
+                String::from(get("alarm_event")),
+            ),
+        };
+        Self {
+            action_name,
+            alarm_id,
+            event_type,
+            placeholders,
+            failed_at: crate::datetime_iso8601(std::time::SystemTime::now()),
+            failure_reason: error.to_string(),
+        }
+    }
+}
+
+// Directory of failed action triggers that are retried with an exponential backoff. The actions
+// are the ones without the queue, so a failed retry does not add the entry again.
+pub struct DeadLetterQueue {
+    path: std::path::PathBuf,
+    max_entries: usize,
+    retry_interval: std::time::Duration,
+    max_retry_interval: std::time::Duration,
+    actions: ActionMap,
+    // Keeps the file names unique if two entries are added within the same nanosecond.
+    counter: std::sync::atomic::AtomicU64,
+    // Adding an entry and evicting the oldest ones is not interleaved with other additions.
+    lock: std::sync::Mutex<()>,
+}
+
+impl DeadLetterQueue {
+    fn new(
+        path: std::path::PathBuf,
+        dead_letter_queue: &config::DeadLetterQueue,
+        actions: ActionMap,
+    ) -> Result<Self> {
+        if dead_letter_queue.max_entries == 0 {
+            Err(Error::invalid_config("max_entries", "cannot be 0."))
+        } else if dead_letter_queue.retry_interval == 0 {
+            Err(Error::invalid_config("retry_interval", "cannot be 0."))
+        } else if dead_letter_queue.max_retry_interval < dead_letter_queue.retry_interval {
+            Err(Error::invalid_config(
+                "max_retry_interval",
+                "cannot be less than 'retry_interval'.",
+            ))
+        } else {
+            std::fs::create_dir_all(&path).map_err(|x| {
+                Error::Message(format!(
+                    "Could not create dead letter queue directory {}: {x}",
+                    path.display()
+                ))
+            })?;
+            Ok(Self {
+                path,
+                max_entries: dead_letter_queue.max_entries as usize,
+                retry_interval: std::time::Duration::from_secs(
+                    dead_letter_queue.retry_interval.into(),
+                ),
+                max_retry_interval: std::time::Duration::from_secs(
+                    dead_letter_queue.max_retry_interval.into(),
+                ),
+                actions,
+                counter: std::sync::atomic::AtomicU64::new(0),
+                lock: std::sync::Mutex::new(()),
+            })
+        }
+    }
+
+    // Sorted by the file names, i.e. the oldest entry first.
+    fn files(&self) -> Result<Vec<std::path::PathBuf>> {
+        let mut files: Vec<_> = std::fs::read_dir(&self.path)
+            .map_err(|x| {
+                Error::Message(format!(
+                    "Could not read dead letter queue directory {}: {x}",
+                    self.path.display()
+                ))
+            })?
+            .filter_map(|x| x.ok().map(|x| x.path()))
+            .filter(|x| x.extension().is_some_and(|x| x == "json"))
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    // The oldest entries are removed if the queue is full.
+    fn push(&self, entry: &DeadLetterEntry) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let counter = self
+            .counter
+            .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let path = self.path.join(format!("{nanos:020}-{counter:06}.json"));
+        let content = serde_json::to_string_pretty(entry)
+            .map_err(|x| Error::Message(format!("Could not serialize dead letter entry: {x}")))?;
+        // Write to a temporary file first so that a retry cannot read a truncated entry.
+        let mut temp_path = path.as_os_str().to_owned();
+        temp_path.push(".tmp");
+        std::fs::write(&temp_path, content)
+            .and_then(|_| std::fs::rename(&temp_path, &path))
+            .map_err(|x| {
+                Error::Message(format!(
+                    "Could not write dead letter entry {}: {x}",
+                    path.display()
+                ))
+            })?;
+        let files = self.files()?;
+        for file in files
+            .iter()
+            .take(files.len().saturating_sub(self.max_entries))
+        {
+            log::warn!(
+                "Dead letter queue is full. Dropping the oldest entry {}.",
+                file.display()
+            );
+            remove_entry(file);
+        }
+        Ok(())
+    }
+
+    // Broken entries are dropped.
+    fn entries(&self) -> Result<Vec<(std::path::PathBuf, DeadLetterEntry)>> {
+        let mut entries = Vec::new();
+        for file in self.files()? {
+            let entry = std::fs::read_to_string(&file)
+                .map_err(|x| x.to_string())
+                .and_then(|x| serde_json::from_str(&x).map_err(|x| x.to_string()));
+            match entry {
+                Ok(entry) => entries.push((file, entry)),
+                // The entry was retried by another process in the meantime.
+                Err(_) if !file.exists() => {}
+                Err(err) => {
+                    log::warn!(
+                        "Dropping broken dead letter entry {}: {}",
+                        file.display(),
+                        err
+                    );
+                    remove_entry(&file);
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    // Triggers the actions of all entries again, the oldest one first. Successful entries and
+    // entries of actions that no longer exist are removed. Returns the number of failed retries.
+    pub async fn retry(&self) -> Result<usize> {
+        let mut failed = 0;
+        for (file, entry) in self.entries()? {
+            let Some(action) = self.actions.get(&entry.action_name) else {
+                log::warn!(
+                    "Dropping dead letter entry {} because action '{}' does not exist.",
+                    file.display(),
+                    entry.action_name
+                );
+                remove_entry(&file);
+                continue;
+            };
+            match action.trigger(entry.placeholders.clone()).await {
+                Ok(()) => {
+                    log::info!(
+                        "Retried action '{}' for '{}' that failed at {}.",
+                        entry.action_name,
+                        entry.alarm_id,
+                        entry.failed_at
+                    );
+                    remove_entry(&file);
+                }
+                Err(err) => {
+                    log::warn!(
+                        "Retry of action '{}' for '{}' failed: {}",
+                        entry.action_name,
+                        entry.alarm_id,
+                        err
+                    );
+                    failed += 1;
+                }
+            }
+        }
+        Ok(failed)
+    }
+
+    // Retries the queued entries forever. The time between two retries is doubled after every
+    // retry with failures (up to the maximum) and reset when all entries were delivered.
+    pub async fn run(&self) {
+        let mut delay = self.retry_interval;
+        loop {
+            tokio::time::sleep(delay).await;
+            delay = match self.retry().await {
+                Ok(0) => self.retry_interval,
+                Ok(_) => (delay * 2).min(self.max_retry_interval),
+                Err(err) => {
+                    log::error!("Could not retry dead letter queue: {}", err);
+                    (delay * 2).min(self.max_retry_interval)
+                }
+            };
+        }
+    }
+}
+
+fn remove_entry(file: &std::path::Path) {
+    if let Err(err) = std::fs::remove_file(file) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            log::error!(
+                "Could not remove dead letter entry {}: {}",
+                file.display(),
+                err
+            );
+        }
+    }
+}
+
+// Adds the failed triggers of the wrapped action to the queue. The error is still returned.
+struct DeadLetterAction {
+    action: std::sync::Arc<dyn Action>,
+    queue: std::sync::Arc<DeadLetterQueue>,
+}
+
+#[async_trait]
+impl Action for DeadLetterAction {
+    async fn trigger(&self, placeholders: PlaceholderMap) -> Result<()> {
+        let res = self.action.trigger(placeholders.clone()).await;
+        if let (Err(err), Some(name)) = (&res, self.action.name()) {
+            let entry = DeadLetterEntry::new(name, placeholders, err);
+            match self.queue.push(&entry) {
+                Ok(()) => log::info!(
+                    "Added failed action '{}' for '{}' to the dead letter queue.",
+                    entry.action_name,
+                    entry.alarm_id
+                ),
+                Err(err) => log::error!("Could not add to the dead letter queue: {}", err),
+            }
+        }
+        res
+    }
+
+    fn name(&self) -> Option<String> {
+        self.action.name()
+    }
+}
+
+// Wraps all actions so that their failed triggers are added to the queue. Returns None if no
+// queue is configured.
+pub(crate) fn init(
+    dead_letter_queue: &config::DeadLetterQueue,
+    actions: &mut ActionMap,
+) -> Result<Option<std::sync::Arc<DeadLetterQueue>>> {
+    let Some(path) = &dead_letter_queue.path else {
+        return Ok(None);
+    };
+    let queue = std::sync::Arc::new(DeadLetterQueue::new(
+        path.clone(),
+        dead_letter_queue,
+        actions.clone(),
+    )?);
+    for action in actions.values_mut() {
+        *action = std::sync::Arc::new(DeadLetterAction {
+            action: action.clone(),
+            queue: queue.clone(),
+        });
+    }
+    Ok(Some(queue))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::{MockAction, TestAction};
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "minmon-test-dead-letter-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::remove_dir_all(&path).ok();
+        path
+    }
+
+    fn config(path: &std::path::Path, max_entries: u32) -> config::DeadLetterQueue {
+        config::DeadLetterQueue {
+            path: Some(path.to_path_buf()),
+            max_entries,
+            retry_interval: 1,
+            max_retry_interval: 4,
+        }
+    }
+
+    fn alarm_placeholders(alarm_name: &str) -> PlaceholderMap {
+        PlaceholderMap::from([
+            (String::from("check_name"), String::from("Check")),
+            (String::from("alarm_name"), String::from(alarm_name)),
+            (String::from("check_id"), String::from("sda")),
+            (String::from("alarm_event"), String::from("trigger")),
+        ])
+    }
+
+    // Fails the first `failures` triggers, like a webhook while the network is down.
+    fn flaky_action(failures: usize) -> std::sync::Arc<dyn Action> {
+        let mut mock_action = MockAction::new();
+        let count = std::sync::atomic::AtomicUsize::new(0);
+        mock_action.expect_trigger().returning(move |_| {
+            if count.fetch_add(1, std::sync::atomic::Ordering::SeqCst) < failures {
+                Err(Error::Message(String::from("Network is unreachable.")))
+            } else {
+                Ok(())
+            }
+        });
+        mock_action
+            .expect_name()
+            .returning(|| Some(String::from("Webhook")));
+        std::sync::Arc::new(mock_action)
+    }
+
+    #[tokio::test]
+    async fn test_failing_action() {
+        let path = temp_dir("failed");
+        let mut actions = ActionMap::from([(String::from("Webhook"), flaky_action(1))]);
+        let queue = init(&config(&path, 10), &mut actions).unwrap().unwrap();
+        let action = &actions["Webhook"];
+        assert!(action.trigger(alarm_placeholders("Alarm")).await.is_err());
+        action.trigger(alarm_placeholders("Alarm")).await.unwrap();
+        let entries = queue.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0].1;
+        assert_eq!(entry.action_name, "Webhook");
+        assert_eq!(entry.alarm_id, "Check/Alarm/sda");
+        assert_eq!(entry.event_type, "trigger");
+        assert_eq!(entry.placeholders, alarm_placeholders("Alarm"));
+        assert_eq!(entry.failure_reason, "Network is unreachable.");
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let path = temp_dir("retry");
+        let test_action = TestAction::new();
+        let actions = ActionMap::from([
+            (
+                String::from("Test"),
+                std::sync::Arc::new(test_action.clone()) as _,
+            ),
+            (String::from("Webhook"), flaky_action(1)),
+        ]);
+        let queue = DeadLetterQueue::new(path.clone(), &config(&path, 10), actions).unwrap();
+        let error = Error::Message(String::from("Timed out."));
+        for action_name in ["Test", "Webhook", "Missing"] {
+            let entry =
+                DeadLetterEntry::new(String::from(action_name), alarm_placeholders("A"), &error);
+            queue.push(&entry).unwrap();
+        }
+        std::fs::write(path.join("broken.json"), "{").unwrap();
+        assert_eq!(queue.retry().await.unwrap(), 1);
+        test_action.assert_triggered_once();
+        assert_eq!(
+            test_action.last_placeholders().unwrap(),
+            alarm_placeholders("A")
+        );
+        let entries = queue.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].1.action_name, "Webhook");
+        assert_eq!(queue.retry().await.unwrap(), 0);
+        assert!(queue.entries().unwrap().is_empty());
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run() {
+        let path = temp_dir("run");
+        let actions = ActionMap::from([(String::from("Webhook"), flaky_action(2))]);
+        let queue = std::sync::Arc::new(
+            DeadLetterQueue::new(path.clone(), &config(&path, 10), actions).unwrap(),
+        );
+        let entry = DeadLetterEntry::new(
+            String::from("Webhook"),
+            alarm_placeholders("A"),
+            &Error::Message(String::from("Timed out.")),
+        );
+        queue.push(&entry).unwrap();
+        let task = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.run().await }
+        });
+        // The retries after 1 and 3 seconds fail, the one after 7 seconds succeeds.
+        tokio::time::sleep(std::time::Duration::from_millis(6500)).await;
+        assert_eq!(queue.entries().unwrap().len(), 1);
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        assert!(queue.entries().unwrap().is_empty());
+        task.abort();
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_max_entries() {
+        let path = temp_dir("max");
+        let queue =
+            DeadLetterQueue::new(path.clone(), &config(&path, 3), ActionMap::new()).unwrap();
+        let error = Error::Message(String::from("Timed out."));
+        for i in 0..5 {
+            let entry = DeadLetterEntry::new(
+                String::from("Webhook"),
+                alarm_placeholders(&i.to_string()),
+                &error,
+            );
+            queue.push(&entry).unwrap();
+        }
+        let alarm_ids: Vec<_> = queue
+            .entries()
+            .unwrap()
+            .into_iter()
+            .map(|x| x.1.alarm_id)
+            .collect();
+        assert_eq!(alarm_ids, ["Check/2/sda", "Check/3/sda", "Check/4/sda"]);
+        std::fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn test_report_entry() {
+        let placeholders =
+            PlaceholderMap::from([(String::from("event_name"), String::from("Heartbeat"))]);
+        let entry = DeadLetterEntry::new(
+            String::from("Webhook"),
+            placeholders,
+            &Error::Message(String::from("Timed out.")),
+        );
+        assert_eq!(entry.alarm_id, "Heartbeat");
+        assert_eq!(entry.event_type, "report");
+    }
+}
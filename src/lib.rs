@@ -4,28 +4,106 @@ compile_error!("Only Linux is supported");
 
 mod action;
 mod alarm;
+#[doc(hidden)]
+pub mod bench;
 mod check;
 pub mod config;
+mod dead_letter_queue;
 mod measurement;
 mod process;
+mod rate_limit;
+mod reload;
 mod report;
+mod shutdown;
 pub mod uptime;
 
+pub use dead_letter_queue::DeadLetterQueue;
+pub use reload::Monitor;
+pub use shutdown::GracefulShutdown;
+
 pub type Result<T> = std::result::Result<T, Error>;
 type PlaceholderMap = std::collections::HashMap<String, String>;
 type ActionMap = std::collections::HashMap<String, std::sync::Arc<dyn action::Action>>;
+type AlarmGroupMap = std::collections::HashMap<String, PlaceholderMap>;
+pub type SharedCheck = std::sync::Arc<tokio::sync::Mutex<Box<dyn check::Check>>>;
 
 pub fn user_agent() -> String {
     format!("MinMon/v{}", env!("CARGO_PKG_VERSION"))
 }
 
+// Most errors are a message that already contains its context. The variants for the other ones
+// can be told apart by callers, e.g. the option of an invalid config. The sources are shared so
+// that an error stays `Clone`, e.g. to hand the error of a check to all of its alarms.
 #[derive(Debug, Clone)]
-pub struct Error(pub String);
-impl std::error::Error for Error {}
+pub enum Error {
+    Message(String),
+    InvalidConfig {
+        field: String,
+        message: String,
+    },
+    ActionFailed {
+        action_name: String,
+        source: std::sync::Arc<dyn std::error::Error + Send + Sync>,
+    },
+    CheckFailed {
+        check_id: String,
+        source: std::sync::Arc<dyn std::error::Error + Send + Sync>,
+    },
+    IoError(std::sync::Arc<std::io::Error>),
+    Timeout {
+        check_id: String,
+        duration: std::time::Duration,
+    },
+}
+
+impl Error {
+    pub fn invalid_config(field: &str, message: impl Into<String>) -> Self {
+        Self::InvalidConfig {
+            field: String::from(field),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::ActionFailed { source, .. } | Self::CheckFailed { source, .. } => {
+                Some(source.as_ref())
+            }
+            Self::IoError(error) => Some(error.as_ref()),
+            Self::Message(_) | Self::InvalidConfig { .. } | Self::Timeout { .. } => None,
+        }
+    }
+}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            Self::Message(message) => write!(f, "{message}"),
+            Self::InvalidConfig { field, message } => write!(f, "'{field}' {message}"),
+            Self::ActionFailed {
+                action_name,
+                source,
+            } => write!(f, "Action '{action_name}' failed: {source}"),
+            Self::CheckFailed { check_id, source } => {
+                write!(f, "Check '{check_id}' failed: {source}")
+            }
+            Self::IoError(error) => write!(f, "{error}"),
+            Self::Timeout { check_id, duration } => write!(
+                f,
+                "Check '{check_id}' timed out after {} seconds.",
+                duration.as_secs()
+            ),
+        }
+    }
+}
+
+// For I/O errors without further context, e.g. reading a file whose path is already in the message
+// of the caller.
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Self::IoError(std::sync::Arc::new(error))
     }
 }
 
@@ -68,9 +146,52 @@ fn fill_placeholders(template: &str, placeholders: &PlaceholderMap) -> String {
     )
 }
 
+// RFC 3339 timestamp in UTC with millisecond precision, e.g. "2024-01-15T14:32:07.123Z", so that
+// state changes in quick succession can still be told apart. Times before the Unix epoch, e.g.
+// from a clock that is far off, are formatted as the epoch.
 fn datetime_iso8601(system_time: std::time::SystemTime) -> String {
-    let date_time: chrono::DateTime<chrono::Utc> = system_time.into();
-    date_time.format("%FT%TZ").to_string()
+    let date_time: chrono::DateTime<chrono::Utc> =
+        system_time.max(std::time::SystemTime::UNIX_EPOCH).into();
+    date_time.format("%FT%T%.3fZ").to_string()
+}
+
+// Two most significant units, e.g. "3m 12s" or "2h 15m".
+fn format_duration(secs: u64) -> String {
+    const SECONDS_PER_MINUTE: u64 = 60;
+    const SECONDS_PER_HOUR: u64 = 60 * SECONDS_PER_MINUTE;
+    const SECONDS_PER_DAY: u64 = 24 * SECONDS_PER_HOUR;
+    if secs >= SECONDS_PER_DAY {
+        format!(
+            "{}d {}h",
+            secs / SECONDS_PER_DAY,
+            secs % SECONDS_PER_DAY / SECONDS_PER_HOUR
+        )
+    } else if secs >= SECONDS_PER_HOUR {
+        format!(
+            "{}h {}m",
+            secs / SECONDS_PER_HOUR,
+            secs % SECONDS_PER_HOUR / SECONDS_PER_MINUTE
+        )
+    } else if secs >= SECONDS_PER_MINUTE {
+        format!(
+            "{}m {}s",
+            secs / SECONDS_PER_MINUTE,
+            secs % SECONDS_PER_MINUTE
+        )
+    } else {
+        format!("{secs}s")
+    }
+}
+
+// Time since the given timestamp, formatted with `format_duration`. A timestamp in the future, e.g.
+// after the system clock was changed, counts as 0 seconds.
+fn format_duration_since(timestamp: std::time::SystemTime) -> String {
+    format_duration(
+        std::time::SystemTime::now()
+            .duration_since(timestamp)
+            .unwrap_or_default()
+            .as_secs(),
+    )
 }
 
 // only up to "days" because the number of days in a month/year are not defined in the standard
@@ -113,19 +234,63 @@ fn duration_iso8601(duration: std::time::Duration) -> String {
     res
 }
 
-fn init_actions(config: &config::Config) -> Result<ActionMap> {
+// The listener actions of `running` with an unchanged config are kept, because new ones could not
+// bind to the socket of the running ones.
+fn init_actions(
+    config: &config::Config,
+    running: &ListenerActions,
+) -> Result<(ActionMap, ListenerActions)> {
     log::info!("Initializing {} actions(s)..", config.actions.len());
     let mut res = ActionMap::new();
-    for action_config in config.actions.iter() {
+    let mut listeners = ListenerActions::new();
+    // Digests forward to other actions, so those are initialized first.
+    let (digests, others): (Vec<_>, Vec<_>) = config
+        .actions
+        .iter()
+        .partition(|x| matches!(x.type_, config::ActionType::Digest(_)));
+    for action_config in others.into_iter().chain(digests) {
         if res.contains_key(&action_config.name) {
-            return Err(Error(format!(
+            return Err(Error::Message(format!(
                 "Found duplicate action name: {}",
                 action_config.name
             )));
         }
-        let action = action::from_action_config(action_config)?;
+        let action = match running.iter().find(|(x, _)| x == action_config) {
+            Some((_, action)) => {
+                log::info!("Action '{}' kept.", action_config.name);
+                action.clone()
+            }
+            None => {
+                let action = action::from_action_config(action_config, &res)?;
+                log::info!("Action '{}' initialized.", action_config.name);
+                action
+            }
+        };
+        if action::is_listener(&action_config.type_) {
+            listeners.push((action_config.clone(), action.clone()));
+        }
         res.insert(action_config.name.clone(), action);
-        log::info!("Action '{}' initialized.", action_config.name);
+    }
+    Ok((res, listeners))
+}
+
+fn init_alarm_groups(config: &config::Config) -> Result<AlarmGroupMap> {
+    let mut res = AlarmGroupMap::new();
+    for group_config in config.alarm_groups.iter() {
+        if group_config.name.is_empty() {
+            return Err(Error::Message(String::from(
+                "Alarm group 'name' cannot be empty.",
+            )));
+        }
+        if res
+            .insert(group_config.name.clone(), group_config.placeholders.clone())
+            .is_some()
+        {
+            return Err(Error::Message(format!(
+                "Found duplicate alarm group name: {}",
+                group_config.name
+            )));
+        }
     }
     Ok(res)
 }
@@ -145,13 +310,19 @@ fn init_report(config: &config::Config, actions: &ActionMap) -> Result<Option<re
     Ok(Some(report))
 }
 
-fn init_checks(config: &config::Config, actions: &ActionMap) -> Result<Vec<Box<dyn check::Check>>> {
+fn init_checks(
+    config: &config::Config,
+    actions: &ActionMap,
+    alarm_groups: &AlarmGroupMap,
+) -> Result<Vec<Box<dyn check::Check>>> {
     log::info!("Initializing {} check(s)..", config.checks.len());
+    alarm::validate_dependencies(&config.checks)?;
+    let registry = alarm::AlarmRegistry::default();
     let mut res: Vec<Box<dyn check::Check>> = Vec::new();
     let mut used_names = std::collections::HashSet::new();
     for check_config in config.checks.iter() {
         if !used_names.insert(check_config.name.clone()) {
-            return Err(Error(format!(
+            return Err(Error::Message(format!(
                 "Found duplicate check name: {}",
                 check_config.name
             )));
@@ -160,24 +331,447 @@ fn init_checks(config: &config::Config, actions: &ActionMap) -> Result<Vec<Box<d
             log::info!("Check '{}' is disabled.", check_config.name);
             continue;
         }
-        let check = check::from_check_config(check_config, actions)?;
-        log::info!(
-            "Check '{}' will be triggered every {} seconds.",
-            check.name(),
-            check.interval().as_secs()
-        );
+        let check = check::from_check_config(check_config, actions, alarm_groups, &registry)?;
+        match check.cron() {
+            Some(schedule) => log::info!(
+                "Check '{}' will be triggered on the schedule '{}' (UTC).",
+                check.name(),
+                schedule
+            ),
+            None => log::info!(
+                "Check '{}' will be triggered every {} seconds.",
+                check.name(),
+                check.interval().as_secs()
+            ),
+        }
         res.push(check);
     }
     Ok(res)
 }
 
-type ConfigState = (Option<report::Report>, Vec<Box<dyn check::Check>>);
+// Restores the alarms' state from the state file if it exists. A broken state file is not fatal
+// because the alarms can always start over in their initial state.
+fn restore_state(
+    path: &std::path::Path,
+    max_age: Option<u32>,
+    checks: &mut [Box<dyn check::Check>],
+    actions: &ActionMap,
+) -> Result<()> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            log::info!("State file {} does not exist yet.", path.display());
+            return Ok(());
+        }
+        Err(err) => {
+            log::warn!("Could not read state file {}: {}", path.display(), err);
+            return Ok(());
+        }
+    };
+    if let Some(max_age) = max_age {
+        if is_stale(path, std::time::Duration::from_secs(max_age.into())) {
+            log::warn!(
+                "State file {} is older than {} seconds. The saved state may be stale.",
+                path.display(),
+                max_age
+            );
+        }
+    }
+    let snapshots: Vec<alarm::AlarmSnapshot> = match serde_json::from_str(&content) {
+        Ok(snapshots) => snapshots,
+        Err(err) => {
+            log::warn!("Could not parse state file {}: {}", path.display(), err);
+            return Ok(());
+        }
+    };
+    let mut snapshots: std::collections::HashMap<_, _> = snapshots
+        .into_iter()
+        .filter(|snapshot| match snapshot.check_version() {
+            Ok(()) => true,
+            Err(err) => {
+                log::warn!("Ignoring saved state of {}: {}", snapshot.log_id, err);
+                false
+            }
+        })
+        .map(|snapshot| (snapshot.log_id.clone(), snapshot))
+        .collect();
+    for check in checks.iter_mut() {
+        check.restore(&mut snapshots, actions)?;
+    }
+    Ok(())
+}
+
+// Whether the file was last modified more than `max_age` ago.
+fn is_stale(path: &std::path::Path, max_age: std::time::Duration) -> bool {
+    std::fs::metadata(path)
+        .and_then(|x| x.modified())
+        .ok()
+        .and_then(|x| x.elapsed().ok())
+        .is_some_and(|age| age > max_age)
+}
+
+// Snapshots of the state file that is already there, if it can be read. Used on shutdown to keep
+// the state of the checks that are still busy.
+pub fn saved_snapshots(path: &std::path::Path) -> Vec<alarm::AlarmSnapshot> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// The snapshots of the alarms of the checks are written along with the `previous` ones.
+pub fn save_state(
+    path: &std::path::Path,
+    checks: &[&dyn check::Check],
+    previous: Vec<alarm::AlarmSnapshot>,
+) -> Result<()> {
+    let mut snapshots = previous;
+    for check in checks.iter() {
+        snapshots.extend(check.snapshots()?);
+    }
+    let content = serde_json::to_string_pretty(&snapshots)
+        .map_err(|x| Error::Message(format!("Could not serialize state: {x}")))?;
+    // Write to a temporary file first so a crash cannot leave a truncated state file behind.
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    std::fs::write(&temp_path, content)
+        .and_then(|_| std::fs::rename(&temp_path, path))
+        .map_err(|x| {
+            Error::Message(format!(
+                "Could not write state file {}: {x}",
+                path.display()
+            ))
+        })?;
+    log::info!(
+        "Saved state of {} alarm(s) to {}.",
+        snapshots.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+// Random delay before the first cycle so that checks with the same interval don't all run at the
+// same time after startup. Without a configured jitter, the delay is up to one interval.
+pub fn start_delay(
+    period: std::time::Duration,
+    jitter: Option<std::time::Duration>,
+    rng: &mut impl rand::Rng,
+) -> std::time::Duration {
+    jitter.unwrap_or(period).mul_f64(rng.gen_range(0.0..=1.0))
+}
+
+// Triggers the check every interval, starting after the given delay. If a cycle takes longer than
+// the interval, e.g. because of a slow action, the cycles that were due in the meantime are skipped
+// and the next one starts right away. A cycle that finds the check held elsewhere is handled by the
+// backpressure of the check, see `check::Backpressure`.
+pub async fn run_check(
+    check: SharedCheck,
+    period: std::time::Duration,
+    start_delay: std::time::Duration,
+    backpressure: check::Backpressure,
+) {
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + start_delay, period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut skipped_total: u64 = 0;
+    let mut misses: u64 = 0;
+    loop {
+        let tick = interval.tick().await;
+        let mut check = match check.try_lock() {
+            Ok(check) => check,
+            Err(_) => {
+                misses = misses.saturating_add(1);
+                backpressure.missed(misses).await;
+                match backpressure.policy {
+                    config::Backpressure::Skip => continue,
+                    config::Backpressure::Wait => check.lock().await,
+                }
+            }
+        };
+        misses = 0;
+        check.trigger().await;
+        // The cycle that was due last runs right away, the ones before it are skipped.
+        let skipped = (tick.elapsed().as_secs_f64() / period.as_secs_f64()) as u64;
+        if skipped > 1 {
+            skipped_total += skipped - 1;
+            log::warn!(
+                "Check '{}': Skipped {} cycle(s) ({} in total) because the cycle took longer than the interval.",
+                check.name(),
+                skipped - 1,
+                skipped_total
+            );
+        }
+    }
+}
+
+// Time until the next scheduled cycle. It is always in the future, so cycles that were missed,
+// e.g. because MinMon was not running or the previous cycle took too long, are not caught up on.
+fn cron_delay(
+    schedule: &cron::Schedule,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<std::time::Duration> {
+    (schedule.after(&now).next()? - now).to_std().ok()
+}
+
+// Triggers the check on its cron schedule instead of every interval.
+pub async fn run_cron_check(check: SharedCheck, schedule: cron::Schedule) {
+    loop {
+        let Some(delay) = cron_delay(&schedule, chrono::Utc::now()) else {
+            log::error!("Cron schedule '{}' has no next cycle.", schedule);
+            return;
+        };
+        tokio::time::sleep(delay).await;
+        check.lock().await.trigger().await;
+    }
+}
+
+// The actions that listen on a socket, with their config. See `init_actions`.
+type ListenerActions = Vec<(config::Action, std::sync::Arc<dyn action::Action>)>;
+
+type ConfigState = (
+    Option<report::Report>,
+    Vec<Box<dyn check::Check>>,
+    Option<std::sync::Arc<DeadLetterQueue>>,
+    ListenerActions,
+);
+
+// Parses the decisions (one `good` or `bad` per line) and simulates all alarms of the checks.
+pub fn simulate(checks: &[Box<dyn check::Check>], text: &str) -> Result<Vec<String>> {
+    let decisions = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            line.trim()
+                .parse()
+                .map_err(|x| Error::Message(format!("Line {}: {}", i + 1, x)))
+        })
+        .collect::<Result<Vec<alarm::SinkDecision>>>()?;
+    Ok(checks
+        .iter()
+        .flat_map(|check| check.simulate(&decisions))
+        .collect())
+}
+
+// Replays the recorded data through all alarms of the checks. Every check must support parsing
+// the data, so the config should only contain the checks to back-test.
+pub async fn replay(checks: &mut [Box<dyn check::Check>], text: &str) -> Result<Vec<String>> {
+    let mut replays = Vec::new();
+    for check in checks.iter_mut() {
+        replays.extend(
+            check
+                .replay(text)
+                .await
+                .map_err(|x| Error::Message(format!("Check '{}': {}", check.name(), x)))?,
+        );
+    }
+    Ok(replays)
+}
+
+// Table of all alarms with their documentation. Missing values are shown as "-".
+pub fn list_alarms(checks: &[Box<dyn check::Check>]) -> String {
+    let header = [
+        "CHECK",
+        "ALARM",
+        "ID",
+        "SEVERITY",
+        "OWNER",
+        "TAGS",
+        "THRESHOLD",
+        "RUNBOOK",
+        "DESCRIPTION",
+    ];
+    let or_dash = |text: &str| {
+        if text.is_empty() {
+            String::from("-")
+        } else {
+            String::from(text)
+        }
+    };
+    let mut rows = vec![header.map(String::from)];
+    for check in checks.iter() {
+        for alarm in check.describe() {
+            rows.push([
+                String::from(check.name()),
+                alarm.name,
+                or_dash(&alarm.id),
+                format!("{:?}", alarm.severity),
+                or_dash(alarm.owner.as_deref().unwrap_or_default()),
+                or_dash(&alarm.tags.join(",")),
+                alarm.threshold_summary,
+                or_dash(alarm.runbook_url.as_deref().unwrap_or_default()),
+                or_dash(&alarm.description),
+            ]);
+        }
+    }
+    let mut widths = [0; 9];
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut table = String::new();
+    for row in rows.iter() {
+        let cells: Vec<_> = row
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect();
+        table.push_str(cells.join("  ").trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+// Entry of the JSON export of the alarms. This is a stable format for external tools: fields may
+// be added, but not removed or renamed.
+#[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+struct ExportedAlarm {
+    // The check name, the alarm name and the ID joined with '/'.
+    alarm_id: String,
+    check_name: String,
+    alarm_name: String,
+    id: String,
+    description: String,
+    severity: config::Severity,
+    runbook_url: Option<String>,
+    owner: Option<String>,
+    tags: Vec<String>,
+    threshold_summary: String,
+}
+
+// All alarms with their documentation, sorted by the alarm ID so that exports can be compared with
+// diff.
+fn exported_alarms(checks: &[Box<dyn check::Check>]) -> Vec<ExportedAlarm> {
+    let mut alarms: Vec<_> = checks
+        .iter()
+        .flat_map(|check| {
+            check.describe().into_iter().map(|alarm| ExportedAlarm {
+                alarm_id: alarm::registry_key(check.name(), &alarm.name, &alarm.id),
+                check_name: String::from(check.name()),
+                alarm_name: alarm.name,
+                id: alarm.id,
+                description: alarm.description,
+                severity: alarm.severity,
+                runbook_url: alarm.runbook_url,
+                owner: alarm.owner,
+                tags: alarm.tags,
+                threshold_summary: alarm.threshold_summary,
+            })
+        })
+        .collect();
+    alarms.sort_by(|a, b| a.alarm_id.cmp(&b.alarm_id));
+    alarms
+}
+
+// JSON array of all alarms, see `exported_alarms`.
+pub fn export_alarms(checks: &[Box<dyn check::Check>]) -> Result<String> {
+    AlarmExport::new(exported_alarms(checks)).to_json(None)
+}
+
+// Exported alarms with an index of their tags, so that the alarms with a tag can be looked up
+// without going through all of them.
+struct AlarmExport {
+    alarms: Vec<ExportedAlarm>,
+    // Indices of the alarms with the tag.
+    by_tag: std::collections::BTreeMap<String, Vec<usize>>,
+}
+
+impl AlarmExport {
+    fn new(alarms: Vec<ExportedAlarm>) -> Self {
+        let mut by_tag = std::collections::BTreeMap::<_, Vec<_>>::new();
+        for (i, alarm) in alarms.iter().enumerate() {
+            for tag in alarm.tags.iter() {
+                let indices = by_tag.entry(tag.clone()).or_default();
+                // An alarm could list the same tag twice.
+                if indices.last() != Some(&i) {
+                    indices.push(i);
+                }
+            }
+        }
+        Self { alarms, by_tag }
+    }
+
+    // All alarms or only the ones with the tag.
+    fn to_json(&self, tag: Option<&str>) -> Result<String> {
+        let alarms: Vec<&ExportedAlarm> = match tag {
+            Some(tag) => self
+                .by_tag
+                .get(tag)
+                .map(|indices| indices.iter().map(|i| &self.alarms[*i]).collect())
+                .unwrap_or_default(),
+            None => self.alarms.iter().collect(),
+        };
+        serde_json::to_string_pretty(&alarms)
+            .map_err(|x| Error::Message(format!("Could not export alarms: {x}")))
+    }
+}
+
+// The export of the alarms of the last loaded config. It is served by the HTTP health server
+// which is created before the checks.
+static ALARM_EXPORT: std::sync::RwLock<Option<AlarmExport>> = std::sync::RwLock::new(None);
+
+// `None` if no config was loaded yet.
+#[cfg(feature = "http-health")]
+fn alarm_export(tag: Option<&str>) -> Option<String> {
+    ALARM_EXPORT
+        .read()
+        .unwrap()
+        .as_ref()
+        .and_then(|export| export.to_json(tag).ok())
+}
 
 pub fn from_config(config: &config::Config) -> Result<ConfigState> {
-    let actions = init_actions(config)?;
+    init(
+        config,
+        &ListenerActions::new(),
+        |checks, actions| match &config.state.path {
+            Some(path) => restore_state(path, config.state.max_age, checks, actions),
+            None => Ok(()),
+        },
+    )
+}
+
+// Like `from_config`, but the alarms continue from the states of the running alarms instead of
+// the state file, see `Check::take_over`.
+fn reload_config(
+    config: &config::Config,
+    states: Vec<alarm::ReloadState>,
+    running: &ListenerActions,
+) -> Result<ConfigState> {
+    let mut states: std::collections::HashMap<_, _> = states
+        .into_iter()
+        .map(|state| (state.snapshot.log_id.clone(), state))
+        .collect();
+    let config_state = init(config, running, |checks, actions| {
+        for check in checks.iter_mut() {
+            check.take_over(&mut states, actions)?;
+        }
+        Ok(())
+    })?;
+    for (log_id, state) in states.iter() {
+        if state.is_bad() {
+            log::warn!("{} was removed while in the bad state.", log_id);
+        } else {
+            log::info!("{} was removed.", log_id);
+        }
+    }
+    Ok(config_state)
+}
+
+fn init(
+    config: &config::Config,
+    running: &ListenerActions,
+    restore: impl FnOnce(&mut [Box<dyn check::Check>], &ActionMap) -> Result<()>,
+) -> Result<ConfigState> {
+    let (mut actions, listeners) = init_actions(config, running)?;
+    let dead_letter_queue = dead_letter_queue::init(&config.dead_letter_queue, &mut actions)?;
+    rate_limit::init(&config.rate_limit, &mut actions)?;
     let report = init_report(config, &actions)?;
-    let checks = init_checks(config, &actions)?;
-    Ok((report, checks))
+    let alarm_groups = init_alarm_groups(config)?;
+    let mut checks = init_checks(config, &actions, &alarm_groups)?;
+    restore(&mut checks, &actions)?;
+    *ALARM_EXPORT.write().unwrap() = Some(AlarmExport::new(exported_alarms(&checks)));
+    Ok((report, checks, dead_letter_queue, listeners))
 }
 
 fn get_number<T>(error_message: &str, line: &str, column: usize) -> Result<T>
@@ -188,11 +782,11 @@ where
     {
         line.split_whitespace()
             .nth(column)
-            .ok_or_else(|| Error(String::from("Column not found.")))?
+            .ok_or_else(|| Error::Message(String::from("Column not found.")))?
             .parse()
-            .map_err(|x| Error(format!("{x}")))
+            .map_err(|x| Error::Message(format!("{x}")))
     }
-    .map_err(|x| Error(format!("{error_message}: {x}")))
+    .map_err(|x| Error::Message(format!("{error_message}: {x}")))
 }
 
 #[cfg(test)]
@@ -211,6 +805,92 @@ mod test {
         assert_eq!(target.get("C").unwrap(), "D");
     }
 
+    #[test]
+    fn test_io_error() {
+        let error = Error::from(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "No such file",
+        ));
+        assert_eq!(error.to_string(), "No such file");
+        let error: Error = std::fs::read("/nonexistent/minmon").unwrap_err().into();
+        assert!(!error.to_string().is_empty());
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(Error::Message(String::from("Foo.")).to_string(), "Foo.");
+        assert_eq!(
+            Error::invalid_config("interval", "cannot be 0.").to_string(),
+            "'interval' cannot be 0."
+        );
+        let source = std::sync::Arc::new(Error::Message(String::from("Connection refused.")));
+        assert_eq!(
+            Error::ActionFailed {
+                action_name: String::from("Webhook"),
+                source: source.clone(),
+            }
+            .to_string(),
+            "Action 'Webhook' failed: Connection refused."
+        );
+        assert_eq!(
+            Error::CheckFailed {
+                check_id: String::from("Disk"),
+                source,
+            }
+            .to_string(),
+            "Check 'Disk' failed: Connection refused."
+        );
+        assert_eq!(
+            Error::Timeout {
+                check_id: String::from("Disk"),
+                duration: std::time::Duration::from_secs(5),
+            }
+            .to_string(),
+            "Check 'Disk' timed out after 5 seconds."
+        );
+    }
+
+    #[test]
+    fn test_error_source() {
+        use std::error::Error as _;
+        let io_error = Error::from(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "No such file",
+        ));
+        let error = Error::ActionFailed {
+            action_name: String::from("Log"),
+            source: std::sync::Arc::new(Error::CheckFailed {
+                check_id: String::from("Disk"),
+                source: std::sync::Arc::new(io_error),
+            }),
+        };
+        let check_error = error.source().unwrap();
+        assert!(matches!(
+            check_error.downcast_ref::<Error>(),
+            Some(Error::CheckFailed { check_id, .. }) if check_id == "Disk"
+        ));
+        let io_error = check_error.source().unwrap();
+        assert!(matches!(
+            io_error.downcast_ref::<Error>(),
+            Some(Error::IoError(_))
+        ));
+        let io_error = io_error.source().unwrap();
+        assert_eq!(
+            io_error.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        assert!(Error::Message(String::from("Foo.")).source().is_none());
+        assert!(Error::invalid_config("interval", "cannot be 0.")
+            .source()
+            .is_none());
+        assert!(Error::Timeout {
+            check_id: String::from("Disk"),
+            duration: std::time::Duration::from_secs(5),
+        }
+        .source()
+        .is_none());
+    }
+
     #[test]
     fn test_fill_placeholders() {
         let template = "X{{A}}{{missing}}Z";
@@ -222,7 +902,13 @@ mod test {
     #[test]
     fn test_datetime_iso8601() {
         let system_time = std::time::SystemTime::UNIX_EPOCH;
-        assert_eq!(datetime_iso8601(system_time), "1970-01-01T00:00:00Z");
+        assert_eq!(datetime_iso8601(system_time), "1970-01-01T00:00:00.000Z");
+        let system_time = system_time + std::time::Duration::from_nanos(1_705_329_127_123_999_999);
+        assert_eq!(datetime_iso8601(system_time), "2024-01-15T14:32:07.123Z");
+        let system_time = std::time::SystemTime::UNIX_EPOCH - std::time::Duration::from_secs(60);
+        assert_eq!(datetime_iso8601(system_time), "1970-01-01T00:00:00.000Z");
+        let now = datetime_iso8601(std::time::SystemTime::now());
+        assert!(chrono::DateTime::parse_from_rfc3339(&now).is_ok());
     }
 
     #[test]
@@ -235,11 +921,339 @@ mod test {
         assert_eq!(duration_iso8601(duration), "PT0S");
     }
 
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0), "0s");
+        assert_eq!(format_duration(47), "47s");
+        assert_eq!(format_duration(59), "59s");
+        assert_eq!(format_duration(60), "1m 0s");
+        assert_eq!(format_duration(192), "3m 12s");
+        assert_eq!(format_duration(3599), "59m 59s");
+        assert_eq!(format_duration(3600), "1h 0m");
+        assert_eq!(format_duration(8130), "2h 15m");
+        assert_eq!(format_duration(86399), "23h 59m");
+        assert_eq!(format_duration(86400), "1d 0h");
+        assert_eq!(format_duration(273600), "3d 4h");
+        let timestamp = std::time::SystemTime::now() + std::time::Duration::from_secs(60);
+        assert_eq!(format_duration_since(timestamp), "0s");
+    }
+
     #[test]
     fn test_get_number() {
         let line = "0 1 2 3 4 5";
         assert_eq!(get_number::<u32>("error", line, 0).unwrap(), 0);
         assert_eq!(get_number::<u32>("error", line, 5).unwrap(), 5);
-        assert!(matches!(get_number::<u32>("error", line, 6), Err(_)));
+        assert!(get_number::<u32>("error", line, 6).is_err());
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let path = std::env::temp_dir().join(format!("minmon-test-stale-{}", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let max_age = std::time::Duration::from_secs(3600);
+        assert!(!is_stale(&path, max_age));
+        file.set_modified(std::time::SystemTime::now() - 2 * max_age)
+            .unwrap();
+        assert!(is_stale(&path, max_age));
+        std::fs::remove_file(&path).unwrap();
+        assert!(!is_stale(&path, max_age));
+    }
+
+    #[test]
+    fn test_list_alarms() {
+        let text = r#"
+            [[actions]]
+            name = "Log"
+            type = "Log"
+            template = "{{alarm_name}}"
+
+            [[checks]]
+            name = "Filesystem"
+            type = "FilesystemUsage"
+            mountpoints = ["/", "/home"]
+
+            [[checks.alarms]]
+            name = "Full"
+            action = "Log"
+            level = 90
+            description = "The filesystem is almost full."
+            severity = "High"
+            runbook_url = "https://example.com/runbook"
+            owner = "ops"
+            tags = ["disk", "storage"]
+
+            [[checks.alarms]]
+            name = "Growing"
+            action = "Log"
+            level = 50
+            alert_on_new_max = true
+        "#;
+        let config = config::Config::try_from(text).unwrap();
+        let (_, checks, ..) = from_config(&config).unwrap();
+        let descriptors = checks[0].describe();
+        assert_eq!(descriptors.len(), 4);
+        assert_eq!(
+            descriptors[0],
+            alarm::AlarmDescriptor {
+                name: String::from("Full"),
+                id: String::from("/"),
+                description: String::from("The filesystem is almost full."),
+                severity: config::Severity::High,
+                runbook_url: Some(String::from("https://example.com/runbook")),
+                owner: Some(String::from("ops")),
+                tags: vec![String::from("disk"), String::from("storage")],
+                threshold_summary: String::from("Bad when level >= 90%"),
+            }
+        );
+        assert_eq!(descriptors[2].id, "/home");
+        assert_eq!(descriptors[2].tags, descriptors[0].tags);
+        assert_eq!(descriptors[1].severity, config::Severity::Info);
+        assert_eq!(descriptors[1].owner, None);
+        assert_eq!(
+            descriptors[1].threshold_summary,
+            "Bad when level >= 50% or on a new all-time maximum"
+        );
+        let table = list_alarms(&checks);
+        let lines: Vec<_> = table.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].starts_with("CHECK       ALARM    ID     SEVERITY"));
+        assert!(lines[2].starts_with("Filesystem  Growing  /      Info      -"));
+    }
+
+    #[test]
+    fn test_export_alarms() {
+        let text = r#"
+            [[actions]]
+            name = "Log"
+            type = "Log"
+            template = "{{alarm_name}}"
+
+            [[checks]]
+            name = "Filesystem"
+            type = "FilesystemUsage"
+            mountpoints = ["/srv", "/home"]
+
+            [[checks.alarms]]
+            name = "Full"
+            action = "Log"
+            level = 90
+            severity = "High"
+            tags = ["disk"]
+
+            [[checks]]
+            name = "Memory"
+            type = "MemoryUsage"
+            memory = true
+
+            [[checks.alarms]]
+            name = "High"
+            action = "Log"
+            level = 80
+        "#;
+        let config = config::Config::try_from(text).unwrap();
+        let (_, checks, ..) = from_config(&config).unwrap();
+        let export = export_alarms(&checks).unwrap();
+        let alarms: Vec<ExportedAlarm> = serde_json::from_str(&export).unwrap();
+        let alarm_ids: Vec<_> = alarms.iter().map(|x| x.alarm_id.as_str()).collect();
+        assert_eq!(
+            alarm_ids,
+            [
+                "Filesystem/Full//home",
+                "Filesystem/Full//srv",
+                "Memory/High/Memory"
+            ]
+        );
+        assert_eq!(
+            alarms[1],
+            ExportedAlarm {
+                alarm_id: String::from("Filesystem/Full//srv"),
+                check_name: String::from("Filesystem"),
+                alarm_name: String::from("Full"),
+                id: String::from("/srv"),
+                description: String::new(),
+                severity: config::Severity::High,
+                runbook_url: None,
+                owner: None,
+                tags: vec![String::from("disk")],
+                threshold_summary: String::from("Bad when level >= 90%"),
+            }
+        );
+        assert!(export.contains(r#""severity": "High""#));
+        let tagged: Vec<ExportedAlarm> = serde_json::from_str(
+            &AlarmExport::new(exported_alarms(&checks))
+                .to_json(Some("disk"))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(tagged, alarms[..2]);
+        assert_eq!(
+            AlarmExport::new(exported_alarms(&checks))
+                .to_json(Some("network"))
+                .unwrap(),
+            "[]"
+        );
+        // The export does not depend on the order of the checks.
+        let (_, mut checks, ..) = from_config(&config).unwrap();
+        checks.reverse();
+        assert_eq!(export_alarms(&checks).unwrap(), export);
+    }
+
+    // Mock check that counts its cycles.
+    fn counting_check(triggers: &std::sync::Arc<std::sync::atomic::AtomicUsize>) -> SharedCheck {
+        let mut mock_check = check::MockCheck::new();
+        let triggers = triggers.clone();
+        mock_check.expect_trigger().returning(move || {
+            triggers.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        });
+        mock_check.expect_name().return_const(String::from("Check"));
+        std::sync::Arc::new(tokio::sync::Mutex::new(Box::new(mock_check)))
+    }
+
+    fn backpressure(
+        policy: config::Backpressure,
+        action: Option<std::sync::Arc<dyn action::Action>>,
+    ) -> check::Backpressure {
+        check::Backpressure::new(policy, String::from("Check"), action, PlaceholderMap::new())
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_check_skips_missed_cycles() {
+        let triggers = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let check = counting_check(&triggers);
+        let period = std::time::Duration::from_secs(100);
+        // Something else holds the check for 2.5 intervals, e.g. a slow cycle.
+        let busy = check.clone().lock_owned().await;
+        let task = tokio::spawn(run_check(
+            check.clone(),
+            period,
+            std::time::Duration::ZERO,
+            backpressure(config::Backpressure::Wait, None),
+        ));
+        let count = || triggers.load(std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_secs(250)).await;
+        assert_eq!(count(), 0);
+        drop(busy);
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        // The first cycle waited for the check. The cycle at 100 seconds is skipped, the one at
+        // 200 seconds runs right away and the next one is due an interval after it.
+        assert_eq!(count(), 2);
+        tokio::time::sleep(std::time::Duration::from_secs(98)).await;
+        assert_eq!(count(), 2);
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        assert_eq!(count(), 3);
+        task.abort();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_check_backpressure() {
+        uptime::init().unwrap();
+        let triggers = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let check = counting_check(&triggers);
+        let depths = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let depths_clone = depths.clone();
+        let mut mock_action = action::MockAction::new();
+        mock_action.expect_trigger().returning(move |placeholders| {
+            depths_clone
+                .lock()
+                .unwrap()
+                .push(placeholders["alarm_queue_depth"].clone());
+            Ok(())
+        });
+        let period = std::time::Duration::from_secs(100);
+        // The first caller is still in its cycle when the second one is due.
+        let busy = check.clone().lock_owned().await;
+        let task = tokio::spawn(run_check(
+            check.clone(),
+            period,
+            std::time::Duration::ZERO,
+            backpressure(
+                config::Backpressure::Skip,
+                Some(std::sync::Arc::new(mock_action)),
+            ),
+        ));
+        let count = || triggers.load(std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_secs(250)).await;
+        // The cycles at 0, 100 and 200 seconds are skipped without waiting for the check.
+        assert_eq!(count(), 0);
+        assert_eq!(*depths.lock().unwrap(), vec!["1", "2", "3"]);
+        drop(busy);
+        tokio::time::sleep(std::time::Duration::from_secs(49)).await;
+        assert_eq!(count(), 0);
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        assert_eq!(count(), 1);
+        // The count of misses in a row starts over after a cycle ran.
+        let busy = check.clone().lock_owned().await;
+        tokio::time::sleep(std::time::Duration::from_secs(100)).await;
+        drop(busy);
+        assert_eq!(*depths.lock().unwrap(), vec!["1", "2", "3", "1"]);
+        tokio::time::sleep(std::time::Duration::from_secs(100)).await;
+        assert_eq!(count(), 2);
+        task.abort();
+    }
+
+    #[test]
+    fn test_cron_delay() {
+        let schedule = "0 0 3 * * *".parse::<cron::Schedule>().unwrap();
+        let time = |text| {
+            chrono::DateTime::parse_from_rfc3339(text)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        assert_eq!(
+            cron_delay(&schedule, time("2024-01-01T02:59:30Z")),
+            Some(std::time::Duration::from_secs(30))
+        );
+        // Restarted 30 seconds after a cycle was due. It is skipped, not run right away.
+        assert_eq!(
+            cron_delay(&schedule, time("2024-01-01T03:00:30Z")),
+            Some(std::time::Duration::from_secs(24 * 3600 - 30))
+        );
+    }
+
+    #[test]
+    fn test_start_delay() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        let period = std::time::Duration::from_secs(300);
+        let jitter = std::time::Duration::from_secs(10);
+        let mut delays: Vec<_> = (0..10)
+            .map(|_| start_delay(period, Some(jitter), &mut rng))
+            .collect();
+        assert!(delays.iter().all(|x| *x <= jitter));
+        let first = delays[0];
+        // Checks with the same interval and jitter don't start within the same millisecond.
+        delays.sort();
+        assert!(delays
+            .windows(2)
+            .all(|x| x[1] - x[0] >= std::time::Duration::from_millis(1)));
+        assert!((0..10).all(|_| start_delay(period, None, &mut rng) <= period));
+        assert!(start_delay(period, Some(std::time::Duration::ZERO), &mut rng).is_zero());
+        // The same seed gives the same delays.
+        let mut rng = rand::rngs::StdRng::seed_from_u64(42);
+        assert_eq!(start_delay(period, Some(jitter), &mut rng), first);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_check_start_delay() {
+        let triggers = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let check = counting_check(&triggers);
+        let period = std::time::Duration::from_secs(100);
+        let task = tokio::spawn(run_check(
+            check,
+            period,
+            std::time::Duration::from_secs(30),
+            backpressure(config::Backpressure::Skip, None),
+        ));
+        let count = || triggers.load(std::sync::atomic::Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_secs(29)).await;
+        assert_eq!(count(), 0);
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        assert_eq!(count(), 1);
+        // Exactly one interval after the first cycle.
+        tokio::time::sleep(std::time::Duration::from_secs(98)).await;
+        assert_eq!(count(), 1);
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        assert_eq!(count(), 2);
+        task.abort();
     }
 }
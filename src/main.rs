@@ -5,11 +5,86 @@ mod systemd;
 
 use minmon::{config, Error, Result};
 
-fn get_config_file_path() -> Result<std::path::PathBuf> {
-    if let Some(path_str) = std::env::args().nth(1) {
-        Ok(std::path::PathBuf::from(path_str))
+struct Args {
+    config_file_path: std::path::PathBuf,
+    dot_graph: bool,
+    export: bool,
+    list: bool,
+    config_hash: Option<String>,
+    dlq_flush: bool,
+    validate: bool,
+    // Path of the data file for the simulation.
+    simulate: Option<std::path::PathBuf>,
+    // Path of the recorded data for the replay.
+    replay: Option<std::path::PathBuf>,
+}
+
+// Flags have to be given before the config file path. With "--simulate" or "--replay" the path of
+// the data file follows the config file path.
+fn get_args() -> Result<Args> {
+    let mut args = std::env::args().skip(1).peekable();
+    let mut dot_graph = false;
+    let mut export = false;
+    let mut list = false;
+    let mut config_hash = None;
+    let mut dlq_flush = false;
+    let mut validate = false;
+    let mut simulate = false;
+    let mut replay = false;
+    while let Some(flag) = args.next_if(|x| x.starts_with("--")) {
+        match flag.as_str() {
+            "--dlq-flush" => dlq_flush = true,
+            "--dot-graph" => dot_graph = true,
+            "--export" => export = true,
+            "--list" => list = true,
+            "--replay" => replay = true,
+            "--simulate" => simulate = true,
+            "--validate" => validate = true,
+            "--verify-config-hash" => {
+                config_hash = Some(args.next().ok_or_else(|| {
+                    Error::Message(String::from(
+                        "'--verify-config-hash' requires a SHA-256 checksum.",
+                    ))
+                })?)
+            }
+            _ => return Err(Error::Message(format!("Unknown flag '{flag}'."))),
+        }
+    }
+    if let Some(path_str) = args.next() {
+        if simulate && replay {
+            return Err(Error::Message(String::from(
+                "'--simulate' cannot be combined with '--replay'.",
+            )));
+        }
+        let data_path = match (simulate || replay, args.next()) {
+            (true, Some(data_path_str)) => Some(std::path::PathBuf::from(data_path_str)),
+            (true, None) => {
+                return Err(Error::Message(String::from(
+                    "Data file path not specified.",
+                )))
+            }
+            (false, _) => None,
+        };
+        let (simulate, replay) = if simulate {
+            (data_path, None)
+        } else {
+            (None, data_path)
+        };
+        Ok(Args {
+            config_file_path: std::path::PathBuf::from(path_str),
+            dot_graph,
+            export,
+            list,
+            config_hash,
+            dlq_flush,
+            validate,
+            simulate,
+            replay,
+        })
     } else {
-        Err(Error(String::from("Config file path not specified.")))
+        Err(Error::Message(String::from(
+            "Config file path not specified.",
+        )))
     }
 }
 
@@ -47,19 +122,92 @@ fn init_logging(config: &config::Config) -> Result<()> {
     Ok(())
 }
 
-async fn random_interval(max: std::time::Duration) {
-    let delay = rand::random::<f32>() * max.as_secs_f32() + 0.001;
-    let mut delay = tokio::time::interval(std::time::Duration::from_secs_f32(delay));
-    delay.tick().await; // the first tick completes immediately
-    delay.tick().await;
-}
-
 async fn main_wrapper() -> Result<()> {
     minmon::uptime::init()?;
 
-    let config_file_path = get_config_file_path()?;
+    let Args {
+        config_file_path,
+        dot_graph,
+        export,
+        list,
+        config_hash,
+        dlq_flush,
+        validate,
+        simulate,
+        replay,
+    } = get_args()?;
+    if let Some(config_hash) = &config_hash {
+        config::Config::verify_checksum(config_file_path.as_path(), config_hash)
+            .map_err(|x| Error::Message(format!("Failed to verify config file: {}", x)))?;
+    }
     let config = config::Config::try_from(config_file_path.as_path())
-        .map_err(|x| Error(format!("Failed to parse config file: {}", x)))?;
+        .map_err(|x| Error::Message(format!("Failed to parse config file: {}", x)))?;
+
+    if validate {
+        let errors = config.validate();
+        for error in errors.iter() {
+            println!("{error}");
+        }
+        if !errors.is_empty() {
+            return Err(Error::Message(format!(
+                "Found {} error(s) in the config file.",
+                errors.len()
+            )));
+        }
+        println!("Config file is valid.");
+        return Ok(());
+    }
+
+    if dot_graph {
+        let (_, checks, ..) = minmon::from_config(&config)?;
+        for check in checks {
+            for graph in check.dot_graphs() {
+                println!("{graph}");
+            }
+        }
+        return Ok(());
+    }
+
+    if export {
+        let (_, checks, ..) = minmon::from_config(&config)?;
+        println!("{}", minmon::export_alarms(&checks)?);
+        return Ok(());
+    }
+
+    if list {
+        let (_, checks, ..) = minmon::from_config(&config)?;
+        print!("{}", minmon::list_alarms(&checks));
+        return Ok(());
+    }
+
+    if dlq_flush {
+        let (_, _, dead_letter_queue, _) = minmon::from_config(&config)?;
+        let dead_letter_queue = dead_letter_queue
+            .ok_or_else(|| Error::Message(String::from("No dead letter queue configured.")))?;
+        let failed = dead_letter_queue.retry().await?;
+        println!("{failed} entries of the dead letter queue could not be delivered.");
+        return Ok(());
+    }
+
+    if let Some(data_path) = simulate {
+        let (_, checks, ..) = minmon::from_config(&config)?;
+        let data = std::fs::read_to_string(&data_path)
+            .map_err(|x| Error::Message(format!("Failed to read data file: {}", x)))?;
+        for simulation in minmon::simulate(&checks, &data)? {
+            println!("{simulation}");
+        }
+        return Ok(());
+    }
+
+    if let Some(data_path) = replay {
+        let (_, mut checks, ..) = minmon::from_config(&config)?;
+        let data = std::fs::read_to_string(&data_path)
+            .map_err(|x| Error::Message(format!("Failed to read data file: {}", x)))?;
+        for replay in minmon::replay(&mut checks, &data).await? {
+            println!("{replay}");
+        }
+        return Ok(());
+    }
 
     init_logging(&config)?;
 
@@ -72,37 +220,20 @@ async fn main_wrapper() -> Result<()> {
         systemd::init().await;
     }
 
-    let (report, checks) = minmon::from_config(&config)?;
+    let mut shutdown = minmon::GracefulShutdown::new(&config.state)?;
+    let mut monitor = minmon::Monitor::new(&config_file_path, config_hash)?;
 
-    for mut check in checks {
-        tokio::spawn(async move {
-            random_interval(check.interval()).await;
-            let mut interval = tokio::time::interval(check.interval());
-            loop {
-                interval.tick().await;
-                check.trigger().await;
-            }
-        });
-    }
+    monitor.start(minmon::from_config(&config)?);
 
-    if let Some(mut report) = report {
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(report.interval());
+    tokio::select! {
+        _ = shutdown.wait() => {}
+        _ = async {
             loop {
-                interval.tick().await;
-                report.trigger().await;
+                monitor.wait_for_reload().await;
             }
-        });
-    }
-
-    use tokio::signal::unix::{signal, SignalKind};
-    let mut sigint = signal(SignalKind::interrupt()).unwrap();
-    let mut sigterm = signal(SignalKind::interrupt()).unwrap();
-
-    tokio::select! {
-        _ = sigint.recv() => log::info!("Received signal SIGINT. Shutting down."),
-        _ = sigterm.recv() => log::info!("Received signal SIGTERM. Shutting down."),
+        } => {}
     }
+    shutdown.save_state(monitor.checks()).await?;
 
     Ok(())
 }
@@ -1,7 +1,7 @@
 use crate::{Error, Result};
 
-pub trait Measurement: std::fmt::Display + Copy + Clone + Default {
-    type Data: Copy + Clone + Default;
+pub trait Measurement: std::fmt::Display + Clone + Default {
+    type Data: Clone + Default;
     const UNIT: &'static str;
 
     fn new(data: Self::Data) -> Result<Self>
@@ -9,6 +9,104 @@ pub trait Measurement: std::fmt::Display + Copy + Clone + Default {
         Self: Sized;
 
     fn data(&self) -> Self::Data;
+
+    fn format(&self) -> String {
+        self.to_string()
+    }
+
+    // Parses recorded data, e.g. for a replay. Only supported by the numeric measurements.
+    fn parse(text: &str) -> Result<Self>
+    where
+        Self: Sized,
+    {
+        Err(Error::Message(format!(
+            "Cannot parse '{text}', the data type does not support it."
+        )))
+    }
+}
+
+// Parses the text as the data of the measurement.
+fn parse_data<T>(text: &str) -> Result<T>
+where
+    T: Measurement,
+    T::Data: std::str::FromStr,
+{
+    text.parse()
+        .map_err(|_| Error::Message(format!("Could not parse '{text}'.")))
+        .and_then(T::new)
+}
+
+macro_rules! impl_Measurement_primitive {
+    ($T:ty) => {
+        impl Measurement for $T {
+            type Data = $T;
+            const UNIT: &'static str = "";
+
+            fn new(data: Self::Data) -> Result<Self> {
+                Ok(data)
+            }
+
+            fn data(&self) -> Self::Data {
+                *self
+            }
+
+            fn parse(text: &str) -> Result<Self> {
+                parse_data(text)
+            }
+        }
+    };
+    ($T:ty, $precision:literal) => {
+        impl Measurement for $T {
+            type Data = $T;
+            const UNIT: &'static str = "";
+
+            fn new(data: Self::Data) -> Result<Self> {
+                if data.is_nan() {
+                    Err(Error::Message(String::from("Value cannot be NaN.")))
+                } else {
+                    Ok(data)
+                }
+            }
+
+            fn data(&self) -> Self::Data {
+                *self
+            }
+
+            fn format(&self) -> String {
+                format!("{self:.*}", $precision)
+            }
+
+            fn parse(text: &str) -> Result<Self> {
+                parse_data(text)
+            }
+        }
+    };
+}
+
+impl_Measurement_primitive!(u8);
+impl_Measurement_primitive!(u16);
+impl_Measurement_primitive!(u32);
+impl_Measurement_primitive!(u64);
+impl_Measurement_primitive!(i32);
+impl_Measurement_primitive!(i64);
+impl_Measurement_primitive!(f32, 2);
+impl_Measurement_primitive!(f64, 2);
+
+impl Measurement for bool {
+    type Data = bool;
+    const UNIT: &'static str = "";
+
+    fn new(data: Self::Data) -> Result<Self> {
+        Ok(data)
+    }
+
+    fn data(&self) -> Self::Data {
+        *self
+    }
+
+    fn format(&self) -> String {
+        String::from(if *self { "up" } else { "down" })
+    }
 }
 
 macro_rules! impl_Display {
@@ -98,6 +196,10 @@ impl Measurement for DataSize {
     fn data(&self) -> Self::Data {
         self.data
     }
+
+    fn parse(text: &str) -> Result<Self> {
+        parse_data(text)
+    }
 }
 
 impl std::ops::Add for DataSize {
@@ -133,7 +235,10 @@ impl Measurement for Level {
 
     fn new(data: Self::Data) -> Result<Self> {
         if data > 100 {
-            Err(Error(String::from("'level' cannot be greater than 100.")))
+            Err(Error::invalid_config(
+                "level",
+                "cannot be greater than 100.",
+            ))
         } else {
             Ok(Self { data })
         }
@@ -142,6 +247,10 @@ impl Measurement for Level {
     fn data(&self) -> Self::Data {
         self.data
     }
+
+    fn parse(text: &str) -> Result<Self> {
+        parse_data(text)
+    }
 }
 
 #[derive(PartialEq, Eq, Copy, Clone, Default)]
@@ -164,22 +273,464 @@ impl Measurement for StatusCode {
     }
 }
 
+#[cfg(feature = "http")]
+#[derive(PartialEq, Clone, Default, Debug)]
+pub struct HttpResponse {
+    status_code: u16,
+    // In milliseconds.
+    response_time: f64,
+    body_snippet: String,
+}
+
+#[cfg(feature = "http")]
+impl HttpResponse {
+    pub fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    pub fn response_time(&self) -> f64 {
+        self.response_time
+    }
+
+    pub fn body_snippet(&self) -> &str {
+        &self.body_snippet
+    }
+}
+
+#[cfg(feature = "http")]
+impl std::fmt::Display for HttpResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "status code {} after {:.2} ms",
+            self.status_code, self.response_time
+        )
+    }
+}
+
+#[cfg(feature = "http")]
+impl Measurement for HttpResponse {
+    type Data = (u16, f64, String);
+    const UNIT: &'static str = "";
+
+    fn new((status_code, response_time, body_snippet): Self::Data) -> Result<Self> {
+        if response_time.is_nan() {
+            Err(Error::Message(String::from("Response time cannot be NaN.")))
+        } else {
+            Ok(Self {
+                status_code,
+                response_time,
+                body_snippet,
+            })
+        }
+    }
+
+    fn data(&self) -> Self::Data {
+        (
+            self.status_code,
+            self.response_time,
+            self.body_snippet.clone(),
+        )
+    }
+}
+
+#[derive(PartialEq, Clone, Default, Debug)]
+pub struct DnsResolution {
+    // Sorted and without duplicates, empty if the name could not be resolved.
+    addresses: Vec<std::net::IpAddr>,
+    // In milliseconds.
+    resolve_time: f64,
+}
+
+impl DnsResolution {
+    pub fn addresses(&self) -> &[std::net::IpAddr] {
+        &self.addresses
+    }
+
+    pub fn resolve_time(&self) -> f64 {
+        self.resolve_time
+    }
+}
+
+impl std::fmt::Display for DnsResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.addresses.is_empty() {
+            write!(f, "no addresses")?;
+        } else {
+            let addresses: Vec<String> = self.addresses.iter().map(|x| x.to_string()).collect();
+            write!(f, "addresses {}", addresses.join(", "))?;
+        }
+        write!(f, " after {:.2} ms", self.resolve_time)
+    }
+}
+
+impl Measurement for DnsResolution {
+    type Data = (Vec<std::net::IpAddr>, f64);
+    const UNIT: &'static str = "";
+
+    fn new((mut addresses, resolve_time): Self::Data) -> Result<Self> {
+        if resolve_time.is_nan() {
+            Err(Error::Message(String::from("Resolve time cannot be NaN.")))
+        } else {
+            addresses.sort();
+            addresses.dedup();
+            Ok(Self {
+                addresses,
+                resolve_time,
+            })
+        }
+    }
+
+    fn data(&self) -> Self::Data {
+        (self.addresses.clone(), self.resolve_time)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct PortScan {
+    // Sorted and without duplicates.
+    offending_ports: Vec<u16>,
+}
+
+impl PortScan {
+    pub fn offending_ports(&self) -> &[u16] {
+        &self.offending_ports
+    }
+}
+
+impl std::fmt::Display for PortScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.offending_ports.is_empty() {
+            write!(f, "no offending ports")
+        } else {
+            let ports: Vec<String> = self.offending_ports.iter().map(|x| x.to_string()).collect();
+            write!(f, "offending ports {}", ports.join(", "))
+        }
+    }
+}
+
+impl Measurement for PortScan {
+    type Data = Vec<u16>;
+    const UNIT: &'static str = "";
+
+    fn new(mut offending_ports: Self::Data) -> Result<Self> {
+        offending_ports.sort();
+        offending_ports.dedup();
+        Ok(Self { offending_ports })
+    }
+
+    fn data(&self) -> Self::Data {
+        self.offending_ports.clone()
+    }
+}
+
+// Lag of a consumer group per partition of a topic, i.e. the number of messages it has not
+// consumed yet.
+#[cfg(feature = "kafka")]
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct ConsumerLag {
+    // Sorted by partition.
+    partitions: Vec<(i32, u64)>,
+}
+
+#[cfg(feature = "kafka")]
+impl ConsumerLag {
+    pub fn partitions(&self) -> &[(i32, u64)] {
+        &self.partitions
+    }
+
+    pub fn total(&self) -> u64 {
+        self.partitions
+            .iter()
+            .fold(0, |total, (_, lag)| total.saturating_add(*lag))
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl std::fmt::Display for ConsumerLag {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "lag {} in {} partitions",
+            self.total(),
+            self.partitions.len()
+        )
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl Measurement for ConsumerLag {
+    type Data = Vec<(i32, u64)>;
+    const UNIT: &'static str = "";
+
+    fn new(mut partitions: Self::Data) -> Result<Self> {
+        partitions.sort();
+        Ok(Self { partitions })
+    }
+
+    fn data(&self) -> Self::Data {
+        self.partitions.clone()
+    }
+}
+
+// Two values compared with each other, e.g. the results of two queries.
+#[cfg(feature = "http")]
+#[derive(PartialEq, Clone, Copy, Default, Debug)]
+pub struct ValuePair {
+    numerator: f64,
+    denominator: f64,
+}
+
+#[cfg(feature = "http")]
+impl ValuePair {
+    pub fn numerator(&self) -> f64 {
+        self.numerator
+    }
+
+    pub fn denominator(&self) -> f64 {
+        self.denominator
+    }
+}
+
+#[cfg(feature = "http")]
+impl std::fmt::Display for ValuePair {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.2} and {:.2}", self.numerator, self.denominator)
+    }
+}
+
+#[cfg(feature = "http")]
+impl Measurement for ValuePair {
+    type Data = (f64, f64);
+    const UNIT: &'static str = "";
+
+    fn new((numerator, denominator): Self::Data) -> Result<Self> {
+        if numerator.is_nan() || denominator.is_nan() {
+            Err(Error::Message(String::from("Values cannot be NaN.")))
+        } else {
+            Ok(Self {
+                numerator,
+                denominator,
+            })
+        }
+    }
+
+    fn data(&self) -> Self::Data {
+        (self.numerator, self.denominator)
+    }
+}
+
+// Value printed by a process with the placeholders printed alongside it.
+#[derive(PartialEq, Clone, Default, Debug)]
+pub struct ProcessValue {
+    value: f64,
+    placeholders: crate::PlaceholderMap,
+}
+
+impl ProcessValue {
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn placeholders(&self) -> &crate::PlaceholderMap {
+        &self.placeholders
+    }
+}
+
+impl std::fmt::Display for ProcessValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.2}", self.value)
+    }
+}
+
+impl Measurement for ProcessValue {
+    type Data = (f64, crate::PlaceholderMap);
+    const UNIT: &'static str = "";
+
+    fn new((value, placeholders): Self::Data) -> Result<Self> {
+        if value.is_nan() {
+            Err(Error::Message(String::from("Value cannot be NaN.")))
+        } else {
+            Ok(Self {
+                value,
+                placeholders,
+            })
+        }
+    }
+
+    fn data(&self) -> Self::Data {
+        (self.value, self.placeholders.clone())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct ConnectionFailure {
+    // E.g. "10.0.0.1 -> db:5432".
+    pub connection: String,
+    pub error: String,
+    pub traceroute: Option<String>,
+}
+
+// Connections of a topology that could not be established, out of all connections tried.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct Topology {
+    connection_count: usize,
+    failures: Vec<ConnectionFailure>,
+}
+
+impl Topology {
+    pub fn connection_count(&self) -> usize {
+        self.connection_count
+    }
+
+    pub fn failures(&self) -> &[ConnectionFailure] {
+        &self.failures
+    }
+}
+
+impl std::fmt::Display for Topology {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} connections failed",
+            self.failures.len(),
+            self.connection_count
+        )
+    }
+}
+
+impl Measurement for Topology {
+    type Data = (usize, Vec<ConnectionFailure>);
+    const UNIT: &'static str = "";
+
+    fn new((connection_count, failures): Self::Data) -> Result<Self> {
+        if failures.len() > connection_count {
+            Err(Error::Message(format!(
+                "{} failures out of only {} connections.",
+                failures.len(),
+                connection_count
+            )))
+        } else {
+            Ok(Self {
+                connection_count,
+                failures,
+            })
+        }
+    }
+
+    fn data(&self) -> Self::Data {
+        (self.connection_count, self.failures.clone())
+    }
+}
+
+// Alarms of an alarm summary that are bad, out of all alarms of the summary.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct AlarmSummary {
+    alarm_count: usize,
+    bad_alarms: Vec<String>,
+}
+
+impl AlarmSummary {
+    pub fn alarm_count(&self) -> usize {
+        self.alarm_count
+    }
+
+    pub fn bad_alarms(&self) -> &[String] {
+        &self.bad_alarms
+    }
+}
+
+impl std::fmt::Display for AlarmSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} alarms bad",
+            self.bad_alarms.len(),
+            self.alarm_count
+        )
+    }
+}
+
+impl Measurement for AlarmSummary {
+    type Data = (usize, Vec<String>);
+    const UNIT: &'static str = "";
+
+    fn new((alarm_count, bad_alarms): Self::Data) -> Result<Self> {
+        if bad_alarms.len() > alarm_count {
+            Err(Error::Message(format!(
+                "{} bad alarms out of only {} alarms.",
+                bad_alarms.len(),
+                alarm_count
+            )))
+        } else {
+            Ok(Self {
+                alarm_count,
+                bad_alarms,
+            })
+        }
+    }
+
+    fn data(&self) -> Self::Data {
+        (self.alarm_count, self.bad_alarms.clone())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+pub struct Text {
+    data: String,
+}
+
+impl Text {
+    const MAX_FORMAT_LENGTH: usize = 256;
+
+    pub fn as_str(&self) -> &str {
+        &self.data
+    }
+}
+
+impl_Display!(Text);
+
+impl Measurement for Text {
+    type Data = String;
+    const UNIT: &'static str = "";
+
+    fn new(data: Self::Data) -> Result<Self> {
+        Ok(Self { data })
+    }
+
+    fn data(&self) -> Self::Data {
+        self.data.clone()
+    }
+
+    fn format(&self) -> String {
+        // Prevents long texts from spamming the log.
+        match self.data.char_indices().nth(Self::MAX_FORMAT_LENGTH) {
+            Some((index, _)) => format!("{}..", &self.data[..index]),
+            None => self.data.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "sensors")]
 #[derive(PartialEq, PartialOrd, Eq, Ord, Copy, Clone, Default)]
 pub struct Temperature {
     data: i16,
 }
 
+#[cfg(feature = "sensors")]
 impl_Display!(Temperature);
 
+#[cfg(feature = "sensors")]
 impl Measurement for Temperature {
     type Data = i16;
     const UNIT: &'static str = "°C";
 
     fn new(data: Self::Data) -> Result<Self> {
         if data < -273 {
-            Err(Error(String::from(
-                "'temperature' cannot be less than -273°C.",
-            )))
+            Err(Error::invalid_config(
+                "temperature",
+                "cannot be less than -273°C.",
+            ))
         } else {
             Ok(Self { data })
         }
@@ -209,4 +760,31 @@ mod test {
         let data_size = DataSize::new(1024 * 1024 * 1024).unwrap();
         assert_eq!(format!("{}", data_size.as_string_binary()), "1GiB");
     }
+
+    #[test]
+    fn test_primitive_format() {
+        assert_eq!(42u8.format(), "42");
+        assert_eq!((-42i64).format(), "-42");
+        assert_eq!(1.0f32.format(), "1.00");
+        assert_eq!(12.3456f64.format(), "12.35");
+        assert!(f64::new(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(f64::parse("12.5").unwrap(), 12.5);
+        assert!(f64::parse("NaN").is_err());
+        assert!(u8::parse("-1").is_err());
+        assert_eq!(Level::parse("42").unwrap().data(), 42);
+        assert!(Level::parse("101").is_err());
+        assert_eq!(DataSize::parse("1024").unwrap().data(), 1024);
+        assert!(Text::parse("foo").is_err());
+    }
+
+    #[test]
+    fn test_text_format() {
+        assert_eq!(Text::new(String::from("foo")).unwrap().format(), "foo");
+        let text = Text::new("ä".repeat(300)).unwrap();
+        assert_eq!(text.format(), format!("{}..", "ä".repeat(256)));
+    }
 }
@@ -15,12 +15,15 @@ impl ProcessConfig {
         let error_str = "Could not parse file name from path.";
         self.path
             .file_name()
-            .ok_or_else(|| Error(error_str.into()))?
+            .ok_or_else(|| Error::Message(error_str.into()))?
             .to_str()
-            .ok_or_else(|| Error(error_str.into()))
+            .ok_or_else(|| Error::Message(error_str.into()))
     }
 
-    pub async fn run(&self, placeholders: Option<PlaceholderMap>) -> Result<(u8, Option<String>)> {
+    async fn output(
+        &self,
+        placeholders: Option<PlaceholderMap>,
+    ) -> Result<(u8, std::process::Output)> {
         let mut command = tokio::process::Command::new(&self.path);
         command.kill_on_drop(true);
         if let Some(placeholders) = placeholders {
@@ -51,26 +54,50 @@ impl ProcessConfig {
         let output = command
             .output()
             .await
-            .map_err(|x| Error(format!("Failed to run process: {x}")))?;
+            .map_err(|x| Error::Message(format!("Failed to run process: {x}")))?;
 
         match output.status.code() {
-            Some(code) => {
-                let code = (code & 0xff) as u8;
-                if output.stderr.is_empty() {
-                    Ok((code, None))
-                } else {
-                    Ok((
-                        code,
-                        std::str::from_utf8(&output.stderr[..])
-                            .map(|x| x.into())
-                            .ok(),
-                    ))
-                }
-            }
-            None => Err(Error(String::from("Process was terminated by a signal."))),
+            Some(code) => Ok(((code & 0xff) as u8, output)),
+            None => Err(Error::Message(String::from(
+                "Process was terminated by a signal.",
+            ))),
         }
     }
 
+    pub async fn run(&self, placeholders: Option<PlaceholderMap>) -> Result<(u8, Option<String>)> {
+        let (code, output) = self.output(placeholders).await?;
+        if output.stderr.is_empty() {
+            Ok((code, None))
+        } else {
+            Ok((
+                code,
+                std::str::from_utf8(&output.stderr[..])
+                    .map(|x| x.into())
+                    .ok(),
+            ))
+        }
+    }
+
+    // Returns the standard output of the process regardless of its exit status code.
+    pub async fn run_stdout(&self) -> Result<String> {
+        let (_, output) = self.output(None).await?;
+        String::from_utf8(output.stdout)
+            .map_err(|x| Error::Message(format!("Process output is not valid UTF-8: {x}")))
+    }
+
+    // Returns the exit status code, the standard output and the standard error output of the
+    // process. Invalid UTF-8 in the standard error output is replaced.
+    pub async fn run_output(&self) -> Result<(u8, String, String)> {
+        let (code, output) = self.output(None).await?;
+        let stdout = String::from_utf8(output.stdout)
+            .map_err(|x| Error::Message(format!("Process output is not valid UTF-8: {x}")))?;
+        Ok((
+            code,
+            stdout,
+            String::from_utf8_lossy(&output.stderr).into_owned(),
+        ))
+    }
+
     pub fn new(
         path: std::path::PathBuf,
         arguments: Vec<String>,
@@ -80,7 +107,10 @@ impl ProcessConfig {
         gid: Option<u32>,
     ) -> Result<Self> {
         if !path.is_file() {
-            Err(Error(format!("'path' is not a file: {}.", path.display())))
+            Err(Error::invalid_config(
+                "path",
+                format!("is not a file: {}.", path.display()),
+            ))
         } else {
             Ok(Self {
                 path,
@@ -0,0 +1,261 @@
+use crate::action::Action;
+use crate::{config, ActionMap, Error, PlaceholderMap, Result};
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
+// Token bucket shared by all actions that limits the total number of notifications, e.g. when
+// many alarms are triggered at once. The bucket holds at most `max_notifications_per_window`
+// tokens and refills with the same number of tokens per window. Each notification takes a token.
+pub struct RateLimiter {
+    capacity: f64,
+    // Tokens per second.
+    refill_rate: f64,
+    overflow: config::RateLimitOverflow,
+    max_queued: usize,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: tokio::time::Instant,
+    queued: usize,
+    // Dropped notifications since the last one that was sent.
+    suppressed: u64,
+}
+
+enum Permit {
+    // Holds the number of notifications that were dropped before.
+    Allowed(u64),
+    Queued,
+    Dropped,
+}
+
+impl RateLimiter {
+    fn new(max_notifications_per_window: u32, rate_limit: &config::RateLimit) -> Result<Self> {
+        if max_notifications_per_window == 0 {
+            Err(Error::invalid_config(
+                "max_notifications_per_window",
+                "cannot be 0.",
+            ))
+        } else if rate_limit.window == 0 {
+            Err(Error::invalid_config("window", "cannot be 0."))
+        } else if rate_limit.overflow == config::RateLimitOverflow::Queue
+            && rate_limit.max_queued == 0
+        {
+            Err(Error::invalid_config("max_queued", "cannot be 0."))
+        } else {
+            let capacity = max_notifications_per_window as f64;
+            Ok(Self {
+                capacity,
+                refill_rate: capacity / rate_limit.window as f64,
+                overflow: rate_limit.overflow,
+                max_queued: rate_limit.max_queued as usize,
+                bucket: Mutex::new(Bucket {
+                    tokens: capacity,
+                    last_refill: tokio::time::Instant::now(),
+                    queued: 0,
+                    suppressed: 0,
+                }),
+            })
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = tokio::time::Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_rate).min(self.capacity);
+        bucket.last_refill = now;
+    }
+
+    fn take_token(&self, bucket: &mut Bucket) -> Option<u64> {
+        self.refill(bucket);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Some(std::mem::take(&mut bucket.suppressed))
+        } else {
+            None
+        }
+    }
+
+    fn acquire(&self) -> Permit {
+        let mut bucket = self.bucket.lock().unwrap();
+        // Queued notifications are sent first.
+        if bucket.queued == 0 {
+            if let Some(suppressed) = self.take_token(&mut bucket) {
+                return Permit::Allowed(suppressed);
+            }
+        }
+        if self.overflow == config::RateLimitOverflow::Queue && bucket.queued < self.max_queued {
+            bucket.queued += 1;
+            Permit::Queued
+        } else {
+            bucket.suppressed += 1;
+            Permit::Dropped
+        }
+    }
+
+    // Waits until a queued notification gets a token.
+    async fn wait_for_token(&self) -> u64 {
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                if let Some(suppressed) = self.take_token(&mut bucket) {
+                    bucket.queued -= 1;
+                    return suppressed;
+                }
+                (1.0 - bucket.tokens) / self.refill_rate
+            };
+            tokio::time::sleep(std::time::Duration::from_secs_f64(wait)).await;
+        }
+    }
+}
+
+struct RateLimitedAction {
+    action: Arc<dyn Action>,
+    limiter: Arc<RateLimiter>,
+}
+
+fn add_placeholders(placeholders: &mut PlaceholderMap, suppressed: u64) {
+    placeholders.insert(String::from("suppressed_count"), suppressed.to_string());
+}
+
+#[async_trait]
+impl Action for RateLimitedAction {
+    async fn trigger(&self, mut placeholders: PlaceholderMap) -> Result<()> {
+        let name = self.action.name().unwrap_or_default();
+        match self.limiter.acquire() {
+            Permit::Allowed(suppressed) => {
+                add_placeholders(&mut placeholders, suppressed);
+                self.action.trigger(placeholders).await
+            }
+            Permit::Queued => {
+                log::info!("Rate limit reached, queued trigger of action '{}'.", name);
+                let action = self.action.clone();
+                let limiter = self.limiter.clone();
+                tokio::spawn(async move {
+                    add_placeholders(&mut placeholders, limiter.wait_for_token().await);
+                    if let Err(err) = action.trigger(placeholders).await {
+                        log::error!("Queued trigger of action '{}' failed: {}", name, err);
+                    }
+                });
+                Ok(())
+            }
+            Permit::Dropped => {
+                log::warn!("Rate limit reached, dropped trigger of action '{}'.", name);
+                Ok(())
+            }
+        }
+    }
+
+    fn name(&self) -> Option<String> {
+        self.action.name()
+    }
+}
+
+// Wraps all actions so that they share one limit. Does nothing if no limit is configured.
+pub(crate) fn init(rate_limit: &config::RateLimit, actions: &mut ActionMap) -> Result<()> {
+    let Some(max_notifications_per_window) = rate_limit.max_notifications_per_window else {
+        return Ok(());
+    };
+    let limiter = Arc::new(RateLimiter::new(max_notifications_per_window, rate_limit)?);
+    for action in actions.values_mut() {
+        *action = Arc::new(RateLimitedAction {
+            action: action.clone(),
+            limiter: limiter.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::action::TestAction;
+
+    fn rate_limited(
+        target: &TestAction,
+        overflow: config::RateLimitOverflow,
+        max_queued: u32,
+    ) -> Arc<dyn Action> {
+        let rate_limit = config::RateLimit {
+            max_notifications_per_window: Some(3),
+            window: 60,
+            overflow,
+            max_queued,
+        };
+        let mut actions =
+            ActionMap::from([(String::from("Target"), Arc::new(target.clone()) as _)]);
+        init(&rate_limit, &mut actions).unwrap();
+        actions.remove("Target").unwrap()
+    }
+
+    async fn trigger(action: &Arc<dyn Action>, count: usize) {
+        for _ in 0..count {
+            action.trigger(PlaceholderMap::new()).await.unwrap();
+        }
+    }
+
+    fn suppressed_counts(target: &TestAction) -> Vec<String> {
+        target
+            .records()
+            .iter()
+            .map(|x| x.placeholders["suppressed_count"].clone())
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drop() {
+        let target = TestAction::new();
+        let action = rate_limited(&target, config::RateLimitOverflow::Drop, 0);
+        trigger(&action, 5).await;
+        assert_eq!(target.trigger_count(), 3);
+
+        // One token per 20 seconds.
+        tokio::time::sleep(std::time::Duration::from_secs(20)).await;
+        trigger(&action, 2).await;
+        assert_eq!(target.trigger_count(), 4);
+        assert_eq!(suppressed_counts(&target), ["0", "0", "0", "2"]);
+
+        // The bucket is full again after a window, but not fuller.
+        tokio::time::sleep(std::time::Duration::from_secs(120)).await;
+        trigger(&action, 4).await;
+        assert_eq!(target.trigger_count(), 7);
+        assert_eq!(
+            suppressed_counts(&target)[4..],
+            [String::from("1"), String::from("0"), String::from("0")]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queue() {
+        let target = TestAction::new();
+        let action = rate_limited(&target, config::RateLimitOverflow::Queue, 2);
+        trigger(&action, 6).await;
+        assert_eq!(target.trigger_count(), 3);
+
+        // The queued triggers are sent in the order of the refilled tokens.
+        tokio::time::sleep(std::time::Duration::from_secs(21)).await;
+        assert_eq!(target.trigger_count(), 4);
+        assert_eq!(suppressed_counts(&target)[3], "1");
+        tokio::time::sleep(std::time::Duration::from_secs(20)).await;
+        assert_eq!(target.trigger_count(), 5);
+        tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+        assert_eq!(target.trigger_count(), 5);
+    }
+
+    #[test]
+    fn test_validation() {
+        let rate_limit = |overflow, max_queued| config::RateLimit {
+            max_notifications_per_window: Some(1),
+            window: 60,
+            overflow,
+            max_queued,
+        };
+        assert!(RateLimiter::new(0, &rate_limit(config::RateLimitOverflow::Drop, 1)).is_err());
+        assert!(RateLimiter::new(1, &rate_limit(config::RateLimitOverflow::Queue, 0)).is_err());
+        assert!(RateLimiter::new(1, &rate_limit(config::RateLimitOverflow::Drop, 0)).is_ok());
+        let mut window = rate_limit(config::RateLimitOverflow::Drop, 1);
+        window.window = 0;
+        assert!(RateLimiter::new(1, &window).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use crate::{config, ConfigState, Error, ListenerActions, Result, SharedCheck};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+
+// Runs the checks, the report and the dead letter queue of the config. On SIGHUP, the config file
+// is read again and all of them are replaced by the ones of the new config. The alarms continue
+// from their current state, see `Check::take_over`, and unchanged actions that listen on a socket
+// are kept. The signal handler is registered on creation so a SIGHUP during start-up does not
+// terminate MinMon.
+pub struct Monitor {
+    config_file_path: std::path::PathBuf,
+    config_hash: Option<String>,
+    sighup: Signal,
+    checks: Vec<SharedCheck>,
+    tasks: Vec<tokio::task::JoinHandle<()>>,
+    listener_actions: ListenerActions,
+}
+
+async fn run(check: SharedCheck) {
+    let (check_interval, jitter, cron, backpressure) = {
+        let check = check.lock().await;
+        (
+            check.interval(),
+            check.jitter(),
+            check.cron(),
+            check.backpressure(),
+        )
+    };
+    if let Some(schedule) = cron {
+        crate::run_cron_check(check, schedule).await;
+        return;
+    }
+    let start_delay = crate::start_delay(check_interval, jitter, &mut rand::thread_rng());
+    crate::run_check(check, check_interval, start_delay, backpressure).await;
+}
+
+impl Monitor {
+    pub fn new(config_file_path: &std::path::Path, config_hash: Option<String>) -> Result<Self> {
+        Ok(Self {
+            config_file_path: config_file_path.to_path_buf(),
+            config_hash,
+            sighup: signal(SignalKind::hangup())
+                .map_err(|x| Error::Message(format!("Could not register signal handler: {x}")))?,
+            checks: Vec::new(),
+            tasks: Vec::new(),
+            listener_actions: ListenerActions::new(),
+        })
+    }
+
+    // The checks are shared with the monitor so their state can be handed over on reload and
+    // saved on shutdown.
+    pub fn start(&mut self, config_state: ConfigState) {
+        let (report, checks, dead_letter_queue, listener_actions) = config_state;
+        self.listener_actions = listener_actions;
+        self.checks = checks
+            .into_iter()
+            .map(|check| std::sync::Arc::new(tokio::sync::Mutex::new(check)))
+            .collect();
+        self.tasks = self
+            .checks
+            .iter()
+            .map(|check| tokio::spawn(run(check.clone())))
+            .collect();
+        if let Some(mut report) = report {
+            self.tasks.push(tokio::spawn(async move {
+                let mut interval = tokio::time::interval(report.interval());
+                loop {
+                    interval.tick().await;
+                    report.trigger().await;
+                }
+            }));
+        }
+        if let Some(dead_letter_queue) = dead_letter_queue {
+            self.tasks
+                .push(tokio::spawn(async move { dead_letter_queue.run().await }));
+        }
+    }
+
+    pub fn checks(&self) -> &[SharedCheck] {
+        &self.checks[..]
+    }
+
+    // Waits for SIGHUP and reloads the config file. If the new config cannot be read or set up,
+    // MinMon continues with the current one.
+    pub async fn wait_for_reload(&mut self) {
+        self.sighup.recv().await;
+        log::info!(
+            "Received signal SIGHUP. Reloading config file {}.",
+            self.config_file_path.display()
+        );
+        match self.reload().await {
+            Ok(()) => log::info!("Reloaded config with {} check(s).", self.checks.len()),
+            Err(err) => log::error!("Could not reload config, keeping the current one: {err}"),
+        }
+    }
+
+    // Locking a check waits for its current cycle to finish. Nothing is changed before all checks
+    // are locked, so the reload can be cancelled while waiting, e.g. by a shutdown.
+    async fn reload(&mut self) -> Result<()> {
+        if let Some(config_hash) = &self.config_hash {
+            config::Config::verify_checksum(self.config_file_path.as_path(), config_hash)
+                .map_err(|x| Error::Message(format!("Failed to verify config file: {x}")))?;
+        }
+        let config = config::Config::try_from(self.config_file_path.as_path())
+            .map_err(|x| Error::Message(format!("Failed to parse config file: {x}")))?;
+        let mut guards = Vec::new();
+        for check in self.checks.iter() {
+            guards.push(check.lock().await);
+        }
+        let mut states = Vec::new();
+        for guard in guards.iter() {
+            states.extend(guard.reload_states()?);
+        }
+        let config_state = crate::reload_config(&config, states, &self.listener_actions)?;
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        drop(guards);
+        self.start(config_state);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_config(path: &std::path::Path, alarms: &str) {
+        std::fs::write(
+            path,
+            format!(
+                r#"
+                [[actions]]
+                name = "Log"
+                type = "Log"
+                template = "{{{{alarm_name}}}}"
+
+                [[actions]]
+                name = "Other"
+                type = "Log"
+                template = "Other {{{{alarm_name}}}}"
+
+                [[checks]]
+                name = "Memory"
+                type = "MemoryUsage"
+                interval = 1
+                memory = true
+                {alarms}
+                "#
+            ),
+        )
+        .unwrap();
+    }
+
+    // Name and whether the alarm is bad for each alarm, without waiting for a running cycle.
+    fn alarm_states(monitor: &Monitor) -> Vec<(String, bool)> {
+        let mut states = Vec::new();
+        for check in monitor.checks() {
+            for state in check.try_lock().unwrap().reload_states().unwrap() {
+                states.push((state.snapshot.name.clone(), state.is_bad()));
+            }
+        }
+        states
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reload() {
+        crate::uptime::init().unwrap();
+        let path = std::env::temp_dir().join(format!("minmon-test-reload-{}", std::process::id()));
+        // Memory is always used, so a level of 0 is bad in every cycle.
+        write_config(
+            &path,
+            r#"
+            [[checks.alarms]]
+            name = "Kept"
+            action = "Log"
+            level = 0
+
+            [[checks.alarms]]
+            name = "Changed"
+            action = "Log"
+            level = 0
+            "#,
+        );
+        let mut monitor = Monitor::new(&path, None).unwrap();
+        let config = config::Config::try_from(path.as_path()).unwrap();
+        monitor.start(crate::from_config(&config).unwrap());
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        assert_eq!(
+            alarm_states(&monitor),
+            [
+                (String::from("Kept"), true),
+                (String::from("Changed"), true)
+            ]
+        );
+
+        write_config(
+            &path,
+            r#"
+            [[checks.alarms]]
+            name = "Kept"
+            action = "Other"
+            level = 0
+
+            [[checks.alarms]]
+            name = "Changed"
+            action = "Log"
+            level = 1
+
+            [[checks.alarms]]
+            name = "Added"
+            action = "Log"
+            level = 0
+            "#,
+        );
+        nix::sys::signal::raise(nix::sys::signal::Signal::SIGHUP).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), monitor.wait_for_reload())
+            .await
+            .unwrap();
+        // A changed action keeps the state, a changed threshold resets it.
+        assert_eq!(
+            alarm_states(&monitor),
+            [
+                (String::from("Kept"), true),
+                (String::from("Changed"), false),
+                (String::from("Added"), false)
+            ]
+        );
+        tokio::time::sleep(std::time::Duration::from_secs(3)).await;
+        assert!(alarm_states(&monitor).iter().all(|(_, bad)| *bad));
+
+        // A broken config is rejected and the checks keep running.
+        std::fs::write(&path, "broken").unwrap();
+        nix::sys::signal::raise(nix::sys::signal::Signal::SIGHUP).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), monitor.wait_for_reload())
+            .await
+            .unwrap();
+        assert_eq!(alarm_states(&monitor).len(), 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(feature = "websocket")]
+    #[tokio::test]
+    async fn test_reload_listener_actions() {
+        crate::uptime::init().unwrap();
+        let path = std::env::temp_dir().join(format!(
+            "minmon-test-reload-listener-{}",
+            std::process::id()
+        ));
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let websocket = format!("type = \"WebSocket\"\nbind_address = \"{address}\"");
+        let write_config = |action: &str, alarms: &str| {
+            std::fs::write(
+                &path,
+                format!(
+                    r#"
+                    [[actions]]
+                    name = "Events"
+                    {action}
+
+                    [[checks]]
+                    name = "Memory"
+                    type = "MemoryUsage"
+                    memory = true
+                    {alarms}
+                    "#
+                ),
+            )
+            .unwrap();
+        };
+        let alarm = "[[checks.alarms]]\nname = \"Alarm\"\naction = \"Events\"\nlevel = 0\n";
+        write_config(&websocket, alarm);
+        let mut monitor = Monitor::new(&path, None).unwrap();
+        let config = config::Config::try_from(path.as_path()).unwrap();
+        monitor.start(crate::from_config(&config).unwrap());
+
+        // The unchanged action keeps its server instead of binding the socket again.
+        write_config(&websocket, &alarm.repeat(2).replacen("Alarm", "Other", 1));
+        monitor.reload().await.unwrap();
+        assert_eq!(alarm_states(&monitor).len(), 2);
+        assert!(std::net::TcpListener::bind(address).is_err());
+
+        // The socket is freed once the removed action is dropped.
+        write_config("type = \"Log\"\ntemplate = \"Log\"", "");
+        monitor.reload().await.unwrap();
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(std::net::TcpListener::bind(address).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
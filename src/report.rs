@@ -12,7 +12,7 @@ pub struct Report {
 impl Report {
     fn new(interval: u32, placeholders: PlaceholderMap, events: Vec<Event>) -> Result<Self> {
         if interval == 0 {
-            Err(Error(String::from("'interval' cannot be 0.")))
+            Err(Error::invalid_config("interval", "cannot be 0."))
         } else {
             Ok(Self {
                 interval,
@@ -51,7 +51,7 @@ impl Event {
         action: std::sync::Arc<dyn action::Action>,
     ) -> Result<Self> {
         if name.is_empty() {
-            Err(Error(String::from("'name' cannot be empty.")))
+            Err(Error::invalid_config("name", "cannot be empty."))
         } else {
             Ok(Self {
                 name,
@@ -77,7 +77,7 @@ pub fn from_report_config(report_config: &config::Report, actions: &ActionMap) -
     let mut used_names = std::collections::HashSet::new();
     for event_config in report_config.events.iter() {
         if !used_names.insert(event_config.name.clone()) {
-            return Err(Error(format!(
+            return Err(Error::Message(format!(
                 "Found duplicate event name: {}",
                 event_config.name
             )));
@@ -102,9 +102,6 @@ mod test {
 
     #[test]
     fn test_report_validation() {
-        assert!(matches!(
-            Report::new(0, PlaceholderMap::new(), Vec::new()),
-            Err(Error(_))
-        ));
+        assert!(Report::new(0, PlaceholderMap::new(), Vec::new()).is_err());
     }
 }
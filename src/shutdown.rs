@@ -0,0 +1,153 @@
+use crate::{config, Error, Result, SharedCheck};
+use tokio::signal::unix::{signal, Signal, SignalKind};
+
+// Waits for SIGINT or SIGTERM and saves the state of the alarms before MinMon exits. The signal
+// handlers are registered on creation so no signal is missed during start-up.
+pub struct GracefulShutdown {
+    state_path: Option<std::path::PathBuf>,
+    timeout: std::time::Duration,
+    sigint: Signal,
+    sigterm: Signal,
+}
+
+impl GracefulShutdown {
+    pub fn new(state: &config::State) -> Result<Self> {
+        let register = |kind| {
+            signal(kind)
+                .map_err(|x| Error::Message(format!("Could not register signal handler: {x}")))
+        };
+        Ok(Self {
+            state_path: state.path.clone(),
+            timeout: std::time::Duration::from_secs(state.shutdown_timeout.into()),
+            sigint: register(SignalKind::interrupt())?,
+            sigterm: register(SignalKind::terminate())?,
+        })
+    }
+
+    pub async fn wait(&mut self) {
+        tokio::select! {
+            _ = self.sigint.recv() => log::info!("Received signal SIGINT. Shutting down."),
+            _ = self.sigterm.recv() => log::info!("Received signal SIGTERM. Shutting down."),
+        }
+    }
+
+    // Locking a check waits for its current cycle to finish, including the actions it triggered.
+    // Checks that are still busy when the timeout expires keep the state they had in the previous
+    // state file, if there is one. Their alarms are recognized by the check name in the log ID.
+    pub async fn save_state(&self, checks: &[SharedCheck]) -> Result<()> {
+        let Some(path) = &self.state_path else {
+            return Ok(());
+        };
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let mut guards = Vec::new();
+        for check in checks.iter() {
+            if let Ok(guard) = tokio::time::timeout_at(deadline, check.lock()).await {
+                guards.push(guard);
+            }
+        }
+        let checks_done: Vec<_> = guards.iter().map(|x| x.as_ref()).collect();
+        let mut previous = Vec::new();
+        if checks_done.len() < checks.len() {
+            log::warn!(
+                "{} check(s) did not finish within the shutdown timeout. Their previously saved state is kept.",
+                checks.len() - checks_done.len()
+            );
+            let suffixes: Vec<String> = checks_done
+                .iter()
+                .map(|x| format!(" from check '{}'", x.name()))
+                .collect();
+            previous = crate::saved_snapshots(path);
+            previous.retain(|snapshot| !suffixes.iter().any(|x| snapshot.log_id.ends_with(x)));
+        }
+        crate::save_state(path, &checks_done, previous)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::check::MockCheck;
+
+    fn shared_check(saved: bool) -> SharedCheck {
+        let mut check = MockCheck::new();
+        check.expect_name().return_const(String::from("Check"));
+        check
+            .expect_snapshots()
+            .times(usize::from(saved))
+            .returning(|| Ok(Vec::new()));
+        std::sync::Arc::new(tokio::sync::Mutex::new(Box::new(check)))
+    }
+
+    fn state_config(path: Option<std::path::PathBuf>) -> config::State {
+        config::State {
+            path,
+            shutdown_timeout: 1,
+            max_age: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_sigterm() {
+        let mut shutdown = GracefulShutdown::new(&state_config(None)).unwrap();
+        nix::sys::signal::raise(nix::sys::signal::Signal::SIGTERM).unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), shutdown.wait())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_save_state() {
+        let path =
+            std::env::temp_dir().join(format!("minmon-test-shutdown-{}", std::process::id()));
+        let shutdown = GracefulShutdown::new(&state_config(Some(path.clone()))).unwrap();
+        let checks = vec![shared_check(true), shared_check(false), shared_check(true)];
+        let _busy = checks[1].lock().await;
+        shutdown.save_state(&checks).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[]");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_save_state_keeps_busy_checks() {
+        let path =
+            std::env::temp_dir().join(format!("minmon-test-shutdown-busy-{}", std::process::id()));
+        let mut action = crate::action::MockAction::new();
+        action.expect_name().return_const(String::from("Log"));
+        let actions = crate::ActionMap::from([(
+            String::from("Log"),
+            std::sync::Arc::new(action) as std::sync::Arc<dyn crate::action::Action>,
+        )]);
+        let snapshots = |name: &str| {
+            let check_config: config::Check = toml::from_str(&format!(
+                "name = \"{name}\"\ntype = \"MemoryUsage\"\nmemory = true\n[[alarms]]\nname = \"Full\"\naction = \"Log\"\nlevel = 90"
+            ))
+            .unwrap();
+            crate::check::from_check_config(
+                &check_config,
+                &actions,
+                &crate::AlarmGroupMap::new(),
+                &crate::alarm::AlarmRegistry::default(),
+            )
+            .unwrap()
+            .snapshots()
+            .unwrap()
+        };
+        let busy = snapshots("Busy");
+        let mut previous = busy.clone();
+        previous.extend(snapshots("Check"));
+        std::fs::write(&path, serde_json::to_string(&previous).unwrap()).unwrap();
+        let shutdown = GracefulShutdown::new(&state_config(Some(path.clone()))).unwrap();
+        let checks = vec![shared_check(false), shared_check(true)];
+        let _busy = checks[0].lock().await;
+        shutdown.save_state(&checks).await.unwrap();
+        // The saved check has no alarms anymore, the busy one keeps its snapshot.
+        assert_eq!(crate::saved_snapshots(&path), busy);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_save_state_without_path() {
+        let shutdown = GracefulShutdown::new(&state_config(None)).unwrap();
+        shutdown.save_state(&[shared_check(false)]).await.unwrap();
+    }
+}
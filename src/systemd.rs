@@ -17,7 +17,7 @@ pub async fn init() {
 pub fn init_journal() -> Result<()> {
     systemd_journal_logger::JournalLog::default()
         .install()
-        .map_err(|x| Error(format!("Could not initialize journal logger: {}", x)))
+        .map_err(|x| Error::Message(format!("Could not initialize journal logger: {}", x)))
 }
 
 async fn sd_notify(state: &[&str]) -> Result<bool> {
@@ -27,7 +27,7 @@ async fn sd_notify(state: &[&str]) -> Result<bool> {
     };
 
     let socket = tokio::net::UnixDatagram::unbound()
-        .map_err(|x| Error(format!("Failed to open Unix datagram socket: {x}")))?;
+        .map_err(|x| Error::Message(format!("Failed to open Unix datagram socket: {x}")))?;
 
     let msg = state
         .iter()
@@ -37,10 +37,10 @@ async fn sd_notify(state: &[&str]) -> Result<bool> {
     let sent_len = socket
         .send_to(&msg, env_sock)
         .await
-        .map_err(|x| Error(format!("Failed to send notify datagram: {x}")))?;
+        .map_err(|x| Error::Message(format!("Failed to send notify datagram: {x}")))?;
 
     if sent_len != msg.len() {
-        return Err(Error(format!(
+        return Err(Error::Message(format!(
             "Incomplete notify message: sent {} out of {}",
             sent_len,
             msg.len()